@@ -0,0 +1,83 @@
+//! Compares the throughput of generating BSW keys and ciphertexts and of decrypting
+//! ciphertexts one by one with the batch functions, which run in parallel if the
+//! `parallel` feature is enabled:
+//!
+//! `cargo bench --features parallel`
+//!
+#[macro_use]
+extern crate criterion;
+extern crate rabe;
+
+use criterion::Criterion;
+use rabe::schemes::bsw::*;
+
+const BATCH_SIZE: usize = 32;
+
+fn attribute_sets() -> Vec<Vec<String>> {
+    return (0..BATCH_SIZE)
+        .map(|_i| vec![String::from("A"), String::from("B"), format!("user{}", _i)])
+        .collect();
+}
+
+fn items() -> Vec<(String, Vec<u8>)> {
+    return (0..BATCH_SIZE)
+        .map(|_i| {
+            (
+                format!(r#"{{"AND": [{{"ATT": "A"}}, {{"ATT": "user{}"}}]}}"#, _i),
+                String::from("dance like no one's watching, encrypt like everyone is!")
+                    .into_bytes(),
+            )
+        })
+        .collect();
+}
+
+fn bench_keygen(c: &mut Criterion) {
+    let (pk, msk) = setup();
+    let _sets = attribute_sets();
+    c.bench_function("bsw keygen serial", |b| {
+        b.iter(|| {
+            _sets
+                .iter()
+                .map(|_attributes| keygen(&pk, &msk, _attributes))
+                .collect::<Vec<_>>()
+        })
+    });
+    c.bench_function("bsw keygen_batch", |b| {
+        b.iter(|| keygen_batch(&pk, &msk, &_sets))
+    });
+}
+
+fn bench_encrypt(c: &mut Criterion) {
+    let (pk, _msk) = setup();
+    let _items = items();
+    c.bench_function("bsw encrypt serial", |b| {
+        b.iter(|| {
+            _items
+                .iter()
+                .map(|&(ref _policy, ref _plaintext)| encrypt(&pk, _policy, _plaintext))
+                .collect::<Vec<_>>()
+        })
+    });
+    c.bench_function("bsw encrypt_batch", |b| {
+        b.iter(|| encrypt_batch(&pk, &_items))
+    });
+}
+
+fn bench_decrypt(c: &mut Criterion) {
+    let (pk, msk) = setup();
+    let _attributes: Vec<String> = (0..BATCH_SIZE).map(|_i| format!("user{}", _i)).collect();
+    let sk = keygen(&pk, &msk, &[vec![String::from("A")], _attributes].concat()).unwrap();
+    let _cts: Vec<CpAbeCiphertext> = encrypt_batch(&pk, &items())
+        .into_iter()
+        .map(|_ct| _ct.unwrap())
+        .collect();
+    c.bench_function("bsw decrypt serial", |b| {
+        b.iter(|| _cts.iter().map(|_ct| decrypt(&sk, _ct)).collect::<Vec<_>>())
+    });
+    c.bench_function("bsw decrypt_batch", |b| {
+        b.iter(|| decrypt_batch(&sk, &_cts))
+    });
+}
+
+criterion_group!(benches, bench_keygen, bench_encrypt, bench_decrypt);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+//! Measures the BSW operations setup, keygen, encrypt and decrypt across attribute sets of 1,
+//! 5 and 20 attributes and the policy shapes of `fixtures` (flat OR, flat AND, nested), as a
+//! baseline for performance regressions:
+//!
+//! `cargo bench --bench bsw`
+//!
+#[macro_use]
+extern crate criterion;
+extern crate rabe;
+
+mod fixtures;
+
+use criterion::{BenchmarkId, Criterion};
+use fixtures::{attributes, policy, ATTRIBUTE_COUNTS, POLICY_SHAPES};
+use rabe::schemes::bsw::*;
+
+fn bench_setup(c: &mut Criterion) {
+    c.bench_function("bsw setup", |b| b.iter(|| setup()));
+}
+
+fn bench_keygen(c: &mut Criterion) {
+    let (pk, msk) = setup();
+    let mut group = c.benchmark_group("bsw keygen");
+    for _n in ATTRIBUTE_COUNTS.iter() {
+        let _attributes = attributes(*_n);
+        group.bench_with_input(BenchmarkId::new("attributes", _n), &_attributes, |b, _a| {
+            b.iter(|| keygen(&pk, &msk, _a))
+        });
+    }
+    group.finish();
+}
+
+fn bench_encrypt(c: &mut Criterion) {
+    let (pk, _msk) = setup();
+    let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+        .into_bytes();
+    for _shape in POLICY_SHAPES.iter() {
+        let mut group = c.benchmark_group(format!("bsw encrypt {}", _shape));
+        for _n in ATTRIBUTE_COUNTS.iter() {
+            let _policy = policy(_shape, *_n);
+            group.bench_with_input(BenchmarkId::new("attributes", _n), &_policy, |b, _p| {
+                b.iter(|| encrypt(&pk, _p, &plaintext))
+            });
+        }
+        group.finish();
+    }
+}
+
+fn bench_decrypt(c: &mut Criterion) {
+    let (pk, msk) = setup();
+    let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+        .into_bytes();
+    for _shape in POLICY_SHAPES.iter() {
+        let mut group = c.benchmark_group(format!("bsw decrypt {}", _shape));
+        for _n in ATTRIBUTE_COUNTS.iter() {
+            let sk: CpAbeSecretKey = keygen(&pk, &msk, &attributes(*_n)).unwrap();
+            let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy(_shape, *_n), &plaintext).unwrap();
+            group.bench_with_input(BenchmarkId::new("attributes", _n), &ct_cp, |b, _ct| {
+                b.iter(|| decrypt(&sk, _ct))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_setup, bench_keygen, bench_encrypt, bench_decrypt);
+criterion_main!(benches);
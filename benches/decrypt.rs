@@ -0,0 +1,55 @@
+//! Measures BSW decryption of a small policy with keys of a growing number of attributes, and
+//! of policies with a growing number of attributes, whose pairings are computed in parallel with
+//! the `parallel` feature:
+//!
+//! `cargo bench --bench decrypt`
+//!
+//! `cargo bench --bench decrypt --features parallel`
+//!
+#[macro_use]
+extern crate criterion;
+extern crate rabe;
+
+use criterion::{BenchmarkId, Criterion};
+use rabe::schemes::bsw::*;
+
+fn bench_decrypt(c: &mut Criterion) {
+    let (pk, msk) = setup();
+    let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+        .into_bytes();
+    let policy = String::from(r#"{"AND": [{"ATT": "attr0"}, {"ATT": "attr1"}]}"#);
+    let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+    let mut group = c.benchmark_group("bsw decrypt small policy");
+    for _n in [2usize, 50, 500].iter() {
+        let _attributes: Vec<String> = (0..*_n).map(|_i| format!("attr{}", _i)).collect();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &_attributes).unwrap();
+        group.bench_with_input(BenchmarkId::new("key attributes", _n), &sk, |b, sk| {
+            b.iter(|| decrypt(sk, &ct_cp))
+        });
+    }
+    group.finish();
+}
+
+fn bench_decrypt_large_policy(c: &mut Criterion) {
+    let (pk, msk) = setup();
+    let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+        .into_bytes();
+    let mut group = c.benchmark_group("bsw decrypt large policy");
+    for _n in [2usize, 16, 64].iter() {
+        let _attributes: Vec<String> = (0..*_n).map(|_i| format!("attr{}", _i)).collect();
+        let _leaves: Vec<String> = _attributes
+            .iter()
+            .map(|_a| format!(r#"{{"ATT": "{}"}}"#, _a))
+            .collect();
+        let policy = format!(r#"{{"AND": [{}]}}"#, _leaves.join(", "));
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &_attributes).unwrap();
+        group.bench_with_input(BenchmarkId::new("policy attributes", _n), &sk, |b, sk| {
+            b.iter(|| decrypt(sk, &ct_cp))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_decrypt, bench_decrypt_large_policy);
+criterion_main!(benches);
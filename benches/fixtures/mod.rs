@@ -0,0 +1,39 @@
+//! Attribute sets and policies shared by the benchmarks, generated in memory.
+//!
+#![allow(dead_code)]
+
+/// The sizes of the attribute sets and policies measured
+pub const ATTRIBUTE_COUNTS: [usize; 3] = [1, 5, 20];
+
+/// The shapes of the policies of `policy`
+pub const POLICY_SHAPES: [&'static str; 3] = ["flat or", "flat and", "nested"];
+
+/// The attributes `attr0` to `attr<n - 1>`
+pub fn attributes(_n: usize) -> Vec<String> {
+    return (0.._n).map(|_i| format!("attr{}", _i)).collect();
+}
+
+/// A JSON policy of the given shape over `attributes(_n)`, satisfied by a key holding all of
+/// them. A nested policy alternates AND and OR, i.e. `attr0 and (attr1 or (attr2 and ...))`.
+pub fn policy(_shape: &str, _n: usize) -> String {
+    let _leaves: Vec<String> = attributes(_n)
+        .iter()
+        .map(|_a| format!(r#"{{"ATT": "{}"}}"#, _a))
+        .collect();
+    // a gate needs at least two children
+    if _leaves.len() == 1 {
+        return _leaves[0].clone();
+    }
+    match _shape {
+        "flat or" => return format!(r#"{{"OR": [{}]}}"#, _leaves.join(", ")),
+        "flat and" => return format!(r#"{{"AND": [{}]}}"#, _leaves.join(", ")),
+        _ => {
+            let mut _policy = _leaves[_n - 1].clone();
+            for _i in (0.._n - 1).rev() {
+                let _gate = if _i % 2 == 0 { "AND" } else { "OR" };
+                _policy = format!(r#"{{"{}": [{}, {}]}}"#, _gate, _leaves[_i], _policy);
+            }
+            return _policy;
+        }
+    }
+}
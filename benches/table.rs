@@ -0,0 +1,37 @@
+//! Compares repeated BSW encryption under the same attribute universe with and without
+//! precomputed attribute hashes (see `CpAbePublicKey::precompute` and `HashCache`):
+//!
+//! `cargo bench --bench table`
+//!
+#[macro_use]
+extern crate criterion;
+extern crate rabe;
+
+use criterion::Criterion;
+use rabe::schemes::bsw::*;
+
+const UNIVERSE_SIZE: usize = 16;
+
+fn bench_encrypt(c: &mut Criterion) {
+    let (pk, _msk) = setup();
+    let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+        .into_bytes();
+    let _universe: Vec<String> = (0..UNIVERSE_SIZE).map(|_i| format!("attr{}", _i)).collect();
+    let _leaves: Vec<String> = _universe
+        .iter()
+        .map(|_a| format!(r#"{{"ATT": "{}"}}"#, _a))
+        .collect();
+    let policy = format!(r#"{{"OR": [{}]}}"#, _leaves.join(", "));
+    let table = pk.precompute(&_universe);
+    let cache = HashCache::new();
+    c.bench_function("bsw encrypt", |b| b.iter(|| encrypt(&pk, &policy, &plaintext)));
+    c.bench_function("bsw encrypt_with_table", |b| {
+        b.iter(|| encrypt_with_table(&pk, &table, &policy, &plaintext))
+    });
+    c.bench_function("bsw encrypt_cached", |b| {
+        b.iter(|| encrypt_cached(&pk, &cache, &policy, &plaintext))
+    });
+}
+
+criterion_group!(benches, bench_encrypt);
+criterion_main!(benches);
@@ -0,0 +1,182 @@
+//! This is the documentation for the BSW console application.
+//!
+//! A small command line interface to the BSW CP-ABE scheme. Keys and ciphertexts are written
+//! as base64 files (see `RabeEncode::to_base64`):
+//!
+//! ```bash
+//! rabe-bsw setup --pk pk.rkey --msk msk.rkey
+//! rabe-bsw keygen --attrs A,B --out sk.rkey
+//! rabe-bsw encrypt --policy "A and (B or C)" --in file.txt --out file.txt.rct
+//! rabe-bsw encrypt --policy-file policy.json --in file.txt --out file.txt.rct
+//! rabe-bsw decrypt --key sk.rkey --in file.txt.rct --out file.txt
+//! ```
+//!
+extern crate clap;
+extern crate rabe;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use rabe::schemes::bsw::*;
+use rabe::utils::encoding::RabeEncode;
+use rabe::utils::error::RabeError;
+use rabe::utils::policy::infix::parse_policy;
+use rabe::utils::secretsharing::policy_from_file;
+use std::path::Path;
+use std::{fs, process};
+
+// Application commands
+const CMD_SETUP: &'static str = "setup";
+const CMD_KEYGEN: &'static str = "keygen";
+const CMD_ENCRYPT: &'static str = "encrypt";
+const CMD_DECRYPT: &'static str = "decrypt";
+
+// Arguments
+const ARG_PK: &'static str = "pk";
+const ARG_MSK: &'static str = "msk";
+const ARG_KEY: &'static str = "key";
+const ARG_ATTRS: &'static str = "attrs";
+const ARG_POLICY: &'static str = "policy";
+const ARG_POLICY_FILE: &'static str = "policy-file";
+const ARG_IN: &'static str = "in";
+const ARG_OUT: &'static str = "out";
+
+// Default file names
+const PK_DEFAULT: &'static str = "pk.rkey";
+const MSK_DEFAULT: &'static str = "msk.rkey";
+const SK_DEFAULT: &'static str = "sk.rkey";
+
+fn main() {
+    let _matches = App::new("rabe-bsw")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("BSW CP-ABE on the command line.")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name(CMD_SETUP)
+                .about("creates a public key and a master key.")
+                .arg(file_arg(ARG_PK, PK_DEFAULT, "public key file to write."))
+                .arg(file_arg(ARG_MSK, MSK_DEFAULT, "master key file to write.")),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_KEYGEN)
+                .about("creates a secret key for a list of attributes.")
+                .arg(file_arg(ARG_PK, PK_DEFAULT, "public key file."))
+                .arg(file_arg(ARG_MSK, MSK_DEFAULT, "master key file."))
+                .arg(
+                    Arg::with_name(ARG_ATTRS)
+                        .long(ARG_ATTRS)
+                        .required(true)
+                        .takes_value(true)
+                        .help("comma separated attributes, e.g. A,B,C."),
+                )
+                .arg(file_arg(ARG_OUT, SK_DEFAULT, "secret key file to write.")),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_ENCRYPT)
+                .about("encrypts a file under a policy.")
+                .arg(file_arg(ARG_PK, PK_DEFAULT, "public key file."))
+                .arg(
+                    Arg::with_name(ARG_POLICY)
+                        .long(ARG_POLICY)
+                        .required_unless(ARG_POLICY_FILE)
+                        .conflicts_with(ARG_POLICY_FILE)
+                        .takes_value(true)
+                        .help("the policy, as JSON or as expression like 'A and (B or C)'."),
+                )
+                .arg(
+                    Arg::with_name(ARG_POLICY_FILE)
+                        .long(ARG_POLICY_FILE)
+                        .takes_value(true)
+                        .help("file holding the policy, as JSON or as expression."),
+                )
+                .arg(required_arg(ARG_IN, "file to encrypt."))
+                .arg(required_arg(ARG_OUT, "ciphertext file to write.")),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_DECRYPT)
+                .about("decrypts a file using a secret key.")
+                .arg(file_arg(ARG_KEY, SK_DEFAULT, "secret key file."))
+                .arg(required_arg(ARG_IN, "ciphertext file to decrypt."))
+                .arg(required_arg(ARG_OUT, "plaintext file to write.")),
+        )
+        .get_matches();
+    if let Err(_e) = run(&_matches) {
+        eprintln!("error: {}", _e);
+        process::exit(1);
+    }
+}
+
+fn file_arg<'a>(_name: &'a str, _default: &'a str, _help: &'a str) -> Arg<'a, 'a> {
+    return Arg::with_name(_name)
+        .long(_name)
+        .takes_value(true)
+        .default_value(_default)
+        .help(_help);
+}
+
+fn required_arg<'a>(_name: &'a str, _help: &'a str) -> Arg<'a, 'a> {
+    return Arg::with_name(_name)
+        .long(_name)
+        .required(true)
+        .takes_value(true)
+        .help(_help);
+}
+
+fn run(_matches: &ArgMatches) -> Result<(), RabeError> {
+    match _matches.subcommand() {
+        (CMD_SETUP, Some(_args)) => {
+            let (_pk, _msk) = setup();
+            write(_args, ARG_PK, &_pk)?;
+            return write(_args, ARG_MSK, &_msk);
+        }
+        (CMD_KEYGEN, Some(_args)) => {
+            let _pk: CpAbePublicKey = read(_args, ARG_PK)?;
+            let _msk: CpAbeMasterKey = read(_args, ARG_MSK)?;
+            let _attributes: Vec<String> = value(_args, ARG_ATTRS)
+                .split(',')
+                .map(|_a| _a.trim().to_string())
+                .collect();
+            let _sk = keygen(&_pk, &_msk, &_attributes)?;
+            return write(_args, ARG_OUT, &_sk);
+        }
+        (CMD_ENCRYPT, Some(_args)) => {
+            let _pk: CpAbePublicKey = read(_args, ARG_PK)?;
+            let _policy = match _args.value_of(ARG_POLICY_FILE) {
+                Some(_file) => policy_from_file(Path::new(_file))?,
+                None => {
+                    let _policy = value(_args, ARG_POLICY);
+                    // a policy that is not JSON is parsed as boolean expression
+                    if _policy.trim_start().starts_with('{') {
+                        _policy.to_string()
+                    } else {
+                        parse_policy(_policy)?
+                    }
+                }
+            };
+            let _plaintext = fs::read(value(_args, ARG_IN))?;
+            let _ct = encrypt(&_pk, &_policy, &_plaintext)?;
+            return write(_args, ARG_OUT, &_ct);
+        }
+        (CMD_DECRYPT, Some(_args)) => {
+            let _sk: CpAbeSecretKey = read(_args, ARG_KEY)?;
+            let _ct: CpAbeCiphertext = read(_args, ARG_IN)?;
+            let _plaintext = decrypt(&_sk, &_ct)?;
+            fs::write(value(_args, ARG_OUT), _plaintext)?;
+            return Ok(());
+        }
+        _ => return Ok(()),
+    }
+}
+
+fn value<'a>(_args: &'a ArgMatches, _name: &str) -> &'a str {
+    // all arguments used this way are either required or have a default value
+    return _args.value_of(_name).unwrap_or_default();
+}
+
+fn read<T: RabeEncode>(_args: &ArgMatches, _name: &str) -> Result<T, RabeError> {
+    let _content = fs::read_to_string(value(_args, _name))?;
+    return T::from_base64(_content.trim());
+}
+
+fn write<T: RabeEncode>(_args: &ArgMatches, _name: &str, _value: &T) -> Result<(), RabeError> {
+    fs::write(value(_args, _name), _value.to_base64()?)?;
+    return Ok(());
+}
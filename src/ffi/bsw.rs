@@ -1,5 +1,9 @@
 use libc::*;
+use ffi::{
+    error_code, RABE_ERR_INVALID_ARGUMENT, RABE_ERR_OUT_OF_MEMORY, RABE_ERR_SERIALIZATION, RABE_OK,
+};
 use schemes::bsw::*;
+use utils::aes::{GCM_NONCE_SIZE, GCM_TAG_SIZE};
 use serde_json;
 use std::ffi::CStr;
 use std::mem;
@@ -11,13 +15,6 @@ use std::{ptr, slice};
 extern crate libc;
 
 
-/// A BSW ABE Context
-#[derive(Serialize, Deserialize, PartialEq, Clone)]
-pub struct CpAbeContext {
-    pub _msk: CpAbeMasterKey,
-    pub _pk: CpAbePublicKey,
-}
-
 #[no_mangle]
 pub extern "C" fn rabe_bsw_context_create() -> *mut CpAbeContext {
     let (_pk, _msk) = setup();
@@ -27,19 +24,37 @@ pub extern "C" fn rabe_bsw_context_create() -> *mut CpAbeContext {
     _ctx
 }
 
+/// Runs the setup of BSW CP-ABE and returns an opaque context holding the public and master
+/// key. Same as `rabe_bsw_context_create`, release it with `rabe_bsw_context_destroy`.
+#[no_mangle]
+pub extern "C" fn rabe_bsw_setup() -> *mut CpAbeContext {
+    rabe_bsw_context_create()
+}
+
 #[no_mangle]
 pub extern "C" fn rabe_bsw_context_destroy(ctx: *mut CpAbeContext) {
+    if ctx.is_null() {
+        return;
+    }
     let _ctx: Box<CpAbeContext> = unsafe { transmute(ctx) };
     let _context = _ctx.deref();
 }
 
+/// Generates a secret key for a comma separated list of attributes. Returns an opaque key, or
+/// NULL if an argument is invalid or the key generation fails.
 #[no_mangle]
 pub extern "C" fn rabe_bsw_keygen(
     ctx: *mut CpAbeContext,
     attributes: *const c_char,
 ) -> *mut CpAbeSecretKey {
+    if ctx.is_null() || attributes.is_null() {
+        return ptr::null_mut();
+    }
     //let _attr = unsafe { &mut *attributes };
-    let _cstr = unsafe { CStr::from_ptr(attributes).to_str().unwrap() };
+    let _cstr = match unsafe { CStr::from_ptr(attributes).to_str() } {
+        Ok(_cstr) => _cstr,
+        Err(_) => return ptr::null_mut(),
+    };
     let mut _attrs = _cstr.split(",");
     let mut _attr_vec = Vec::new();
     for _a in _attrs {
@@ -47,34 +62,63 @@ pub extern "C" fn rabe_bsw_keygen(
     }
     //let attr_vec: Vec<_> = _attr.iter().map(|arg| arg.to_string()).collect();
     let _ctx = unsafe { &*ctx };
-    let _sk = unsafe {
-        transmute(Box::new(
-            keygen(&(_ctx._pk), &(_ctx._msk), &_attr_vec).unwrap(),
-        ))
-    };
-    _sk
+    match keygen(&(_ctx._pk), &(_ctx._msk), &_attr_vec) {
+        Ok(_key) => unsafe { transmute(Box::new(_key)) },
+        Err(_) => ptr::null_mut(),
+    }
 }
 
 #[no_mangle]
 pub extern "C" fn rabe_bsw_keygen_destroy(sk: *mut CpAbeSecretKey) {
+    if sk.is_null() {
+        return;
+    }
     let _sk: Box<CpAbeSecretKey> = unsafe { transmute(sk) };
     let _sk = _sk.deref();
 }
 
+/// Delegates a secret key to the `attributes_len` NUL terminated attributes of `attributes`,
+/// which have to be a subset of the attributes of `sk`. On success, `dsk` points to the opaque
+/// delegated key (release it with `rabe_bsw_keygen_destroy`). Returns `RABE_OK` or an error code.
 #[no_mangle]
 pub extern "C" fn rabe_bsw_delegate(
     ctx: *mut CpAbeContext,
     sk: *mut CpAbeSecretKey,
-    attributes: *mut Vec<String>,
-) -> *mut CpAbeSecretKey {
-    let _attr = unsafe { &mut *attributes };
-    let attr_vec: Vec<_> = _attr.iter().map(|arg| arg.to_string()).collect();
+    attributes: *const *const c_char,
+    attributes_len: u32,
+    dsk: *mut *mut CpAbeSecretKey,
+) -> i32 {
+    if ctx.is_null() || sk.is_null() || (attributes.is_null() && attributes_len > 0) ||
+        dsk.is_null()
+    {
+        return RABE_ERR_INVALID_ARGUMENT;
+    }
+    let mut _attr_vec = Vec::new();
+    if attributes_len > 0 {
+        for _attr in unsafe { slice::from_raw_parts(attributes, attributes_len as usize) } {
+            if _attr.is_null() {
+                return RABE_ERR_INVALID_ARGUMENT;
+            }
+            match unsafe { CStr::from_ptr(*_attr).to_str() } {
+                Ok(_attr) => _attr_vec.push(String::from(_attr)),
+                Err(_) => return RABE_ERR_INVALID_ARGUMENT,
+            }
+        }
+    }
     let _ctx = unsafe { &*ctx };
     let _sk = unsafe { &*sk };
-    let _dsk = unsafe { transmute(Box::new(delegate(&_ctx._pk, &_sk, &attr_vec).unwrap())) };
-    _dsk
+    match delegate(&_ctx._pk, &_sk, &_attr_vec) {
+        Err(_e) => return error_code(&_e),
+        Ok(_dsk) => {
+            unsafe { *dsk = transmute(Box::new(_dsk)) };
+            return RABE_OK;
+        }
+    }
 }
 
+/// Encrypts `pt_len` bytes of `pt` under a JSON policy. On success, `ct_buf` points to the
+/// NUL terminated ciphertext (release it with `rabe_free`) and `ct_buf_len` holds its length
+/// including the NUL byte. Returns `RABE_OK` or an error code.
 #[no_mangle]
 pub extern "C" fn rabe_bsw_encrypt(
     ctx: *mut CpAbeContext,
@@ -84,45 +128,61 @@ pub extern "C" fn rabe_bsw_encrypt(
     ct_buf: *mut *mut u8,
     ct_buf_len: *mut u32,
 ) -> i32 {
+    if ctx.is_null() || policy.is_null() || (pt.is_null() && pt_len > 0) || ct_buf.is_null() ||
+        ct_buf_len.is_null()
+    {
+        return RABE_ERR_INVALID_ARGUMENT;
+    }
     let p = unsafe { &mut *policy };
     let mut _pol = unsafe { CStr::from_ptr(p) };
     let mut pol_tmp = String::with_capacity(_pol.to_bytes().len());
     let _pol_str = _pol.to_str();
     if let Err(_) = _pol_str {
-        return -1;
+        return RABE_ERR_INVALID_ARGUMENT;
     }
     pol_tmp.insert_str(0, _pol_str.unwrap());
     let _ctx = unsafe { &*ctx };
-    let _slice = unsafe { slice::from_raw_parts(pt, pt_len as usize) };
     let mut _data_vec = Vec::new();
-    _data_vec.extend_from_slice(_slice);
-    let _res = encrypt(&(_ctx._pk), &pol_tmp, &_data_vec);
-    if let None = _res {
-        return -1;
+    if pt_len > 0 {
+        _data_vec.extend_from_slice(unsafe { slice::from_raw_parts(pt, pt_len as usize) });
     }
-    let _ct = _res.unwrap();
+    let _ct = match encrypt(&(_ctx._pk), &pol_tmp, &_data_vec) {
+        Ok(_ct) => _ct,
+        Err(_e) => return error_code(&_e),
+    };
     let _ct_ser_str = serde_json::to_string(&_ct);
     if let Err(_) = _ct_ser_str {
-        return -1;
+        return RABE_ERR_SERIALIZATION;
     }
     let _ct_str = _ct_ser_str.unwrap();
     unsafe {
         let _size = (_ct_str.len() + 1) as u32;
         *ct_buf = libc::malloc(_size as usize) as *mut u8;
+        if (*ct_buf).is_null() {
+            return RABE_ERR_OUT_OF_MEMORY;
+        }
         ptr::write_bytes(*ct_buf, 0, _size as usize);
         ptr::copy_nonoverlapping(_ct_str.as_ptr(), *ct_buf, _ct_str.len() as usize);
 
         ptr::copy_nonoverlapping(&_size, ct_buf_len, mem::size_of::<u32>());
     }
-    0
+    RABE_OK
 }
 
+/// Returns the size of the plaintext of an uncompressed ciphertext, i.e. its payload without the
+/// nonce and the authentication tag.
 #[no_mangle]
 pub extern "C" fn rabe_bsw_decrypt_get_size(ct: *mut CpAbeCiphertext) -> u32 {
+    if ct.is_null() {
+        return 0;
+    }
     let _ct = unsafe { &mut *ct };
-    (_ct._ct.len() as u32) - 16
+    _ct._ct.len().saturating_sub(GCM_NONCE_SIZE + GCM_TAG_SIZE) as u32
 }
 
+/// Decrypts a NUL terminated ciphertext of `rabe_bsw_encrypt`, `ct_len` includes the NUL byte.
+/// On success, `pt_buf` points to the plaintext (release it with `rabe_free`) and `pt_buf_len`
+/// holds its length. Returns `RABE_OK` or an error code.
 #[no_mangle]
 pub extern "C" fn rabe_bsw_decrypt(
     sk: *mut CpAbeSecretKey,
@@ -131,29 +191,180 @@ pub extern "C" fn rabe_bsw_decrypt(
     pt_buf: *mut *mut u8,
     pt_buf_len: *mut u32,
 ) -> i32 {
+    if sk.is_null() || ct.is_null() || pt_buf.is_null() || pt_buf_len.is_null() {
+        return RABE_ERR_INVALID_ARGUMENT;
+    }
     let _sk = unsafe { &mut *sk };
 
-    let mut _cstr = unsafe { CStr::from_ptr(ct as *mut c_char) };
-    let _cstr_str = _cstr.to_str();
+    // only the ct_len bytes of ct are read, they have to end with the only NUL byte
+    let _bytes = unsafe { slice::from_raw_parts(ct, ct_len as usize) };
+    let _cstr_str = match CStr::from_bytes_with_nul(_bytes) {
+        Ok(_cstr) => _cstr.to_str(),
+        Err(_) => return RABE_ERR_INVALID_ARGUMENT,
+    };
     if let Err(_) = _cstr_str {
-        return -1;
+        return RABE_ERR_INVALID_ARGUMENT;
     }
-    assert_eq!(_cstr_str.unwrap().len(), (ct_len - 1) as usize);
     let _serde_res = serde_json::from_str(_cstr_str.unwrap());
     if let Err(_) = _serde_res {
-        return -1;
+        return RABE_ERR_SERIALIZATION;
     }
     let _ct: CpAbeCiphertext = _serde_res.unwrap();
     match decrypt(_sk, &_ct) {
-        None => return -1,
-        Some(_pt) => {
+        Err(_e) => return error_code(&_e),
+        Ok(_pt) => {
             unsafe {
-                let _size = (_ct._ct.len() as u32) - 16;
-                *pt_buf = libc::malloc(_size as usize) as *mut u8;
-                ptr::copy_nonoverlapping(&_pt.as_slice()[0], *pt_buf, _size as usize);
+                let _size = _pt.len() as u32;
+                // malloc(0) may return NULL, allocate at least one byte
+                *pt_buf = libc::malloc(_pt.len().max(1)) as *mut u8;
+                if (*pt_buf).is_null() {
+                    return RABE_ERR_OUT_OF_MEMORY;
+                }
+                ptr::copy_nonoverlapping(_pt.as_ptr(), *pt_buf, _pt.len());
                 ptr::copy_nonoverlapping(&_size, pt_buf_len, mem::size_of::<u32>());
             }
-            return 0;
+            return RABE_OK;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use ffi::{
+        rabe_free, RABE_ERR_ATTRIBUTE_NOT_IN_KEY, RABE_ERR_INSUFFICIENT_ATTRIBUTES,
+        RABE_ERR_INVALID_POLICY,
+    };
+    use std::ffi::CString;
+
+    #[test]
+    fn ffi_roundtrip() {
+        // setup scheme
+        let ctx = rabe_bsw_setup();
+        let attributes = CString::new("A,B").unwrap();
+        let sk = rabe_bsw_keygen(ctx, attributes.as_ptr());
+        assert_eq!(sk.is_null(), false);
+        let policy = CString::new(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#).unwrap();
+        let mut plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let mut ct_buf: *mut u8 = ptr::null_mut();
+        let mut ct_len: u32 = 0;
+        assert_eq!(
+            rabe_bsw_encrypt(
+                ctx,
+                policy.as_ptr() as *mut c_char,
+                plaintext.as_mut_ptr(),
+                plaintext.len() as u32,
+                &mut ct_buf,
+                &mut ct_len,
+            ),
+            RABE_OK
+        );
+        let mut pt_buf: *mut u8 = ptr::null_mut();
+        let mut pt_len: u32 = 0;
+        assert_eq!(rabe_bsw_decrypt(sk, ct_buf, ct_len, &mut pt_buf, &mut pt_len), RABE_OK);
+        let _pt = unsafe { slice::from_raw_parts(pt_buf, pt_len as usize) }.to_vec();
+        assert_eq!(_pt, plaintext);
+        // a key that does not satisfy the policy
+        let attributes_a = CString::new("A").unwrap();
+        let sk_a = rabe_bsw_keygen(ctx, attributes_a.as_ptr());
+        let mut buf: *mut u8 = ptr::null_mut();
+        assert_eq!(
+            rabe_bsw_decrypt(sk_a, ct_buf, ct_len, &mut buf, &mut pt_len),
+            RABE_ERR_INSUFFICIENT_ATTRIBUTES
+        );
+        // only the given length of the ciphertext is read
+        assert_eq!(
+            rabe_bsw_decrypt(sk, ct_buf, ct_len - 1, &mut buf, &mut pt_len),
+            RABE_ERR_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            rabe_bsw_decrypt(sk, ct_buf, 0, &mut buf, &mut pt_len),
+            RABE_ERR_INVALID_ARGUMENT
+        );
+        // a delegated key for A and B decrypts, one for A does not
+        let _a = CString::new("A").unwrap();
+        let _b = CString::new("B").unwrap();
+        let _attr = vec![_a.as_ptr(), _b.as_ptr()];
+        let mut dsk: *mut CpAbeSecretKey = ptr::null_mut();
+        assert_eq!(rabe_bsw_delegate(ctx, sk, _attr.as_ptr(), 2, &mut dsk), RABE_OK);
+        let mut dsk_buf: *mut u8 = ptr::null_mut();
+        assert_eq!(rabe_bsw_decrypt(dsk, ct_buf, ct_len, &mut dsk_buf, &mut pt_len), RABE_OK);
+        let mut dsk_a: *mut CpAbeSecretKey = ptr::null_mut();
+        assert_eq!(rabe_bsw_delegate(ctx, sk, _attr.as_ptr(), 1, &mut dsk_a), RABE_OK);
+        assert_eq!(
+            rabe_bsw_decrypt(dsk_a, ct_buf, ct_len, &mut buf, &mut pt_len),
+            RABE_ERR_INSUFFICIENT_ATTRIBUTES
+        );
+        rabe_free(dsk_buf as *mut c_void);
+        rabe_free(pt_buf as *mut c_void);
+        rabe_free(ct_buf as *mut c_void);
+        rabe_bsw_keygen_destroy(dsk_a);
+        rabe_bsw_keygen_destroy(dsk);
+        rabe_bsw_keygen_destroy(sk_a);
+        rabe_bsw_keygen_destroy(sk);
+        rabe_bsw_context_destroy(ctx);
+    }
+
+    #[test]
+    fn ffi_errors() {
+        let ctx = rabe_bsw_setup();
+        let mut ct_buf: *mut u8 = ptr::null_mut();
+        let mut ct_len: u32 = 0;
+        let policy = CString::new("joking-around?").unwrap();
+        assert_eq!(
+            rabe_bsw_encrypt(
+                ctx,
+                policy.as_ptr() as *mut c_char,
+                ptr::null_mut(),
+                0,
+                &mut ct_buf,
+                &mut ct_len,
+            ),
+            RABE_ERR_INVALID_POLICY
+        );
+        assert_eq!(
+            rabe_bsw_encrypt(
+                ptr::null_mut(),
+                policy.as_ptr() as *mut c_char,
+                ptr::null_mut(),
+                0,
+                &mut ct_buf,
+                &mut ct_len,
+            ),
+            RABE_ERR_INVALID_ARGUMENT
+        );
+        assert_eq!(rabe_bsw_keygen(ctx, ptr::null()).is_null(), true);
+        let attributes = CString::new("A").unwrap();
+        let sk = rabe_bsw_keygen(ctx, attributes.as_ptr());
+        let mut dsk: *mut CpAbeSecretKey = ptr::null_mut();
+        let _null: Vec<*const c_char> = vec![ptr::null()];
+        assert_eq!(
+            rabe_bsw_delegate(ctx, sk, _null.as_ptr(), 1, &mut dsk),
+            RABE_ERR_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            rabe_bsw_delegate(ctx, sk, ptr::null(), 1, &mut dsk),
+            RABE_ERR_INVALID_ARGUMENT
+        );
+        // attributes the key does not hold can not be delegated
+        let _c = CString::new("C").unwrap();
+        let _attr = vec![_c.as_ptr()];
+        assert_eq!(
+            rabe_bsw_delegate(ctx, sk, _attr.as_ptr(), 1, &mut dsk),
+            RABE_ERR_ATTRIBUTE_NOT_IN_KEY
+        );
+        assert_eq!(dsk.is_null(), true);
+        rabe_bsw_keygen_destroy(sk);
+        let mut pt_buf: *mut u8 = ptr::null_mut();
+        assert_eq!(
+            rabe_bsw_decrypt(ptr::null_mut(), ptr::null_mut(), 0, &mut pt_buf, &mut ct_len),
+            RABE_ERR_INVALID_ARGUMENT
+        );
+        // NULL is ignored
+        rabe_free(ptr::null_mut());
+        rabe_bsw_keygen_destroy(ptr::null_mut());
+        rabe_bsw_context_destroy(ctx);
+    }
+}
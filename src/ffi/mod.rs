@@ -3,4 +3,77 @@
 //! Currently those are:
 //! * BSW CP-ABE for C
 //!
+//! The FFI is part of the optional feature `ffi`. Functions returning an `int32_t` return
+//! `RABE_OK` on success and one of the negative `RABE_ERR_*` codes below otherwise, see
+//! `error_code`. Buffers returned by the library are allocated with `malloc` and have to be
+//! released with `rabe_free`.
+//!
+use libc::{c_void, free};
+use utils::error::RabeError;
+
 pub mod bsw;
+
+/// Success
+pub const RABE_OK: i32 = 0;
+/// A pointer is NULL or a string is not valid UTF-8
+pub const RABE_ERR_INVALID_ARGUMENT: i32 = -1;
+/// `RabeError::Io`
+pub const RABE_ERR_IO: i32 = -2;
+/// `RabeError::EmptyAttributes`
+pub const RABE_ERR_EMPTY_ATTRIBUTES: i32 = -3;
+/// `RabeError::EmptyPolicy`
+pub const RABE_ERR_EMPTY_POLICY: i32 = -4;
+/// `RabeError::PolicyParse`, `RabeError::InvalidPolicy`, `RabeError::NotAPolicy`,
+/// `RabeError::PolicyTooDeep`, `RabeError::PolicyTooLarge` and `RabeError::GateTooWide`
+pub const RABE_ERR_INVALID_POLICY: i32 = -5;
+/// `RabeError::Interpolation`
+pub const RABE_ERR_INTERPOLATION: i32 = -6;
+/// `RabeError::AttributeNotInKey`
+pub const RABE_ERR_ATTRIBUTE_NOT_IN_KEY: i32 = -7;
+/// `RabeError::InsufficientAttributes`
+pub const RABE_ERR_INSUFFICIENT_ATTRIBUTES: i32 = -8;
+/// `RabeError::UnauthorizedAttribute` and `RabeError::InvalidAttribute`
+pub const RABE_ERR_INVALID_ATTRIBUTE: i32 = -9;
+/// `RabeError::Serialization` and `RabeError::InvalidGroupElement`
+pub const RABE_ERR_SERIALIZATION: i32 = -10;
+/// `RabeError::Encryption` and `RabeError::EphemeralReuse`
+pub const RABE_ERR_ENCRYPTION: i32 = -11;
+/// `RabeError::DecryptionFailed` and `RabeError::SymmetricDecryptionFailed`
+pub const RABE_ERR_DECRYPTION: i32 = -12;
+/// A buffer could not be allocated
+pub const RABE_ERR_OUT_OF_MEMORY: i32 = -14;
+
+/// Returns the (negative) FFI error code of a RabeError.
+pub fn error_code(_error: &RabeError) -> i32 {
+    match *_error {
+        RabeError::Io(_) => return RABE_ERR_IO,
+        RabeError::EmptyAttributes => return RABE_ERR_EMPTY_ATTRIBUTES,
+        RabeError::EmptyPolicy => return RABE_ERR_EMPTY_POLICY,
+        RabeError::PolicyParse(_) |
+        RabeError::InvalidPolicy(_) |
+        RabeError::NotAPolicy(_) |
+        RabeError::PolicyTooDeep |
+        RabeError::PolicyTooLarge |
+        RabeError::GateTooWide(_) => return RABE_ERR_INVALID_POLICY,
+        RabeError::Interpolation(_) => return RABE_ERR_INTERPOLATION,
+        RabeError::AttributeNotInKey(_) => return RABE_ERR_ATTRIBUTE_NOT_IN_KEY,
+        RabeError::InsufficientAttributes => return RABE_ERR_INSUFFICIENT_ATTRIBUTES,
+        RabeError::UnauthorizedAttribute(_) |
+        RabeError::InvalidAttribute(_) => return RABE_ERR_INVALID_ATTRIBUTE,
+        RabeError::Serialization(_) |
+        RabeError::InvalidGroupElement(_) => return RABE_ERR_SERIALIZATION,
+        RabeError::Encryption(_) |
+        RabeError::EphemeralReuse => return RABE_ERR_ENCRYPTION,
+        RabeError::DecryptionFailed(_) |
+        RabeError::SymmetricDecryptionFailed => return RABE_ERR_DECRYPTION,
+    }
+}
+
+/// Releases a buffer returned by the library, e.g. the ciphertext of `rabe_bsw_encrypt` or the
+/// plaintext of `rabe_bsw_decrypt`. Does nothing if `buf` is NULL.
+#[no_mangle]
+pub extern "C" fn rabe_free(buf: *mut c_void) {
+    if !buf.is_null() {
+        unsafe { free(buf) };
+    }
+}
@@ -16,14 +16,31 @@ extern crate blake2_rfc;
 extern crate bn;
 extern crate byteorder;
 extern crate crypto;
+#[cfg(feature = "compression")]
+extern crate flate2;
 extern crate libc;
 extern crate num_bigint;
 extern crate rand;
+#[cfg(feature = "parallel")]
+extern crate rayon;
 extern crate serde;
 extern crate serde_json;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+
+// rayon spawns threads and the C interface needs libc, neither works in the browser
+#[cfg(all(target_arch = "wasm32", feature = "parallel"))]
+compile_error!("the feature parallel is not supported on wasm32");
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "ffi"))]
+compile_error!("build for wasm32-unknown-unknown without the feature ffi, e.g. --features wasm");
+
 /// foriegn function interface
+#[cfg(feature = "ffi")]
 pub mod ffi;
 /// implemented schemes
 pub mod schemes;
 /// various utilities
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+/// WebAssembly bindings
+#[cfg(feature = "wasm")]
+pub mod wasm;
\ No newline at end of file
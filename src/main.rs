@@ -10,8 +10,12 @@ extern crate bn;
 #[macro_use]
 extern crate clap;
 extern crate crypto;
+#[cfg(feature = "compression")]
+extern crate flate2;
 extern crate num_bigint;
 extern crate rand;
+#[cfg(feature = "parallel")]
+extern crate rayon;
 #[macro_use]
 extern crate serde_derive;
 #[macro_use]
@@ -33,12 +37,14 @@ use schemes::{
     mke08::*
 };
 use utils::file::{
+    load_attributes_file,
     write_file,
     read_file,
     read_raw,
     write_from_vec,
     read_to_vec
 };
+use utils::secretsharing::policy_from_file;
 use serde_cbor::{
     from_slice,
     ser::to_vec_packed
@@ -61,7 +67,10 @@ const DOT: &'static str = ".";
 // Object names
 const ATTRIBUTES: &'static str = "attr";
 const ATTRIBUTES_LONG: &'static str = "attribute(s)";
+const ATTRIBUTES_FILE: &'static str = "attr-file";
+const ATTRIBUTES_FILE_LONG: &'static str = "attribute-file";
 const POLICY: &'static str = "policy";
+const POLICY_FILE: &'static str = "policy-file";
 const NAME: &'static str = "name";
 const SCHEME: &'static str = "scheme";
 const JSON: &'static str = "json";
@@ -140,6 +149,12 @@ impl Error for RabeError {
     }
 }
 
+impl From<utils::error::RabeError> for RabeError {
+    fn from(_error: utils::error::RabeError) -> RabeError {
+        RabeError::new(&_error.to_string())
+    }
+}
+
 fn main() {
     arg_enum! {
         #[derive(Debug)]
@@ -314,6 +329,13 @@ fn main() {
                         .multiple(true)
                         .help("attributes to use."),
                 )
+                .arg(
+                    Arg::with_name(ATTRIBUTES_FILE)
+                        .long(ATTRIBUTES_FILE_LONG)
+                        .required(false)
+                        .takes_value(true)
+                        .help("file listing the attributes to use, one per line."),
+                )
                 .arg(
                     Arg::with_name(POLICY)
                         .long(POLICY)
@@ -394,8 +416,16 @@ fn main() {
                         .long(POLICY)
                         .required(false)
                         .takes_value(true)
+                        .conflicts_with(POLICY_FILE)
                         .help("the policy to use."),
                 )
+                .arg(
+                    Arg::with_name(POLICY_FILE)
+                        .long(POLICY_FILE)
+                        .required(false)
+                        .takes_value(true)
+                        .help("file holding the policy to use, as JSON or boolean expression."),
+                )
                 .arg(
                     Arg::with_name(FILE)
                         .long(FILE)
@@ -774,18 +804,18 @@ fn main() {
             Scheme::AW11 => {
                 let mut _gp: Aw11GlobalKey;
                 if _as_json {
-                    _gp = serde_json::from_str(&read_file(Path::new(&_gp_file))).unwrap();
+                    _gp = serde_json::from_str(&read_file(Path::new(&_gp_file))?).unwrap();
                 } else {
-                    _gp = from_slice(&decode(&read_raw(&read_file(Path::new(&_gp_file))))
+                    _gp = from_slice(&decode(&read_raw(&read_file(Path::new(&_gp_file))?))
                         .unwrap()).unwrap();
                 }
                 match schemes::aw11::authgen(&_gp, &_attributes) {
-                    None => {
+                    Err(_) => {
                         return Err(RabeError::new(
                             "sorry, could not generate authority. The attribute set empty.",
                         ));
                     }
-                    Some((_pk, _msk)) => {
+                    Ok((_pk, _msk)) => {
                         if _as_json {
                             write_file(
                                 Path::new(&_msk_file),
@@ -814,12 +844,12 @@ fn main() {
                 let mut _pk: BdabePublicKey;
                 let mut _msk: BdabeMasterKey;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
+                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                         .unwrap()).unwrap();
-                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))))
+                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))?))
                         .unwrap()).unwrap();
                 }
                 let _sk: BdabeSecretAuthorityKey = schemes::bdabe::authgen(&_pk, &_msk, &_name);
@@ -840,12 +870,12 @@ fn main() {
                 let mut _pk: Mke08PublicKey;
                 let mut _msk: Mke08MasterKey;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
+                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                         .unwrap()).unwrap();
-                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))))
+                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))?))
                         .unwrap()).unwrap();
                 }
                 let _sk: Mke08SecretAuthorityKey = schemes::mke08::authgen(&_name);
@@ -933,6 +963,15 @@ fn main() {
                 }
             }
         }
+        match arguments.value_of(ATTRIBUTES_FILE) {
+            None => {}
+            Some(_file) => {
+                match load_attributes_file(Path::new(_file)) {
+                    Err(_e) => return Err(RabeError::new(&_e.to_string())),
+                    Ok(_loaded) => _attributes.extend(_loaded),
+                }
+            }
+        }
         match arguments.value_of(POLICY) {
             None => {}
             Some(_pol) => _policy = _pol.to_string(),
@@ -950,9 +989,9 @@ fn main() {
             Scheme::AC17CP => {
                 let mut _msk: Ac17MasterKey;
                 if _as_json {
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))?).unwrap();
                 } else {
-                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))))
+                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))?))
                         .unwrap()).unwrap();
                 }
                 let _sk: Ac17CpSecretKey = schemes::ac17::cp_keygen(&_msk, &_attributes).unwrap();
@@ -972,9 +1011,9 @@ fn main() {
             Scheme::AC17KP => {
                 let mut _msk: Ac17MasterKey;
                 if _as_json {
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))?).unwrap();
                 } else {
-                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))))
+                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))?))
                         .unwrap()).unwrap();
                 }
                 let _sk: Ac17KpSecretKey = schemes::ac17::kp_keygen(&_msk, &_policy).unwrap();
@@ -995,12 +1034,12 @@ fn main() {
                 let mut _pk: CpAbePublicKey;
                 let mut _msk: CpAbeMasterKey;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
+                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                         .unwrap()).unwrap();
-                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))))
+                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))?))
                         .unwrap()).unwrap();
                 }
                 let _sk: CpAbeSecretKey = schemes::bsw::keygen(&_pk, &_msk, &_attributes).unwrap();
@@ -1021,12 +1060,12 @@ fn main() {
                 let mut _pk: KpAbePublicKey;
                 let mut _msk: KpAbeMasterKey;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
+                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                         .unwrap()).unwrap();
-                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))))
+                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))?))
                         .unwrap()).unwrap();
                 }
                 let _sk: KpAbeSecretKey = schemes::lsw::keygen(&_pk, &_msk, &_policy).unwrap();
@@ -1047,12 +1086,12 @@ fn main() {
                 let mut _pk: Aw11GlobalKey;
                 let mut _msk: Aw11MasterKey;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_gp_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_gp_file))?).unwrap();
+                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_gp_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_gp_file))?))
                         .unwrap()).unwrap();
-                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))))
+                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))?))
                         .unwrap()).unwrap();
                 }
                 let _sk: Aw11SecretKey = schemes::aw11::keygen(&_pk, &_msk, &_name, &_attributes)
@@ -1074,12 +1113,12 @@ fn main() {
                 let mut _pk: BdabePublicKey;
                 let mut _msk: BdabeSecretAuthorityKey;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_ska_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
+                    _msk = serde_json::from_str(&read_file(Path::new(&_ska_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                         .unwrap()).unwrap();
-                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_ska_file))))
+                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_ska_file))?))
                         .unwrap()).unwrap();
                 }
                 let _sk: BdabeUserKey = schemes::bdabe::keygen(&_pk, &_msk, &_name);
@@ -1100,12 +1139,12 @@ fn main() {
                 let mut _pk: Mke08PublicKey;
                 let mut _msk: Mke08MasterKey;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
+                    _msk = serde_json::from_str(&read_file(Path::new(&_msk_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                         .unwrap()).unwrap();
-                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))))
+                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_msk_file))?))
                         .unwrap()).unwrap();
                 }
                 if _name != String::from("") {
@@ -1189,22 +1228,22 @@ fn main() {
                 let mut _pk: CpAbePublicKey;
                 let mut _msk: CpAbeSecretKey;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _msk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
+                    _msk = serde_json::from_str(&read_file(Path::new(&_sk_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                         .unwrap()).unwrap();
-                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))))
+                    _msk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))?))
                         .unwrap()).unwrap();
                 }
-                let _sk: Option<CpAbeSecretKey> = schemes::bsw::delegate(&_pk, &_msk, &_attributes);
-                match _sk {
-                    None => {
-                        return Err(RabeError::new(
-                            "sorry, could not delegate attributes. The given attributes are not a subset.",
-                        ));
+                match schemes::bsw::delegate(&_pk, &_msk, &_attributes) {
+                    Err(_e) => {
+                        return Err(RabeError::new(&format!(
+                            "sorry, could not delegate attributes: {}",
+                            _e
+                        )));
                     }
-                    Some(_delegated_key) => {
+                    Ok(_delegated_key) => {
                         if _as_json {
                             write_file(
                                 Path::new(&_dg_file),
@@ -1273,6 +1312,15 @@ fn main() {
             None => {}
             Some(_pol) => _policy = _pol.to_string(),
         }
+        match arguments.value_of(POLICY_FILE) {
+            None => {}
+            Some(_file) => {
+                match policy_from_file(Path::new(_file)) {
+                    Err(_e) => return Err(RabeError::new(&_e.to_string())),
+                    Ok(_loaded) => _policy = _loaded,
+                }
+            }
+        }
         match arguments.value_of(FILE) {
             None => {}
             Some(_file) => {
@@ -1290,12 +1338,15 @@ fn main() {
                 if _pk_files.len() == 1 {
                     _pk_file = _pk_files[0].clone();
                     if _as_json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
+                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
                     } else {
-                        _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                        _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                             .unwrap()).unwrap();
                     }
-                    let _ct = schemes::ac17::cp_encrypt(&_pk, &_policy, &buffer);
+                    let _ct = match schemes::ac17::cp_encrypt(&_pk, &_policy, &buffer) {
+                        Ok(_ct) => _ct,
+                        Err(_e) => return Err(RabeError::new(&_e.to_string())),
+                    };
                     if _as_json {
                         write_file(
                             Path::new(&_ct_file),
@@ -1320,12 +1371,15 @@ fn main() {
                 if _pk_files.len() == 1 {
                     _pk_file = _pk_files[0].clone();
                     if _as_json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
+                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
                     } else {
-                        _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                        _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                             .unwrap()).unwrap();
                     }
-                    let _ct = schemes::ac17::kp_encrypt(&_pk, &_attributes, &buffer);
+                    let _ct = match schemes::ac17::kp_encrypt(&_pk, &_attributes, &buffer) {
+                        Ok(_ct) => _ct,
+                        Err(_e) => return Err(RabeError::new(&_e.to_string())),
+                    };
                     if _as_json {
                         write_file(
                             Path::new(&_ct_file),
@@ -1349,14 +1403,17 @@ fn main() {
                 // only one pk is allowed
                 if _pk_files.len() == 1 {
                     if _as_json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_files[0].clone())))
+                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_files[0].clone()))?)
                             .unwrap();
                     } else {
                         _pk = from_slice(&decode(
-                            &read_raw(&read_file(Path::new(&_pk_files[0].clone()))),
+                            &read_raw(&read_file(Path::new(&_pk_files[0].clone()))?),
                         ).unwrap()).unwrap();
                     }
-                    let _ct = schemes::bsw::encrypt(&_pk, &_policy, &buffer);
+                    let _ct = match schemes::bsw::encrypt(&_pk, &_policy, &buffer) {
+                        Ok(_ct) => _ct,
+                        Err(_e) => return Err(RabeError::new(&_e.to_string())),
+                    };
                     if _as_json {
                         write_file(
                             Path::new(&_ct_file),
@@ -1380,14 +1437,17 @@ fn main() {
                 // only one pk is allowed
                 if _pk_files.len() == 1 {
                     if _as_json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_files[0].clone())))
+                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_files[0].clone()))?)
                             .unwrap();
                     } else {
                         _pk = from_slice(&decode(
-                            &read_raw(&read_file(Path::new(&_pk_files[0].clone()))),
+                            &read_raw(&read_file(Path::new(&_pk_files[0].clone()))?),
                         ).unwrap()).unwrap();
                     }
-                    let _ct = schemes::lsw::encrypt(&_pk, &_attributes, &buffer);
+                    let _ct = match schemes::lsw::encrypt(&_pk, &_attributes, &buffer) {
+                        Ok(_ct) => _ct,
+                        Err(_e) => return Err(RabeError::new(&_e.to_string())),
+                    };
                     if _as_json {
                         write_file(
                             Path::new(&_ct_file),
@@ -1409,23 +1469,26 @@ fn main() {
             Scheme::AW11 => {
                 let mut _gp: Aw11GlobalKey;
                 if _as_json {
-                    _gp = serde_json::from_str(&read_file(Path::new(&_gp_file))).unwrap();
+                    _gp = serde_json::from_str(&read_file(Path::new(&_gp_file))?).unwrap();
                 } else {
-                    _gp = from_slice(&decode(&read_raw(&read_file(Path::new(&_gp_file))))
+                    _gp = from_slice(&decode(&read_raw(&read_file(Path::new(&_gp_file))?))
                         .unwrap()).unwrap();
                 }
                 let mut _pks: Vec<Aw11PublicKey> = Vec::new();
                 for filename in _pk_files {
                     let mut _pka: Aw11PublicKey;
                     if _as_json {
-                        _pka = serde_json::from_str(&read_file(Path::new(&filename))).unwrap();
+                        _pka = serde_json::from_str(&read_file(Path::new(&filename))?).unwrap();
                     } else {
-                        _pka = from_slice(&decode(&read_raw(&read_file(Path::new(&filename))))
+                        _pka = from_slice(&decode(&read_raw(&read_file(Path::new(&filename))?))
                             .unwrap()).unwrap();
                     }
                     _pks.push(_pka);
                 }
-                let _ct = schemes::aw11::encrypt(&_gp, &_pks, &_policy, &buffer);
+                let _ct = match schemes::aw11::encrypt(&_gp, &_pks, &_policy, &buffer) {
+                    Ok(_ct) => _ct,
+                    Err(_e) => return Err(RabeError::new(&_e.to_string())),
+                };
                 if _as_json {
                     write_file(
                         Path::new(&_ct_file),
@@ -1442,23 +1505,26 @@ fn main() {
             Scheme::BDABE => {
                 let mut _pk: BdabePublicKey;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                         .unwrap()).unwrap();
                 }
                 let mut _attr_vec: Vec<BdabePublicAttributeKey> = Vec::new();
                 for filename in _pk_files {
                     let mut _pka: BdabePublicAttributeKey;
                     if _as_json {
-                        _pka = serde_json::from_str(&read_file(Path::new(&filename))).unwrap();
+                        _pka = serde_json::from_str(&read_file(Path::new(&filename))?).unwrap();
                     } else {
-                        _pka = from_slice(&decode(&read_raw(&read_file(Path::new(&filename))))
+                        _pka = from_slice(&decode(&read_raw(&read_file(Path::new(&filename))?))
                             .unwrap()).unwrap();
                     }
                     _attr_vec.push(_pka);
                 }
-                let _ct = schemes::bdabe::encrypt(&_pk, &_attr_vec, &_policy, &buffer);
+                let _ct = match schemes::bdabe::encrypt(&_pk, &_attr_vec, &_policy, &buffer) {
+                    Ok(_ct) => _ct,
+                    Err(_e) => return Err(RabeError::new(&_e.to_string())),
+                };
                 if _as_json {
                     write_file(
                         Path::new(&_ct_file),
@@ -1475,23 +1541,26 @@ fn main() {
             Scheme::MKE08 => {
                 let mut _pk: Mke08PublicKey;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                         .unwrap()).unwrap();
                 }
                 let mut _attr_vec: Vec<Mke08PublicAttributeKey> = Vec::new();
                 for filename in _pk_files {
                     let mut _pka: Mke08PublicAttributeKey;
                     if _as_json {
-                        _pka = serde_json::from_str(&read_file(Path::new(&filename))).unwrap();
+                        _pka = serde_json::from_str(&read_file(Path::new(&filename))?).unwrap();
                     } else {
-                        _pka = from_slice(&decode(&read_raw(&read_file(Path::new(&filename))))
+                        _pka = from_slice(&decode(&read_raw(&read_file(Path::new(&filename))?))
                             .unwrap()).unwrap();
                     }
                     _attr_vec.push(_pka);
                 }
-                let _ct = schemes::mke08::encrypt(&_pk, &_attr_vec, &_policy, &buffer);
+                let _ct = match schemes::mke08::encrypt(&_pk, &_attr_vec, &_policy, &buffer) {
+                    Ok(_ct) => _ct,
+                    Err(_e) => return Err(RabeError::new(&_e.to_string())),
+                };
                 if _as_json {
                     write_file(
                         Path::new(&_ct_file),
@@ -1557,111 +1626,111 @@ fn main() {
                 let mut _sk: Ac17CpSecretKey;
                 let mut _ct: Ac17CpCiphertext;
                 if _as_json {
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))?).unwrap();
+                    _ct = serde_json::from_str(&read_file(Path::new(&_file))?).unwrap();
                 } else {
-                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))))
+                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))?))
                         .unwrap()).unwrap();
-                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file)))).unwrap())
+                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file))?)).unwrap())
                         .unwrap();
                 }
-                _pt_option = schemes::ac17::cp_decrypt(&_sk, &_ct);
+                _pt_option = schemes::ac17::cp_decrypt(&_sk, &_ct).ok();
             }
             Scheme::AC17KP => {
                 let mut _sk: Ac17KpSecretKey;
                 let mut _ct: Ac17KpCiphertext;
                 if _as_json {
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))?).unwrap();
+                    _ct = serde_json::from_str(&read_file(Path::new(&_file))?).unwrap();
                 } else {
-                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))))
+                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))?))
                         .unwrap()).unwrap();
-                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file)))).unwrap())
+                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file))?)).unwrap())
                         .unwrap();
                 }
-                _pt_option = schemes::ac17::kp_decrypt(&_sk, &_ct);
+                _pt_option = schemes::ac17::kp_decrypt(&_sk, &_ct).ok();
             }
             Scheme::BSW => {
                 let mut _sk: CpAbeSecretKey;
                 let mut _ct: CpAbeCiphertext;
                 if _as_json {
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))?).unwrap();
+                    _ct = serde_json::from_str(&read_file(Path::new(&_file))?).unwrap();
                 } else {
-                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))))
+                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))?))
                         .unwrap()).unwrap();
-                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file)))).unwrap())
+                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file))?)).unwrap())
                         .unwrap();
                 }
-                _pt_option = schemes::bsw::decrypt(&_sk, &_ct);
+                _pt_option = schemes::bsw::decrypt(&_sk, &_ct).ok();
             }
             Scheme::LSW => {
                 let mut _sk: KpAbeSecretKey;
                 let mut _ct: KpAbeCiphertext;
                 if _as_json {
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))?).unwrap();
+                    _ct = serde_json::from_str(&read_file(Path::new(&_file))?).unwrap();
                 } else {
-                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))))
+                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))?))
                         .unwrap()).unwrap();
-                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file)))).unwrap())
+                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file))?)).unwrap())
                         .unwrap();
                 }
-                _pt_option = schemes::lsw::decrypt(&_sk, &_ct);
+                _pt_option = schemes::lsw::decrypt(&_sk, &_ct).ok();
             }
             Scheme::AW11 => {
                 let mut _gp: Aw11GlobalKey;
                 let mut _sk: Aw11SecretKey;
                 let mut _ct: Aw11Ciphertext;
                 if _as_json {
-                    _gp = serde_json::from_str(&read_file(Path::new(&_gp_file))).unwrap();
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _gp = serde_json::from_str(&read_file(Path::new(&_gp_file))?).unwrap();
+                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))?).unwrap();
+                    _ct = serde_json::from_str(&read_file(Path::new(&_file))?).unwrap();
                 } else {
-                    _gp = from_slice(&decode(&read_raw(&read_file(Path::new(&_gp_file))))
+                    _gp = from_slice(&decode(&read_raw(&read_file(Path::new(&_gp_file))?))
                         .unwrap()).unwrap();
-                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))))
+                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))?))
                         .unwrap()).unwrap();
-                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file)))).unwrap())
+                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file))?)).unwrap())
                         .unwrap();
                 }
-                _pt_option = schemes::aw11::decrypt(&_gp, &_sk, &_ct);
+                _pt_option = schemes::aw11::decrypt(&_gp, &_sk, &_ct).ok();
             }
             Scheme::BDABE => {
                 let mut _pk: BdabePublicKey;
                 let mut _sk: BdabeUserKey;
                 let mut _ct: BdabeCiphertext;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
+                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))?).unwrap();
+                    _ct = serde_json::from_str(&read_file(Path::new(&_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                         .unwrap()).unwrap();
-                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))))
+                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))?))
                         .unwrap()).unwrap();
-                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file)))).unwrap())
+                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file))?)).unwrap())
                         .unwrap();
                 }
-                _pt_option = schemes::bdabe::decrypt(&_pk, &_sk, &_ct);
+                _pt_option = schemes::bdabe::decrypt(&_pk, &_sk, &_ct).ok();
             }
             Scheme::MKE08 => {
                 let mut _pk: Mke08PublicKey;
                 let mut _sk: Mke08UserKey;
                 let mut _ct: Mke08Ciphertext;
                 if _as_json {
-                    _pk = serde_json::from_str(&read_file(Path::new(&_gp_file))).unwrap();
-                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                    _ct = serde_json::from_str(&read_file(Path::new(&_file))).unwrap();
+                    _pk = serde_json::from_str(&read_file(Path::new(&_gp_file))?).unwrap();
+                    _sk = serde_json::from_str(&read_file(Path::new(&_sk_file))?).unwrap();
+                    _ct = serde_json::from_str(&read_file(Path::new(&_file))?).unwrap();
                 } else {
-                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_gp_file))))
+                    _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_gp_file))?))
                         .unwrap()).unwrap();
-                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))))
+                    _sk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))?))
                         .unwrap()).unwrap();
-                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file)))).unwrap())
+                    _ct = from_slice(&decode(&read_raw(&read_file(Path::new(&_file))?)).unwrap())
                         .unwrap();
                 }
-                _pt_option = schemes::mke08::decrypt(&_pk, &_sk, &_ct);
+                _pt_option = schemes::mke08::decrypt(&_pk, &_sk, &_ct).ok();
             }
         }
         match _pt_option {
@@ -1730,17 +1799,17 @@ fn main() {
                     let mut _pk: Mke08PublicKey;
                     let mut _ska: Mke08SecretAuthorityKey;
                     if _as_json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                        _ska = serde_json::from_str(&read_file(Path::new(&_au_sk_file))).unwrap();
+                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
+                        _ska = serde_json::from_str(&read_file(Path::new(&_au_sk_file))?).unwrap();
                     } else {
-                        _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                        _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                             .unwrap()).unwrap();
-                        _ska = from_slice(&decode(&read_raw(&read_file(Path::new(&_au_sk_file))))
+                        _ska = from_slice(&decode(&read_raw(&read_file(Path::new(&_au_sk_file))?))
                             .unwrap()).unwrap();
                     }
                     match schemes::mke08::request_authority_pk(&_pk, &_attributes[0], &_ska) {
-                        None => {}
-                        Some(_a_pk) => {
+                        Err(_) => {}
+                        Ok(_a_pk) => {
                             if _as_json {
                                 write_file(
                                     Path::new(&_pka_file),
@@ -1761,17 +1830,17 @@ fn main() {
                     let mut _pk: BdabePublicKey;
                     let mut _ska: BdabeSecretAuthorityKey;
                     if _as_json {
-                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))).unwrap();
-                        _ska = serde_json::from_str(&read_file(Path::new(&_au_sk_file))).unwrap();
+                        _pk = serde_json::from_str(&read_file(Path::new(&_pk_file))?).unwrap();
+                        _ska = serde_json::from_str(&read_file(Path::new(&_au_sk_file))?).unwrap();
                     } else {
-                        _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))))
+                        _pk = from_slice(&decode(&read_raw(&read_file(Path::new(&_pk_file))?))
                             .unwrap()).unwrap();
-                        _ska = from_slice(&decode(&read_raw(&read_file(Path::new(&_au_sk_file))))
+                        _ska = from_slice(&decode(&read_raw(&read_file(Path::new(&_au_sk_file))?))
                             .unwrap()).unwrap();
                     }
                     match schemes::bdabe::request_attribute_pk(&_pk, &_ska, &_attributes[0]) {
-                        None => {}
-                        Some(_a_pk) => {
+                        Err(_) => {}
+                        Ok(_a_pk) => {
                             if _as_json {
                                 write_file(
                                     Path::new(&_pka_file),
@@ -1853,12 +1922,12 @@ fn main() {
                     let mut _ska: Mke08SecretAttributeKey;
                     let mut _skau: Mke08SecretAuthorityKey;
                     if _as_json {
-                        _usk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                        _skau = serde_json::from_str(&read_file(Path::new(&_au_sk_file))).unwrap();
+                        _usk = serde_json::from_str(&read_file(Path::new(&_sk_file))?).unwrap();
+                        _skau = serde_json::from_str(&read_file(Path::new(&_au_sk_file))?).unwrap();
                     } else {
-                        _usk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))))
+                        _usk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))?))
                             .unwrap()).unwrap();
-                        _skau = from_slice(&decode(&read_raw(&read_file(Path::new(&_au_sk_file))))
+                        _skau = from_slice(&decode(&read_raw(&read_file(Path::new(&_au_sk_file))?))
                             .unwrap()).unwrap();
                     }
                     match schemes::mke08::request_authority_sk(
@@ -1866,8 +1935,8 @@ fn main() {
                         &_skau,
                         &_usk._pk_u,
                     ) {
-                        None => {}
-                        Some(_a_sk) => {
+                        Err(_) => {}
+                        Ok(_a_sk) => {
                             if _as_json {
                                 write_file(
                                     Path::new(&_ask_file),
@@ -1889,17 +1958,17 @@ fn main() {
                     let mut _ska: BdabeSecretAttributeKey;
                     let mut _skau: BdabeSecretAuthorityKey;
                     if _as_json {
-                        _usk = serde_json::from_str(&read_file(Path::new(&_sk_file))).unwrap();
-                        _skau = serde_json::from_str(&read_file(Path::new(&_au_sk_file))).unwrap();
+                        _usk = serde_json::from_str(&read_file(Path::new(&_sk_file))?).unwrap();
+                        _skau = serde_json::from_str(&read_file(Path::new(&_au_sk_file))?).unwrap();
                     } else {
-                        _usk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))))
+                        _usk = from_slice(&decode(&read_raw(&read_file(Path::new(&_sk_file))?))
                             .unwrap()).unwrap();
-                        _skau = from_slice(&decode(&read_raw(&read_file(Path::new(&_au_sk_file))))
+                        _skau = from_slice(&decode(&read_raw(&read_file(Path::new(&_au_sk_file))?))
                             .unwrap()).unwrap();
                     }
                     match schemes::bdabe::request_attribute_sk(&_usk._pk, &_skau, &_attributes[0]) {
-                        None => {}
-                        Some(_a_sk) => {
+                        Err(_) => {}
+                        Ok(_a_sk) => {
                             if _as_json {
                                 write_file(
                                     Path::new(&_ask_file),
@@ -8,6 +8,9 @@
 //! * Authors: Georg Bramm
 //! * Date: 04/2018
 //!
+//! The public key has a constant size, independent of the attribute universe: attributes are
+//! hashed to the groups when needed, so no attribute has to be known at `setup`.
+//!
 //! # Examples
 //!
 //! An AC17 KP-ABE Example:
@@ -44,6 +47,7 @@ use utils::{
     tools::*,
     secretsharing::*,
     aes::*,
+    error::RabeError,
     hash::blake2b_hash_g1
 };
 
@@ -164,11 +168,14 @@ pub fn setup() -> (Ac17PublicKey, Ac17MasterKey) {
 ///	* `msk` - A Master Key (MSK), generated by the function setup()
 ///	* `attributes` - A Vector of String attributes assigned to this user key
 ///
-pub fn cp_keygen(msk: &Ac17MasterKey, attributes: &Vec<String>) -> Option<Ac17CpSecretKey> {
+pub fn cp_keygen(
+    msk: &Ac17MasterKey,
+    attributes: &Vec<String>,
+) -> Result<Ac17CpSecretKey, RabeError> {
     // if no attibutes or an empty policy
     // maybe add empty msk also here
     if attributes.is_empty() {
-        return None;
+        return Err(RabeError::EmptyAttributes);
     }
     // random number generator
     let mut _rng = rand::thread_rng();
@@ -234,7 +241,7 @@ pub fn cp_keygen(msk: &Ac17MasterKey, attributes: &Vec<String>) -> Option<Ac17Cp
     _k_p.push(_g_k[ASSUMPTION_SIZE] + (msk._g * _sigma.neg()));
     let _attr = attributes.clone();
     let _sk = Ac17SecretKey {_k_0, _k, _k_p};
-    return Some(Ac17CpSecretKey {_attr, _sk});
+    return Ok(Ac17CpSecretKey {_attr, _sk});
 }
 
 /// The encrypt algorithm of AC17CP. Generates an Ac17CpCiphertext using an Ac17PublicKey, an access policy given as String and some plaintext data given as [u8].
@@ -249,11 +256,11 @@ pub fn cp_encrypt(
     pk: &Ac17PublicKey,
     policy: &String,
     _plaintext: &[u8],
-) -> Option<Ac17CpCiphertext> {
+) -> Result<Ac17CpCiphertext, RabeError> {
     // random number generator
     let mut _rng = rand::thread_rng();
     // an msp policy from the given String
-    let msp: AbePolicy = AbePolicy::from_string(&policy).unwrap();
+    let msp: AbePolicy = msp_policy(policy)?;
     let _num_cols = msp._m[0].len();
     let _num_rows = msp._m.len();
     // pick randomness
@@ -325,11 +332,11 @@ pub fn cp_encrypt(
     }
     // random msg
     let _msg: Gt = _rng.gen();
-    let _aes_ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
+    let _aes_ct = symmetric(&_msg, _plaintext)?;
     _c_p = _c_p * _msg;
     let _policy = policy.to_string();
     //Encrypt plaintext using derived key from secret
-    return Some(Ac17CpCiphertext {
+    return Ok(Ac17CpCiphertext {
         _policy,
         _ct: Ac17Ciphertext { _c_0, _c, _c_p, _ct: _aes_ct },
     });
@@ -342,47 +349,40 @@ pub fn cp_encrypt(
 ///	* `sk` - A Secret Key (SK), generated by the function cp_keygen()
 ///	* `ct` - An AC17CP Ciphertext
 ///
-pub fn cp_decrypt(sk: &Ac17CpSecretKey, ct: &Ac17CpCiphertext) -> Option<Vec<u8>> {
-    return if traverse_str(&sk._attr, &ct._policy) == false {
-        //println!("Error: attributes in sk do not match policy in ct.");
-        None
-    } else {
-        match calc_pruned_str(&sk._attr, &ct._policy) {
-            None => {
-                //println!("Error: attributes in sk do not match policy in ct.");
-                None
+pub fn cp_decrypt(sk: &Ac17CpSecretKey, ct: &Ac17CpCiphertext) -> Result<Vec<u8>, RabeError> {
+    validate_policy(&ct._policy)?;
+    if traverse_str(&sk._attr, &ct._policy) == false {
+        return Err(RabeError::InsufficientAttributes);
+    }
+    let (_match, _list) = calc_pruned_str(&sk._attr, &ct._policy)?;
+    if !_match {
+        return Err(RabeError::InsufficientAttributes);
+    }
+    let mut _prod1_gt = Gt::one();
+    let mut _prod2_gt = Gt::one();
+    for _i in 0usize..(ASSUMPTION_SIZE + 1) {
+        let mut _prod_h = G1::zero();
+        let mut _prod_g = G1::zero();
+        for _current in _list.iter() {
+            for _attr in ct._ct._c.iter() {
+                if _attr.0 == _current.to_string() {
+                    _prod_g = _prod_g + _attr.1[_i];
+                }
             }
-            Some((_match, _list)) => {
-                if _match {
-                    let mut _prod1_gt = Gt::one();
-                    let mut _prod2_gt = Gt::one();
-                    for _i in 0usize..(ASSUMPTION_SIZE + 1) {
-                        let mut _prod_h = G1::zero();
-                        let mut _prod_g = G1::zero();
-                        for _current in _list.iter() {
-                            for _attr in ct._ct._c.iter() {
-                                if _attr.0 == _current.to_string() {
-                                    _prod_g = _prod_g + _attr.1[_i];
-                                }
-                            }
-                            for _attr in sk._sk._k.iter() {
-                                if _attr.0 == _current.to_string() {
-                                    _prod_h = _prod_h + _attr.1[_i];
-                                }
-                            }
-                        }
-                        _prod1_gt = _prod1_gt * pairing(sk._sk._k_p[_i] + _prod_h, ct._ct._c_0[_i]);
-                        _prod2_gt = _prod2_gt * pairing(_prod_g, sk._sk._k_0[_i]);
-                    }
-                    let _msg = ct._ct._c_p * (_prod2_gt * _prod1_gt.inverse());
-                    // Decrypt plaintext using derived secret from cp-abe scheme
-                    decrypt_symmetric(&_msg, &ct._ct._ct)
-                } else {
-                    println!("Error: attributes in sk do not match policy in ct.");
-                    None
+            for _attr in sk._sk._k.iter() {
+                if _attr.0 == _current.to_string() {
+                    _prod_h = _prod_h + _attr.1[_i];
                 }
             }
         }
+        _prod1_gt = _prod1_gt * pairing(sk._sk._k_p[_i] + _prod_h, ct._ct._c_0[_i]);
+        _prod2_gt = _prod2_gt * pairing(_prod_g, sk._sk._k_0[_i]);
+    }
+    let _msg = ct._ct._c_p * (_prod2_gt * _prod1_gt.inverse());
+    // Decrypt plaintext using derived secret from cp-abe scheme
+    match decrypt_symmetric(&_msg, &ct._ct._ct) {
+        None => return Err(RabeError::SymmetricDecryptionFailed),
+        Some(_pt) => return Ok(_pt),
     }
 }
 
@@ -393,11 +393,11 @@ pub fn cp_decrypt(sk: &Ac17CpSecretKey, ct: &Ac17CpCiphertext) -> Option<Vec<u8>
 ///	* `msk` - A Master Key (MSK), generated by the function setup()
 ///	* `policy` - An access policy given as JSON String
 ///
-pub fn kp_keygen(msk: &Ac17MasterKey, policy: &String) -> Option<Ac17KpSecretKey> {
+pub fn kp_keygen(msk: &Ac17MasterKey, policy: &String) -> Result<Ac17KpSecretKey, RabeError> {
     // random number generator
     let mut _rng = rand::thread_rng();
     // an msp policy from the given String
-    let msp: AbePolicy = AbePolicy::from_string(&policy).unwrap();
+    let msp: AbePolicy = msp_policy(policy)?;
     let _num_cols = msp._m[0].len();
     let _num_rows = msp._m.len();
     // pick randomness
@@ -487,7 +487,7 @@ pub fn kp_keygen(msk: &Ac17MasterKey, policy: &String) -> Option<Ac17KpSecretKey
         _key.push(_sk_i3);
         _k.push((msp._pi[_i].to_string(), _key));
     }
-    return Some(Ac17KpSecretKey {
+    return Ok(Ac17KpSecretKey {
         _policy: policy.clone(),
         _sk: Ac17SecretKey { _k_0, _k, _k_p: Vec::new()},
     });
@@ -505,7 +505,7 @@ pub fn kp_encrypt(
     pk: &Ac17PublicKey,
     attributes: &Vec<String>,
     _plaintext: &[u8],
-) -> Option<Ac17KpCiphertext> {
+) -> Result<Ac17KpCiphertext, RabeError> {
     // random number generator
     let mut _rng = rand::thread_rng();
     // pick randomness
@@ -548,10 +548,10 @@ pub fn kp_encrypt(
     }
     // random msg
     let _msg: Gt = _rng.gen();
-    let _ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
+    let _ct = symmetric(&_msg, _plaintext)?;
     _c_p = _c_p * _msg;
     //Encrypt plaintext using derived key from secret
-    return Some(Ac17KpCiphertext {
+    return Ok(Ac17KpCiphertext {
         _attr: attributes.clone(),
         _ct: Ac17Ciphertext {_c_0, _c, _c_p, _ct},
     });
@@ -564,52 +564,59 @@ pub fn kp_encrypt(
 ///	* `sk` - A Secret Key (SK), generated by the function kp_keygen()
 ///	* `ct` - An AC17KP Ciphertext
 ///
-pub fn kp_decrypt(sk: &Ac17KpSecretKey, ct: &Ac17KpCiphertext) -> Option<Vec<u8>> {
-    return if traverse_str(&ct._attr, &sk._policy) == false {
-        println!("Error: attributes in ct do not match policy in sk.");
-        None
-    } else {
-        match calc_pruned_str(&ct._attr, &sk._policy) {
-            None => {
-                println!("Error: attributes in sk do not match policy in ct.");
-                None
+pub fn kp_decrypt(sk: &Ac17KpSecretKey, ct: &Ac17KpCiphertext) -> Result<Vec<u8>, RabeError> {
+    if traverse_str(&ct._attr, &sk._policy) == false {
+        return Err(RabeError::InsufficientAttributes);
+    }
+    let (_match, _list) = calc_pruned_str(&ct._attr, &sk._policy)?;
+    if !_match {
+        return Err(RabeError::InsufficientAttributes);
+    }
+    let mut _prod1_gt = Gt::one();
+    let mut _prod2_gt = Gt::one();
+    for _i in 0usize..(ASSUMPTION_SIZE + 1) {
+        let mut _prod_h = G1::zero();
+        let mut _prod_g = G1::zero();
+        for _current in _list.iter() {
+            for _attr in ct._ct._c.iter() {
+                if _attr.0 == _current.to_string() {
+                    _prod_g = _prod_g + _attr.1[_i];
+                }
             }
-            Some(_p) => {
-                let (_match, _list) = _p;
-                if _match {
-                    let mut _prod1_gt = Gt::one();
-                    let mut _prod2_gt = Gt::one();
-                    for _i in 0usize..(ASSUMPTION_SIZE + 1) {
-                        let mut _prod_h = G1::zero();
-                        let mut _prod_g = G1::zero();
-                        for _current in _list.iter() {
-                            for _attr in ct._ct._c.iter() {
-                                if _attr.0 == _current.to_string() {
-                                    _prod_g = _prod_g + _attr.1[_i];
-                                }
-                            }
-                            for _attr in sk._sk._k.iter() {
-                                if _attr.0 == _current.to_string() {
-                                    _prod_h = _prod_h + _attr.1[_i];
-                                }
-                            }
-                        }
-                        // for _j in 0usize..ct._ct._c.len() {
-                        //     _prod_h = _prod_h + sk._sk._k[_j].1[_i];
-                        //     _prod_g = _prod_g + ct._ct._c[_j].1[_i];
-                        // }
-                        _prod1_gt = _prod1_gt * pairing(_prod_h, ct._ct._c_0[_i]);
-                        _prod2_gt = _prod2_gt * pairing(_prod_g, sk._sk._k_0[_i]);
-                    }
-                    let _msg = ct._ct._c_p * (_prod2_gt * _prod1_gt.inverse());
-                    // Decrypt plaintext using derived secret from cp-abe scheme
-                    decrypt_symmetric(&_msg, &ct._ct._ct)
-                } else {
-                    println!("Error: attributes in sk do not match policy in ct.");
-                    None
+            for _attr in sk._sk._k.iter() {
+                if _attr.0 == _current.to_string() {
+                    _prod_h = _prod_h + _attr.1[_i];
                 }
             }
         }
+        // for _j in 0usize..ct._ct._c.len() {
+        //     _prod_h = _prod_h + sk._sk._k[_j].1[_i];
+        //     _prod_g = _prod_g + ct._ct._c[_j].1[_i];
+        // }
+        _prod1_gt = _prod1_gt * pairing(_prod_h, ct._ct._c_0[_i]);
+        _prod2_gt = _prod2_gt * pairing(_prod_g, sk._sk._k_0[_i]);
+    }
+    let _msg = ct._ct._c_p * (_prod2_gt * _prod1_gt.inverse());
+    // Decrypt plaintext using derived secret from cp-abe scheme
+    match decrypt_symmetric(&_msg, &ct._ct._ct) {
+        None => return Err(RabeError::SymmetricDecryptionFailed),
+        Some(_pt) => return Ok(_pt),
+    }
+}
+
+// the monotone span program of a JSON policy
+fn msp_policy(_policy: &String) -> Result<AbePolicy, RabeError> {
+    match AbePolicy::from_string(_policy) {
+        None => return Err(RabeError::PolicyParse(_policy.clone())),
+        Some(_msp) => return Ok(_msp),
+    }
+}
+
+// encrypts the plaintext using a key derived from the given message
+fn symmetric(_msg: &Gt, _plaintext: &[u8]) -> Result<Vec<u8>, RabeError> {
+    match encrypt_symmetric(_msg, &_plaintext.to_vec()) {
+        None => return Err(RabeError::Encryption(String::from("symmetric encryption failed"))),
+        Some(_ct) => return Ok(_ct),
     }
 }
 
@@ -634,6 +641,9 @@ mod tests {
         let sk: Ac17KpSecretKey = kp_keygen(&msk, &policy).unwrap();
         // and now decrypt again
         assert_eq!(kp_decrypt(&sk, &ct).unwrap(), plaintext);
+        // a ciphertext with only one of the attributes does not match the key policy
+        let ct: Ac17KpCiphertext = kp_encrypt(&pk, &vec!["A".to_string()], &plaintext).unwrap();
+        assert_eq!(kp_decrypt(&sk, &ct).is_err(), true);
     }
 
     #[test]
@@ -692,7 +702,7 @@ mod tests {
         // a kp-abe SK key
         let sk: Ac17KpSecretKey = kp_keygen(&msk, &policy).unwrap();
         // and now decrypt again
-        assert_eq!(kp_decrypt(&sk, &ct), None);
+        assert_eq!(kp_decrypt(&sk, &ct).is_err(), true);
     }
 
     #[test]
@@ -734,7 +744,7 @@ mod tests {
         // and now decrypt again
         assert_eq!(cp_decrypt(&sk_m2, &ct).unwrap(), plaintext);
         // and now decrypt again
-        assert_eq!(cp_decrypt(&sk_nm, &ct).is_none(), true);
+        assert_eq!(cp_decrypt(&sk_nm, &ct).is_err(), true);
     }
 
     #[test]
@@ -776,6 +786,6 @@ mod tests {
         // a kp-abe SK key
         let sk: Ac17CpSecretKey = cp_keygen(&msk, &vec!["C".to_string()]).unwrap();
         // and now decrypt again
-        assert_eq!(cp_decrypt(&sk, &ct), None);
+        assert_eq!(cp_decrypt(&sk, &ct).is_err(), true);
     }
 }
@@ -28,11 +28,13 @@ use utils::{
     secretsharing::{
         calc_coefficients_str,
         calc_pruned_str,
-        gen_shares_str
+        gen_shares_str,
+        validate_policy
     },
     policy::msp::AbePolicy,
     tools::*,
     aes::*,
+    error::RabeError,
     hash::blake2b_hash_g1
 };
 
@@ -104,11 +106,11 @@ pub fn setup() -> Aw11GlobalKey {
 pub fn authgen(
     _gk: &Aw11GlobalKey,
     _attributes: &Vec<String>,
-) -> Option<(Aw11PublicKey, Aw11MasterKey)> {
+) -> Result<(Aw11PublicKey, Aw11MasterKey), RabeError> {
     // if no attibutes or an empty policy
     // maybe add empty msk also here
     if _attributes.is_empty() {
-        return None;
+        return Err(RabeError::EmptyAttributes);
     }
     // random number generator
     let mut _rng = rand::thread_rng();
@@ -128,7 +130,7 @@ pub fn authgen(
         ));
     }
     // return PK and MSK
-    return Some((Aw11PublicKey { _attr: _pk }, Aw11MasterKey { _attr: _sk }));
+    return Ok((Aw11PublicKey { _attr: _pk }, Aw11MasterKey { _attr: _sk }));
 }
 
 /// Sets up and generates a new User by creating a secret user key (SK). The key is created for a user with a given "name" on the given set of attributes.
@@ -149,10 +151,10 @@ pub fn keygen(
     _msk: &Aw11MasterKey,
     _name: &String,
     _attributes: &Vec<String>,
-) -> Option<Aw11SecretKey> {
+) -> Result<Aw11SecretKey, RabeError> {
     // if no attibutes or no gid
     if _attributes.is_empty() || _name.is_empty() {
-        return None;
+        return Err(RabeError::EmptyAttributes);
     }
     let mut _sk: Aw11SecretKey = Aw11SecretKey {
         _gid: _name.clone(),
@@ -161,7 +163,7 @@ pub fn keygen(
     for _attribute in _attributes {
         add_attribute(_gk, _msk, _attribute, &mut _sk);
     }
-    return Some(_sk);
+    return Ok(_sk);
 }
 
 /// This function does not create a new User key, but adds a new attribute to an already generated key (SK).
@@ -208,18 +210,21 @@ pub fn encrypt(
     _pks: &Vec<Aw11PublicKey>,
     _policy: &String,
     _plaintext: &[u8],
-) -> Option<Aw11Ciphertext> {
+) -> Result<Aw11Ciphertext, RabeError> {
     // random number generator
     let mut _rng = rand::thread_rng();
     // an msp policy from the given String
-    let msp: AbePolicy = AbePolicy::from_string(&_policy).unwrap();
+    let msp: AbePolicy = match AbePolicy::from_string(&_policy) {
+        None => return Err(RabeError::PolicyParse(_policy.clone())),
+        Some(_msp) => _msp,
+    };
     let _num_cols = msp._m[0].len();
     let _num_rows = msp._m.len();
     // pick randomness
     let _s:Fr = _rng.gen();
     // and calculate shares "s" and "zero"
-    let _s_shares = gen_shares_str(_s, _policy).unwrap();
-    let _w_shares = gen_shares_str(Fr::zero(), _policy).unwrap();
+    let _s_shares = gen_shares_str(_s, _policy)?;
+    let _w_shares = gen_shares_str(Fr::zero(), _policy)?;
     // calculate c0 with a randomly selected "msg"
     let _msg: Gt = _rng.gen();
     let _c_0 = _msg * pairing(_gk._g1, _gk._g2).pow(_s);
@@ -229,7 +234,11 @@ pub fn encrypt(
         let _r_x:Fr = _rng.gen();
         let _pk_attr = find_pk_attr(_pks, &_attr_name.to_uppercase());
         match _pk_attr {
-            None => return None,
+            None => {
+                return Err(RabeError::Encryption(
+                    format!("attribute {} is not part of any public key", _attr_name),
+                ))
+            }
             Some(_attr) => {
                 _c.push((
                     _attr_name.clone().to_uppercase(),
@@ -242,8 +251,11 @@ pub fn encrypt(
     }
     //Encrypt plaintext using derived key from secret
     let _policy = _policy.to_string();
-    let _ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
-    return Some(Aw11Ciphertext { _policy, _c_0, _c, _ct });
+    let _ct = match encrypt_symmetric(&_msg, &_plaintext.to_vec()) {
+        None => return Err(RabeError::Encryption(String::from("symmetric encryption failed"))),
+        Some(_ct) => _ct,
+    };
+    return Ok(Aw11Ciphertext { _policy, _c_0, _c, _ct });
 }
 
 /// This function decrypts a 'Aw11Ciphertext' if the attributes in SK match the policy of CT. If successfull, returns the plaintext data as a Vetor of u8's.
@@ -253,7 +265,11 @@ pub fn encrypt(
 ///	* `_gk` - A Global Parameters Key (GK), generated by setup()
 ///	* `_sk` - A secret user key (SK), associated with a set of attributes.
 ///	* `_ct` - A Aw11Ciphertext
-pub fn decrypt(gk: &Aw11GlobalKey, sk: &Aw11SecretKey, ct: &Aw11Ciphertext) -> Option<Vec<u8>> {
+pub fn decrypt(
+    gk: &Aw11GlobalKey,
+    sk: &Aw11SecretKey,
+    ct: &Aw11Ciphertext,
+) -> Result<Vec<u8>, RabeError> {
     let _str_attr = sk
         ._attr
         .iter()
@@ -262,57 +278,50 @@ pub fn decrypt(gk: &Aw11GlobalKey, sk: &Aw11SecretKey, ct: &Aw11Ciphertext) -> O
             _str
         })
         .collect::<Vec<_>>();
-    return if traverse_str(&_str_attr, &ct._policy) == false {
-        //println!("Error: attributes in sk do not match policy in ct.");
-        None
-    } else {
-        let _pruned = calc_pruned_str(&_str_attr, &ct._policy);
-        match _pruned {
+    validate_policy(&ct._policy)?;
+    if traverse_str(&_str_attr, &ct._policy) == false {
+        return Err(RabeError::InsufficientAttributes);
+    }
+    let (_match, _list) = calc_pruned_str(&_str_attr, &ct._policy)?;
+    if !_match {
+        return Err(RabeError::InsufficientAttributes);
+    }
+    let _coeffs = calc_coefficients_str(&ct._policy)?;
+    let _h_g1 = blake2b_hash_g1(gk._g1, &sk._gid);
+    let mut _egg_s = Gt::one();
+    for _current in _list.iter() {
+        let _sk_attr = match sk._attr.iter().find(|_attr| _attr.0 == *_current) {
+            None => return Err(RabeError::AttributeNotInKey(_current.to_string())),
+            Some(_sk_attr) => _sk_attr,
+        };
+        let _ct_attr = match ct._c.iter().find(|_attr| _attr.0 == *_current) {
             None => {
-                //println!("Error: attributes in sk do not match policy in ct.");
-                None
+                return Err(RabeError::DecryptionFailed(
+                    format!("attribute {} is not part of the ciphertext", _current),
+                ))
             }
-            Some(_p) => {
-                let (_match, _list) = _p;
-                let _coeffs = calc_coefficients_str(&ct._policy).unwrap();
-                if _match {
-                    let _h_g1 = blake2b_hash_g1(gk._g1, &sk._gid);
-                    let mut _egg_s = Gt::one();
-                    for _current in _list.iter() {
-                        let _sk_attr = sk
-                            ._attr
-                            .iter()
-                            .filter(|_attr| _attr.0 == _current.to_string())
-                            .nth(0)
-                            .unwrap();
-                        let _ct_attr = ct
-                            ._c
-                            .iter()
-                            .filter(|_attr| _attr.0 == _current.to_string())
-                            .nth(0)
-                            .unwrap();
-                        let num = _ct_attr.1 * pairing(_h_g1, _ct_attr.3);
-                        let dem = pairing(_sk_attr.1, _ct_attr.2);
-                        let _coeff = _coeffs
-                            .iter()
-                            .filter(|_c| _c.0 == _current.to_string())
-                            .map(|_c| _c.1)
-                            .nth(0)
-                            .unwrap();
-                        _egg_s = _egg_s * ((num * dem.inverse()).pow(_coeff));
-                    }
-                    let _msg = ct._c_0 * _egg_s.inverse();
-                    //println!("dec: {:?}", serde_json::to_string(&_msg).unwrap());
-                    // Decrypt plaintext using derived secret from cp-abe scheme
-                    decrypt_symmetric(&_msg, &ct._ct)
-                } else {
-                    println!("Error: attributes in sk do not match policy in ct.");
-                    None
-                }
+            Some(_ct_attr) => _ct_attr,
+        };
+        let num = _ct_attr.1 * pairing(_h_g1, _ct_attr.3);
+        let dem = pairing(_sk_attr.1, _ct_attr.2);
+        let _coeff = match _coeffs.iter().find(|_c| _c.0 == *_current) {
+            None => {
+                return Err(RabeError::DecryptionFailed(
+                    format!("no coefficient for attribute {}", _current),
+                ))
             }
-        }
+            Some(_c) => _c.1,
+        };
+        _egg_s = _egg_s * ((num * dem.inverse()).pow(_coeff));
+    }
+    let _msg = ct._c_0 * _egg_s.inverse();
+    // Decrypt plaintext using derived secret from cp-abe scheme
+    match decrypt_symmetric(&_msg, &ct._ct) {
+        None => return Err(RabeError::SymmetricDecryptionFailed),
+        Some(_pt) => return Ok(_pt),
     }
 }
+
 /// private function. finds the value vector of a specific attribute in a vector of various public keys
 ///
 /// # Arguments
@@ -527,6 +536,6 @@ mod tests {
         // cp-abe ciphertext
         let ct_cp: Aw11Ciphertext = encrypt(&_gp, &_pks, &_policy, &_plaintext).unwrap();
         // and now decrypt again
-        assert_eq!(decrypt(&_gp, &_bob, &ct_cp), None);
+        assert_eq!(decrypt(&_gp, &_bob, &ct_cp).is_err(), true);
     }
 }
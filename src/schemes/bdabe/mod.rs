@@ -30,11 +30,17 @@ use rand::Rng;
 use bn::{Group, Fr, G1, G2, Gt, pairing};
 use utils::{
     policy::*,
+    secretsharing::validate_policy,
     tools::*,
     aes::*,
-    hash::blake2b_hash_fr
+    error::RabeError,
+    hash::hash_to_fr
 };
 
+// the domains of the hashes of attributes and authority names, see hash_to_fr
+const BDABE_ATTRIBUTE_DOMAIN: &'static str = "bdabe-attribute";
+const BDABE_AUTHORITY_DOMAIN: &'static str = "bdabe-authority";
+
 /// A BDABE Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct BdabePublicKey {
@@ -206,19 +212,20 @@ pub fn request_attribute_pk(
     _pk: &BdabePublicKey,
     _ska: &BdabeSecretAuthorityKey,
     _attribute: &String,
-) -> Option<BdabePublicAttributeKey> {
+) -> Result<BdabePublicAttributeKey, RabeError> {
     // if attribute a is from authority sk_a
     return if from_authority(_attribute, &_ska._a) {
-        let exponent = blake2b_hash_fr(_attribute) * blake2b_hash_fr(&_ska._a) * _ska._a3;
+        let exponent = hash_to_fr(_attribute, BDABE_ATTRIBUTE_DOMAIN) *
+            hash_to_fr(&_ska._a, BDABE_AUTHORITY_DOMAIN) * _ska._a3;
         // return PK and mke
-        Some(BdabePublicAttributeKey {
+        Ok(BdabePublicAttributeKey {
             _str: _attribute.clone(),
             _a1: _pk._g1 * exponent,
             _a2: _pk._g2 * exponent,
             _a3: _pk._e_gg_y.pow(exponent),
         })
     } else {
-        None
+        Err(RabeError::UnauthorizedAttribute(_attribute.clone()))
     }
 }
 
@@ -234,18 +241,19 @@ pub fn request_attribute_sk(
     _pku: &BdabePublicUserKey,
     _ska: &BdabeSecretAuthorityKey,
     _attribute: &String,
-) -> Option<BdabeSecretAttributeKey> {
+) -> Result<BdabeSecretAttributeKey, RabeError> {
     // if attribute a is from authority sk_a
     return if from_authority(_attribute, &_ska._a) && is_eligible(_attribute, &_pku._u) {
-        let exponent = blake2b_hash_fr(_attribute) * blake2b_hash_fr(&_ska._a) * _ska._a3;
+        let exponent = hash_to_fr(_attribute, BDABE_ATTRIBUTE_DOMAIN) *
+            hash_to_fr(&_ska._a, BDABE_AUTHORITY_DOMAIN) * _ska._a3;
         // return PK and mke
-        Some(BdabeSecretAttributeKey {
+        Ok(BdabeSecretAttributeKey {
             _str: _attribute.to_string(),
             _au1: _pku._u1 * exponent,
             _au2: _pku._u2 * exponent,
         })
     } else {
-        None
+        Err(RabeError::UnauthorizedAttribute(_attribute.clone()))
     }
 }
 
@@ -264,18 +272,24 @@ pub fn encrypt(
     _attr_pks: &Vec<BdabePublicAttributeKey>,
     _policy: &String,
     _plaintext: &[u8],
-) -> Option<BdabeCiphertext> {
+) -> Result<BdabeCiphertext, RabeError> {
     // if policy is in DNF
     if dnf::DnfPolicy::is_in_dnf(&_policy) {
         // random number generator
         let mut _rng = rand::thread_rng();
         let _policy = _policy.to_string();
         // an DNF policy from the given String
-        let dnf: dnf::DnfPolicy = dnf::DnfPolicy::from_string(&_policy, _attr_pks).unwrap();
+        let dnf: dnf::DnfPolicy = match dnf::DnfPolicy::from_string(&_policy, _attr_pks) {
+            None => return Err(RabeError::PolicyParse(_policy.clone())),
+            Some(_dnf) => _dnf,
+        };
         // random Gt msg
         let _msg = pairing(_rng.gen(), _rng.gen());
         // CT result vector
-        let _ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
+        let _ct = match encrypt_symmetric(&_msg, &_plaintext.to_vec()) {
+            None => return Err(RabeError::Encryption(String::from("symmetric encryption failed"))),
+            Some(_ct) => _ct,
+        };
         let mut _j: Vec<BdabeCiphertextTuple> = Vec::new();
         // now add randomness using _r_j
         for _term in dnf._terms {
@@ -290,9 +304,9 @@ pub fn encrypt(
             });
         }
         //Encrypt plaintext using derived key from secret
-        Some(BdabeCiphertext {_policy, _j, _ct })
+        Ok(BdabeCiphertext {_policy, _j, _ct })
     } else {
-        None
+        Err(RabeError::InvalidPolicy(String::from("the policy is not in DNF")))
     }
 }
 
@@ -304,15 +318,19 @@ pub fn encrypt(
 ///	* `_sk` - A BdabeUserKey (SK), generated by the function keygen()
 ///	* `_ct` - A BdabeCiphertext Ciphertext
 ///
-pub fn decrypt(_pk: &BdabePublicKey, _sk: &BdabeUserKey, _ct: &BdabeCiphertext) -> Option<Vec<u8>> {
+pub fn decrypt(
+    _pk: &BdabePublicKey,
+    _sk: &BdabeUserKey,
+    _ct: &BdabeCiphertext,
+) -> Result<Vec<u8>, RabeError> {
     let _str_attr = _sk
         ._ska
         .iter()
         .map(|_values| _values._str.to_string())
         .collect::<Vec<_>>();
+    validate_policy(&_ct._policy)?;
     return if traverse_str(&_str_attr, &_ct._policy) == false {
-        //println!("Error: attributes in sk do not match policy in ct.");
-        None
+        Err(RabeError::InsufficientAttributes)
     } else {
         let mut _msg = Gt::one();
         for (_i, _ct_j) in _ct._j.iter().enumerate() {
@@ -326,7 +344,10 @@ pub fn decrypt(_pk: &BdabePublicKey, _sk: &BdabeUserKey, _ct: &BdabeCiphertext)
             }
         }
         // Decrypt plaintext using derived secret from Bdabe scheme
-        decrypt_symmetric(&_msg, &_ct._ct)
+        match decrypt_symmetric(&_msg, &_ct._ct) {
+            None => Err(RabeError::SymmetricDecryptionFailed),
+            Some(_pt) => Ok(_pt),
+        }
     }
 }
 
@@ -580,7 +601,7 @@ mod tests {
             encrypt(&_pk, &vec![_att1_pk, _att2_pk], &_policy, &_plaintext).unwrap();
         // and now decrypt again with mathcing sk
         let _match = decrypt(&_pk, &_u_key, &_ct);
-        assert_eq!(_match.is_none(), true);
+        assert_eq!(_match.is_err(), true);
     }
 
 }
@@ -19,15 +19,49 @@
 //!let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec!["A".to_string(), "B".to_string()]).unwrap();
 //!assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
 //! ```
-use bn::{Fr, G1, G2, Gt, pairing};
-use rand::Rng;
+use bn::{Fr, G1, G2, Gt, Group, pairing};
+use rand::{Rng, RngCore};
+use blake2_rfc::blake2b::blake2b;
+#[cfg(feature = "compression")]
+use flate2::{Compression, read::DeflateDecoder, write::DeflateEncoder};
+use std::io::{Read, Write};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use schemes::AbeScheme;
 use utils::{
-    secretsharing::{gen_shares_str, calc_pruned_str, calc_coefficients_str},
+    secretsharing::{
+        gen_shares_str_with_rng, calc_pruned_str, calc_coefficients_indexed_str, validate_policy,
+        TRUE_ATTRIBUTE, RESERVED_PREFIX, canonicalize_policy, check_reserved, share_paths,
+    },
     tools::*,
     aes::*,
-    hash::blake2b_hash_g2
+    encoding::{check_g1, check_g2, check_gt, encode, decode, decode_reader, RabeEncode},
+    error::RabeError,
+    hash::{hash_to_fr, hash_to_g2},
+    policy::compact::{policy_to_compact, compact_to_policy},
+    policy::numeric::expand_numeric_attributes
 };
 
+// the domain of the attribute hashes, see hash_to_g2
+const BSW_HASH_DOMAIN: &'static str = "bsw-attribute";
+
+// prefix of the attributes that encode time slots
+const TIME_SLOT_PREFIX: &'static str = "time:";
+
+// the domain of the secret offsets of the master key in revocation epochs, see hash_to_fr
+const BSW_EPOCH_DOMAIN: &'static str = "bsw-epoch";
+
+// binary ciphertexts start with this tag and a format version; the encoding of a ciphertext
+// without tag starts with the length of its policy, which is never 2^64 - 1
+const CIPHERTEXT_FORMAT_TAG: [u8; 8] = [0xff; 8];
+
+// the version of the binary ciphertext format, increase it whenever CpAbeCiphertext changes;
+// versions 0 and 1 are the layouts without tag, see CpAbeCiphertextV0 and CpAbeCiphertextV1
+const CIPHERTEXT_FORMAT_VERSION: u8 = 2;
+
 /// A BSW Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct CpAbePublicKey {
@@ -53,6 +87,50 @@ pub struct CpAbeCiphertext {
     pub _c_p: Gt,
     pub _c_y: Vec<CpAbeAttribute>,
     pub _ct: Vec<u8>,
+    /// Whether the plaintext was compressed, see `encrypt_compressed`. Ciphertexts of versions
+    /// without this flag decode with `false`, in JSON by default and in binary via the format
+    /// version of `to_bytes`.
+    #[serde(default)]
+    pub _compressed: bool,
+    /// Whether `_policy` is in compact notation, see `encrypt_compact_policy`. Like
+    /// `_compressed`, ciphertexts of versions without this flag decode with `false`.
+    #[serde(default)]
+    pub _compact_policy: bool,
+}
+
+// the binary layout of a ciphertext before the format version and the flags
+#[derive(Deserialize)]
+struct CpAbeCiphertextV0 {
+    _policy: String,
+    _c: G1,
+    _c_p: Gt,
+    _c_y: Vec<CpAbeAttribute>,
+    _ct: Vec<u8>,
+}
+
+// the binary layout of a ciphertext before the format version and `_compact_policy`
+#[derive(Deserialize)]
+struct CpAbeCiphertextV1 {
+    _policy: String,
+    _c: G1,
+    _c_p: Gt,
+    _c_y: Vec<CpAbeAttribute>,
+    _ct: Vec<u8>,
+    _compressed: bool,
+}
+
+impl CpAbeCiphertextV0 {
+    fn into_ciphertext(self) -> CpAbeCiphertext {
+        return CpAbeCiphertext {
+            _policy: self._policy,
+            _c: self._c,
+            _c_p: self._c_p,
+            _c_y: self._c_y,
+            _ct: self._ct,
+            _compressed: false,
+            _compact_policy: false,
+        };
+    }
 }
 
 /// A BSW Secret User Key (SK)
@@ -70,10 +148,300 @@ pub struct CpAbeAttribute {
     pub _g2: G2,
 }
 
+/// A public commitment to an attribute of a BSW Secret User Key, i.e. `D'_j = g1^r_j`
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct CpAbeAttributeCommitment {
+    pub _str: String,
+    pub _g1: G1,
+}
+
+/// A BSW decryption challenge, see `make_challenge`
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct CpAbeChallenge {
+    pub _policy: String,
+    pub _c: G1,
+    pub _c_p: Gt,
+    pub _c_y: Vec<CpAbeAttribute>,
+}
+
+/// A table of precomputed attribute hashes. The hash of an attribute does not depend on the
+/// public key, so a table may be used with every BSW Public Key.
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct AttributeTable {
+    pub _table: BTreeMap<String, G2>,
+}
+
+/// A BSW Ciphertext whose policy was tightened by `update_policy`: the payload of the original
+/// ciphertext is encrypted again under an additional clause
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct CpAbeUpdatedCiphertext {
+    /// the original ciphertext without its payload
+    pub _inner: CpAbeCiphertext,
+    /// a ciphertext under the additional clause, its payload is the one of the original
+    pub _outer: CpAbeCiphertext,
+}
+
+/// The revocation state of a BSW authority, see `setup_revocable`. It holds no secrets: the
+/// current epoch and `e(g1, g2)^alpha_e` of every epoch so far, which encryptors need.
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct CpAbeRevocationState {
+    pub _epoch: u64,
+    pub _e_gg_alpha: BTreeMap<u64, Gt>,
+}
+
+/// A BSW ABE Context, holding the keys of an authority
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct CpAbeContext {
+    pub _msk: CpAbeMasterKey,
+    pub _pk: CpAbePublicKey,
+}
+
+impl RabeEncode for CpAbePublicKey {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_g1(&self._g1)?;
+        check_g2(&self._g2)?;
+        check_g1(&self._h)?;
+        check_g2(&self._f)?;
+        return check_gt(&self._e_gg_alpha);
+    }
+}
+
+impl RabeEncode for CpAbeMasterKey {
+    fn validate(&self) -> Result<(), RabeError> {
+        return check_g2(&self._g2_alpha);
+    }
+}
+
+impl RabeEncode for CpAbeCiphertext {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_g1(&self._c)?;
+        check_gt(&self._c_p)?;
+        for _c_y in self._c_y.iter() {
+            _c_y.validate()?;
+        }
+        return Ok(());
+    }
+
+    /// Encodes the ciphertext as bytes: the format tag and version, followed by the ciphertext.
+    fn to_bytes(&self) -> Result<Vec<u8>, RabeError> {
+        let mut _bytes = CIPHERTEXT_FORMAT_TAG.to_vec();
+        _bytes.push(CIPHERTEXT_FORMAT_VERSION);
+        _bytes.extend(encode(self)?);
+        return Ok(_bytes);
+    }
+
+    /// Decodes a ciphertext previously encoded by `to_bytes`, including ciphertexts of versions
+    /// without format version.
+    fn from_bytes(_bytes: &[u8]) -> Result<Self, RabeError> {
+        return CpAbeCiphertext::from_reader(_bytes);
+    }
+
+    fn from_base64_reader<R: Read>(mut _reader: R) -> Result<Self, RabeError> {
+        return CpAbeCiphertext::from_reader(
+            base64::read::DecoderReader::new(&mut _reader, base64::STANDARD),
+        );
+    }
+}
+
+impl CpAbeCiphertext {
+    // decodes the binary format of to_bytes and the layout without format version
+    fn from_reader<R: Read>(mut _reader: R) -> Result<CpAbeCiphertext, RabeError> {
+        let mut _head = [0u8; 8];
+        if let Err(_e) = _reader.read_exact(&mut _head) {
+            return Err(RabeError::Serialization(_e.to_string()));
+        }
+        let _ct: CpAbeCiphertext = if _head == CIPHERTEXT_FORMAT_TAG {
+            let mut _version = [0u8; 1];
+            if let Err(_e) = _reader.read_exact(&mut _version) {
+                return Err(RabeError::Serialization(_e.to_string()));
+            }
+            if _version[0] != CIPHERTEXT_FORMAT_VERSION {
+                return Err(RabeError::Serialization(
+                    format!("unknown ciphertext format {}", _version[0]),
+                ));
+            }
+            decode_reader(_reader)?
+        } else {
+            // the layouts without tag only differ in the trailing flag, so both are tried
+            let mut _bytes = _head.to_vec();
+            if let Err(_e) = _reader.read_to_end(&mut _bytes) {
+                return Err(RabeError::Serialization(_e.to_string()));
+            }
+            match decode::<CpAbeCiphertextV0>(&_bytes) {
+                Ok(_v0) => _v0.into_ciphertext(),
+                Err(_) => {
+                    let _v1: CpAbeCiphertextV1 = decode(&_bytes)?;
+                    CpAbeCiphertext {
+                        _policy: _v1._policy,
+                        _c: _v1._c,
+                        _c_p: _v1._c_p,
+                        _c_y: _v1._c_y,
+                        _ct: _v1._ct,
+                        _compressed: _v1._compressed,
+                        _compact_policy: false,
+                    }
+                }
+            }
+        };
+        _ct.validate()?;
+        return Ok(_ct);
+    }
+}
+
+impl RabeEncode for CpAbeSecretKey {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_g2(&self._d)?;
+        for _d_j in self._d_j.iter() {
+            _d_j.validate()?;
+        }
+        return Ok(());
+    }
+}
+
+impl RabeEncode for CpAbeAttribute {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_g1(&self._g1)?;
+        return check_g2(&self._g2);
+    }
+}
+
+impl RabeEncode for CpAbeAttributeCommitment {
+    fn validate(&self) -> Result<(), RabeError> {
+        return check_g1(&self._g1);
+    }
+}
+
+impl RabeEncode for CpAbeChallenge {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_g1(&self._c)?;
+        check_gt(&self._c_p)?;
+        for _c_y in self._c_y.iter() {
+            _c_y.validate()?;
+        }
+        return Ok(());
+    }
+}
+
+impl RabeEncode for AttributeTable {
+    fn validate(&self) -> Result<(), RabeError> {
+        for _hash in self._table.values() {
+            check_g2(_hash)?;
+        }
+        return Ok(());
+    }
+}
+
+impl RabeEncode for CpAbeUpdatedCiphertext {
+    fn validate(&self) -> Result<(), RabeError> {
+        self._inner.validate()?;
+        return self._outer.validate();
+    }
+}
+
+impl RabeEncode for CpAbeRevocationState {
+    fn validate(&self) -> Result<(), RabeError> {
+        for _e_gg_alpha in self._e_gg_alpha.values() {
+            check_gt(_e_gg_alpha)?;
+        }
+        return Ok(());
+    }
+}
+
+// a secret field in the output of Debug
+struct Redacted;
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "<redacted>");
+    }
+}
+
+// prints nothing but the type, so that master keys do not end up in logs
+impl fmt::Debug for CpAbeMasterKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return f.debug_struct("CpAbeMasterKey")
+            .field("beta", &Redacted)
+            .field("g2_alpha", &Redacted)
+            .finish();
+    }
+}
+
+// prints the attribute names only, the key components are redacted
+impl fmt::Debug for CpAbeSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let _attrs: Vec<&str> = self._d_j.iter().map(|_d_j| _d_j._str.as_str()).collect();
+        return f.debug_struct("CpAbeSecretKey")
+            .field("attrs", &_attrs)
+            .field("d", &Redacted)
+            .finish();
+    }
+}
+
+// the commitment to the parts e(g1, g2)^alpha and h = g1^beta of a public key
+fn master_commitment(_e_gg_alpha: &Gt, _h: &G1) -> Vec<u8> {
+    let mut _data = encode(_e_gg_alpha).expect("elements of Gt can always be encoded");
+    _data.extend(encode(_h).expect("elements of G1 can always be encoded"));
+    return blake2b(32, b"rabe-bsw-msk", &_data).as_bytes().to_vec();
+}
+
+impl CpAbeMasterKey {
+    /// Returns a public commitment to this master key, e.g. to publish it in a transparency log.
+    ///
+    /// The commitment is a hash of `e(g1, g2^alpha)` and `g1^beta`, computed from the master key
+    /// (and the generator `g1` of `_pk`). `verify_master_commitment` recomputes it from the public key
+    /// alone, so a matching commitment shows that this master key (alpha and beta) is the one behind
+    /// the public key. It reveals nothing that is not already part of the public key; in particular it
+    /// can not show which master key was used to generate a given secret key (see `verify_key`).
+    ///
+    /// # Arguments
+    ///
+    ///	* `_pk` - The Public Key (PK) generated together with this master key by setup()
+    ///
+    pub fn public_commitment(&self, _pk: &CpAbePublicKey) -> Vec<u8> {
+        return master_commitment(&pairing(_pk._g1, self._g2_alpha), &(_pk._g1 * self._beta));
+    }
+}
+
+/// Checks a commitment generated by `CpAbeMasterKey::public_commitment` against a public key.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_commitment` - A commitment to a master key
+///
+pub fn verify_master_commitment(_pk: &CpAbePublicKey, _commitment: &[u8]) -> bool {
+    return master_commitment(&_pk._e_gg_alpha, &_pk._h).as_slice() == _commitment;
+}
+
+/// Checks that a public key and a master key were generated together by setup(), e.g. after
+/// loading them from separate files and before using them in keygen. Checks the relations
+/// `e(g1, g2^alpha) = e(g1, g2)^alpha`, `h = g1^beta` and `f^beta = g2`, which detects mismatched
+/// and corrupted key pairs.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_msk` - A Master Key (MSK), generated by the function setup()
+///
+pub fn verify_keypair(_pk: &CpAbePublicKey, _msk: &CpAbeMasterKey) -> bool {
+    return pairing(_pk._g1, _msk._g2_alpha) == _pk._e_gg_alpha &&
+        _pk._g1 * _msk._beta == _pk._h && _pk._f * _msk._beta == _pk._g2;
+}
+
 /// The setup algorithm of BSW CP-ABE. Generates a new CpAbePublicKey and a new CpAbeMasterKey.
 pub fn setup() -> (CpAbePublicKey, CpAbeMasterKey) {
-    // random number generator
-    let mut _rng = rand::thread_rng();
+    return setup_with_rng(&mut rand::thread_rng());
+}
+
+/// The setup algorithm of BSW CP-ABE using the given random number generator instead of
+/// `thread_rng`, e.g. a seeded one to reproduce keys in tests. A predictable `_rng` yields a
+/// predictable master key, so never use a fixed seed in production.
+///
+/// # Arguments
+///
+///	* `_rng` - The random number generator
+///
+pub fn setup_with_rng(_rng: &mut impl RngCore) -> (CpAbePublicKey, CpAbeMasterKey) {
     // generator of group G1: g1 and generator of group G2: g2
     let _g1:G1 = _rng.gen();
     let _g2:G2 = _rng.gen();
@@ -104,477 +472,3796 @@ pub fn setup() -> (CpAbePublicKey, CpAbeMasterKey) {
 ///	* `_msk` - A Master Key (MSK), generated by the function setup()
 ///	* `_attributes` - A Vector of String attributes assigned to this user key
 ///
+/// Every key additionally holds the reserved attribute `TRUE_ATTRIBUTE`, so that it satisfies the policy `{"TRUE": true}`.
+/// Reserved attributes (see `RESERVED_PREFIX`) given in `_attributes` are rejected with `RabeError::InvalidAttribute`.
+///
+/// Duplicate attributes are removed (the first occurrence is kept), a key holds one component
+/// per attribute. Decryption and `delegate` only ever use the first component of an attribute,
+/// so rejecting duplicates would break callers without making keys any more useful.
+///
 pub fn keygen(
     _pk: &CpAbePublicKey,
     _msk: &CpAbeMasterKey,
     _attributes: &Vec<String>,
-) -> Option<CpAbeSecretKey> {
+) -> Result<CpAbeSecretKey, RabeError> {
+    return keygen_with_rng(_pk, _msk, _attributes, &mut rand::thread_rng());
+}
+
+/// Same as `keygen`, but draws all randomness of the CpAbeSecretKey from `_rng`.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_msk` - A Master Key (MSK), generated by the function setup()
+///	* `_attributes` - A Vector of String attributes assigned to this user key
+///	* `_rng` - The random number generator
+///
+pub fn keygen_with_rng(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _attributes: &Vec<String>,
+    _rng: &mut impl RngCore,
+) -> Result<CpAbeSecretKey, RabeError> {
+    return keygen_with_hash(_pk, _msk, _attributes, _rng, |_j| hash_to_g2(_j, BSW_HASH_DOMAIN));
+}
+
+/// The key generation algorithm of BSW CP-ABE for keys meant to decrypt ciphertexts of a given policy.
+/// Checks that the attributes satisfy `_intended_policy` before generating the CpAbeSecretKey.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_msk` - A Master Key (MSK), generated by the function setup()
+///	* `_attributes` - A Vector of String attributes assigned to this user key
+///	* `_intended_policy` - The access policy (as JSON String) the key is meant for
+///
+/// # Errors
+///
+///	* `RabeError::EmptyAttributes` - if `_attributes` is empty
+///	* `RabeError::InsufficientAttributes` - if `_attributes` do not satisfy `_intended_policy`
+///
+pub fn try_keygen(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _attributes: &Vec<String>,
+    _intended_policy: &String,
+) -> Result<CpAbeSecretKey, RabeError> {
+    if _attributes.is_empty() {
+        return Err(RabeError::EmptyAttributes);
+    }
+    validate_policy(_intended_policy)?;
+    // the key will hold the reserved attribute of the policy {"TRUE": true} as well
+    let mut _attr = expand_numeric_attributes(_attributes)?;
+    _attr.push(String::from(TRUE_ATTRIBUTE));
+    if !traverse_str(&_attr, _intended_policy) {
+        return Err(RabeError::InsufficientAttributes);
+    }
+    return keygen(_pk, _msk, _attributes);
+}
+
+/// The key generation algorithm of BSW CP-ABE, additionally returning a public commitment
+/// `D'_j = g1^r_j` for each attribute of the generated CpAbeSecretKey.
+///
+/// The commitments reveal nothing but the attribute names of the key. Given the commitments,
+/// anyone holding the key components `D_j` can check `e(g1, D_j) / e(D'_j, H(j)) = e(g1, g2)^r`,
+/// i.e. that each component is bound to its attribute `j` and that all components share the same
+/// randomness `r` (and thus stem from a single key). The commitments alone do not prove that a key
+/// exists or that it was derived from the master key.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_msk` - A Master Key (MSK), generated by the function setup()
+///	* `_attributes` - A Vector of String attributes assigned to this user key
+///
+pub fn keygen_with_commitments(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _attributes: &Vec<String>,
+) -> Result<(CpAbeSecretKey, Vec<CpAbeAttributeCommitment>), RabeError> {
+    let _sk = keygen(_pk, _msk, _attributes)?;
+    let _commitments = _sk._d_j
+        .iter()
+        .map(|_d_j| {
+            CpAbeAttributeCommitment {
+                _str: _d_j._str.clone(),
+                _g1: _d_j._g1,
+            }
+        })
+        .collect();
+    return Ok((_sk, _commitments));
+}
+
+// keygen using the given function to hash attributes to G2
+fn keygen_with_hash<F>(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _attributes: &Vec<String>,
+    _rng: &mut impl RngCore,
+    _hash: F,
+) -> Result<CpAbeSecretKey, RabeError>
+where
+    F: Fn(&String) -> G2,
+{
+    // the reserved attributes are only added by issue_key
+    check_reserved(_attributes)?;
+    return issue_key(_pk, _msk, _attributes, _rng, _hash);
+}
+
+// keygen_with_hash, but accepting reserved attributes, e.g. the bits of numeric attributes
+fn issue_key<F>(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _attributes: &Vec<String>,
+    _rng: &mut impl RngCore,
+    _hash: F,
+) -> Result<CpAbeSecretKey, RabeError>
+where
+    F: Fn(&String) -> G2,
+{
     // if no attibutes or an empty policy
     // maybe add empty msk also here
-    if _attributes.is_empty() || _attributes.len() == 0 {
-        return None;
+    if _attributes.is_empty() {
+        return Err(RabeError::EmptyAttributes);
     }
-    // random number generator
-    let mut _rng = rand::thread_rng();
     // generate random r1 and r2 and sum of both
     // compute Br as well because it will be used later too
     let _r:Fr = _rng.gen();
     let _g_r = _pk._g2 * _r;
     let _d = (_msk._g2_alpha + _g_r) * _msk._beta.inverse().unwrap();
     let mut _d_j: Vec<CpAbeAttribute> = Vec::new();
-    for _j in _attributes {
+    // every key holds the bits of its numeric attributes and the reserved attribute of the
+    // policy {"TRUE": true}
+    let mut _attributes = expand_numeric_attributes(_attributes)?;
+    let _true = String::from(TRUE_ATTRIBUTE);
+    if !contains(&_attributes, &_true) {
+        _attributes.push(_true);
+    }
+    let mut _seen: HashSet<String> = HashSet::new();
+    _attributes.retain(|_j| _seen.insert(_j.clone()));
+    for _j in &_attributes {
         let _r_j:Fr = _rng.gen();
         _d_j.push(CpAbeAttribute {
             _str: _j.clone(), // attribute name
             _g1: _pk._g1 * _r_j, // D_j Prime
-            _g2: _g_r + (blake2b_hash_g2(_pk._g2, &_j) * _r_j), // D_j
+            _g2: _g_r + (_hash(_j) * _r_j), // D_j
         });
     }
-    return Some(CpAbeSecretKey {_d, _d_j});
+    return Ok(CpAbeSecretKey {_d, _d_j});
+}
+
+/// Checks that a CpAbeSecretKey is well-formed and belongs to the authority of the given CpAbePublicKey,
+/// e.g. after loading it from storage.
+///
+/// For the shared randomness `X = e(g1, g2)^r` of the key, every attribute component has to satisfy
+/// `e(g1, D_j) / e(D'_j, H(j)) = X` and the key component `D` has to satisfy `e(h, D) = e(g1, g2)^alpha * X`.
+/// This detects corrupted or tampered components, components mixed from different keys and keys of another
+/// authority. It can not tell whether a well-formed key was issued to the user presenting it, and it costs
+/// two pairings per attribute.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_sk` - A Secret User Key (SK), generated by the function keygen()
+///
+pub fn verify_key(_pk: &CpAbePublicKey, _sk: &CpAbeSecretKey) -> bool {
+    if _sk._d_j.is_empty() {
+        return false;
+    }
+    let _x = pairing(_pk._h, _sk._d) * _pk._e_gg_alpha.inverse();
+    return _sk._d_j.iter().all(|_d_j| {
+        pairing(_pk._g1, _d_j._g2) ==
+            _x * pairing(_d_j._g1, hash_to_g2(&_d_j._str, BSW_HASH_DOMAIN))
+    });
 }
 
 /// The delegate generation algorithm of BSW CP-ABE. Generates a new CpAbeSecretKey using a CpAbePublicKey, a CpAbeSecretKey and a subset of attributes (of the key _sk) given as Vec<String>.
 ///
+/// The delegated key is re-randomized: a fresh `r` is added to the shared randomness of the key
+/// and every attribute component receives a fresh `r_j`. Thus the delegated key is independent of
+/// `_sk` and can not be combined with it (or other keys) to decrypt more than either key alone.
+///
+/// Attributes of the subset are matched against the key after normalization (see
+/// `normalize_attribute`), the delegated key keeps the attribute names stored in `_sk`.
+///
 /// # Arguments
 ///
 ///	* `_pk` - A Public Key (PK), generated by the function setup()
 ///	* `_sk` - A Secret User Key (SK), generated by the function keygen()
-///	* `_attributes` - A Vector of String attributes assigned to this user key
+///	* `_subset` - A Vector of String attributes of `_sk` that the delegated key should contain
+///
+/// # Errors
+///
+///	* `RabeError::EmptyAttributes` - if `_subset` is empty
+///	* `RabeError::AttributeNotInKey` - if an attribute of `_subset` is not part of `_sk`
 ///
 pub fn delegate(
     _pk: &CpAbePublicKey,
     _sk: &CpAbeSecretKey,
     _subset: &Vec<String>,
-) -> Option<CpAbeSecretKey> {
-    let _str_attr = _sk._d_j
-        .iter()
-        .map(|_values| _values._str.to_string())
-        .collect::<Vec<_>>();
-
-    return if !is_subset(&_subset, &_str_attr) {
-        println!("Error: the given attribute set is not a subset of the given sk.");
-        None
-    } else {
-        // if no attibutes or an empty policy
-        // maybe add empty msk also here
-        if _subset.is_empty() || _subset.len() == 0 {
-            println!("Error: the given attribute subset is empty.");
-            return None;
-        }
-        // random number generator
-        let mut _rng = rand::thread_rng();
-        // generate random r
-        let _r: Fr = _rng.gen();
-        // calculate derived _k_0
-        let mut _d_k: Vec<CpAbeAttribute> = Vec::new();
-        // calculate derived attributes
-        for _attr in _subset {
-            let _r_j: Fr = _rng.gen();
-            let _d_j_val = _sk._d_j
-                .iter()
-                .find(|x| x._str == _attr.to_string())
-                .map(|x| (x._g1, x._g2))
-                .unwrap();
-            _d_k.push(CpAbeAttribute {
-                _str: _attr.clone(),
-                _g1: _d_j_val.0 + (_pk._g1 * _r_j),
-                _g2: _d_j_val.1 + (blake2b_hash_g2(_pk._g2, &_attr) * _r_j) + (_pk._g2 * _r),
-            });
-        }
-        Some(CpAbeSecretKey {
-            _d: _sk._d + (_pk._f * _r),
-            _d_j: _d_k,
-        })
+) -> Result<CpAbeSecretKey, RabeError> {
+    // the delegated key needs at least one attribute
+    if _subset.is_empty() {
+        return Err(RabeError::EmptyAttributes);
+    }
+    // the delegated key keeps the reserved attribute of the policy {"TRUE": true}
+    let mut _subset = _subset.clone();
+    let _true = String::from(TRUE_ATTRIBUTE);
+    if !contains(&_subset, &_true) && _sk._d_j.iter().any(|x| x._str == _true) {
+        _subset.push(_true);
+    }
+    // as well as the bits of its numeric attributes
+    for _bit in expand_numeric_attributes(&_subset)?.into_iter().skip(_subset.len()) {
+        if _sk._d_j.iter().any(|x| x._str == _bit) {
+            _subset.push(_bit);
+        }
+    }
+    // random number generator
+    let mut _rng = rand::thread_rng();
+    // generate random r
+    let _r: Fr = _rng.gen();
+    // calculate derived attributes
+    let mut _d_k: Vec<CpAbeAttribute> = Vec::new();
+    for _attr in &_subset {
+        let _attr = normalize_attribute(_attr);
+        let _d_j = match _sk._d_j.iter().find(
+            |x| normalize_attribute(&x._str) == _attr,
+        ) {
+            Some(_d_j) => _d_j,
+            None => return Err(RabeError::AttributeNotInKey(_attr)),
+        };
+        // an attribute given twice is delegated once
+        if _d_k.iter().any(|x| x._str == _d_j._str) {
+            continue;
+        }
+        let _r_j: Fr = _rng.gen();
+        _d_k.push(CpAbeAttribute {
+            _str: _d_j._str.clone(),
+            _g1: _d_j._g1 + (_pk._g1 * _r_j),
+            _g2: _d_j._g2 + (hash_to_g2(&_d_j._str, BSW_HASH_DOMAIN) * _r_j) + (_pk._g2 * _r),
+        });
     }
+    return Ok(CpAbeSecretKey {
+        _d: _sk._d + (_pk._f * _r),
+        _d_j: _d_k,
+    });
 }
 
-/// The encrypt algorithm of BSW CP-ABE. Generates a new CpAbeCiphertext using an Ac17PublicKey, an access policy given as String and some plaintext data given as [u8].
+/// Extends a CpAbeSecretKey by a new attribute without reissuing the whole key. The existing
+/// components are kept, so ciphertexts the key could decrypt before are still decryptable.
+///
+/// A new component `D_j = g2^r * H(j)^r_j` needs the shared randomness `g2^r` of the key. It
+/// is not stored in the key, but recomputed as `D^beta / g2^alpha` using the master key, so the
+/// key format stays unchanged. A numeric attribute adds the components of its bits as well.
+///
+/// Returns None if the attribute is empty, reserved (see `RESERVED_PREFIX`), already part of
+/// the key, conflicts with a numeric attribute of the key, or if the key was not issued using
+/// `_msk`.
 ///
 /// # Arguments
 ///
 ///	* `_pk` - A Public Key (PK), generated by the function setup()
-///	* `_policy` - An access policy given as JSON String
-///	* `_plaintext` - plaintext data given as a Vector of u8
+///	* `_msk` - The Master Key (MSK) `_sk` was generated with
+///	* `_sk` - A Secret User Key (SK), generated by the function keygen()
+///	* `_attr` - The new attribute
 ///
-pub fn encrypt(
+pub fn add_attribute(
     _pk: &CpAbePublicKey,
-    _policy: &String,
-    _plaintext: &Vec<u8>,
-) -> Option<CpAbeCiphertext> {
-    if _plaintext.is_empty() || _policy.is_empty() {
+    _msk: &CpAbeMasterKey,
+    _sk: &CpAbeSecretKey,
+    _attr: &String,
+) -> Option<CpAbeSecretKey> {
+    if _attr.is_empty() || _attr.starts_with(RESERVED_PREFIX) ||
+        _sk._d_j.iter().any(|x| x._str == *_attr)
+    {
+        return None;
+    }
+    // recover g2^r and check it against a component of the key
+    let _g_r = (_sk._d * _msk._beta) - _msk._g2_alpha;
+    let _first = _sk._d_j.first()?;
+    if pairing(_pk._g1, _first._g2) !=
+        pairing(_pk._g1, _g_r) * pairing(_first._g1, hash_to_g2(&_first._str, BSW_HASH_DOMAIN))
+    {
+        return None;
+    }
+    // the new attribute and its bits, checked against the numeric attributes of the key
+    let mut _names: Vec<String> = _sk._d_j.iter().map(|x| x._str.clone()).collect();
+    _names.push(_attr.clone());
+    let _expanded = expand_numeric_attributes(&vec![_attr.clone()]);
+    if expand_numeric_attributes(&_names).is_err() || _expanded.is_err() {
         return None;
     }
     let mut _rng = rand::thread_rng();
-    // the shared root secret
-    let _s:Fr = _rng.gen();
-    let _msg: Gt = _rng.gen();
-    let _shares: Vec<(String, Fr)> = gen_shares_str(_s, _policy).unwrap();
-    let _c = _pk._h * _s;
-    let _c_p = _pk._e_gg_alpha.pow(_s) * _msg;
-    let mut _c_y: Vec<CpAbeAttribute> = Vec::new();
-    for (_j, _j_val) in _shares {
-        _c_y.push(CpAbeAttribute {
-            _str: _j.clone(),
-            _g1: _pk._g1 * _j_val,
-            _g2: blake2b_hash_g2(_pk._g2, &_j) * _j_val,
+    let mut _result = _sk.clone();
+    for _j in _expanded.unwrap() {
+        if _result._d_j.iter().any(|x| x._str == _j) {
+            continue;
+        }
+        let _r_j: Fr = _rng.gen();
+        _result._d_j.push(CpAbeAttribute {
+            _g1: _pk._g1 * _r_j,
+            _g2: _g_r + (hash_to_g2(&_j, BSW_HASH_DOMAIN) * _r_j),
+            _str: _j,
         });
     }
-    let _policy = _policy.to_string();
-    let _ct = encrypt_symmetric(&_msg, &_plaintext).unwrap();
-    //Encrypt plaintext using derived key from secret
-    return Some(CpAbeCiphertext {_policy, _c, _c_p, _c_y, _ct});
+    return Some(_result);
 }
 
-/// The decrypt algorithm of BSW CP-ABE. Reconstructs the original plaintext data as Vec<u8>, given a CpAbeCiphertext with a matching CpAbeSecretKey.
+/// The encrypt algorithm of BSW CP-ABE. Generates a new CpAbeCiphertext using a CpAbePublicKey, an access policy given as String and some plaintext data given as [u8].
+///
+/// The plaintext is encrypted using authenticated AES-256/GCM, so `decrypt` detects a modified payload.
 ///
 /// # Arguments
 ///
-///	* `_sk` - A Secret Key (SK), generated by the function keygen()
-///	* `_ct` - An BSW CP-ABE Ciphertext
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
 ///
-pub fn decrypt(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Option<Vec<u8>> {
-    let _str_attr = _sk._d_j
-        .iter()
-        .map(|_values| _values._str.to_string())
-        .collect::<Vec<_>>();
-    return if traverse_str(&_str_attr, &_ct._policy) == false {
-        None
-    } else {
-        match calc_pruned_str(&_str_attr, &_ct._policy) {
-            None => None,
-            Some(_pruned) => {
-                if !_pruned.0 {
-                    None
-                } else {
-                    println!("_pruned {:?}", _pruned.1);
-                    let _z = calc_coefficients_str(&_ct._policy).unwrap();
-                    let mut _a = Gt::one();
-                    for _j in _pruned.1 {
-                        match _ct._c_y.iter().find(|x| x._str == _j.to_string()) {
-                            Some(_c_j) => {
-                                match _sk._d_j.iter().find(|x| x._str == _j.to_string()) {
-                                    Some(_d_j) => {
-                                        for _z_tuple in _z.iter() {
-                                            if _z_tuple.0 == _j {
-                                                _a = _a *
-                                                    (pairing(_c_j._g1, _d_j._g2) *
-                                                        pairing(_d_j._g1, _c_j._g2).inverse())
-                                                        .pow(_z_tuple.1);
-                                            }
-                                        }
-                                    }
-                                    None => {
-                                        // do nothing
-                                    }
-                                }
-                            }
-                            None => {
-                                // do nothing
-                            }
-                        }
-                    }
-                    let _msg = _ct._c_p * ((pairing(_ct._c, _sk._d)) * _a.inverse()).inverse();
-                    // Decrypt plaintext using derived secret from cp-abe scheme
-                    decrypt_symmetric(&_msg, &_ct._ct)
-                }
+pub fn encrypt(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+) -> Result<CpAbeCiphertext, RabeError> {
+    return encrypt_with_rng(_pk, _policy, _plaintext, &mut rand::thread_rng());
+}
+
+/// Same as `encrypt`, but draws all randomness of the CpAbeCiphertext (including the nonce of
+/// the payload) from `_rng`.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///	* `_rng` - The random number generator
+///
+pub fn encrypt_with_rng(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+    _rng: &mut impl RngCore,
+) -> Result<CpAbeCiphertext, RabeError> {
+    let _s: Fr = _rng.gen();
+    return encrypt_with_hash(_pk, _policy, _plaintext, _s, false, _rng, |_j| {
+        hash_to_g2(_j, BSW_HASH_DOMAIN)
+    });
+}
+
+// encrypt using the given root secret _s and the given function to hash attributes to G2,
+// the iv of the symmetric encryption is either random or derived from _c and _c_p
+fn encrypt_with_hash<F>(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+    _s: Fr,
+    _derive_iv: bool,
+    _rng: &mut impl RngCore,
+    _hash: F,
+) -> Result<CpAbeCiphertext, RabeError>
+where
+    F: Fn(&String) -> G2,
+{
+    if _plaintext.is_empty() {
+        return Err(RabeError::Encryption(String::from("empty plaintext")));
+    }
+    let (mut _ct, _msg) = encapsulate_with_hash(_pk, _policy, _s, _rng, _hash)?;
+    //Encrypt plaintext using derived key from secret
+    let _symmetric = if _derive_iv {
+        let mut _nonce_data = encode(&_ct._c)?;
+        _nonce_data.extend(encode(&_ct._c_p)?);
+        encrypt_symmetric_gcm_derived_nonce(&_msg, &_plaintext, &_nonce_data)
+    } else {
+        encrypt_symmetric_gcm_with_rng(&_msg, &_plaintext, _rng)
+    };
+    match _symmetric {
+        None => return Err(RabeError::Encryption(String::from("symmetric encryption failed"))),
+        Some(_payload) => {
+            _ct._ct = _payload;
+            return Ok(_ct);
+        }
+    }
+}
+
+// encapsulates a random element of Gt under the policy using the root secret _s, the returned
+// ciphertext has no payload
+fn encapsulate_with_hash<F>(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _s: Fr,
+    _rng: &mut impl RngCore,
+    _hash: F,
+) -> Result<(CpAbeCiphertext, Gt), RabeError>
+where
+    F: Fn(&String) -> G2,
+{
+    validate_policy(_policy)?;
+    let _msg: Gt = _rng.gen();
+    let _shares: Vec<(String, Fr)> = gen_shares_str_with_rng(_s, _policy, _rng)?;
+    let _c = _pk._h * _s;
+    let _c_p = _pk._e_gg_alpha.pow(_s) * _msg;
+    let mut _c_y: Vec<CpAbeAttribute> = Vec::new();
+    for (_j, _j_val) in _shares {
+        _c_y.push(CpAbeAttribute {
+            _g1: _pk._g1 * _j_val,
+            _g2: _hash(&_j) * _j_val,
+            _str: _j,
+        });
+    }
+    let _policy = _policy.to_string();
+    let _ct = Vec::new();
+    return Ok((
+        CpAbeCiphertext {_policy, _c, _c_p, _c_y, _ct, _compressed: false, _compact_policy: false},
+        _msg,
+    ));
+}
+
+/// The encrypt algorithm of BSW CP-ABE, deriving the iv of the symmetric encryption from the
+/// components `_c` and `_c_p` of the CpAbeCiphertext instead of using random bytes. Thus the payload
+/// `_ct` only depends on the randomness of the ABE layer, which makes ciphertexts reproducible
+/// under a deterministic random number generator.
+///
+/// The derived iv is unique because every ciphertext has a fresh secret `s` (and message). Reusing
+/// `s` (see `encrypt_with_secret`) with this mode could reuse the iv. Its first 12 bytes are used
+/// as the nonce of the authenticated AES-GCM encryption.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///
+pub fn encrypt_with_derived_iv(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+) -> Result<CpAbeCiphertext, RabeError> {
+    let _s: Fr = rand::thread_rng().gen();
+    return encrypt_with_hash(_pk, _policy, _plaintext, _s, true, &mut rand::thread_rng(), |_j| {
+        hash_to_g2(_j, BSW_HASH_DOMAIN)
+    });
+}
+
+/// Remembers the ephemeral secrets `s` of ciphertexts (by their commitment `C = h^s`), so that
+/// `encrypt_with_secret` can reject a secret that has been used before.
+#[derive(Clone, Default)]
+pub struct SecretTracker {
+    _seen: HashSet<Vec<u8>>,
+}
+
+// read-only accessor for white-box tests, see the `test-internals` feature
+#[cfg(feature = "test-internals")]
+impl SecretTracker {
+    pub fn seen(&self) -> &HashSet<Vec<u8>> {
+        return &self._seen;
+    }
+}
+
+impl SecretTracker {
+    /// Returns a new, empty SecretTracker.
+    pub fn new() -> SecretTracker {
+        return SecretTracker { _seen: HashSet::new() };
+    }
+
+    /// Returns true if a ciphertext with the commitment `_c` (i.e. `CpAbeCiphertext._c`) has been tracked.
+    pub fn contains(&self, _c: &G1) -> bool {
+        match encode(_c) {
+            Err(_) => return false,
+            Ok(_bytes) => return self._seen.contains(&_bytes),
+        }
+    }
+
+    /// Tracks the secret of a ciphertext with the commitment `_c`. Returns false if it was already tracked.
+    pub fn insert(&mut self, _c: &G1) -> bool {
+        match encode(_c) {
+            Err(_) => return false,
+            Ok(_bytes) => return self._seen.insert(_bytes),
+        }
+    }
+}
+
+/// The encrypt algorithm of BSW CP-ABE using the given root secret `s` instead of a random one.
+///
+/// Reusing `s` for two ciphertexts can leak information, so protocols using this function have to
+/// make sure every secret is used only once. If a SecretTracker is given, secrets it has already seen
+/// are rejected and the secret of the new ciphertext is tracked.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///	* `_secret` - The root secret `s` of the ciphertext
+///	* `_tracker` - An optional SecretTracker
+///
+/// # Errors
+///
+///	* `RabeError::EphemeralReuse` - if `_tracker` has already seen `_secret`
+///
+pub fn encrypt_with_secret(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+    _secret: Fr,
+    _tracker: Option<&mut SecretTracker>,
+) -> Result<CpAbeCiphertext, RabeError> {
+    let _hash = |_j: &String| hash_to_g2(_j, BSW_HASH_DOMAIN);
+    let mut _rng = rand::thread_rng();
+    match _tracker {
+        None => return encrypt_with_hash(_pk, _policy, _plaintext, _secret, false, &mut _rng, _hash),
+        Some(_tracker) => {
+            if _tracker.contains(&(_pk._h * _secret)) {
+                return Err(RabeError::EphemeralReuse);
+            }
+            let _ct = encrypt_with_hash(_pk, _policy, _plaintext, _secret, false, &mut _rng, _hash)?;
+            _tracker.insert(&_ct._c);
+            return Ok(_ct);
+        }
+    }
+}
+
+/// Returns the attribute that encodes the time slot `_slot`, i.e. `time:<slot>`.
+pub fn time_slot_attribute(_slot: u64) -> String {
+    return format!("{}{}", TIME_SLOT_PREFIX, _slot);
+}
+
+/// The key generation algorithm of BSW CP-ABE for time-bound keys. In addition to the given
+/// attributes, the CpAbeSecretKey holds the time slot attribute (see `time_slot_attribute`) of each valid slot.
+/// Since time slots are ordinary attributes, a key can only decrypt a CpAbeCiphertext generated by
+/// `encrypt_at` for one of its slots.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_msk` - A Master Key (MSK), generated by the function setup()
+///	* `_attributes` - A Vector of String attributes assigned to this user key
+///	* `_valid_slots` - The time slots in which this user key is valid
+///
+pub fn keygen_timeslot(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _attributes: &Vec<String>,
+    _valid_slots: &[u64],
+) -> Result<CpAbeSecretKey, RabeError> {
+    let mut _attr = _attributes.clone();
+    for _slot in _valid_slots {
+        let _slot_attr = time_slot_attribute(*_slot);
+        if !contains(&_attr, &_slot_attr) {
+            _attr.push(_slot_attr);
+        }
+    }
+    return keygen(_pk, _msk, &_attr);
+}
+
+/// The encrypt algorithm of BSW CP-ABE for time-bound ciphertexts. The access policy is ANDed with the
+/// time slot attribute of `_slot`, so that only keys valid in this slot (see `keygen_timeslot`) can decrypt.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_slot` - The time slot of this ciphertext
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///
+pub fn encrypt_at(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _slot: u64,
+    _plaintext: &Vec<u8>,
+) -> Result<CpAbeCiphertext, RabeError> {
+    if _policy.is_empty() {
+        return Err(RabeError::EmptyPolicy);
+    }
+    let _timed_policy = format!(
+        r#"{{"AND": [{}, {{"ATT": "{}"}}]}}"#,
+        _policy,
+        time_slot_attribute(_slot)
+    );
+    return encrypt(_pk, &_timed_policy, _plaintext);
+}
+
+// the secret offset delta_e of the master secret alpha in epoch _epoch, a hash keyed with beta
+fn epoch_delta(_msk: &CpAbeMasterKey, _epoch: u64) -> Result<Fr, RabeError> {
+    let _key: String = encode(&_msk._beta)?.iter().map(|_b| format!("{:02x}", _b)).collect();
+    return Ok(hash_to_fr(&format!("{}:{}", _key, _epoch), BSW_EPOCH_DOMAIN));
+}
+
+// the master key of epoch _epoch, i.e. with g2^alpha_e = g2^(alpha + delta_e)
+fn epoch_master_key(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _epoch: u64,
+) -> Result<CpAbeMasterKey, RabeError> {
+    return Ok(CpAbeMasterKey {
+        _beta: _msk._beta,
+        _g2_alpha: _msk._g2_alpha + (_pk._g2 * epoch_delta(_msk, _epoch)?),
+    });
+}
+
+/// The setup algorithm of BSW CP-ABE with revocation. Same as `setup`, the authority starts
+/// in epoch 0 of the returned CpAbeRevocationState.
+///
+/// Revocation is folded into the blinding factor `e(g1, g2)^(alpha * s)` of the ciphertexts: in
+/// epoch e the master secret is `alpha_e = alpha + delta_e`, where `delta_e` is a hash of e
+/// keyed with the secret `beta`. Keys of epoch e (see `keygen_revocable`) are BSW keys for
+/// `alpha_e` and ciphertexts of epoch e (see `encrypt_revocable`) are blinded by
+/// `e(g1, g2)^(alpha_e * s)`, everything else is plain BSW. After `revoke`, ciphertexts are bound
+/// to the new epoch and all keys of older epochs, including the revoked ones, fail on them, as
+/// `delta_e` of the new epoch is only known to the authority. Users that are not revoked need a
+/// key of the new epoch. Ciphertexts of older epochs can still be decrypted with the old keys.
+pub fn setup_revocable() -> (CpAbePublicKey, CpAbeMasterKey, CpAbeRevocationState) {
+    let (_pk, _msk) = setup();
+    let _msk_0 =
+        epoch_master_key(&_pk, &_msk, 0).expect("elements of Fr can always be encoded");
+    let mut _e_gg_alpha = BTreeMap::new();
+    _e_gg_alpha.insert(0, pairing(_pk._g1, _msk_0._g2_alpha));
+    return (_pk, _msk, CpAbeRevocationState { _epoch: 0, _e_gg_alpha });
+}
+
+/// The key generation algorithm of BSW CP-ABE with revocation. Generates a CpAbeSecretKey of
+/// the current epoch of `_state` holding the given attributes. Note that functions taking the
+/// master key, such as `add_attribute`, do not accept keys of epochs.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup_revocable()
+///	* `_msk` - A Master Key (MSK), generated by the function setup_revocable()
+///	* `_state` - The revocation state, generated by the function setup_revocable()
+///	* `_attributes` - A Vector of String attributes assigned to this user key
+///
+pub fn keygen_revocable(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _state: &CpAbeRevocationState,
+    _attributes: &Vec<String>,
+) -> Result<CpAbeSecretKey, RabeError> {
+    return keygen(_pk, &epoch_master_key(_pk, _msk, _state._epoch)?, _attributes);
+}
+
+/// The encrypt algorithm of BSW CP-ABE with revocation. The CpAbeCiphertext is bound to
+/// `_epoch`, or to the current epoch of `_state` if `_epoch` is None, so that only keys issued
+/// in this epoch (see `keygen_revocable`) can decrypt. It is decrypted with `decrypt`.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup_revocable()
+///	* `_state` - The revocation state of the authority
+///	* `_epoch` - The epoch of this ciphertext, the current epoch if None
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///
+/// # Errors
+///
+///	* `RabeError::InvalidAttribute` - if `_state` does not know `_epoch`
+///
+pub fn encrypt_revocable(
+    _pk: &CpAbePublicKey,
+    _state: &CpAbeRevocationState,
+    _epoch: Option<u64>,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+) -> Result<CpAbeCiphertext, RabeError> {
+    let _epoch = _epoch.unwrap_or(_state._epoch);
+    let mut _epoch_pk = _pk.clone();
+    match _state._e_gg_alpha.get(&_epoch) {
+        Some(_e_gg_alpha) => _epoch_pk._e_gg_alpha = *_e_gg_alpha,
+        None => return Err(RabeError::InvalidAttribute(format!("unknown epoch {}", _epoch))),
+    }
+    return encrypt(&_epoch_pk, _policy, _plaintext);
+}
+
+/// Revokes all keys issued so far by moving the revocation state to `_epoch`, which has to be
+/// later than the current epoch. Returns the new epoch.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup_revocable()
+///	* `_msk` - A Master Key (MSK), generated by the function setup_revocable()
+///	* `_state` - The revocation state of the authority
+///	* `_epoch` - The new epoch
+///
+/// # Errors
+///
+///	* `RabeError::InvalidAttribute` - if `_epoch` is not later than the current epoch
+///
+pub fn revoke(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _state: &mut CpAbeRevocationState,
+    _epoch: u64,
+) -> Result<u64, RabeError> {
+    if _epoch <= _state._epoch {
+        return Err(RabeError::InvalidAttribute(format!(
+            "epoch {} is not later than the current epoch {}",
+            _epoch,
+            _state._epoch
+        )));
+    }
+    let _msk_e = epoch_master_key(_pk, _msk, _epoch)?;
+    _state._e_gg_alpha.insert(_epoch, pairing(_pk._g1, _msk_e._g2_alpha));
+    _state._epoch = _epoch;
+    return Ok(_epoch);
+}
+
+/// Builds an AttributeTable of precomputed attribute hashes, usable with every CpAbePublicKey.
+///
+/// Hashing an attribute to G2 is a scalar multiplication in G2 and part of every keygen and
+/// encrypt. Authorities and senders working with a fixed attribute universe can compute the
+/// hashes once and pass the table to keygen_with_table() and encrypt_with_table().
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_universe` - All attributes that should be part of the table
+///
+pub fn build_attribute_table(_pk: &CpAbePublicKey, _universe: &Vec<String>) -> AttributeTable {
+    let mut _table = AttributeTable {
+        _table: BTreeMap::new(),
+    };
+    for _attr in _universe {
+        _table.insert(_attr);
+    }
+    return _table;
+}
+
+impl CpAbePublicKey {
+    /// Precomputes the hashes of an attribute universe, same as `build_attribute_table`. Pass the
+    /// table to `encrypt_with_table` and `keygen_with_table` to skip hashing the attributes.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_universe` - All attributes that should be part of the table
+    ///
+    pub fn precompute(&self, _universe: &[String]) -> AttributeTable {
+        return build_attribute_table(self, &_universe.to_vec());
+    }
+}
+
+impl AttributeTable {
+    /// Adds an attribute to the table (if not already present).
+    pub fn insert(&mut self, _attr: &String) {
+        if !self._table.contains_key(_attr) {
+            let _hash = hash_to_g2(_attr, BSW_HASH_DOMAIN);
+            self._table.insert(_attr.clone(), _hash);
+        }
+    }
+
+    /// Returns the hash of an attribute, computing it if it is not part of the table.
+    pub fn hash(&self, _attr: &String) -> G2 {
+        match self._table.get(_attr) {
+            Some(_hash) => return *_hash,
+            None => return hash_to_g2(_attr, BSW_HASH_DOMAIN),
+        }
+    }
+}
+
+/// The key generation algorithm of BSW CP-ABE, using an AttributeTable for the attribute hashes.
+/// Attributes not part of the table are hashed on demand.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_msk` - A Master Key (MSK), generated by the function setup()
+///	* `_table` - An AttributeTable, generated by the function build_attribute_table()
+///	* `_attributes` - A Vector of String attributes assigned to this user key
+///
+pub fn keygen_with_table(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _table: &AttributeTable,
+    _attributes: &Vec<String>,
+) -> Result<CpAbeSecretKey, RabeError> {
+    return keygen_with_hash(_pk, _msk, _attributes, &mut rand::thread_rng(), |_j| _table.hash(_j));
+}
+
+/// The key generation algorithm of BSW CP-ABE for keys holding wildcard attributes like
+/// `dept.*` (see `matches_attribute`). Each wildcard is replaced by the attributes of
+/// `_universe` it matches, e.g. `dept.sales` and `dept.hr`, and the CpAbeSecretKey holds a
+/// component for each of them.
+///
+/// A key component is bound to a concrete attribute name, so the wildcard can not be derived
+/// into components later on: attributes added to the universe after keygen require a new key.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_msk` - A Master Key (MSK), generated by the function setup()
+///	* `_attributes` - A Vector of String attributes assigned to this user key, possibly wildcards
+///	* `_universe` - The concrete attributes the wildcards are expanded to
+///
+/// # Errors
+///
+///	* `RabeError::EmptyAttributes` - if no attribute is left after expanding the wildcards
+///
+pub fn keygen_with_wildcards(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _attributes: &Vec<String>,
+    _universe: &Vec<String>,
+) -> Result<CpAbeSecretKey, RabeError> {
+    return keygen(_pk, _msk, &expand_wildcards(_attributes, _universe));
+}
+
+/// The encrypt algorithm of BSW CP-ABE, using an AttributeTable for the attribute hashes.
+/// Attributes not part of the table are hashed on demand.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_table` - An AttributeTable, generated by the function build_attribute_table()
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///
+pub fn encrypt_with_table(
+    _pk: &CpAbePublicKey,
+    _table: &AttributeTable,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+) -> Result<CpAbeCiphertext, RabeError> {
+    let _s: Fr = rand::thread_rng().gen();
+    return encrypt_with_hash(_pk, _policy, _plaintext, _s, false, &mut rand::thread_rng(), |_j| {
+        _table.hash(_j)
+    });
+}
+
+/// A session-scoped cache of attribute hashes, shared between `keygen_cached` and `encrypt_cached`.
+///
+/// Unlike an AttributeTable, the cache is filled on demand. The hash of an attribute does not
+/// depend on the public key, so the cache may be used with several public keys. The cache is
+/// `Sync`, threads may share it.
+#[derive(Default)]
+pub struct HashCache {
+    _hashes: Mutex<HashMap<String, G2>>,
+}
+
+// read-only accessor for white-box tests, see the `test-internals` feature. The Mutex itself
+// is not handed out, a snapshot of the cached hashes is returned instead
+#[cfg(feature = "test-internals")]
+impl HashCache {
+    pub fn hashes(&self) -> HashMap<String, G2> {
+        return self.lock().clone();
+    }
+}
+
+impl HashCache {
+    /// Returns a new, empty HashCache.
+    pub fn new() -> HashCache {
+        return HashCache { _hashes: Mutex::new(HashMap::new()) };
+    }
+
+    // a thread that panicked while holding the lock can not leave a wrong hash behind, so a
+    // poisoned lock is used as is
+    fn lock(&self) -> MutexGuard<HashMap<String, G2>> {
+        return self._hashes.lock().unwrap_or_else(|_e| _e.into_inner());
+    }
+
+    /// Returns the hash of an attribute to G2, computing and caching it if necessary.
+    pub fn hash(&self, _attr: &String) -> G2 {
+        if let Some(_hash) = self.lock().get(_attr) {
+            return *_hash;
+        }
+        // hashed without holding the lock, so other threads are not blocked meanwhile
+        let _hash = hash_to_g2(_attr, BSW_HASH_DOMAIN);
+        self.lock().insert(_attr.clone(), _hash);
+        return _hash;
+    }
+
+    /// Returns the number of cached hashes.
+    pub fn len(&self) -> usize {
+        return self.lock().len();
+    }
+
+    /// Returns true if no hashes are cached.
+    pub fn is_empty(&self) -> bool {
+        return self.lock().is_empty();
+    }
+}
+
+/// The key generation algorithm of BSW CP-ABE, using a HashCache for the attribute hashes.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_msk` - A Master Key (MSK), generated by the function setup()
+///	* `_cache` - A HashCache
+///	* `_attributes` - A Vector of String attributes assigned to this user key
+///
+pub fn keygen_cached(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _cache: &HashCache,
+    _attributes: &Vec<String>,
+) -> Result<CpAbeSecretKey, RabeError> {
+    return keygen_with_hash(_pk, _msk, _attributes, &mut rand::thread_rng(), |_j| {
+        _cache.hash(_j)
+    });
+}
+
+/// The encrypt algorithm of BSW CP-ABE, using a HashCache for the attribute hashes.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_cache` - A HashCache
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///
+pub fn encrypt_cached(
+    _pk: &CpAbePublicKey,
+    _cache: &HashCache,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+) -> Result<CpAbeCiphertext, RabeError> {
+    let _s: Fr = rand::thread_rng().gen();
+    return encrypt_with_hash(_pk, _policy, _plaintext, _s, false, &mut rand::thread_rng(), |_j| {
+        _cache.hash(_j)
+    });
+}
+
+/// The encrypt algorithm of BSW CP-ABE, compressing the plaintext before encryption.
+///
+/// The plaintext is compressed using deflate. If this does not make it smaller, the plaintext is
+/// encrypted as is. The ciphertext records whether compression was used, so `decrypt` always
+/// returns the original plaintext. Only available with the `compression` feature.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///
+#[cfg(feature = "compression")]
+pub fn encrypt_compressed(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+) -> Result<CpAbeCiphertext, RabeError> {
+    let mut _encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    _encoder.write_all(_plaintext)?;
+    let _deflated = _encoder.finish()?;
+    // fall back to the uncompressed plaintext if compression does not pay off
+    if _deflated.len() >= _plaintext.len() {
+        return encrypt(_pk, _policy, _plaintext);
+    }
+    let mut _ct = encrypt(_pk, _policy, &_deflated)?;
+    _ct._compressed = true;
+    return Ok(_ct);
+}
+
+#[cfg(feature = "compression")]
+fn decompress(_ct: &CpAbeCiphertext, _pt: Vec<u8>) -> Option<Vec<u8>> {
+    if !_ct._compressed {
+        return Some(_pt);
+    }
+    let mut _inflated: Vec<u8> = Vec::new();
+    match DeflateDecoder::new(&_pt[..]).read_to_end(&mut _inflated) {
+        Err(_) => return None,
+        Ok(_) => return Some(_inflated),
+    }
+}
+
+// compressed ciphertexts can only be decrypted with the compression feature
+#[cfg(not(feature = "compression"))]
+fn decompress(_ct: &CpAbeCiphertext, _pt: Vec<u8>) -> Option<Vec<u8>> {
+    if _ct._compressed {
+        return None;
+    }
+    return Some(_pt);
+}
+
+/// The encrypt algorithm of BSW CP-ABE, storing the access policy in its compact prefix
+/// notation (see `utils::policy::compact::policy_to_compact`) to reduce the size of the
+/// CpAbeCiphertext. The ciphertext is flagged so that `decrypt` converts the policy back.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///
+pub fn encrypt_compact_policy(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+) -> Result<CpAbeCiphertext, RabeError> {
+    let _compact = policy_to_compact(_policy)?;
+    let mut _ct = encrypt(_pk, _policy, _plaintext)?;
+    _ct._policy = _compact;
+    _ct._compact_policy = true;
+    return Ok(_ct);
+}
+
+/// Returns the size in bytes of the binary encoding (see `RabeEncode::to_bytes`) of a
+/// CpAbeCiphertext generated by `encrypt` for the given policy and plaintext length, without
+/// encrypting. The size follows from the pinned format: the policy, `_c` (G1) and `_c_p` (Gt),
+/// one component (attribute name, G1 and G2) per share of the policy and the AES-GCM payload
+/// (nonce, plaintext and tag, no padding). Other encrypt functions (e.g. compression or compact
+/// policies) produce ciphertexts of different sizes.
+///
+/// # Arguments
+///
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext_len` - The length of the plaintext in bytes
+///
+pub fn ciphertext_size_estimate(_policy: &str, _plaintext_len: usize) -> Result<usize, RabeError> {
+    // lengths of strings and vectors are encoded as u64
+    let _len = 8;
+    let _g1 = encode(&G1::one())?.len();
+    let _g2 = encode(&G2::one())?.len();
+    let _gt = encode(&Gt::one())?.len();
+    let mut _size = _len + _policy.len() + _g1 + _gt + _len;
+    for (_, _attr) in share_paths(_policy)? {
+        _size += _len + _attr.len() + _g1 + _g2;
+    }
+    _size += _len + GCM_NONCE_SIZE + _plaintext_len + GCM_TAG_SIZE;
+    // the flags _compressed and _compact_policy, the format tag and version
+    return Ok(_size + 2 + CIPHERTEXT_FORMAT_TAG.len() + 1);
+}
+
+impl CpAbeCiphertext {
+    // returns the access policy of this ciphertext in JSON format
+    fn json_policy(&self) -> Option<String> {
+        if !self._compact_policy {
+            return Some(self._policy.clone());
+        }
+        match compact_to_policy(&self._policy) {
+            Err(_) => return None,
+            Ok(_policy) => return Some(_policy),
+        }
+    }
+
+    /// Returns true if a key holding the given attributes would satisfy the policy of this
+    /// CpAbeCiphertext. Unlike a decryption this needs no key, only attribute names.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_attributes` - A list of attribute names
+    ///
+    pub fn policy_matches(&self, _attributes: &[String]) -> bool {
+        match self.json_policy() {
+            None => return false,
+            Some(_policy) => {
+                // keys always hold the reserved attribute of the policy {"TRUE": true}
+                let mut _attr = _attributes.to_vec();
+                _attr.push(String::from(TRUE_ATTRIBUTE));
+                return traverse_str(&_attr, &_policy);
+            }
+        }
+    }
+
+    /// Returns the access policy of this CpAbeCiphertext as stored, i.e. in compact notation
+    /// if it was encrypted using `encrypt_compact_policy`.
+    pub fn policy(&self) -> &str {
+        return &self._policy;
+    }
+
+    /// Returns true if this and `_other` are encrypted under the same policy, regardless of
+    /// their randomized components and payloads. Unlike `==`, this holds for two independent
+    /// encryptions under one policy, e.g. to deduplicate or route ciphertexts. Policies are
+    /// compared in canonical form (see `canonicalize_policy`), so a policy in compact notation
+    /// or with reordered children is the same policy; the attribute names of the components
+    /// have to agree as well.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_other` - Another BSW CP-ABE Ciphertext
+    ///
+    pub fn same_policy(&self, _other: &CpAbeCiphertext) -> bool {
+        let _canonical = |_ct: &CpAbeCiphertext| {
+            _ct.json_policy().and_then(|_policy| canonicalize_policy(&_policy).ok())
+        };
+        let _names = |_ct: &CpAbeCiphertext| {
+            _ct._c_y.iter().map(|_c_y| _c_y._str.clone()).collect::<BTreeSet<String>>()
+        };
+        match (_canonical(self), _canonical(_other)) {
+            (Some(_a), Some(_b)) => return _a == _b && _names(self) == _names(_other),
+            _ => return false,
+        }
+    }
+
+    /// Returns the attribute names of the ciphertext components in the order of the policy
+    /// leaves, e.g. to check whether a key could satisfy the policy before decrypting.
+    pub fn attributes(&self) -> Vec<String> {
+        return self._c_y.iter().map(|_c_y| _c_y._str.clone()).collect();
+    }
+
+    /// Serializes this CpAbeCiphertext without its symmetric payload `_ct`, e.g. to advertise
+    /// the ciphertext and its policy. The header can be used to check whether a key satisfies
+    /// the policy (see `decrypt_cost`), but cannot be decrypted.
+    pub fn header_bytes(&self) -> Vec<u8> {
+        let mut _header = self.clone();
+        _header._ct = Vec::new();
+        return _header.to_bytes().expect("a ciphertext can always be serialized");
+    }
+
+    /// Reconstitutes a CpAbeCiphertext from a header generated by `header_bytes` and its payload.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_header` - The serialized header of a BSW CP-ABE Ciphertext
+    ///	* `_payload` - The symmetric payload `_ct` of the ciphertext
+    ///
+    pub fn attach_payload(
+        _header: &[u8],
+        _payload: Vec<u8>,
+    ) -> Result<CpAbeCiphertext, RabeError> {
+        let mut _ct = CpAbeCiphertext::from_bytes(_header)?;
+        _ct._ct = _payload;
+        return Ok(_ct);
+    }
+}
+
+/// An iterator over the attribute names of the components `_c_y` of a CpAbeCiphertext
+pub struct CpAbeAttributeNames<'a> {
+    _iter: ::std::slice::Iter<'a, CpAbeAttribute>,
+}
+
+impl<'a> Iterator for CpAbeAttributeNames<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        return self._iter.next().map(|_c_y| _c_y._str.as_str());
+    }
+}
+
+/// Iterates over the attribute names of the ciphertext components, an attribute occurring
+/// multiple times in the policy is yielded multiple times.
+impl<'a> IntoIterator for &'a CpAbeCiphertext {
+    type Item = &'a str;
+    type IntoIter = CpAbeAttributeNames<'a>;
+
+    fn into_iter(self) -> CpAbeAttributeNames<'a> {
+        return CpAbeAttributeNames { _iter: self._c_y.iter() };
+    }
+}
+
+// maps attribute names to their (first) component
+fn attribute_index(_attributes: &Vec<CpAbeAttribute>) -> HashMap<&str, &CpAbeAttribute> {
+    let mut _index: HashMap<&str, &CpAbeAttribute> = HashMap::new();
+    for _attribute in _attributes {
+        _index.entry(_attribute._str.as_str()).or_insert(_attribute);
+    }
+    return _index;
+}
+
+// the components of ciphertext and key of a leaf and its coefficient
+type PairingTerm<'a> = (&'a CpAbeAttribute, &'a CpAbeAttribute, Fr);
+
+// (e(C_j, D_j) / e(D'_j, C'_j))^z for the coefficient z of the leaf
+fn pairing_term(&(_c_j, _d_j, _z_j): &PairingTerm) -> Gt {
+    let _e = pairing(_c_j._g1, _d_j._g2) * pairing(_d_j._g1, _c_j._g2).inverse();
+    return _e.pow(_z_j);
+}
+
+// the product of the pairing terms of all attributes, computed in parallel with the
+// `parallel` feature
+fn pairing_product(_terms: &Vec<PairingTerm>) -> Gt {
+    #[cfg(feature = "parallel")]
+    let _product = _terms.par_iter().map(pairing_term).reduce(Gt::one, |_a, _b| _a * _b);
+    #[cfg(not(feature = "parallel"))]
+    let _product = pairing_product_serial(_terms);
+    return _product;
+}
+
+#[cfg_attr(feature = "parallel", allow(dead_code))]
+fn pairing_product_serial(_terms: &Vec<PairingTerm>) -> Gt {
+    return _terms.iter().map(pairing_term).fold(Gt::one(), |_a, _b| _a * _b);
+}
+
+/// The decrypt algorithm of BSW CP-ABE. Reconstructs the original plaintext data as Vec<u8>, given a CpAbeCiphertext with a matching CpAbeSecretKey.
+/// With the `parallel` feature the pairings of the attributes are computed in parallel.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_ct` - An BSW CP-ABE Ciphertext
+///
+/// # Errors
+///
+///	* `RabeError::PolicyTooLarge` - if the policy exceeds the default limits (see `PolicyLimits`)
+///	* `RabeError::InsufficientAttributes` - if the attributes of `_sk` do not satisfy the policy
+///	* `RabeError::DecryptionFailed` - if a component of a leaf of the policy is missing
+///	* `RabeError::SymmetricDecryptionFailed` - if `_sk` satisfies the policy, but the payload
+///	is missing, corrupt or can not be decompressed
+///
+/// Only `InsufficientAttributes` means that another key may succeed; all other errors point to
+/// a broken ciphertext (or key). Tampered group elements of a well-formed ciphertext yield a
+/// wrong symmetric key and are reported as `SymmetricDecryptionFailed`.
+///
+pub fn decrypt(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+    return decrypt_indexed(_sk, &key_index(_sk), _ct);
+}
+
+// decrypt using the attributes of the key indexed by key_index
+fn decrypt_indexed(
+    _sk: &CpAbeSecretKey,
+    _key: &KeyIndex,
+    _ct: &CpAbeCiphertext,
+) -> Result<Vec<u8>, RabeError> {
+    let _msg = decapsulate_indexed(_sk, _key, _ct)?;
+    // the key satisfies the policy from here on, so any failure is caused by the payload
+    return open_payload(_ct, &_msg);
+}
+
+// decrypts the payload of a ciphertext using the element of Gt it encapsulates
+fn open_payload(_ct: &CpAbeCiphertext, _msg: &Gt) -> Result<Vec<u8>, RabeError> {
+    match decrypt_symmetric_gcm(_msg, &_ct._ct) {
+        None => return Err(RabeError::SymmetricDecryptionFailed),
+        Some(_pt) => {
+            match decompress(_ct, _pt) {
+                None => return Err(RabeError::SymmetricDecryptionFailed),
+                Some(_pt) => return Ok(_pt),
+            }
+        }
+    }
+}
+
+// recovers the element of Gt encapsulated by the ciphertext, if the key satisfies its policy
+fn decapsulate(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Gt, RabeError> {
+    return decapsulate_indexed(_sk, &key_index(_sk), _ct);
+}
+
+// the attribute names of a key and its components indexed by attribute, computed once per key
+struct KeyIndex<'a> {
+    _attributes: Vec<String>,
+    _components: HashMap<&'a str, &'a CpAbeAttribute>,
+}
+
+fn key_index(_sk: &CpAbeSecretKey) -> KeyIndex {
+    return KeyIndex {
+        _attributes: _sk._d_j.iter().map(|_values| _values._str.to_string()).collect(),
+        _components: attribute_index(&_sk._d_j),
+    };
+}
+
+fn decapsulate_indexed(
+    _sk: &CpAbeSecretKey,
+    _key: &KeyIndex,
+    _ct: &CpAbeCiphertext,
+) -> Result<Gt, RabeError> {
+    let _str_attr = &_key._attributes;
+    let _policy = match _ct.json_policy() {
+        None => return Err(RabeError::PolicyParse(_ct._policy.clone())),
+        Some(_policy) => _policy,
+    };
+    // reject oversized policies of hostile ciphertexts before doing any work
+    validate_policy(&_policy)?;
+    if traverse_str(&_str_attr, &_policy) == false {
+        return Err(RabeError::InsufficientAttributes);
+    }
+    let _pruned = calc_pruned_str(&_str_attr, &_policy)?;
+    if !_pruned.0 {
+        return Err(RabeError::InsufficientAttributes);
+    }
+    // the coefficients of the leaves used, e.g. of the children selected in a THRESHOLD. A leaf
+    // is identified by the index of its share, i.e. of its ciphertext component (see
+    // share_paths), so that an attribute occurring twice in the policy uses both components
+    let _z = match calc_coefficients_indexed_str(&_str_attr, &_policy)? {
+        None => return Err(RabeError::InsufficientAttributes),
+        Some(_z) => _z,
+    };
+    // the key is indexed by attribute once, so that keys with many attributes do not have to
+    // be scanned for every attribute
+    let _d = &_key._components;
+    let mut _terms: Vec<PairingTerm> = Vec::new();
+    for &(_i, ref _j, _z_j) in _z.iter() {
+        // a crafted ciphertext may lack the component of a leaf of its policy
+        let _c_j = match _ct._c_y.get(_i) {
+            Some(_c_j) if _c_j._str == *_j => _c_j,
+            _ => return Err(RabeError::DecryptionFailed(format!("ciphertext component of {}", _j))),
+        };
+        let _d_j = match _d.get(_j.as_str()) {
+            Some(_d_j) => _d_j,
+            None => return Err(RabeError::DecryptionFailed(format!("key component of {}", _j))),
+        };
+        _terms.push((_c_j, *_d_j, _z_j));
+    }
+    let _a = pairing_product(&_terms);
+    return Ok(_ct._c_p * ((pairing(_ct._c, _sk._d)) * _a.inverse()).inverse());
+}
+
+/// Generates a decryption challenge for the given policy, e.g. to let a user prove that they
+/// hold a key satisfying the policy without revealing the key. The verifier sends the
+/// CpAbeChallenge to the user and keeps the returned Gt secret. The user answers with
+/// `respond_challenge` and the verifier compares the response with the secret.
+///
+/// Note: a response is the decapsulated key of the challenge. A user must never answer a
+/// challenge built from the header of a ciphertext (same `_c`, `_c_p` and `_c_y`), as the
+/// response would be the symmetric key of that ciphertext.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///
+pub fn make_challenge(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+) -> Result<(CpAbeChallenge, Gt), RabeError> {
+    let _s: Fr = rand::thread_rng().gen();
+    let (_ct, _msg) = encapsulate_with_hash(_pk, _policy, _s, &mut rand::thread_rng(), |_j| {
+        hash_to_g2(_j, BSW_HASH_DOMAIN)
+    })?;
+    let _challenge = CpAbeChallenge {
+        _policy: _ct._policy,
+        _c: _ct._c,
+        _c_p: _ct._c_p,
+        _c_y: _ct._c_y,
+    };
+    return Ok((_challenge, _msg));
+}
+
+/// Answers a decryption challenge generated by `make_challenge`. Returns the secret Gt of the
+/// challenge if the key satisfies its policy and `None` otherwise.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_challenge` - A BSW CP-ABE decryption challenge
+///
+pub fn respond_challenge(_sk: &CpAbeSecretKey, _challenge: &CpAbeChallenge) -> Option<Gt> {
+    let _ct = CpAbeCiphertext {
+        _policy: _challenge._policy.clone(),
+        _c: _challenge._c,
+        _c_p: _challenge._c_p,
+        _c_y: _challenge._c_y.clone(),
+        _ct: Vec::new(),
+        _compressed: false,
+        _compact_policy: false,
+    };
+    return decapsulate(_sk, &_ct).ok();
+}
+
+/// The encrypt algorithm of BSW CP-ABE for UTF-8 text. Same as `encrypt`, but takes the plaintext as &str.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext given as &str
+///
+pub fn encrypt_str(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _plaintext: &str,
+) -> Result<CpAbeCiphertext, RabeError> {
+    return encrypt(_pk, _policy, &_plaintext.as_bytes().to_vec());
+}
+
+/// The decrypt algorithm of BSW CP-ABE for UTF-8 text. Same as `decrypt`, but returns the plaintext as String.
+/// Returns `None` if the plaintext is not valid UTF-8.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_ct` - An BSW CP-ABE Ciphertext
+///
+pub fn decrypt_to_string(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Option<String> {
+    match decrypt(_sk, _ct) {
+        Err(_) => return None,
+        Ok(_pt) => return String::from_utf8(_pt).ok(),
+    }
+}
+
+/// The decrypt algorithm of BSW CP-ABE for auditing. Same as `decrypt`, but additionally
+/// returns the attributes of the key used to satisfy the policy (as given by `calc_pruned_str`),
+/// e.g. to log them. Returns `None` if the decryption fails.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_ct` - An BSW CP-ABE Ciphertext
+///
+pub fn decrypt_with_audit(
+    _sk: &CpAbeSecretKey,
+    _ct: &CpAbeCiphertext,
+) -> Option<(Vec<u8>, Vec<String>)> {
+    let _pt = decrypt(_sk, _ct).ok()?;
+    let _str_attr = _sk._d_j
+        .iter()
+        .map(|_values| _values._str.to_string())
+        .collect::<Vec<_>>();
+    let (_found, _used) = calc_pruned_str(&_str_attr, &_ct.json_policy()?).ok()?;
+    if !_found {
+        return None;
+    }
+    return Some((_pt, _used));
+}
+
+/// Wraps a caller-provided symmetric data key, e.g. a data key managed by an external KMS,
+/// under an access policy. The payload of the returned CpAbeCiphertext is the sealed data key,
+/// the bulk data is encrypted elsewhere using that key.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_data_key` - The symmetric data key to wrap
+///
+pub fn wrap_key(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    _data_key: &[u8],
+) -> Result<CpAbeCiphertext, RabeError> {
+    return encrypt(_pk, _policy, &_data_key.to_vec());
+}
+
+/// Recovers a data key wrapped by `wrap_key`. Fails like `decrypt` if the CpAbeSecretKey does
+/// not satisfy the policy or the wrapped key can not be decrypted.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_ct` - A BSW CP-ABE Ciphertext, generated by the function wrap_key()
+///
+pub fn unwrap_key(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+    return decrypt(_sk, _ct);
+}
+
+/// Tightens the policy of a CpAbeCiphertext to `{"AND": [<policy>, <_clause>]}` without the
+/// master key and without learning the plaintext, e.g. for an administrator holding the
+/// public key only. Use `CpAbeContext::change_policy` to replace a policy entirely.
+///
+/// Re-sharing the root secret `s` of the ciphertext under a new policy is not possible without
+/// knowing `s`, so the symmetric payload is encrypted once more under `_clause` instead and
+/// `decrypt_updated` requires a key satisfying both. The two layers are independent
+/// ciphertexts though: unlike a single policy, they do not resist collusion, two users
+/// satisfying one layer each can decrypt together. The inner ABE components are exposed as
+/// before, so the clause only restricts who can open the payload.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_ct` - A BSW CP-ABE Ciphertext, generated using `_pk`
+///	* `_clause` - The additional access policy given as JSON String
+///
+pub fn update_policy(
+    _pk: &CpAbePublicKey,
+    _ct: &CpAbeCiphertext,
+    _clause: &String,
+) -> Result<CpAbeUpdatedCiphertext, RabeError> {
+    let _outer = encrypt(_pk, _clause, &_ct._ct)?;
+    let mut _inner = _ct.clone();
+    _inner._ct = Vec::new();
+    return Ok(CpAbeUpdatedCiphertext { _inner, _outer });
+}
+
+/// The decrypt algorithm of BSW CP-ABE for ciphertexts of `update_policy`. Fails like `decrypt`
+/// unless the CpAbeSecretKey satisfies both the original policy and the additional clause.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_ct` - A CpAbeUpdatedCiphertext, generated by the function update_policy()
+///
+pub fn decrypt_updated(
+    _sk: &CpAbeSecretKey,
+    _ct: &CpAbeUpdatedCiphertext,
+) -> Result<Vec<u8>, RabeError> {
+    let mut _inner = _ct._inner.clone();
+    _inner._ct = decrypt(_sk, &_ct._outer)?;
+    return decrypt(_sk, &_inner);
+}
+
+/// The encapsulation algorithm of BSW CP-ABE, i.e. `encrypt` without the symmetric encryption.
+/// Returns a CpAbeCiphertext without payload and the random element of Gt it encapsulates, e.g.
+/// to derive the key of an own AEAD over large payloads (see `kdf_from_gt`).
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///
+pub fn encrypt_kem(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+) -> Result<(CpAbeCiphertext, Gt), RabeError> {
+    let _s: Fr = rand::thread_rng().gen();
+    return encapsulate_with_hash(_pk, _policy, _s, &mut rand::thread_rng(), |_j| {
+        hash_to_g2(_j, BSW_HASH_DOMAIN)
+    });
+}
+
+/// The decapsulation algorithm of BSW CP-ABE, i.e. `decrypt` without the symmetric decryption.
+/// Recovers the element of Gt encapsulated by `encrypt_kem` (or the mask of any other
+/// CpAbeCiphertext) if the CpAbeSecretKey satisfies the policy. The payload is ignored.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_ct` - A BSW CP-ABE Ciphertext
+///
+/// # Errors
+///
+///	* `RabeError::InsufficientAttributes` - if the attributes of `_sk` do not satisfy the policy
+///
+pub fn decrypt_kem(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Gt, RabeError> {
+    return decapsulate(_sk, _ct);
+}
+
+/// The size of the plaintext chunks of `encrypt_stream`
+pub const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+// the largest header accepted by decrypt_stream
+const STREAM_MAX_HEADER_SIZE: usize = 16 * 1024 * 1024;
+
+/// The encryption algorithm of BSW CP-ABE for large payloads that do not fit into memory.
+/// Encapsulates a key once (see `encrypt_kem`) and writes the serialized CpAbeCiphertext as
+/// header, followed by the plaintext of `_reader` in chunks of `STREAM_CHUNK_SIZE` bytes.
+///
+/// The header and every chunk are framed by their length (4 bytes, big endian). Each chunk is
+/// encrypted with AES-256/GCM under a nonce derived from its index and whether it is the last
+/// chunk, so that reordered, dropped or truncated chunks are detected by `decrypt_stream`.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_reader` - The plaintext
+///	* `_writer` - Receives the ciphertext
+///
+pub fn encrypt_stream<R: Read, W: Write>(
+    _pk: &CpAbePublicKey,
+    _policy: &String,
+    mut _reader: R,
+    mut _writer: W,
+) -> Result<(), RabeError> {
+    let (_ct, _msg) = encrypt_kem(_pk, _policy)?;
+    write_frame(&mut _writer, &_ct.to_bytes()?)?;
+    let mut _chunk: Vec<u8> = vec![0; STREAM_CHUNK_SIZE];
+    let mut _next: Vec<u8> = vec![0; STREAM_CHUNK_SIZE];
+    let mut _len = read_chunk(&mut _reader, &mut _chunk)?;
+    let mut _index = 0u64;
+    loop {
+        // a chunk is the last one if nothing follows, an empty input yields one empty chunk
+        let _next_len = if _len == STREAM_CHUNK_SIZE {
+            read_chunk(&mut _reader, &mut _next)?
+        } else {
+            0
+        };
+        let _last = _next_len == 0;
+        let _nonce_data = stream_nonce_data(_index, _last);
+        let _frame =
+            match encrypt_symmetric_gcm_derived_nonce(&_msg, &_chunk[.._len].to_vec(), &_nonce_data) {
+                Some(_frame) => _frame,
+                None => {
+                    return Err(RabeError::Encryption(String::from("symmetric encryption failed")))
+                }
+            };
+        write_frame(&mut _writer, &_frame)?;
+        if _last {
+            break;
+        }
+        ::std::mem::swap(&mut _chunk, &mut _next);
+        _len = _next_len;
+        _index += 1;
+    }
+    _writer.flush()?;
+    return Ok(());
+}
+
+/// The decryption algorithm of BSW CP-ABE for streams written by `encrypt_stream`. Writes the
+/// plaintext to `_writer` chunk by chunk, every chunk is authenticated before it is written.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_reader` - The ciphertext, generated by encrypt_stream()
+///	* `_writer` - Receives the plaintext
+///
+/// # Errors
+///
+///	* `RabeError::InsufficientAttributes` - if the attributes of `_sk` do not satisfy the policy
+///	* `RabeError::SymmetricDecryptionFailed` - if a chunk has been modified, reordered or
+///	dropped or the stream is truncated. The chunks before have already been written.
+///
+pub fn decrypt_stream<R: Read, W: Write>(
+    _sk: &CpAbeSecretKey,
+    mut _reader: R,
+    mut _writer: W,
+) -> Result<(), RabeError> {
+    let _header = read_frame(&mut _reader, STREAM_MAX_HEADER_SIZE)?
+        .ok_or(RabeError::SymmetricDecryptionFailed)?;
+    let _ct = CpAbeCiphertext::from_bytes(&_header)?;
+    let _msg = decrypt_kem(_sk, &_ct)?;
+    let mut _index = 0u64;
+    loop {
+        let _frame = read_frame(&mut _reader, STREAM_CHUNK_SIZE + GCM_NONCE_SIZE + GCM_TAG_SIZE)?
+            .ok_or(RabeError::SymmetricDecryptionFailed)?;
+        // the nonce of the chunk tells whether it claims to be the last one
+        let _nonce = &_frame[..GCM_NONCE_SIZE.min(_frame.len())];
+        let _last = if _nonce == &derive_iv(&stream_nonce_data(_index, true))[..GCM_NONCE_SIZE] {
+            true
+        } else if _nonce == &derive_iv(&stream_nonce_data(_index, false))[..GCM_NONCE_SIZE] {
+            false
+        } else {
+            return Err(RabeError::SymmetricDecryptionFailed);
+        };
+        let _plaintext =
+            decrypt_symmetric_gcm(&_msg, &_frame).ok_or(RabeError::SymmetricDecryptionFailed)?;
+        _writer.write_all(&_plaintext)?;
+        if _last {
+            break;
+        }
+        _index += 1;
+    }
+    // nothing may follow the last chunk
+    let mut _trailing: [u8; 1] = [0; 1];
+    if _reader.read(&mut _trailing)? != 0 {
+        return Err(RabeError::SymmetricDecryptionFailed);
+    }
+    _writer.flush()?;
+    return Ok(());
+}
+
+fn stream_nonce_data(_index: u64, _last: bool) -> Vec<u8> {
+    let mut _data = b"bsw-stream".to_vec();
+    _data.extend_from_slice(&_index.to_be_bytes());
+    _data.push(_last as u8);
+    return _data;
+}
+
+// fills _buf as far as possible, returns less than its length only at the end of the input
+fn read_chunk<R: Read>(_reader: &mut R, _buf: &mut [u8]) -> Result<usize, RabeError> {
+    let mut _len = 0usize;
+    while _len < _buf.len() {
+        match _reader.read(&mut _buf[_len..])? {
+            0 => break,
+            _n => _len += _n,
+        }
+    }
+    return Ok(_len);
+}
+
+fn write_frame<W: Write>(_writer: &mut W, _data: &[u8]) -> Result<(), RabeError> {
+    _writer.write_all(&(_data.len() as u32).to_be_bytes())?;
+    _writer.write_all(_data)?;
+    return Ok(());
+}
+
+// reads a frame of at most _max bytes, None at the end of the input
+fn read_frame<R: Read>(_reader: &mut R, _max: usize) -> Result<Option<Vec<u8>>, RabeError> {
+    let mut _len: [u8; 4] = [0; 4];
+    match read_chunk(_reader, &mut _len)? {
+        0 => return Ok(None),
+        4 => {}
+        _ => return Err(RabeError::SymmetricDecryptionFailed),
+    }
+    let _len = u32::from_be_bytes(_len) as usize;
+    if _len > _max {
+        return Err(RabeError::SymmetricDecryptionFailed);
+    }
+    let mut _data: Vec<u8> = vec![0; _len];
+    if read_chunk(_reader, &mut _data)? != _len {
+        return Err(RabeError::SymmetricDecryptionFailed);
+    }
+    return Ok(Some(_data));
+}
+
+/// The BSW CP-ABE scheme as `AbeScheme`, keys are generated for attributes and ciphertexts for
+/// a JSON policy.
+pub struct Bsw;
+
+impl AbeScheme for Bsw {
+    type PublicKey = CpAbePublicKey;
+    type MasterKey = CpAbeMasterKey;
+    type SecretKey = CpAbeSecretKey;
+    type Ciphertext = CpAbeCiphertext;
+    type KeyInput = Vec<String>;
+    type EncryptInput = String;
+
+    fn setup() -> (CpAbePublicKey, CpAbeMasterKey) {
+        return setup();
+    }
+
+    fn keygen(
+        _pk: &CpAbePublicKey,
+        _msk: &CpAbeMasterKey,
+        _attributes: &Vec<String>,
+    ) -> Result<CpAbeSecretKey, RabeError> {
+        return keygen(_pk, _msk, _attributes);
+    }
+
+    fn encrypt(
+        _pk: &CpAbePublicKey,
+        _policy: &String,
+        _plaintext: &Vec<u8>,
+    ) -> Result<CpAbeCiphertext, RabeError> {
+        return encrypt(_pk, _policy, _plaintext);
+    }
+
+    fn decrypt(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+        return decrypt(_sk, _ct);
+    }
+}
+
+impl CpAbeContext {
+    /// Re-encrypts a CpAbeCiphertext of this authority under a new access policy.
+    ///
+    /// The authority recovers the symmetric key of the ciphertext with its master key, i.e.
+    /// without a key satisfying the policy, so every policy (including negated attributes) can
+    /// be changed. The plaintext is encrypted again under `_new_policy`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_ct` - A BSW CP-ABE Ciphertext, generated using the public key of this context
+    ///	* `_new_policy` - An access policy given as JSON String
+    ///
+    pub fn change_policy(
+        &self,
+        _ct: &CpAbeCiphertext,
+        _new_policy: &String,
+    ) -> Result<CpAbeCiphertext, RabeError> {
+        // e(h^s, g2^(alpha / beta)) = e(g1, g2)^(alpha * s) blinds the encapsulated element
+        let _beta_inv = match self._msk._beta.inverse() {
+            None => return Err(RabeError::DecryptionFailed(String::from("master key"))),
+            Some(_beta_inv) => _beta_inv,
+        };
+        let _msg = _ct._c_p * pairing(_ct._c, self._msk._g2_alpha * _beta_inv).inverse();
+        let _plaintext = open_payload(_ct, &_msg)?;
+        return encrypt(&self._pk, _new_policy, &_plaintext);
+    }
+}
+
+/// Returns true if the CpAbeSecretKey satisfies the policy of the CpAbeCiphertext, i.e. if
+/// `decrypt` would succeed with a valid ciphertext. No pairings are computed.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_ct` - An BSW CP-ABE Ciphertext
+///
+pub fn can_decrypt(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> bool {
+    let _str_attr = _sk._d_j
+        .iter()
+        .map(|_values| _values._str.to_string())
+        .collect::<Vec<_>>();
+    let _policy = match _ct.json_policy() {
+        None => return false,
+        Some(_policy) => _policy,
+    };
+    if traverse_str(&_str_attr, &_policy) == false {
+        return false;
+    }
+    match calc_pruned_str(&_str_attr, &_policy) {
+        Ok((_match, _)) => return _match,
+        Err(_) => return false,
+    }
+}
+
+/// Estimates the cost of decrypting a CpAbeCiphertext with a CpAbeSecretKey before actually doing so.
+///
+/// Returns the number of pairings `decrypt` would compute for the attributes selected by
+/// pruning (two per attribute), or `None` if the key does not satisfy the policy.
+/// The final pairing with the key component `_d` is not included.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_ct` - An BSW CP-ABE Ciphertext
+///
+pub fn decrypt_cost(_sk: &CpAbeSecretKey, _ct: &CpAbeCiphertext) -> Option<usize> {
+    let _str_attr = _sk._d_j
+        .iter()
+        .map(|_values| _values._str.to_string())
+        .collect::<Vec<_>>();
+    let _policy = match _ct.json_policy() {
+        None => return None,
+        Some(_policy) => _policy,
+    };
+    match calc_pruned_str(&_str_attr, &_policy) {
+        Ok((true, _pruned)) => return Some(2 * _pruned.len()),
+        _ => return None,
+    }
+}
+
+/// Generates a CpAbeSecretKey for each of the given sets of attributes (see `keygen`).
+///
+/// With the `parallel` feature the keys are generated in parallel. Each key uses fresh
+/// randomness of the thread generating it.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_msk` - A Master Key (MSK), generated by the function setup()
+///	* `_attribute_sets` - A Vector of attribute sets, one for each user key
+///
+pub fn keygen_batch(
+    _pk: &CpAbePublicKey,
+    _msk: &CpAbeMasterKey,
+    _attribute_sets: &Vec<Vec<String>>,
+) -> Vec<Result<CpAbeSecretKey, RabeError>> {
+    #[cfg(feature = "parallel")]
+    let _iter = _attribute_sets.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let _iter = _attribute_sets.iter();
+    return _iter.map(|_attributes| keygen(_pk, _msk, _attributes)).collect();
+}
+
+/// Generates a CpAbeCiphertext for each of the given pairs of access policy and plaintext (see `encrypt`).
+///
+/// With the `parallel` feature the ciphertexts are generated in parallel. Each ciphertext uses
+/// fresh randomness of the thread generating it.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_items` - A Vector of access policies (as JSON String) and plaintext data
+///
+pub fn encrypt_batch(
+    _pk: &CpAbePublicKey,
+    _items: &Vec<(String, Vec<u8>)>,
+) -> Vec<Result<CpAbeCiphertext, RabeError>> {
+    #[cfg(feature = "parallel")]
+    let _iter = _items.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let _iter = _items.iter();
+    return _iter
+        .map(|&(ref _policy, ref _plaintext)| encrypt(_pk, _policy, _plaintext))
+        .collect();
+}
+
+/// Decrypts each of the given ciphertexts using the same CpAbeSecretKey (see `decrypt`).
+///
+/// The attribute names and the index of the components of the key are computed once for all
+/// ciphertexts. The pairings can not be shared, as each of them involves a component of a
+/// ciphertext. With the `parallel` feature the ciphertexts are decrypted in parallel.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_cts` - The BSW CP-ABE Ciphertexts to decrypt
+///
+pub fn decrypt_batch(
+    _sk: &CpAbeSecretKey,
+    _cts: &[CpAbeCiphertext],
+) -> Vec<Result<Vec<u8>, RabeError>> {
+    let _key = key_index(_sk);
+    #[cfg(feature = "parallel")]
+    let _iter = _cts.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let _iter = _cts.iter();
+    return _iter.map(|_ct| decrypt_indexed(_sk, &_key, _ct)).collect();
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use utils::policy::attributes::AttributeSet;
+    use utils::secretsharing::{calc_coefficients_pruned_str, threshold_policy, MAX_POLICY_NODES};
+
+    // serializes _value using bincode and serde_json and checks that all three values are equal
+    fn formats_agree<T: Serialize + DeserializeOwned + PartialEq>(_value: &T) -> bool {
+        let _from_bincode: T = bincode::deserialize(&bincode::serialize(_value).unwrap()).unwrap();
+        let _from_json: T = serde_json::from_str(&serde_json::to_string(_value).unwrap()).unwrap();
+        return _from_bincode == *_value && _from_json == *_value && _from_bincode == _from_json;
+    }
+
+    #[test]
+    fn parallel_pairings() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let _attributes: Vec<String> = (0..16).map(|_i| format!("attr{}", _i)).collect();
+        let _leaves: Vec<String> = _attributes
+            .iter()
+            .map(|_a| format!(r#"{{"ATT": "{}"}}"#, _a))
+            .collect();
+        let policy = format!(r#"{{"AND": [{}]}}"#, _leaves.join(", "));
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &_attributes).unwrap();
+        // the same product of pairings, computed in parallel (with the feature) and serially
+        let _z = calc_coefficients_pruned_str(&_attributes, &policy).unwrap().unwrap();
+        let _d = attribute_index(&sk._d_j);
+        let _c = attribute_index(&ct_cp._c_y);
+        let _terms: Vec<PairingTerm> = _z
+            .iter()
+            .map(|_t| (_c[_t.0.as_str()], _d[_t.0.as_str()], _t.1))
+            .collect();
+        assert_eq!(_terms.len(), 16);
+        assert_eq!(pairing_product(&_terms) == pairing_product_serial(&_terms), true);
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn kem() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let (ct_cp, _key) = encrypt_kem(&pk, &policy).unwrap();
+        assert_eq!(ct_cp._ct.is_empty(), true);
+        let sk = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")]).unwrap();
+        assert_eq!(decrypt_kem(&sk, &ct_cp).unwrap() == _key, true);
+        let sk_a = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(decrypt_kem(&sk_a, &ct_cp).is_err(), true);
+        // the key of an own symmetric encryption
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let _payload = encrypt_symmetric_gcm(&_key, &plaintext).unwrap();
+        let _recovered = decrypt_kem(&sk, &ct_cp).unwrap();
+        assert_eq!(decrypt_symmetric_gcm(&_recovered, &_payload).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn stream() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let sk = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")]).unwrap();
+        let sk_a = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        // 3 MB, not a multiple of the chunk size
+        let plaintext: Vec<u8> = (0..3 * 1024 * 1024 + 17).map(|_i| (_i % 251) as u8).collect();
+        let mut _ct: Vec<u8> = Vec::new();
+        encrypt_stream(&pk, &policy, std::io::Cursor::new(&plaintext), &mut _ct).unwrap();
+        let mut _pt: Vec<u8> = Vec::new();
+        decrypt_stream(&sk, std::io::Cursor::new(&_ct), &mut _pt).unwrap();
+        assert_eq!(_pt == plaintext, true);
+        assert_eq!(
+            decrypt_stream(&sk_a, std::io::Cursor::new(&_ct), &mut Vec::new()).is_err(),
+            true
+        );
+        // a truncated stream and trailing data are detected
+        let _last_frame = 4 + GCM_NONCE_SIZE + (plaintext.len() % STREAM_CHUNK_SIZE) + GCM_TAG_SIZE;
+        let _truncated = _ct[.._ct.len() - _last_frame].to_vec();
+        assert_eq!(
+            decrypt_stream(&sk, std::io::Cursor::new(&_truncated), &mut Vec::new()).err(),
+            Some(RabeError::SymmetricDecryptionFailed)
+        );
+        let mut _trailing = _ct.clone();
+        _trailing.push(0);
+        assert_eq!(
+            decrypt_stream(&sk, std::io::Cursor::new(&_trailing), &mut Vec::new()).err(),
+            Some(RabeError::SymmetricDecryptionFailed)
+        );
+        // an empty input and an input of exactly one chunk
+        for _len in vec![0, STREAM_CHUNK_SIZE] {
+            let _input = vec![7u8; _len];
+            let mut _ct: Vec<u8> = Vec::new();
+            encrypt_stream(&pk, &policy, &_input[..], &mut _ct).unwrap();
+            let mut _pt: Vec<u8> = Vec::new();
+            decrypt_stream(&sk, &_ct[..], &mut _pt).unwrap();
+            assert_eq!(_pt, _input);
+        }
+    }
+
+    #[test]
+    fn audit() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(
+            r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}"#,
+        );
+        let ct_cp = encrypt(&pk, &policy, &plaintext).unwrap();
+        // only the attributes of the satisfied AND are reported
+        let sk = keygen(
+            &pk,
+            &msk,
+            &vec![String::from("B"), String::from("C"), String::from("D"), String::from("E")],
+        ).unwrap();
+        let (_pt, _used) = decrypt_with_audit(&sk, &ct_cp).unwrap();
+        assert_eq!(_pt, plaintext);
+        assert_eq!(_used, vec![String::from("C"), String::from("D")]);
+        let sk_a_c = keygen(&pk, &msk, &vec![String::from("A"), String::from("C")]).unwrap();
+        assert_eq!(decrypt_with_audit(&sk_a_c, &ct_cp).is_none(), true);
+    }
+
+    #[test]
+    fn duplicate_attributes() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let ct_cp = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk = keygen(
+            &pk,
+            &msk,
+            &vec![String::from("A"), String::from("B"), String::from("A")],
+        ).unwrap();
+        let _names: Vec<&str> = sk._d_j.iter().map(|_d| _d._str.as_str()).collect();
+        assert_eq!(_names, vec!["A", "B", TRUE_ATTRIBUTE]);
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        // the same holds for delegated keys
+        let sk_d = delegate(&pk, &sk, &vec![String::from("B"), String::from("A"), String::from("B")])
+            .unwrap();
+        assert_eq!(sk_d._d_j.len(), 3);
+        assert_eq!(decrypt(&sk_d, &ct_cp).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn or() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // a set of two attributes matching the policy
+        let mut att_matching: Vec<String> = Vec::new();
+        att_matching.push(String::from("D"));
+        att_matching.push(String::from("B"));
+
+        // a set of two attributes NOT matching the policy
+        let mut att_not_matching: Vec<String> = Vec::new();
+        att_not_matching.push(String::from("C"));
+        att_not_matching.push(String::from("D"));
+
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+
+        // our policy
+        let policy = String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+
+        // cp-abe ciphertext
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+
+        // and now decrypt again with mathcing sk
+        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
+        assert_eq!(_match.is_ok(), true);
+        assert_eq!(_match.unwrap(), plaintext);
+
+        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
+        assert_eq!(_no_match.is_err(), true);
+    }
+
+    #[test]
+    fn and10() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // a set of two attributes matching the policy
+        let mut att_matching: Vec<String> = Vec::new();
+        for n in 1..11 {
+            let mut _c = String::from("attr");
+            _c.push_str(&n.to_string());
+            att_matching.push(_c);
+        }
+
+        // a set of two attributes NOT matching the policy
+        let mut att_not_matching: Vec<String> = Vec::new();
+        att_not_matching.push(String::from("attr201"));
+        att_not_matching.push(String::from("attr200"));
+
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+
+        let mut _policy = String::from("{\"AND\": [");
+        for n in 1..11 {
+            let mut _current = String::from("{\"ATT\": \"attr");
+            if n < 10 {
+                _current.push_str(&n.to_string());
+                _current.push_str(&String::from("\"}, "));
+            } else {
+                _current.push_str(&n.to_string());
+                _current.push_str(&String::from("\"}]"));
             }
+            _policy.push_str(&_current);
+        }
+        _policy.push_str(&String::from("}"));
+        println!("{:?}", _policy);
+        // cp-abe ciphertext
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &_policy, &plaintext).unwrap();
+
+        // and now decrypt again with mathcing sk
+        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
+        assert_eq!(_match.is_ok(), true);
+        assert_eq!(_match.unwrap(), plaintext);
+
+        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
+        assert_eq!(_no_match.is_err(), true);
+    }
+
+    #[test]
+    fn nested() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let _num_nested = 30; // maximum at about 50 to 60
+        // a set of two attributes matching the policy
+        let mut att_matching: Vec<String> = Vec::new();
+        for _i in 1..(_num_nested + 1) {
+            let mut _attr = String::from("a");
+            _attr.push_str(&_i.to_string());
+            att_matching.push(_attr);
+        }
+        // a set of two attributes NOT matching the policy
+        let mut att_not_matching: Vec<String> = Vec::new();
+        att_not_matching.push(String::from("x"));
+        att_not_matching.push(String::from("y"));
+
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+
+        let mut _policy_mut = String::from("{\"AND\": [{\"ATT\": \"a2\"}, {\"ATT\": \"a1\"}]}");
+
+        for _i in 3.._num_nested {
+            let mut _str = String::from("{\"AND\":[");
+            _str.push_str("{\"ATT\":\"");
+            _str.push_str(&att_matching[_i - 1]);
+            _str.push_str("\"},");
+            _str.push_str(&_policy_mut);
+            _str.push_str("]}");
+            _policy_mut = _str.clone();
+        }
+
+        println!("{}", _policy_mut);
+
+        // cp-abe ciphertext
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &_policy_mut, &plaintext).unwrap();
+
+        // and now decrypt again with mathcing sk
+        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
+        assert_eq!(_match.is_ok(), true);
+        assert_eq!(_match.unwrap(), plaintext);
+
+        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
+        assert_eq!(_no_match.is_err(), true);
+    }
+
+
+    #[test]
+    fn or3() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // a set of two attributes matching the policy
+        let mut att_matching: Vec<String> = Vec::new();
+        att_matching.push(String::from("A"));
+
+        // a set of two attributes NOT matching the policy
+        let mut att_not_matching: Vec<String> = Vec::new();
+        att_not_matching.push(String::from("B"));
+        att_not_matching.push(String::from("C"));
+
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+
+        // our policy
+        let policy = String::from(r#"{"OR": [{"ATT": "X"}, {"ATT": "Y"}, {"ATT": "A"}]}"#);
+
+        // cp-abe ciphertext
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+
+        // and now decrypt again with mathcing sk
+        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
+        assert_eq!(_match.is_ok(), true);
+        assert_eq!(_match.unwrap(), plaintext);
+
+        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
+        assert_eq!(_no_match.is_err(), true);
+    }
+
+    #[test]
+    fn and() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // a set of two attributes matching the policy
+        let mut att_matching: Vec<String> = Vec::new();
+        att_matching.push(String::from("A"));
+        att_matching.push(String::from("B"));
+        att_matching.push(String::from("C"));
+
+        // a set of two attributes NOT matching the policy
+        let mut att_not_matching: Vec<String> = Vec::new();
+        att_not_matching.push(String::from("A"));
+        att_not_matching.push(String::from("D"));
+
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+
+        // our policy
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+
+        // cp-abe ciphertext
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+
+        // and now decrypt again with mathcing sk
+        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
+        assert_eq!(_match.is_ok(), true);
+        assert_eq!(_match.unwrap(), plaintext);
+        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
+        assert_eq!(_no_match.is_err(), true);
+    }
+
+    #[test]
+    fn and3() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // a set of two attributes matching the policy
+        let mut att_matching: Vec<String> = Vec::new();
+        att_matching.push(String::from("A"));
+        att_matching.push(String::from("B"));
+        att_matching.push(String::from("C"));
+
+        // a set of two attributes NOT matching the policy
+        let mut att_not_matching: Vec<String> = Vec::new();
+        att_not_matching.push(String::from("A"));
+        att_not_matching.push(String::from("D"));
+
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+
+        // our policy
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}"#);
+
+        // cp-abe ciphertext
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+
+        // and now decrypt again with mathcing sk
+        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
+        assert_eq!(_match.is_ok(), true);
+        assert_eq!(_match.unwrap(), plaintext);
+
+        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
+        assert_eq!(_no_match.is_err(), true);
+    }
+
+    #[test]
+    fn or_and() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // a set of two attributes matching the policy
+        let mut att_matching: Vec<String> = Vec::new();
+        att_matching.push(String::from("A"));
+        att_matching.push(String::from("B"));
+        att_matching.push(String::from("C"));
+        att_matching.push(String::from("D"));
+
+        // a set of two attributes NOT matching the policy
+        let mut att_not_matching: Vec<String> = Vec::new();
+        att_not_matching.push(String::from("A"));
+        att_not_matching.push(String::from("C"));
+
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+
+        // our policy
+        let policy = String::from(r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}"#);
+
+        // cp-abe ciphertext
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+
+        // and now decrypt again with mathcing sk
+        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
+        assert_eq!(_match.is_ok(), true);
+        assert_eq!(_match.unwrap(), plaintext);
+        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
+        assert_eq!(_no_match.is_err(), true);
+    }
+
+    #[test]
+    fn delegate_ab() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // a set of three attributes matching the policy
+        let mut _atts: Vec<String> = Vec::new();
+        _atts.push(String::from("A"));
+        _atts.push(String::from("B"));
+        _atts.push(String::from("C"));
+        // a set of two delegated attributes
+        let mut _delegate_att: Vec<String> = Vec::new();
+        _delegate_att.push(String::from("A"));
+        _delegate_att.push(String::from("B"));
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // our policy
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        // cp-abe ciphertext
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        // a cp-abe SK key matching
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &_atts).unwrap();
+        // delegate a cp-abe SK
+        let del: CpAbeSecretKey = delegate(&pk, &sk, &_delegate_att).unwrap();
+        // and now decrypt again with mathcing sk
+        let _match = decrypt(&del, &ct_cp);
+        assert_eq!(_match.is_ok(), true);
+        assert_eq!(_match.unwrap(), plaintext);
+    }
+
+    #[test]
+    fn delegate_rerandomized() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let _atts = vec![String::from("A"), String::from("B")];
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &_atts).unwrap();
+        let del: CpAbeSecretKey = delegate(&pk, &sk, &_atts).unwrap();
+        // same attributes, but fresh randomness in every component
+        assert_eq!(del._d_j.len(), sk._d_j.len());
+        assert_eq!(del._d == sk._d, false);
+        for (_parent, _child) in sk._d_j.iter().zip(del._d_j.iter()) {
+            assert_eq!(_parent._str, _child._str);
+            assert_eq!(_parent._g1 == _child._g1, false);
+            assert_eq!(_parent._g2 == _child._g2, false);
+        }
+    }
+
+    #[test]
+    fn delegate_errors() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")]).unwrap();
+        // empty subset
+        assert_eq!(delegate(&pk, &sk, &Vec::new()).is_err(), true);
+        match delegate(&pk, &sk, &Vec::new()) {
+            Err(RabeError::EmptyAttributes) => {}
+            _ => panic!("expected RabeError::EmptyAttributes"),
+        }
+        // attribute not part of the key
+        match delegate(&pk, &sk, &vec![String::from("A"), String::from("C")]) {
+            Err(RabeError::AttributeNotInKey(_attr)) => assert_eq!(_attr, String::from("C")),
+            _ => panic!("expected RabeError::AttributeNotInKey"),
+        }
+        // normalization trims whitespace but does not fold case
+        match delegate(&pk, &sk, &vec![String::from(" a ")]) {
+            Err(RabeError::AttributeNotInKey(_attr)) => assert_eq!(_attr, String::from("a")),
+            _ => panic!("expected RabeError::AttributeNotInKey"),
+        }
+        let del: CpAbeSecretKey = delegate(&pk, &sk, &vec![String::from(" A ")]).unwrap();
+        assert_eq!(del._d_j[0]._str, String::from("A"));
+    }
+
+    #[test]
+    fn decrypt_cost_pruned() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"OR": [{"ATT": "A"}, {"AND": [{"ATT": "B"}, {"ATT": "C"}]}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        // pruning selects a single attribute
+        let sk_a = keygen(&pk, &msk, &vec![String::from("A"), String::from("B"), String::from("C")]).unwrap();
+        assert_eq!(decrypt_cost(&sk_a, &ct_cp), Some(2));
+        // pruning selects both attributes of the AND branch
+        let sk_bc = keygen(&pk, &msk, &vec![String::from("B"), String::from("C")]).unwrap();
+        assert_eq!(decrypt_cost(&sk_bc, &ct_cp), Some(4));
+        // no decryption possible
+        let sk_b = keygen(&pk, &msk, &vec![String::from("B")]).unwrap();
+        assert_eq!(decrypt_cost(&sk_b, &ct_cp), None);
+    }
+
+    #[test]
+    fn encoding() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"ATT": "A"}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        let _bytes = ct_cp.to_bytes().unwrap();
+        // the policy comes first, with a fixed-width little-endian length prefix
+        assert_eq!(&_bytes[..8], &[policy.len() as u8, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&_bytes[8..8 + policy.len()], policy.as_bytes());
+        let ct_decoded = CpAbeCiphertext::from_bytes(&_bytes).unwrap();
+        let sk_decoded = CpAbeSecretKey::from_bytes(&sk.to_bytes().unwrap()).unwrap();
+        assert_eq!(decrypt(&sk_decoded, &ct_decoded).unwrap(), plaintext);
+        assert_eq!(CpAbePublicKey::from_bytes(&pk.to_bytes().unwrap()).unwrap() == pk, true);
+        assert_eq!(CpAbeMasterKey::from_bytes(&msk.to_bytes().unwrap()).unwrap() == msk, true);
+    }
+
+    #[test]
+    fn change_policy_and_to_or() {
+        // setup scheme
+        let (_pk, _msk) = setup();
+        let ctx = CpAbeContext { _pk, _msk };
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let new_policy = String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "C"}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&ctx._pk, &policy, &plaintext).unwrap();
+        let sk_a: CpAbeSecretKey = keygen(&ctx._pk, &ctx._msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(decrypt(&sk_a, &ct_cp).is_err(), true);
+        // re-encrypt under the weaker policy
+        let ct_new: CpAbeCiphertext = ctx.change_policy(&ct_cp, &new_policy).unwrap();
+        assert_eq!(ct_new._policy, new_policy);
+        assert_eq!(decrypt(&sk_a, &ct_new).unwrap(), plaintext);
+        let sk_c: CpAbeSecretKey = keygen(&ctx._pk, &ctx._msk, &vec![String::from("C")]).unwrap();
+        assert_eq!(decrypt(&sk_c, &ct_new).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn change_policy_negated() {
+        // setup scheme
+        let (_pk, _msk) = setup();
+        let ctx = CpAbeContext { _pk, _msk };
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let sk_c: CpAbeSecretKey = keygen(&ctx._pk, &ctx._msk, &vec![String::from("C")]).unwrap();
+        let new_policy = String::from(r#"{"ATT": "C"}"#);
+        // no single set of attributes satisfies both negations
+        let _policies = vec![
+            String::from(r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#),
+            String::from(
+                r#"{"OR": [{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}, {"AND": [{"ATT": "B"}, {"NOT": {"ATT": "A"}}]}]}"#,
+            ),
+        ];
+        for _policy in _policies {
+            let ct_cp: CpAbeCiphertext = encrypt(&ctx._pk, &_policy, &plaintext).unwrap();
+            let ct_new = ctx.change_policy(&ct_cp, &new_policy).unwrap();
+            assert_eq!(decrypt(&sk_c, &ct_new).unwrap(), plaintext);
         }
+        // a ciphertext of another authority is rejected
+        let (pk_other, _msk_other) = setup();
+        let ct_other = encrypt(&pk_other, &new_policy, &plaintext).unwrap();
+        assert_eq!(
+            ctx.change_policy(&ct_other, &new_policy).err(),
+            Some(RabeError::SymmetricDecryptionFailed)
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
+    #[test]
+    fn reserved_attributes() {
+        // setup scheme
+        let (_pk, _msk) = setup();
+        let ctx = CpAbeContext { _pk, _msk };
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // the reserved attributes can not be issued directly, e.g. to forge the bits of age=20
+        for _attr in vec![TRUE_ATTRIBUTE, "#age:bit4:1"] {
+            let _attributes = vec![String::from("A"), String::from(_attr)];
+            assert_eq!(
+                keygen(&ctx._pk, &ctx._msk, &_attributes).err(),
+                Some(RabeError::InvalidAttribute(format!("{} is reserved", _attr)))
+            );
+        }
+        // TRUE_ATTRIBUTE is not a valid leaf, the policy is written {"TRUE": true}
+        let _policy = String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "#TRUE"}]}"#);
+        assert_eq!(encrypt(&ctx._pk, &_policy, &plaintext).is_err(), true);
+        let _policy = String::from(r#"{"OR": [{"ATT": "A"}, {"TRUE": true}]}"#);
+        assert_eq!(encrypt(&ctx._pk, &_policy, &plaintext).is_ok(), true);
+        // ciphertexts holding reserved attributes can still change their policy
+        let policy = String::from(r#"{"AND": [{"TRUE": true}, {"GE": {"att": "age", "value": 18}}]}"#);
+        let ct_cp = encrypt(&ctx._pk, &policy, &plaintext).unwrap();
+        let new_policy = String::from(r#"{"ATT": "A"}"#);
+        let ct_new = ctx.change_policy(&ct_cp, &new_policy).unwrap();
+        let sk_a = keygen(&ctx._pk, &ctx._msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(decrypt(&sk_a, &ct_new).unwrap(), plaintext);
+    }
 
-    use super::*;
+    #[test]
+    fn bincode_and_json_agree() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        assert_eq!(formats_agree(&pk), true);
+        assert_eq!(formats_agree(&msk), true);
+        assert_eq!(formats_agree(&ct_cp), true);
+        assert_eq!(formats_agree(&sk), true);
+        assert_eq!(formats_agree(&sk._d_j[0]), true);
+        assert_eq!(formats_agree(&ct_cp._c_y[1]), true);
+        // the values survive both formats intact
+        let _sk_json: CpAbeSecretKey = serde_json::from_str(&serde_json::to_string(&sk).unwrap())
+            .unwrap();
+        let _ct_bincode: CpAbeCiphertext = bincode::deserialize(&bincode::serialize(&ct_cp).unwrap())
+            .unwrap();
+        assert_eq!(decrypt(&_sk_json, &_ct_bincode).unwrap(), plaintext);
+    }
 
     #[test]
-    fn or() {
+    fn json_and_bincode_encoding() {
         // setup scheme
         let (pk, msk) = setup();
-        // a set of two attributes matching the policy
-        let mut att_matching: Vec<String> = Vec::new();
-        att_matching.push(String::from("D"));
-        att_matching.push(String::from("B"));
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        // round trips of each format
+        let pk_json = CpAbePublicKey::from_json(&pk.to_json().unwrap()).unwrap();
+        let pk_bincode = CpAbePublicKey::from_bincode(&pk.to_bincode().unwrap()).unwrap();
+        let msk_json = CpAbeMasterKey::from_json(&msk.to_json().unwrap()).unwrap();
+        let msk_bincode = CpAbeMasterKey::from_bincode(&msk.to_bincode().unwrap()).unwrap();
+        let sk_json = CpAbeSecretKey::from_json(&sk.to_json().unwrap()).unwrap();
+        let sk_bincode = CpAbeSecretKey::from_bincode(&sk.to_bincode().unwrap()).unwrap();
+        let ct_json = CpAbeCiphertext::from_json(&ct_cp.to_json().unwrap()).unwrap();
+        let ct_bincode = CpAbeCiphertext::from_bincode(&ct_cp.to_bincode().unwrap()).unwrap();
+        // both formats decode to equal values
+        assert_eq!(pk_json == pk && pk_json == pk_bincode, true);
+        assert_eq!(msk_json == msk && msk_json == msk_bincode, true);
+        assert_eq!(sk_json == sk && sk_json == sk_bincode, true);
+        assert_eq!(ct_json == ct_cp && ct_json == ct_bincode, true);
+        assert_eq!(decrypt(&sk_json, &ct_bincode).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk_bincode, &ct_json).unwrap(), plaintext);
+        // JSON is readable, but larger
+        let _json = ct_cp.to_json().unwrap();
+        assert_eq!(_json.contains("_policy"), true);
+        assert_eq!(_json.len() > ct_cp.to_bincode().unwrap().len(), true);
+        assert_eq!(CpAbeCiphertext::from_json("joking-around?").is_err(), true);
+    }
 
-        // a set of two attributes NOT matching the policy
-        let mut att_not_matching: Vec<String> = Vec::new();
-        att_not_matching.push(String::from("C"));
-        att_not_matching.push(String::from("D"));
+    #[test]
+    fn policy_matches() {
+        // setup scheme
+        let (pk, _msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_cp.policy_matches(&[String::from("A"), String::from("C")]), true);
+        assert_eq!(ct_cp.policy_matches(&[String::from("A")]), false);
+        assert_eq!(ct_cp.policy_matches(&[]), false);
+        // compact policies are decoded
+        let ct_compact: CpAbeCiphertext = encrypt_compact_policy(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_compact.policy_matches(&[String::from("A"), String::from("B")]), true);
+        // anyone matches the policy {"TRUE": true}
+        let ct_true: CpAbeCiphertext = encrypt(&pk, &String::from(r#"{"TRUE": true}"#), &plaintext)
+            .unwrap();
+        assert_eq!(ct_true.policy_matches(&[String::from("X")]), true);
+    }
 
-        // our plaintext
+    #[test]
+    fn true_policy() {
+        // setup scheme
+        let (pk, msk) = setup();
         let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
             .into_bytes();
+        let policy = String::from(r#"{"TRUE": true}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        // any key of the authority decrypts
+        for _attributes in vec![vec!["A"], vec!["B", "C"], vec!["time:42"]] {
+            let _attributes: Vec<String> = _attributes.iter().map(|a| a.to_string()).collect();
+            let sk: CpAbeSecretKey = keygen(&pk, &msk, &_attributes).unwrap();
+            assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+            // as well as keys delegated from it
+            let del: CpAbeSecretKey = delegate(&pk, &sk, &vec![_attributes[0].clone()]).unwrap();
+            assert_eq!(decrypt(&del, &ct_cp).unwrap(), plaintext);
+        }
+        // keys of another authority do not
+        let (pk_other, msk_other) = setup();
+        let sk_other: CpAbeSecretKey = keygen(&pk_other, &msk_other, &vec![String::from("A")])
+            .unwrap();
+        assert_eq!(decrypt(&sk_other, &ct_cp).ok() == Some(plaintext.clone()), false);
+        // TRUE can be combined with other gates
+        let ct_and: CpAbeCiphertext = encrypt(
+            &pk,
+            &String::from(r#"{"AND": [{"TRUE": true}, {"ATT": "A"}]}"#),
+            &plaintext,
+        ).unwrap();
+        let sk_a: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        let sk_b: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("B")]).unwrap();
+        assert_eq!(decrypt(&sk_a, &ct_and).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk_b, &ct_and).is_err(), true);
+    }
 
-        // our policy
+    #[test]
+    fn derived_iv() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt_with_derived_iv(&pk, &policy, &plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        // the iv only depends on the ABE components
+        let mut _nonce_data = encode(&ct_cp._c).unwrap();
+        _nonce_data.extend(encode(&ct_cp._c_p).unwrap());
+        assert_eq!(
+            ct_cp._ct[..GCM_NONCE_SIZE].to_vec(),
+            derive_iv(&_nonce_data)[..GCM_NONCE_SIZE].to_vec()
+        );
+    }
+
+    #[test]
+    fn secret_reuse() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let other_policy = String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "C"}]}"#);
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        let _s: Fr = rand::thread_rng().gen();
+        let mut _tracker = SecretTracker::new();
+        let ct_cp = encrypt_with_secret(&pk, &policy, &plaintext, _s, Some(&mut _tracker)).unwrap();
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        assert_eq!(_tracker.contains(&ct_cp._c), true);
+        // the same secret is rejected, even under another policy
+        match encrypt_with_secret(&pk, &other_policy, &plaintext, _s, Some(&mut _tracker)) {
+            Err(RabeError::EphemeralReuse) => {}
+            _ => panic!("expected RabeError::EphemeralReuse"),
+        }
+        // a fresh secret is accepted
+        let _t: Fr = rand::thread_rng().gen();
+        assert_eq!(
+            encrypt_with_secret(&pk, &other_policy, &plaintext, _t, Some(&mut _tracker)).is_ok(),
+            true
+        );
+        // without a tracker reuse is not detected
+        assert_eq!(encrypt_with_secret(&pk, &policy, &plaintext, _s, None).is_ok(), true);
+    }
+
+    #[test]
+    fn str_roundtrip() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = "tanze als würde niemand zusehen, verschlüssle als sähe jeder zu! 🔐 ダンス";
         let policy = String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt_str(&pk, &policy, plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("B")]).unwrap();
+        assert_eq!(decrypt_to_string(&sk, &ct_cp).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext.as_bytes().to_vec());
+        let sk_c: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("C")]).unwrap();
+        assert_eq!(decrypt_to_string(&sk_c, &ct_cp).is_none(), true);
+        // binary plaintexts are not returned as text
+        let ct_bin: CpAbeCiphertext = encrypt(&pk, &policy, &vec![0xff, 0xfe, 0xfd]).unwrap();
+        assert_eq!(decrypt_to_string(&sk, &ct_bin).is_none(), true);
+    }
 
-        // cp-abe ciphertext
+    #[test]
+    fn batch() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let _sets: Vec<Vec<String>> = (0..8)
+            .map(|_i| vec![String::from("A"), format!("user{}", _i)])
+            .collect();
+        let _items: Vec<(String, Vec<u8>)> = (0..8)
+            .map(|_i| {
+                (
+                    format!(r#"{{"AND": [{{"ATT": "A"}}, {{"ATT": "user{}"}}]}}"#, _i),
+                    format!("dance like no one's watching, encrypt like everyone is! {}", _i)
+                        .into_bytes(),
+                )
+            })
+            .collect();
+        let _keys = keygen_batch(&pk, &msk, &_sets);
+        let _cts = encrypt_batch(&pk, &_items);
+        assert_eq!(_keys.len(), 8);
+        assert_eq!(_cts.len(), 8);
+        // every output is valid on its own and in input order
+        for _i in 0..8 {
+            let _sk = _keys[_i].as_ref().unwrap();
+            let _ct = _cts[_i].as_ref().unwrap();
+            assert_eq!(_sk._d_j[1]._str, _sets[_i][1]);
+            assert_eq!(decrypt(_sk, _ct).unwrap(), _items[_i].1);
+            assert_eq!(decrypt(_sk, _cts[(_i + 1) % 8].as_ref().unwrap()).is_err(), true);
+        }
+        // fresh randomness per item
+        assert_eq!(_keys[0].as_ref().unwrap()._d == _keys[1].as_ref().unwrap()._d, false);
+        // invalid items fail individually
+        assert_eq!(keygen_batch(&pk, &msk, &vec![Vec::new()])[0].is_err(), true);
+    }
+
+    #[test]
+    fn base64_roundtrip() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        // keys survive being stashed as base64 strings, e.g. in environment variables
+        let pk_decoded = CpAbePublicKey::from_base64(&pk.to_base64().unwrap()).unwrap();
+        let msk_decoded = CpAbeMasterKey::from_base64(&msk.to_base64().unwrap()).unwrap();
+        assert_eq!(pk_decoded == pk, true);
+        assert_eq!(msk_decoded == msk, true);
+        let sk: CpAbeSecretKey = keygen(
+            &pk_decoded,
+            &msk_decoded,
+            &vec![String::from("A"), String::from("B")],
+        ).unwrap();
+        let sk_decoded = CpAbeSecretKey::from_base64(&sk.to_base64().unwrap()).unwrap();
+        let ct_cp: CpAbeCiphertext = encrypt(&pk_decoded, &policy, &plaintext).unwrap();
+        let ct_decoded = CpAbeCiphertext::from_base64(&ct_cp.to_base64().unwrap()).unwrap();
+        assert_eq!(decrypt(&sk_decoded, &ct_decoded).unwrap(), plaintext);
+        assert_eq!(CpAbeSecretKey::from_base64("not base64!").is_err(), true);
+    }
+
+    #[test]
+    fn base64_reader_large() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext: Vec<u8> = (0..1 << 20).map(|_i| (_i % 251) as u8).collect();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
         let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let _armored = ct_cp.to_base64().unwrap();
+        let ct_streamed = CpAbeCiphertext::from_base64_reader(std::io::Cursor::new(_armored.as_bytes()))
+            .unwrap();
+        assert_eq!(ct_streamed == ct_cp, true);
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        assert_eq!(decrypt(&sk, &ct_streamed).unwrap(), plaintext);
+    }
 
-        // and now decrypt again with mathcing sk
-        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
-        assert_eq!(_match.is_some(), true);
-        assert_eq!(_match.unwrap(), plaintext);
+    #[test]
+    fn many_attributes() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let _attributes: Vec<String> = (0..500).map(|_i| format!("attr{}", _i)).collect();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &_attributes).unwrap();
+        let policy = String::from(r#"{"AND": [{"ATT": "attr499"}, {"ATT": "attr7"}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        let ct_missing: CpAbeCiphertext = encrypt(
+            &pk,
+            &String::from(r#"{"AND": [{"ATT": "attr499"}, {"ATT": "attr500"}]}"#),
+            &plaintext,
+        ).unwrap();
+        assert_eq!(decrypt(&sk, &ct_missing).is_err(), true);
+    }
 
-        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
-        assert_eq!(_no_match.is_none(), true);
+    #[test]
+    fn master_key_commitment() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let _commitment = msk.public_commitment(&pk);
+        assert_eq!(verify_master_commitment(&pk, &_commitment), true);
+        // a mismatched master key
+        let (pk_other, msk_other) = setup();
+        assert_eq!(verify_master_commitment(&pk, &msk_other.public_commitment(&pk)), false);
+        assert_eq!(verify_master_commitment(&pk, &msk_other.public_commitment(&pk_other)), false);
+        assert_eq!(verify_master_commitment(&pk_other, &_commitment), false);
+        // same beta, another alpha
+        let mut _equivocated = msk.clone();
+        _equivocated._g2_alpha = _equivocated._g2_alpha + pk._g2;
+        assert_eq!(verify_master_commitment(&pk, &_equivocated.public_commitment(&pk)), false);
     }
 
     #[test]
-    fn and10() {
+    fn hash_cache() {
         // setup scheme
         let (pk, msk) = setup();
-        // a set of two attributes matching the policy
-        let mut att_matching: Vec<String> = Vec::new();
-        for n in 1..11 {
-            let mut _c = String::from("attr");
-            _c.push_str(&n.to_string());
-            att_matching.push(_c);
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#);
+        let _cache = HashCache::new();
+        let sk_cached = keygen_cached(&pk, &msk, &_cache, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        // A, B and the reserved attribute
+        assert_eq!(_cache.len(), 3);
+        let ct_cached = encrypt_cached(&pk, &_cache, &policy, &plaintext).unwrap();
+        // only C is new
+        assert_eq!(_cache.len(), 4);
+        // the cached hashes are the uncached ones
+        for _attr in vec!["A", "B", "C"] {
+            let _attr = String::from(_attr);
+            assert_eq!(_cache.hash(&_attr) == hash_to_g2(&_attr, BSW_HASH_DOMAIN), true);
         }
+        // keys and ciphertexts of both paths are interchangeable
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(verify_key(&pk, &sk_cached), true);
+        assert_eq!(decrypt(&sk_cached, &ct_cp).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk, &ct_cached).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk_cached, &ct_cached).unwrap(), plaintext);
+        // another authority uses the same hashes
+        let (pk_other, msk_other) = setup();
+        let sk_other = keygen_cached(&pk_other, &msk_other, &_cache, &vec![String::from("A")])
+            .unwrap();
+        assert_eq!(_cache.len(), 4);
+        assert_eq!(verify_key(&pk_other, &sk_other), true);
+    }
 
-        // a set of two attributes NOT matching the policy
-        let mut att_not_matching: Vec<String> = Vec::new();
-        att_not_matching.push(String::from("attr201"));
-        att_not_matching.push(String::from("attr200"));
+    #[test]
+    fn subgroup_checks() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        // valid elements pass
+        assert_eq!(ct_cp.validate().is_ok(), true);
+        assert_eq!(sk.validate().is_ok(), true);
+        assert_eq!(pk.validate().is_ok(), true);
+        assert_eq!(msk.validate().is_ok(), true);
+        assert_eq!(CpAbeCiphertext::from_bytes(&ct_cp.to_bytes().unwrap()).is_ok(), true);
+        // a crafted element of Fq12 that is not in Gt: tamper with the encoding of _c_p
+        let _c_p = encode(&ct_cp._c_p).unwrap();
+        for _i in vec![0, _c_p.len() / 2, _c_p.len() - 33] {
+            let mut _crafted = _c_p.clone();
+            _crafted[_i] ^= 0x01;
+            // bn may already reject the bytes, otherwise the subgroup check has to
+            if let Ok(_element) = bincode::deserialize::<Gt>(&_crafted) {
+                match check_gt(&_element) {
+                    Err(RabeError::InvalidGroupElement(_group)) => assert_eq!(_group, "Gt"),
+                    _ => panic!("expected RabeError::InvalidGroupElement"),
+                }
+                let mut _ct = ct_cp.clone();
+                _ct._c_p = _element;
+                assert_eq!(CpAbeCiphertext::from_bytes(&_ct.to_bytes().unwrap()).is_err(), true);
+            }
+        }
+    }
 
-        // our plaintext
+    #[test]
+    fn verify_keys() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        assert_eq!(verify_key(&pk, &sk), true);
+        assert_eq!(verify_key(&pk, &delegate(&pk, &sk, &vec![String::from("A")]).unwrap()), true);
+        // tampered attribute name
+        let mut _renamed = sk.clone();
+        _renamed._d_j[0]._str = String::from("C");
+        assert_eq!(verify_key(&pk, &_renamed), false);
+        // tampered components
+        let mut _tampered = sk.clone();
+        _tampered._d_j[1]._g1 = _tampered._d_j[1]._g1 + pk._g1;
+        assert_eq!(verify_key(&pk, &_tampered), false);
+        let mut _tampered_d = sk.clone();
+        _tampered_d._d = _tampered_d._d + pk._g2;
+        assert_eq!(verify_key(&pk, &_tampered_d), false);
+        // components mixed from two keys
+        let sk2: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        let mut _mixed = sk.clone();
+        _mixed._d_j[1] = sk2._d_j[1].clone();
+        assert_eq!(verify_key(&pk, &_mixed), false);
+        // another authority
+        let (pk_other, _msk_other) = setup();
+        assert_eq!(verify_key(&pk_other, &sk), false);
+    }
+
+    #[test]
+    fn try_keygen_policy() {
+        // setup scheme
+        let (pk, msk) = setup();
         let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
             .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#);
+        // sufficient attributes
+        let sk = try_keygen(&pk, &msk, &vec![String::from("A"), String::from("C")], &policy)
+            .unwrap();
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        // insufficient attributes
+        match try_keygen(&pk, &msk, &vec![String::from("B"), String::from("C")], &policy) {
+            Err(RabeError::InsufficientAttributes) => {}
+            _ => panic!("expected RabeError::InsufficientAttributes"),
+        }
+        match try_keygen(&pk, &msk, &Vec::new(), &policy) {
+            Err(RabeError::EmptyAttributes) => {}
+            _ => panic!("expected RabeError::EmptyAttributes"),
+        }
+        // a policy that can not be parsed is never satisfied
+        assert_eq!(
+            try_keygen(&pk, &msk, &vec![String::from("A")], &String::from("joking-around?"))
+                .is_err(),
+            true
+        );
+    }
 
-        let mut _policy = String::from("{\"AND\": [");
-        for n in 1..11 {
-            let mut _current = String::from("{\"ATT\": \"attr");
-            if n < 10 {
-                _current.push_str(&n.to_string());
-                _current.push_str(&String::from("\"}, "));
-            } else {
-                _current.push_str(&n.to_string());
-                _current.push_str(&String::from("\"}]"));
-            }
-            _policy.push_str(&_current);
+    #[test]
+    fn numeric_comparisons() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let sk_18: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("age=18")]).unwrap();
+        let sk_40: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("age=40")]).unwrap();
+        let _cases = vec![
+            (r#"{"GE": {"att": "age", "value": 18}}"#, true, true),
+            (r#"{"GE": {"att": "age", "value": 21}}"#, false, true),
+            (r#"{"LE": {"att": "age", "value": 18}}"#, true, false),
+            (r#"{"EQ": {"att": "age", "value": 18}}"#, true, false),
+            (r#"{"AND": [{"GE": {"att": "age", "value": 30}}, {"LE": {"att": "age", "value": 65}}]}"#, false, true),
+        ];
+        for (_policy, _match_18, _match_40) in _cases {
+            let ct_cp: CpAbeCiphertext = encrypt(&pk, &String::from(_policy), &plaintext).unwrap();
+            assert_eq!(decrypt(&sk_18, &ct_cp).is_ok(), _match_18);
+            assert_eq!(decrypt(&sk_40, &ct_cp).is_ok(), _match_40);
         }
-        _policy.push_str(&String::from("}"));
-        println!("{:?}", _policy);
-        // cp-abe ciphertext
-        let ct_cp: CpAbeCiphertext = encrypt(&pk, &_policy, &plaintext).unwrap();
+        // delegated keys keep the bits of their numeric attributes
+        let del: CpAbeSecretKey = delegate(&pk, &sk_40, &vec![String::from("age=40")]).unwrap();
+        let ct_cp = encrypt(&pk, &String::from(r#"{"GE": {"att": "age", "value": 21}}"#), &plaintext)
+            .unwrap();
+        assert_eq!(decrypt(&del, &ct_cp).unwrap(), plaintext);
+        // a key may only hold one value per numeric attribute
+        assert_eq!(
+            keygen(&pk, &msk, &vec![String::from("age=18"), String::from("age=40")]).is_err(),
+            true
+        );
+    }
 
-        // and now decrypt again with mathcing sk
-        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
-        assert_eq!(_match.is_some(), true);
-        assert_eq!(_match.unwrap(), plaintext);
+    #[test]
+    fn keygen_commitments() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let _attributes = vec![String::from("A"), String::from("B"), String::from("C")];
+        let (sk, _commitments) = keygen_with_commitments(&pk, &msk, &_attributes).unwrap();
+        // including the reserved attribute of the policy {"TRUE": true}
+        assert_eq!(_commitments.len(), _attributes.len() + 1);
+        // e(g1, D_j) / e(D'_j, H(j)) is the same for all attributes
+        let mut _e_gg_r: Vec<Gt> = Vec::new();
+        for (_d_j, _commitment) in sk._d_j.iter().zip(_commitments.iter()) {
+            assert_eq!(_commitment._str, _d_j._str);
+            assert_eq!(_commitment._g1 == _d_j._g1, true);
+            _e_gg_r.push(
+                pairing(pk._g1, _d_j._g2) *
+                    pairing(_commitment._g1, hash_to_g2(&_commitment._str, BSW_HASH_DOMAIN))
+                        .inverse(),
+            );
+        }
+        assert_eq!(_e_gg_r.iter().all(|_e| *_e == _e_gg_r[0]), true);
+        // commitments of another key do not match
+        let (_, _other) = keygen_with_commitments(&pk, &msk, &_attributes).unwrap();
+        assert_eq!(
+            pairing(pk._g1, sk._d_j[0]._g2) *
+                pairing(_other[0]._g1, hash_to_g2(&_other[0]._str, BSW_HASH_DOMAIN)).inverse() ==
+                _e_gg_r[0],
+            false
+        );
+    }
 
-        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
-        assert_eq!(_no_match.is_none(), true);
+    #[test]
+    fn header_and_payload() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        let sk_a: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        let _header = ct_cp.header_bytes();
+        assert_eq!(_header.len() < ct_cp.to_bytes().unwrap().len(), true);
+        // the header alone can be checked, but not decrypted
+        let ct_header = CpAbeCiphertext::from_bytes(&_header).unwrap();
+        assert_eq!(ct_header._policy, policy);
+        assert_eq!(ct_header._ct.is_empty(), true);
+        assert_eq!(decrypt_cost(&sk, &ct_header), decrypt_cost(&sk, &ct_cp));
+        assert_eq!(decrypt_cost(&sk_a, &ct_header).is_none(), true);
+        assert_eq!(decrypt(&sk, &ct_header).is_err(), true);
+        // rejoin
+        let ct_joined = CpAbeCiphertext::attach_payload(&_header, ct_cp._ct.clone()).unwrap();
+        assert_eq!(ct_joined == ct_cp, true);
+        assert_eq!(decrypt(&sk, &ct_joined).unwrap(), plaintext);
+        assert_eq!(CpAbeCiphertext::attach_payload(&_header[1..], Vec::new()).is_err(), true);
+    }
+
+    #[test]
+    fn decryption_challenge() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let (challenge, secret) = make_challenge(&pk, &policy).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        let sk_a: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        // a satisfying key recovers the secret, also after serialization
+        let challenge = CpAbeChallenge::from_bytes(&challenge.to_bytes().unwrap()).unwrap();
+        assert_eq!(respond_challenge(&sk, &challenge).unwrap() == secret, true);
+        // a key not satisfying the policy cannot respond
+        assert_eq!(respond_challenge(&sk_a, &challenge).is_none(), true);
+        // every challenge has a fresh secret
+        let (_, other) = make_challenge(&pk, &policy).unwrap();
+        assert_eq!(other == secret, false);
+        assert_eq!(make_challenge(&pk, &String::new()).is_err(), true);
+    }
+
+    #[test]
+    fn decrypt_failure_causes() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        let sk_a: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        // attribute mismatch
+        assert_eq!(decrypt(&sk_a, &ct_cp), Err(RabeError::InsufficientAttributes));
+        // matching key, but truncated or missing payload
+        let mut ct_corrupt = ct_cp.clone();
+        ct_corrupt._ct.truncate(8);
+        assert_eq!(decrypt(&sk, &ct_corrupt), Err(RabeError::SymmetricDecryptionFailed));
+        ct_corrupt._ct = Vec::new();
+        assert_eq!(decrypt(&sk, &ct_corrupt), Err(RabeError::SymmetricDecryptionFailed));
+        // a key of another authority satisfies the policy but recovers a wrong symmetric key
+        let (pk_other, msk_other) = setup();
+        let sk_other: CpAbeSecretKey = keygen(
+            &pk_other,
+            &msk_other,
+            &vec![String::from("A"), String::from("B")],
+        ).unwrap();
+        assert_eq!(decrypt(&sk_other, &ct_cp).is_err(), true);
+        assert_eq!(decrypt(&sk, &ct_cp), Ok(plaintext));
+    }
+
+    #[test]
+    fn tampered_payload() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        // the payload is authenticated, a flipped byte anywhere is detected
+        for _i in vec![0, GCM_NONCE_SIZE, ct_cp._ct.len() / 2, ct_cp._ct.len() - 1] {
+            let mut ct_tampered = ct_cp.clone();
+            ct_tampered._ct[_i] ^= 0xff;
+            assert_eq!(decrypt(&sk, &ct_tampered), Err(RabeError::SymmetricDecryptionFailed));
+        }
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn wrapped_keys() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let policy = String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let _data_key: [u8; 32] = rand::thread_rng().gen();
+        let ct_key: CpAbeCiphertext = wrap_key(&pk, &policy, &_data_key).unwrap();
+        let sk_b: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("B")]).unwrap();
+        let sk_c: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("C")]).unwrap();
+        assert_eq!(unwrap_key(&sk_b, &ct_key).unwrap(), _data_key.to_vec());
+        assert_eq!(unwrap_key(&sk_c, &ct_key).is_err(), true);
+        // the same data key can be wrapped under several policies
+        let ct_other: CpAbeCiphertext = wrap_key(&pk, &String::from(r#"{"ATT": "C"}"#), &_data_key)
+            .unwrap();
+        assert_eq!(unwrap_key(&sk_c, &ct_other).unwrap(), _data_key.to_vec());
+        assert_eq!(wrap_key(&pk, &policy, &[]).is_err(), true);
+    }
+
+    #[test]
+    #[cfg(feature = "test-internals")]
+    fn internals() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let mut _tracker = SecretTracker::new();
+        let _secret: Fr = rand::thread_rng().gen();
+        encrypt_with_secret(&pk, &policy, &plaintext, _secret, Some(&mut _tracker)).unwrap();
+        assert_eq!(_tracker.seen().len(), 1);
+        let _cache = HashCache::new();
+        keygen_cached(&pk, &msk, &_cache, &vec![String::from("A"), String::from("B")]).unwrap();
+        assert_eq!(_cache.hashes().len(), _cache.len());
+    }
+
+    // the fixtures are generated from fixed seeds and checked against tests/data, see
+    // tests/data/README.md
+    #[test]
+    fn golden_fixtures() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+        use std::fs;
+        use std::path::PathBuf;
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"ATT": "C"}]}"#);
+        let _attributes = vec![String::from("A"), String::from("B")];
+        let (pk, msk) = setup_with_rng(&mut StdRng::seed_from_u64(42));
+        let sk = keygen_with_rng(&pk, &msk, &_attributes, &mut StdRng::seed_from_u64(1)).unwrap();
+        let ct_cp = encrypt_with_rng(&pk, &policy, &plaintext, &mut StdRng::seed_from_u64(2))
+            .unwrap();
+        let _fixtures = vec![
+            ("bsw_pk.bin", pk.to_bytes().unwrap()),
+            ("bsw_sk.bin", sk.to_bytes().unwrap()),
+            ("bsw_ct.bin", ct_cp.to_bytes().unwrap()),
+        ];
+        let _dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data");
+        if ::std::env::var("RABE_REGENERATE_FIXTURES").is_ok() {
+            for &(_file, ref _bytes) in _fixtures.iter() {
+                fs::write(_dir.join(_file), _bytes).unwrap();
+            }
+            println!("regenerated the fixtures in {:?}, commit them", _dir);
+        }
+        // the generated bytes are the pinned format, committed fixtures have to match them
+        for &(_file, ref _bytes) in _fixtures.iter() {
+            if let Ok(_pinned) = fs::read(_dir.join(_file)) {
+                assert_eq!(&_pinned, _bytes, "{} changed, see tests/data/README.md", _file);
+            }
+        }
+        let pk = CpAbePublicKey::from_bytes(&_fixtures[0].1).unwrap();
+        let sk = CpAbeSecretKey::from_bytes(&_fixtures[1].1).unwrap();
+        let ct_cp = CpAbeCiphertext::from_bytes(&_fixtures[2].1).unwrap();
+        // serialization, coefficient recovery and the symmetric layer
+        assert_eq!(ct_cp._policy, policy);
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        // hashing attributes to G2
+        assert_eq!(verify_key(&pk, &sk), true);
+        // secret sharing of a new ciphertext against the pinned key
+        let ct_new = encrypt(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(decrypt(&sk, &ct_new).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn threshold() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(
+            r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}}"#,
+        );
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        // any two of the three attributes decrypt
+        for _set in vec![vec!["A", "B"], vec!["A", "C"], vec!["B", "C"], vec!["A", "B", "C"]] {
+            let _attributes: Vec<String> = _set.iter().map(|_a| _a.to_string()).collect();
+            let sk: CpAbeSecretKey = keygen(&pk, &msk, &_attributes).unwrap();
+            assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        }
+        let sk_c: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("C"), String::from("D")])
+            .unwrap();
+        assert_eq!(decrypt(&sk_c, &ct_cp), Err(RabeError::InsufficientAttributes));
+        let _invalid = String::from(
+            r#"{"THRESHOLD": {"k": 4, "children": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}}"#,
+        );
+        assert_eq!(encrypt(&pk, &_invalid, &plaintext).is_err(), true);
     }
 
     #[test]
-    fn nested() {
+    fn negation() {
         // setup scheme
         let (pk, msk) = setup();
-        let _num_nested = 30; // maximum at about 50 to 60
-        // a set of two attributes matching the policy
-        let mut att_matching: Vec<String> = Vec::new();
-        for _i in 1..(_num_nested + 1) {
-            let mut _attr = String::from("a");
-            _attr.push_str(&_i.to_string());
-            att_matching.push(_attr);
-        }
-        // a set of two attributes NOT matching the policy
-        let mut att_not_matching: Vec<String> = Vec::new();
-        att_not_matching.push(String::from("x"));
-        att_not_matching.push(String::from("y"));
-
-        // our plaintext
         let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
             .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#);
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk_a: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        let sk_ab: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        assert_eq!(decrypt(&sk_a, &ct_cp).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk_ab, &ct_cp), Err(RabeError::InsufficientAttributes));
+    }
 
-        let mut _policy_mut = String::from("{\"AND\": [{\"ATT\": \"a2\"}, {\"ATT\": \"a1\"}]}");
-
-        for _i in 3.._num_nested {
-            let mut _str = String::from("{\"AND\":[");
-            _str.push_str("{\"ATT\":\"");
-            _str.push_str(&att_matching[_i - 1]);
-            _str.push_str("\"},");
-            _str.push_str(&_policy_mut);
-            _str.push_str("]}");
-            _policy_mut = _str.clone();
+    #[test]
+    fn attribute_names() {
+        // setup scheme
+        let (pk, _msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(
+            r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "A"}, {"ATT": "C"}]}]}"#,
+        );
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let _names: Vec<&str> = (&ct_cp).into_iter().collect();
+        assert_eq!(_names, vec!["A", "B", "A", "C"]);
+        let _set: AttributeSet = ct_cp.into_iter().filter(|_a| *_a != "B").collect();
+        assert_eq!(_set.attributes(), &vec![String::from("A"), String::from("C")]);
+        let mut _count = 0;
+        for _name in &ct_cp {
+            assert_eq!(_name.is_empty(), false);
+            _count += 1;
         }
-
-        println!("{}", _policy_mut);
-
-        // cp-abe ciphertext
-        let ct_cp: CpAbeCiphertext = encrypt(&pk, &_policy_mut, &plaintext).unwrap();
-
-        // and now decrypt again with mathcing sk
-        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
-        assert_eq!(_match.is_some(), true);
-        assert_eq!(_match.unwrap(), plaintext);
-
-        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
-        assert_eq!(_no_match.is_none(), true);
+        assert_eq!(_count, ct_cp._c_y.len());
     }
 
-
     #[test]
-    fn or3() {
+    fn can_decrypt_without_pairings() {
         // setup scheme
         let (pk, msk) = setup();
-        // a set of two attributes matching the policy
-        let mut att_matching: Vec<String> = Vec::new();
-        att_matching.push(String::from("A"));
-
-        // a set of two attributes NOT matching the policy
-        let mut att_not_matching: Vec<String> = Vec::new();
-        att_not_matching.push(String::from("B"));
-        att_not_matching.push(String::from("C"));
-
-        // our plaintext
         let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
             .into_bytes();
-
-        // our policy
-        let policy = String::from(r#"{"OR": [{"ATT": "X"}, {"ATT": "Y"}, {"ATT": "A"}]}"#);
-
-        // cp-abe ciphertext
+        let policy = String::from(
+            r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "C"}, {"OR": [{"ATT": "D"}, {"ATT": "E"}]}]}]}"#,
+        );
         let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let _sets = vec![
+            (vec!["A", "B"], true),
+            (vec!["C", "E"], true),
+            (vec!["B", "C", "D"], true),
+            (vec!["A", "C"], false),
+            (vec!["D", "E"], false),
+            (vec!["X"], false),
+        ];
+        for (_set, _expected) in _sets {
+            let _attributes: Vec<String> = _set.iter().map(|_a| _a.to_string()).collect();
+            let sk: CpAbeSecretKey = keygen(&pk, &msk, &_attributes).unwrap();
+            assert_eq!(can_decrypt(&sk, &ct_cp), _expected);
+            // agrees with an actual decryption
+            assert_eq!(decrypt(&sk, &ct_cp).is_ok(), _expected);
+        }
+    }
 
-        // and now decrypt again with mathcing sk
-        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
-        assert_eq!(_match.is_some(), true);
-        assert_eq!(_match.unwrap(), plaintext);
-
-        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
-        assert_eq!(_no_match.is_none(), true);
+    #[test]
+    fn policy_and_attributes() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(
+            r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+        );
+        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_cp.policy(), policy);
+        assert_eq!(
+            ct_cp.attributes(),
+            vec![String::from("A"), String::from("B"), String::from("C")]
+        );
+        // a relying party can check a key's attributes up front
+        let _key_attributes = vec![String::from("A"), String::from("C")];
+        assert_eq!(
+            _key_attributes.iter().all(|_a| ct_cp.attributes().contains(_a)),
+            true
+        );
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &_key_attributes).unwrap();
+        assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        // the policy is returned as stored
+        let ct_compact = encrypt_compact_policy(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_compact.policy(), "&(A,|(B,C))");
+        assert_eq!(ct_compact.attributes(), ct_cp.attributes());
     }
 
     #[test]
-    fn and() {
+    fn time_slots() {
         // setup scheme
         let (pk, msk) = setup();
-        // a set of two attributes matching the policy
-        let mut att_matching: Vec<String> = Vec::new();
-        att_matching.push(String::from("A"));
-        att_matching.push(String::from("B"));
-        att_matching.push(String::from("C"));
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let sk: CpAbeSecretKey = keygen_timeslot(&pk, &msk, &vec![String::from("A")], &[41, 42])
+            .unwrap();
+        // in-slot
+        let ct_42: CpAbeCiphertext = encrypt_at(&pk, &policy, 42, &plaintext).unwrap();
+        assert_eq!(decrypt(&sk, &ct_42).unwrap(), plaintext);
+        // out-of-slot
+        let ct_43: CpAbeCiphertext = encrypt_at(&pk, &policy, 43, &plaintext).unwrap();
+        assert_eq!(decrypt(&sk, &ct_43).is_err(), true);
+        // in-slot, but the policy is not satisfied
+        let sk_c: CpAbeSecretKey = keygen_timeslot(&pk, &msk, &vec![String::from("C")], &[42])
+            .unwrap();
+        assert_eq!(decrypt(&sk_c, &ct_42).is_err(), true);
+        // a plain key without slots
+        let sk_plain: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(decrypt(&sk_plain, &ct_42).is_err(), true);
+    }
 
-        // a set of two attributes NOT matching the policy
-        let mut att_not_matching: Vec<String> = Vec::new();
-        att_not_matching.push(String::from("A"));
-        att_not_matching.push(String::from("D"));
+    #[test]
+    fn revocation() {
+        // setup scheme
+        let (pk, msk, mut state) = setup_revocable();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let sk_0 = keygen_revocable(&pk, &msk, &state, &vec![String::from("A")]).unwrap();
+        let ct_0 = encrypt_revocable(&pk, &state, None, &policy, &plaintext).unwrap();
+        assert_eq!(decrypt(&sk_0, &ct_0).unwrap(), plaintext);
+        // the epoch is not part of the policy, keys and ciphertexts of epochs are not plain BSW
+        assert_eq!(ct_0._policy, policy);
+        let sk_plain = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(decrypt(&sk_plain, &ct_0).is_err(), true);
+        // after the revocation, keys of epoch 0 fail on new ciphertexts
+        assert_eq!(revoke(&pk, &msk, &mut state, 1).unwrap(), 1);
+        let ct_1 = encrypt_revocable(&pk, &state, None, &policy, &plaintext).unwrap();
+        assert_eq!(decrypt(&sk_0, &ct_1).err(), Some(RabeError::SymmetricDecryptionFailed));
+        let sk_1 = keygen_revocable(&pk, &msk, &state, &vec![String::from("B")]).unwrap();
+        assert_eq!(decrypt(&sk_1, &ct_1).unwrap(), plaintext);
+        // old ciphertexts stay readable for old keys, but not for new ones
+        assert_eq!(decrypt(&sk_0, &ct_0).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk_1, &ct_0).is_err(), true);
+        // an explicit epoch
+        let ct = encrypt_revocable(&pk, &state, Some(0), &policy, &plaintext).unwrap();
+        assert_eq!(decrypt(&sk_0, &ct).unwrap(), plaintext);
+        assert_eq!(encrypt_revocable(&pk, &state, Some(2), &policy, &plaintext).is_err(), true);
+        // epochs only move forward
+        assert_eq!(revoke(&pk, &msk, &mut state, 1).is_err(), true);
+        assert_eq!(state._epoch, 1);
+        let state = CpAbeRevocationState::from_bytes(&state.to_bytes().unwrap()).unwrap();
+        assert_eq!(state._e_gg_alpha.len(), 2);
+    }
 
-        // our plaintext
+    #[test]
+    fn compact_policy() {
+        // setup scheme
+        let (pk, msk) = setup();
         let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
             .into_bytes();
+        let policy = String::from(
+            r#"{"AND": [{"OR": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}"#,
+        );
+        let ct_json: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+        let ct_compact: CpAbeCiphertext = encrypt_compact_policy(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_compact._compact_policy, true);
+        assert_eq!(ct_compact._policy, "&(|(A,B),&(C,D))");
+        assert_eq!(
+            ct_compact.to_bytes().unwrap().len() < ct_json.to_bytes().unwrap().len(),
+            true
+        );
+        let sk_bcd: CpAbeSecretKey = keygen(
+            &pk,
+            &msk,
+            &vec![String::from("B"), String::from("C"), String::from("D")],
+        ).unwrap();
+        let sk_ab: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        assert_eq!(decrypt(&sk_bcd, &ct_compact).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk_ab, &ct_compact).is_err(), true);
+        assert_eq!(decrypt_cost(&sk_bcd, &ct_compact), decrypt_cost(&sk_bcd, &ct_json));
+        // the flag survives serialization
+        let ct_decoded = CpAbeCiphertext::from_bytes(&ct_compact.to_bytes().unwrap()).unwrap();
+        assert_eq!(decrypt(&sk_bcd, &ct_decoded).unwrap(), plaintext);
+        // TRUE has its own compact token
+        let policy = String::from(r#"{"OR": [{"ATT": "A"}, {"TRUE": true}]}"#);
+        let ct_true: CpAbeCiphertext = encrypt_compact_policy(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_true._policy, "|(A,#TRUE)");
+        assert_eq!(decrypt(&sk_bcd, &ct_true).unwrap(), plaintext);
+    }
 
-        // our policy
+    #[test]
+    fn ciphertext_without_flags() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
         let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let sk = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")]).unwrap();
+        let ct_cp = encrypt(&pk, &policy, &plaintext).unwrap();
+        // JSON of a version before _compressed and _compact_policy decodes with their defaults
+        let mut _json: serde_json::Value = serde_json::from_str(&ct_cp.to_json().unwrap()).unwrap();
+        _json.as_object_mut().unwrap().remove("_compressed");
+        _json.as_object_mut().unwrap().remove("_compact_policy");
+        let ct_old = CpAbeCiphertext::from_json(&_json.to_string()).unwrap();
+        assert_eq!(decrypt(&sk, &ct_old).unwrap(), plaintext);
+        // bincode has no defaults, the bytes of that version (without tag and flags) decode
+        // through the format version
+        let _v0 = encode(&(&ct_cp._policy, &ct_cp._c, &ct_cp._c_p, &ct_cp._c_y, &ct_cp._ct))
+            .unwrap();
+        let ct_old = CpAbeCiphertext::from_bytes(&_v0).unwrap();
+        assert_eq!(ct_old._compressed, false);
+        assert_eq!(ct_old._compact_policy, false);
+        assert_eq!(decrypt(&sk, &ct_old).unwrap(), plaintext);
+        let ct_old = CpAbeCiphertext::from_base64(&base64::encode(&_v0)).unwrap();
+        assert_eq!(decrypt(&sk, &ct_old).unwrap(), plaintext);
+        let ct_old = CpAbeCiphertext::from_base64_reader(base64::encode(&_v0).as_bytes()).unwrap();
+        assert_eq!(decrypt(&sk, &ct_old).unwrap(), plaintext);
+        // the bytes of the version with _compressed but without _compact_policy decode as well
+        let mut _v1 = _v0.clone();
+        _v1.push(1);
+        let ct_old = CpAbeCiphertext::from_bytes(&_v1).unwrap();
+        assert_eq!(ct_old._compressed, true);
+        assert_eq!(ct_old._compact_policy, false);
+        _v1.push(0);
+        assert_eq!(CpAbeCiphertext::from_bytes(&_v1).is_err(), true);
+        // the current format starts with the tag and version, unknown versions fail
+        let mut _bytes = ct_cp.to_bytes().unwrap();
+        assert_eq!(_bytes[..8], CIPHERTEXT_FORMAT_TAG);
+        assert_eq!(_bytes[8], CIPHERTEXT_FORMAT_VERSION);
+        _bytes[8] = CIPHERTEXT_FORMAT_VERSION + 1;
+        assert_eq!(CpAbeCiphertext::from_bytes(&_bytes).is_err(), true);
+        assert_eq!(CpAbeCiphertext::from_bytes(&_v0[..4]).is_err(), true);
+    }
 
-        // cp-abe ciphertext
-        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")]).unwrap();
+        // a highly redundant plaintext is stored compressed
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .repeat(100)
+            .into_bytes();
+        let ct_compressed = encrypt_compressed(&pk, &policy, &plaintext).unwrap();
+        let ct_plain = encrypt(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_compressed._compressed, true);
+        assert_eq!(ct_compressed._ct.len() < ct_plain._ct.len(), true);
+        assert_eq!(decrypt(&sk, &ct_compressed).unwrap(), plaintext);
+        // a short plaintext does not compress and is stored as is
+        let plaintext = String::from("A&B").into_bytes();
+        let ct_fallback = encrypt_compressed(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_fallback._compressed, false);
+        assert_eq!(decrypt(&sk, &ct_fallback).unwrap(), plaintext);
+    }
 
-        // and now decrypt again with mathcing sk
-        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
-        assert_eq!(_match.is_some(), true);
-        assert_eq!(_match.unwrap(), plaintext);
-        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
-        assert_eq!(_no_match.is_none(), true);
+    #[test]
+    fn attribute_table() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#);
+        let table = build_attribute_table(&pk, &vec![String::from("A"), String::from("B")]);
+        assert_eq!(table._table.len(), 2);
+        assert_eq!(pk.precompute(&[String::from("B"), String::from("A")]) == table, true);
+        // the table survives serialization
+        let table = AttributeTable::from_bytes(&table.to_bytes().unwrap()).unwrap();
+        // C is not part of the table and is hashed on demand
+        let ct_table = encrypt_with_table(&pk, &table, &policy, &plaintext).unwrap();
+        let ct_plain = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk_table = keygen_with_table(&pk, &msk, &table, &vec![String::from("A"), String::from("C")]).unwrap();
+        let sk_plain = keygen(&pk, &msk, &vec![String::from("A"), String::from("C")]).unwrap();
+        // keys and ciphertexts are interchangeable
+        assert_eq!(decrypt(&sk_table, &ct_table).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk_table, &ct_plain).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk_plain, &ct_table).unwrap(), plaintext);
+        // the table does not depend on the public key
+        let (pk_other, msk_other) = setup();
+        assert_eq!(pk_other.precompute(&[String::from("A"), String::from("B")]) == table, true);
+        let ct_other = encrypt_with_table(&pk_other, &table, &policy, &plaintext).unwrap();
+        let _attributes = vec![String::from("A"), String::from("B")];
+        let sk_other = keygen_with_table(&pk_other, &msk_other, &table, &_attributes).unwrap();
+        assert_eq!(decrypt(&sk_other, &ct_other).unwrap(), plaintext);
     }
 
     #[test]
-    fn and3() {
+    fn oversized_policy() {
         // setup scheme
         let (pk, msk) = setup();
-        // a set of two attributes matching the policy
-        let mut att_matching: Vec<String> = Vec::new();
-        att_matching.push(String::from("A"));
-        att_matching.push(String::from("B"));
-        att_matching.push(String::from("C"));
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let sk = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        let mut ct = encrypt(&pk, &String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#), &plaintext)
+            .unwrap();
+        assert_eq!(decrypt(&sk, &ct).unwrap(), plaintext);
+        // a hostile ciphertext with a gigantic policy is rejected before any pairing
+        let _leaves: Vec<String> = (0..MAX_POLICY_NODES)
+            .map(|_i| format!(r#"{{"ATT": "A{}"}}"#, _i))
+            .collect();
+        ct._policy = format!(r#"{{"OR": [{{"ATT": "A"}}, {}]}}"#, _leaves.join(", "));
+        assert_eq!(decrypt(&sk, &ct).err(), Some(RabeError::PolicyTooLarge));
+    }
 
-        // a set of two attributes NOT matching the policy
-        let mut att_not_matching: Vec<String> = Vec::new();
-        att_not_matching.push(String::from("A"));
-        att_not_matching.push(String::from("D"));
+    #[test]
+    fn seeded_rng() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+        // setup scheme
+        let (pk, msk) = setup_with_rng(&mut StdRng::seed_from_u64(42));
+        let (pk_again, msk_again) = setup_with_rng(&mut StdRng::seed_from_u64(42));
+        assert_eq!(pk.to_bytes().unwrap(), pk_again.to_bytes().unwrap());
+        assert_eq!(msk.to_bytes().unwrap(), msk_again.to_bytes().unwrap());
+        let (pk_other, _msk_other) = setup_with_rng(&mut StdRng::seed_from_u64(43));
+        assert_eq!(pk.to_bytes().unwrap() == pk_other.to_bytes().unwrap(), false);
+        // keys and ciphertexts are reproducible as well
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(
+            r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "C"}}, {"OR": [{"ATT": "B"}, {"ATT": "D"}]}]}"#,
+        );
+        let _attributes = vec![String::from("A"), String::from("B")];
+        let sk = keygen_with_rng(&pk, &msk, &_attributes, &mut StdRng::seed_from_u64(1)).unwrap();
+        let sk_again = keygen_with_rng(&pk, &msk, &_attributes, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(sk.to_bytes().unwrap(), sk_again.to_bytes().unwrap());
+        let ct = encrypt_with_rng(&pk, &policy, &plaintext, &mut StdRng::seed_from_u64(2)).unwrap();
+        let ct_again = encrypt_with_rng(&pk, &policy, &plaintext, &mut StdRng::seed_from_u64(2)).unwrap();
+        assert_eq!(ct.to_bytes().unwrap(), ct_again.to_bytes().unwrap());
+        assert_eq!(decrypt(&sk, &ct).unwrap(), plaintext);
+    }
 
-        // our plaintext
+    #[test]
+    fn missing_component() {
+        // setup scheme
+        let (pk, msk) = setup();
         let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
             .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let sk = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")]).unwrap();
+        let mut ct = encrypt(&pk, &policy, &plaintext).unwrap();
+        // a crafted ciphertext whose components omit a leaf of its policy
+        ct._c_y.retain(|_c| _c._str != "B");
+        assert_eq!(
+            decrypt(&sk, &ct).err(),
+            Some(RabeError::DecryptionFailed(String::from("ciphertext component of B")))
+        );
+        assert_eq!(decrypt_kem(&sk, &ct).is_err(), true);
+        assert_eq!(decrypt_with_audit(&sk, &ct).is_none(), true);
+    }
 
-        // our policy
-        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}"#);
+    #[test]
+    fn non_matching_key_vs_tampered_ciphertext() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let ct = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")]).unwrap();
+        // a key that does not match
+        let sk_a = keygen(&pk, &msk, &vec![String::from("A"), String::from("C")]).unwrap();
+        assert_eq!(decrypt(&sk_a, &ct).err(), Some(RabeError::InsufficientAttributes));
+        // tampered components of a matching ciphertext
+        let mut _tampered = ct.clone();
+        _tampered._c_y[0]._g1 = _tampered._c_y[0]._g1 + pk._g1;
+        assert_eq!(decrypt(&sk, &_tampered).err(), Some(RabeError::SymmetricDecryptionFailed));
+        let mut _tampered = ct.clone();
+        _tampered._c_y[1]._g2 = _tampered._c_y[1]._g2 + pk._g2;
+        assert_eq!(decrypt(&sk, &_tampered).err(), Some(RabeError::SymmetricDecryptionFailed));
+        let mut _renamed = ct.clone();
+        for _c in _renamed._c_y.iter_mut().filter(|_c| _c._str == "A") {
+            _c._str = String::from("C");
+        }
+        assert_eq!(
+            decrypt(&sk, &_renamed).err(),
+            Some(RabeError::DecryptionFailed(String::from("ciphertext component of A")))
+        );
+        assert_eq!(decrypt(&sk, &ct).unwrap(), plaintext);
+    }
 
-        // cp-abe ciphertext
-        let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
+    #[test]
+    fn repeated_attributes() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // A is used twice: both of its components are required with the key {A}
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "A"}, {"ATT": "B"}]}]}"#);
+        let ct_cp = encrypt(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_cp.attributes(), vec!["A", "A", "B"]);
+        let sk_a = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(decrypt(&sk_a, &ct_cp).unwrap(), plaintext);
+        let sk_b = keygen(&pk, &msk, &vec![String::from("B")]).unwrap();
+        assert_eq!(decrypt(&sk_b, &ct_cp).err(), Some(RabeError::InsufficientAttributes));
+        let policy = String::from(r#"{"OR": [{"ATT": "A"}, {"AND": [{"ATT": "A"}, {"ATT": "B"}]}]}"#);
+        let ct_cp = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk_ab = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")]).unwrap();
+        assert_eq!(decrypt(&sk_a, &ct_cp).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk_ab, &ct_cp).unwrap(), plaintext);
+        let policy = String::from(
+            r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "A"}, {"ATT": "B"}]}}"#,
+        );
+        let ct_cp = encrypt(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(decrypt(&sk_a, &ct_cp).unwrap(), plaintext);
+        assert_eq!(decrypt_batch(&sk_a, &[ct_cp]), vec![Ok(plaintext)]);
+    }
 
-        // and now decrypt again with mathcing sk
-        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
-        assert_eq!(_match.is_some(), true);
-        assert_eq!(_match.unwrap(), plaintext);
+    #[test]
+    fn size_estimate() {
+        // setup scheme
+        let (pk, _msk) = setup();
+        let _policies = vec![
+            String::from(r#"{"ATT": "A"}"#),
+            String::from(r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#),
+            String::from(r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#),
+            String::from(r#"{"GE": {"att": "age", "value": 18}}"#),
+        ];
+        for _policy in _policies {
+            for _len in vec![1, 55, 4096] {
+                let ct_cp = encrypt(&pk, &_policy, &vec![7u8; _len]).unwrap();
+                let _actual = ct_cp.to_bytes().unwrap().len();
+                let _estimate = ciphertext_size_estimate(&_policy, _len).unwrap();
+                assert_eq!(_estimate, _actual);
+            }
+        }
+        assert_eq!(ciphertext_size_estimate("joking-around?", 1).is_err(), true);
+    }
 
-        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
-        assert_eq!(_no_match.is_none(), true);
+    #[test]
+    fn same_policies() {
+        // setup scheme
+        let (pk, _msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#);
+        let ct_1 = encrypt(&pk, &policy, &plaintext).unwrap();
+        let ct_2 = encrypt(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_1.same_policy(&ct_2), true);
+        assert_eq!(ct_1 == ct_2, false);
+        // equivalent policies and other plaintexts
+        let _reordered = String::from(r#"{"AND": [{"OR": [{"ATT": "C"}, {"ATT": "B"}]}, {"ATT": "A"}]}"#);
+        let ct_3 = encrypt(&pk, &_reordered, &String::from("other").into_bytes()).unwrap();
+        assert_eq!(ct_1.same_policy(&ct_3), true);
+        let ct_4 = encrypt_compact_policy(&pk, &policy, &plaintext).unwrap();
+        assert_eq!(ct_4.same_policy(&ct_1), true);
+        // other policies
+        let _other = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        assert_eq!(ct_1.same_policy(&encrypt(&pk, &_other, &plaintext).unwrap()), false);
+        let mut _renamed = ct_2.clone();
+        _renamed._c_y[0]._str = String::from("D");
+        assert_eq!(ct_1.same_policy(&_renamed), false);
     }
 
     #[test]
-    fn or_and() {
+    fn wildcards() {
         // setup scheme
         let (pk, msk) = setup();
-        // a set of two attributes matching the policy
-        let mut att_matching: Vec<String> = Vec::new();
-        att_matching.push(String::from("A"));
-        att_matching.push(String::from("B"));
-        att_matching.push(String::from("C"));
-        att_matching.push(String::from("D"));
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let _universe = vec![
+            String::from("dept.sales"),
+            String::from("dept.hr"),
+            String::from("lab.sales"),
+        ];
+        let sk = keygen_with_wildcards(&pk, &msk, &vec![String::from("dept.*")], &_universe)
+            .unwrap();
+        for _attr in ["dept.sales", "dept.hr"].iter() {
+            let ct = encrypt(&pk, &format!(r#"{{"ATT": "{}"}}"#, _attr), &plaintext).unwrap();
+            assert_eq!(decrypt(&sk, &ct).unwrap(), plaintext);
+        }
+        // other prefixes and attributes unknown at keygen do not match
+        for _attr in ["lab.sales", "dept.it", "dept"].iter() {
+            let ct = encrypt(&pk, &format!(r#"{{"ATT": "{}"}}"#, _attr), &plaintext).unwrap();
+            assert_eq!(decrypt(&sk, &ct).is_err(), true);
+        }
+        assert_eq!(
+            keygen_with_wildcards(&pk, &msk, &vec![String::from("it.*")], &_universe).err(),
+            Some(RabeError::EmptyAttributes)
+        );
+    }
 
-        // a set of two attributes NOT matching the policy
-        let mut att_not_matching: Vec<String> = Vec::new();
-        att_not_matching.push(String::from("A"));
-        att_not_matching.push(String::from("C"));
+    #[test]
+    fn redacted_debug() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let sk = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")]).unwrap();
+        let _debug = format!("{:?}", sk);
+        assert_eq!(_debug.starts_with("CpAbeSecretKey { attrs: [\"A\", \"B\""), true);
+        assert_eq!(_debug.contains("d: <redacted>"), true);
+        assert_eq!(
+            format!("{:?}", msk),
+            "CpAbeMasterKey { beta: <redacted>, g2_alpha: <redacted> }"
+        );
+        // neither the encoded components nor the key itself leak into the output
+        let mut _secrets = vec![sk.to_base64().unwrap(), base64::encode(&encode(&sk._d).unwrap())];
+        for _d_j in sk._d_j.iter() {
+            _secrets.push(base64::encode(&encode(&_d_j._g2).unwrap()));
+        }
+        _secrets.push(base64::encode(&encode(&msk._beta).unwrap()));
+        for _secret in _secrets {
+            assert_eq!(_debug.contains(&_secret) || format!("{:?}", msk).contains(&_secret), false);
+        }
+        assert_eq!(format!("{:#?}", sk).contains("<redacted>"), true);
+    }
 
-        // our plaintext
+    #[test]
+    fn update_policy_and() {
+        // setup scheme
+        let (pk, msk) = setup();
         let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
             .into_bytes();
+        let policy = String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let ct_cp = encrypt(&pk, &policy, &plaintext).unwrap();
+        let sk_a = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        let sk_ac = keygen(&pk, &msk, &vec![String::from("A"), String::from("C")]).unwrap();
+        let sk_c = keygen(&pk, &msk, &vec![String::from("C")]).unwrap();
+        assert_eq!(decrypt(&sk_a, &ct_cp).unwrap(), plaintext);
+        // only the public key is needed to require C in addition
+        let ct_updated = update_policy(&pk, &ct_cp, &String::from(r#"{"ATT": "C"}"#)).unwrap();
+        let ct_updated = CpAbeUpdatedCiphertext::from_bytes(&ct_updated.to_bytes().unwrap()).unwrap();
+        assert_eq!(decrypt_updated(&sk_ac, &ct_updated).unwrap(), plaintext);
+        // A satisfied the old policy, but not the new one
+        assert_eq!(decrypt_updated(&sk_a, &ct_updated).is_err(), true);
+        assert_eq!(decrypt_updated(&sk_c, &ct_updated).is_err(), true);
+        // the original ciphertext lost its payload
+        assert_eq!(decrypt(&sk_a, &ct_updated._inner).is_err(), true);
+    }
 
-        // our policy
-        let policy = String::from(r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}"#);
+    #[test]
+    fn verify_keypairs() {
+        // setup scheme
+        let (pk, msk) = setup();
+        assert_eq!(verify_keypair(&pk, &msk), true);
+        let _loaded = CpAbeMasterKey::from_bytes(&msk.to_bytes().unwrap()).unwrap();
+        assert_eq!(verify_keypair(&pk, &_loaded), true);
+        // keys of different setups
+        let (pk_other, msk_other) = setup();
+        assert_eq!(verify_keypair(&pk, &msk_other), false);
+        assert_eq!(verify_keypair(&pk_other, &msk), false);
+        // corrupted keys
+        let mut _beta = msk.clone();
+        _beta._beta = _beta._beta + Fr::one();
+        assert_eq!(verify_keypair(&pk, &_beta), false);
+        let mut _alpha = msk.clone();
+        _alpha._g2_alpha = _alpha._g2_alpha + pk._g2;
+        assert_eq!(verify_keypair(&pk, &_alpha), false);
+        let mut _f = pk.clone();
+        _f._f = _f._f + pk._g2;
+        assert_eq!(verify_keypair(&_f, &msk), false);
+    }
 
-        // cp-abe ciphertext
+    #[test]
+    fn threshold_policy_2_of_3() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let _attrs = vec![String::from("A"), String::from("B"), String::from("C")];
+        let policy = threshold_policy(&_attrs, 2).unwrap();
         let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
-
-        // and now decrypt again with mathcing sk
-        let _match = decrypt(&keygen(&pk, &msk, &att_matching).unwrap(), &ct_cp);
-        assert_eq!(_match.is_some(), true);
-        assert_eq!(_match.unwrap(), plaintext);
-        let _no_match = decrypt(&keygen(&pk, &msk, &att_not_matching).unwrap(), &ct_cp);
-        assert_eq!(_no_match.is_none(), true);
+        // any two of the three attributes
+        for _pair in vec![vec!["A", "B"], vec!["A", "C"], vec!["B", "C"], vec!["C", "A", "B"]] {
+            let _set: Vec<String> = _pair.iter().map(|_a| _a.to_string()).collect();
+            let sk: CpAbeSecretKey = keygen(&pk, &msk, &_set).unwrap();
+            assert_eq!(decrypt(&sk, &ct_cp).unwrap(), plaintext);
+        }
+        // a single attribute or others do not suffice
+        for _set in vec![vec!["A"], vec!["C"], vec!["A", "D"]] {
+            let _set: Vec<String> = _set.iter().map(|_a| _a.to_string()).collect();
+            let sk: CpAbeSecretKey = keygen(&pk, &msk, &_set).unwrap();
+            assert_eq!(decrypt(&sk, &ct_cp).is_err(), true);
+        }
     }
 
     #[test]
-    fn delegate_ab() {
+    fn add_attributes() {
         // setup scheme
         let (pk, msk) = setup();
-        // a set of three attributes matching the policy
-        let mut _atts: Vec<String> = Vec::new();
-        _atts.push(String::from("A"));
-        _atts.push(String::from("B"));
-        _atts.push(String::from("C"));
-        // a set of two delegated attributes
-        let mut _delegate_att: Vec<String> = Vec::new();
-        _delegate_att.push(String::from("A"));
-        _delegate_att.push(String::from("B"));
-        // our plaintext
         let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
             .into_bytes();
-        // our policy
         let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
-        // cp-abe ciphertext
         let ct_cp: CpAbeCiphertext = encrypt(&pk, &policy, &plaintext).unwrap();
-        // a cp-abe SK key matching
-        let sk: CpAbeSecretKey = keygen(&pk, &msk, &_atts).unwrap();
-        // delegate a cp-abe SK
-        let del: CpAbeSecretKey = delegate(&pk, &sk, &_delegate_att).unwrap();
-        // and now decrypt again with mathcing sk
-        let _match = decrypt(&del, &ct_cp);
-        assert_eq!(_match.is_some(), true);
-        assert_eq!(_match.unwrap(), plaintext);
+        let ct_a: CpAbeCiphertext = encrypt(&pk, &String::from(r#"{"ATT": "A"}"#), &plaintext)
+            .unwrap();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A")]).unwrap();
+        assert_eq!(decrypt(&sk, &ct_cp).is_err(), true);
+        // the extended key decrypts the policy requiring B and still decrypts the old one
+        let sk_b = add_attribute(&pk, &msk, &sk, &String::from("B")).unwrap();
+        assert_eq!(sk_b._d_j.len(), sk._d_j.len() + 1);
+        assert_eq!(sk_b._d == sk._d, true);
+        assert_eq!(verify_key(&pk, &sk_b), true);
+        assert_eq!(decrypt(&sk_b, &ct_cp).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk_b, &ct_a).unwrap(), plaintext);
+        // numeric attributes add their bits
+        let sk_age = add_attribute(&pk, &msk, &sk_b, &String::from("age=20")).unwrap();
+        let ct_age = encrypt(&pk, &String::from(r#"{"GE": {"att": "age", "value": 18}}"#), &plaintext)
+            .unwrap();
+        assert_eq!(decrypt(&sk_age, &ct_age).unwrap(), plaintext);
+        assert_eq!(add_attribute(&pk, &msk, &sk_age, &String::from("age=30")).is_none(), true);
+        // attributes the key holds, empty attributes and another master key
+        assert_eq!(add_attribute(&pk, &msk, &sk_b, &String::from("B")).is_none(), true);
+        assert_eq!(add_attribute(&pk, &msk, &sk_b, &String::new()).is_none(), true);
+        assert_eq!(add_attribute(&pk, &msk, &sk_b, &String::from("#age:bit0:1")).is_none(), true);
+        let (pk_other, msk_other) = setup();
+        assert_eq!(add_attribute(&pk_other, &msk_other, &sk, &String::from("B")).is_none(), true);
+    }
+
+    #[test]
+    fn decrypt_batches() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let sk: CpAbeSecretKey = keygen(&pk, &msk, &vec![String::from("A"), String::from("B")])
+            .unwrap();
+        let _policies = vec![
+            r#"{"ATT": "A"}"#,
+            r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#,
+            r#"{"AND": [{"ATT": "A"}, {"ATT": "C"}]}"#,
+            r#"{"OR": [{"ATT": "C"}, {"ATT": "B"}]}"#,
+        ];
+        let mut _cts: Vec<CpAbeCiphertext> = _policies
+            .iter()
+            .enumerate()
+            .map(|(_i, _policy)| {
+                encrypt(&pk, &_policy.to_string(), &format!("plaintext {}", _i).into_bytes())
+                    .unwrap()
+            })
+            .collect();
+        let mut _corrupt = _cts[0].clone();
+        _corrupt._ct.push(0);
+        _cts.push(_corrupt);
+        let _results = decrypt_batch(&sk, &_cts);
+        assert_eq!(_results.len(), _cts.len());
+        for (_result, _ct) in _results.iter().zip(_cts.iter()) {
+            assert_eq!(*_result, decrypt(&sk, _ct));
+        }
+        assert_eq!(_results[1], Ok(String::from("plaintext 1").into_bytes()));
+        assert_eq!(_results[2], Err(RabeError::InsufficientAttributes));
+        assert_eq!(_results[4].is_err(), true);
+        assert_eq!(decrypt_batch(&sk, &[]).is_empty(), true);
+    }
+
+    // compile-time check: keys and ciphertexts can be shared across threads
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<CpAbePublicKey>();
+        assert_send_sync::<CpAbeMasterKey>();
+        assert_send_sync::<CpAbeCiphertext>();
+        assert_send_sync::<CpAbeSecretKey>();
+        assert_send_sync::<CpAbeAttribute>();
+        assert_send_sync::<CpAbeContext>();
+        assert_send_sync::<AttributeTable>();
+        assert_send_sync::<CpAbeChallenge>();
+        assert_send_sync::<CpAbeUpdatedCiphertext>();
+        assert_send_sync::<CpAbeRevocationState>();
+        assert_send_sync::<CpAbeAttributeCommitment>();
+        assert_send_sync::<SecretTracker>();
+        assert_send_sync::<HashCache>();
+        assert_send_sync::<RabeError>();
     }
 }
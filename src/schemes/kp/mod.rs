@@ -0,0 +1,415 @@
+//! This is the documentation for the `KP` scheme:
+//!
+//! * Developped by Vipul Goyal, Omkant Pandey, Amit Sahai, Brent Waters, "Attribute-Based Encryption for Fine-Grained Access Control of Encrypted Data"
+//! * Published in Proceedings of the 13th ACM Conference on Computer and Communications Security, 2006. CCS'06. ACM
+//! * Available from https://doi.org/10.1145/1180405.1180418
+//! * Type: encryption (key-policy attribute-based)
+//! * Setting: bilinear groups (asymmetric)
+//!
+//! This is the key-policy dual of `BSW`: the policy lives in the user key and a ciphertext
+//! carries a set of attributes. Attributes are hashed to G1 with `hash_to_g1` (large universe
+//! construction).
+//!
+//! # Examples
+//!
+//! ```
+//!use rabe::schemes::kp::*;
+//!let (pk, msk) = setup();
+//!let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+//!let policy = r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#;
+//!let ct_kp: KpAbeCiphertext = encrypt(&pk, &vec!["A".to_string(), "B".to_string()], &plaintext).unwrap();
+//!let sk: KpAbeSecretKey = keygen(&pk, &msk, policy).unwrap();
+//!assert_eq!(decrypt(&sk, &ct_kp).unwrap(), plaintext);
+//! ```
+use bn::{Fr, G1, G2, Gt, pairing};
+use rand::Rng;
+use std::collections::HashMap;
+use schemes::AbeScheme;
+use utils::{
+    secretsharing::{
+        gen_shares_str, calc_coefficients_indexed_str, validate_policy, check_reserved,
+        TRUE_ATTRIBUTE,
+    },
+    aes::*,
+    encoding::{check_g1, check_g2, check_gt, RabeEncode},
+    error::RabeError,
+    hash::hash_to_g1,
+    policy::numeric::expand_numeric_attributes
+};
+
+// the domain of the attribute hashes, see hash_to_g1
+const KP_ATTRIBUTE_DOMAIN: &'static str = "kp-attribute";
+
+/// A KP Public Key (PK)
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct KpAbePublicKey {
+    pub _g1: G1,
+    pub _g2: G2,
+    pub _e_gg_y: Gt,
+}
+
+/// A KP Master Key (MSK)
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct KpAbeMasterKey {
+    pub _y: Fr,
+}
+
+/// A KP Secret User Key (SK), holding one KpAbeAttribute per leaf of the policy
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct KpAbeSecretKey {
+    pub _policy: String,
+    pub _d_x: Vec<KpAbeAttribute>,
+}
+
+/// A KP Attribute of a Secret User Key, i.e. `D_x = g1^q_x(0) * H(x)^r_x` and `R_x = g2^r_x`
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct KpAbeAttribute {
+    pub _str: String,
+    pub _g1: G1,
+    pub _g2: G2,
+}
+
+/// A KP Ciphertext (CT)
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct KpAbeCiphertext {
+    pub _e_p: Gt,
+    pub _e_pp: G2,
+    pub _e_i: Vec<(String, G1)>,
+    pub _ct: Vec<u8>,
+}
+
+impl RabeEncode for KpAbePublicKey {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_g1(&self._g1)?;
+        check_g2(&self._g2)?;
+        return check_gt(&self._e_gg_y);
+    }
+}
+
+impl RabeEncode for KpAbeMasterKey {}
+
+impl RabeEncode for KpAbeSecretKey {
+    fn validate(&self) -> Result<(), RabeError> {
+        for _d_x in self._d_x.iter() {
+            _d_x.validate()?;
+        }
+        return Ok(());
+    }
+}
+
+impl RabeEncode for KpAbeAttribute {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_g1(&self._g1)?;
+        return check_g2(&self._g2);
+    }
+}
+
+impl RabeEncode for KpAbeCiphertext {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_gt(&self._e_p)?;
+        check_g2(&self._e_pp)?;
+        for &(_, ref _e) in self._e_i.iter() {
+            check_g1(_e)?;
+        }
+        return Ok(());
+    }
+}
+
+/// The setup algorithm of KP-ABE. Generates a new KpAbePublicKey and a new KpAbeMasterKey.
+pub fn setup() -> (KpAbePublicKey, KpAbeMasterKey) {
+    // random number generator
+    let mut _rng = rand::thread_rng();
+    // generator of group G1: g1 and generator of group G2: g2
+    let _g1: G1 = _rng.gen();
+    let _g2: G2 = _rng.gen();
+    // the master secret y and the pairing e(g1, g2)^y
+    let _y: Fr = _rng.gen();
+    let _e_gg_y = pairing(_g1, _g2).pow(_y);
+    // return PK and MSK
+    return (KpAbePublicKey {_g1, _g2, _e_gg_y}, KpAbeMasterKey {_y});
+}
+
+/// The key generation algorithm of KP-ABE. Generates a KpAbeSecretKey using a KpAbePublicKey, a KpAbeMasterKey and a policy given as JSON String.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_msk` - A Master Key (MSK), generated by the function setup()
+///	* `_policy` - An access policy given as JSON String
+///
+pub fn keygen(
+    _pk: &KpAbePublicKey,
+    _msk: &KpAbeMasterKey,
+    _policy: &str,
+) -> Result<KpAbeSecretKey, RabeError> {
+    validate_policy(_policy)?;
+    let _policy = _policy.to_string();
+    let mut _rng = rand::thread_rng();
+    let _shares: Vec<(String, Fr)> = gen_shares_str(_msk._y, &_policy)?;
+    let mut _d_x: Vec<KpAbeAttribute> = Vec::new();
+    for (_x, _x_val) in _shares {
+        let _r: Fr = _rng.gen();
+        _d_x.push(KpAbeAttribute {
+            _g1: (_pk._g1 * _x_val) + (hash_to_g1(&_x, KP_ATTRIBUTE_DOMAIN) * _r),
+            _g2: _pk._g2 * _r,
+            _str: _x,
+        });
+    }
+    return Ok(KpAbeSecretKey {_policy, _d_x});
+}
+
+/// The encrypt algorithm of KP-ABE. Generates a new KpAbeCiphertext using a KpAbePublicKey, a set of attributes given as Vec<String> and some plaintext data given as [u8].
+/// The plaintext is encrypted with authenticated AES-GCM.
+///
+/// Every ciphertext additionally holds the reserved attribute `TRUE_ATTRIBUTE`, so that it satisfies the policy `{"TRUE": true}`.
+///
+/// # Arguments
+///
+///	* `_pk` - A Public Key (PK), generated by the function setup()
+///	* `_attributes` - A set of attributes given as String Vector
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///
+pub fn encrypt(
+    _pk: &KpAbePublicKey,
+    _attributes: &Vec<String>,
+    _plaintext: &Vec<u8>,
+) -> Result<KpAbeCiphertext, RabeError> {
+    if _attributes.is_empty() {
+        return Err(RabeError::EmptyAttributes);
+    }
+    if _plaintext.is_empty() {
+        return Err(RabeError::Encryption(String::from("empty plaintext")));
+    }
+    let mut _rng = rand::thread_rng();
+    let _s: Fr = _rng.gen();
+    let _msg: Gt = _rng.gen();
+    let _e_p = _pk._e_gg_y.pow(_s) * _msg;
+    let _e_pp = _pk._g2 * _s;
+    // the reserved attributes are only added here
+    check_reserved(_attributes)?;
+    let _attributes = expand_numeric_attributes(_attributes)?;
+    let mut _e_i: Vec<(String, G1)> = Vec::new();
+    for _i in _attributes.iter().map(|_a| _a.as_str()).chain(Some(TRUE_ATTRIBUTE)) {
+        if !_e_i.iter().any(|&(ref _str, _)| _str == _i) {
+            _e_i.push((_i.to_string(), hash_to_g1(_i, KP_ATTRIBUTE_DOMAIN) * _s));
+        }
+    }
+    //Encrypt plaintext using derived key from secret
+    match encrypt_symmetric_gcm(&_msg, &_plaintext) {
+        None => return Err(RabeError::Encryption(String::from("symmetric encryption failed"))),
+        Some(_ct) => return Ok(KpAbeCiphertext {_e_p, _e_pp, _e_i, _ct}),
+    }
+}
+
+/// The decrypt algorithm of KP-ABE. Reconstructs the original plaintext data as Vec<u8>, given a KpAbeCiphertext with a matching KpAbeSecretKey.
+///
+/// # Arguments
+///
+///	* `_sk` - A Secret Key (SK), generated by the function keygen()
+///	* `_ct` - A KP-ABE Ciphertext
+///
+/// # Errors
+///
+///	* `RabeError::InsufficientAttributes` - if the attributes of `_ct` do not satisfy the policy of `_sk`
+///	* `RabeError::DecryptionFailed` - if a component of `_sk` or `_ct` is missing
+///	* `RabeError::SymmetricDecryptionFailed` - if the payload is missing or corrupt
+///
+pub fn decrypt(_sk: &KpAbeSecretKey, _ct: &KpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+    let _attributes: Vec<String> = _ct._e_i.iter().map(|_e| _e.0.to_string()).collect();
+    // the coefficients of the leaves used, indexed like the components of the key, so that a
+    // policy may contain the same attribute more than once
+    let _z = match calc_coefficients_indexed_str(&_attributes, &_sk._policy)? {
+        None => return Err(RabeError::InsufficientAttributes),
+        Some(_z) => _z,
+    };
+    let _e: HashMap<&str, &G1> = _ct._e_i
+        .iter()
+        .map(|&(ref _i, ref _e_i)| (_i.as_str(), _e_i))
+        .collect();
+    let mut _a = Gt::one();
+    for (_index, _x, _z_x) in _z {
+        let _d_x = match _sk._d_x.get(_index) {
+            Some(_d_x) if _d_x._str == _x => _d_x,
+            _ => return Err(RabeError::DecryptionFailed(format!("key component of {}", _x))),
+        };
+        let _e_i = match _e.get(_x.as_str()) {
+            Some(_e_i) => _e_i,
+            None => {
+                return Err(RabeError::DecryptionFailed(format!("ciphertext component of {}", _x)))
+            }
+        };
+        // e(D_x, E'') / e(E_i, R_x) = e(g1, g2)^(s * q_x(0))
+        let _f = pairing(_d_x._g1, _ct._e_pp) * pairing(**_e_i, _d_x._g2).inverse();
+        _a = _a * _f.pow(_z_x);
+    }
+    let _msg = _ct._e_p * _a.inverse();
+    // Decrypt plaintext using derived secret from kp-abe scheme
+    match decrypt_symmetric_gcm(&_msg, &_ct._ct) {
+        None => return Err(RabeError::SymmetricDecryptionFailed),
+        Some(_pt) => return Ok(_pt),
+    }
+}
+
+/// The GPSW KP-ABE scheme as `AbeScheme`, keys are generated for a JSON policy and
+/// ciphertexts for attributes.
+pub struct Gpsw;
+
+impl AbeScheme for Gpsw {
+    type PublicKey = KpAbePublicKey;
+    type MasterKey = KpAbeMasterKey;
+    type SecretKey = KpAbeSecretKey;
+    type Ciphertext = KpAbeCiphertext;
+    type KeyInput = String;
+    type EncryptInput = Vec<String>;
+
+    fn setup() -> (KpAbePublicKey, KpAbeMasterKey) {
+        return setup();
+    }
+
+    fn keygen(
+        _pk: &KpAbePublicKey,
+        _msk: &KpAbeMasterKey,
+        _policy: &String,
+    ) -> Result<KpAbeSecretKey, RabeError> {
+        return keygen(_pk, _msk, _policy);
+    }
+
+    fn encrypt(
+        _pk: &KpAbePublicKey,
+        _attributes: &Vec<String>,
+        _plaintext: &Vec<u8>,
+    ) -> Result<KpAbeCiphertext, RabeError> {
+        return encrypt(_pk, _attributes, _plaintext);
+    }
+
+    fn decrypt(_sk: &KpAbeSecretKey, _ct: &KpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
+        return decrypt(_sk, _ct);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::hash::blake2b_hash_fr;
+    use utils::secretsharing::calc_coefficients_str;
+
+    fn attributes(_list: &[&str]) -> Vec<String> {
+        return _list.iter().map(|_a| _a.to_string()).collect();
+    }
+
+    #[test]
+    fn and() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // our policy
+        let policy = r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}"#;
+        let sk: KpAbeSecretKey = keygen(&pk, &msk, policy).unwrap();
+        // kp-abe ciphertexts with matching and not matching attributes
+        let ct_match = encrypt(&pk, &attributes(&["A", "B", "C", "D"]), &plaintext).unwrap();
+        let ct_not_match = encrypt(&pk, &attributes(&["A", "B", "D"]), &plaintext).unwrap();
+        assert_eq!(decrypt(&sk, &ct_match).unwrap(), plaintext);
+        assert_eq!(decrypt(&sk, &ct_not_match), Err(RabeError::InsufficientAttributes));
+    }
+
+    #[test]
+    fn or() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // our policy
+        let policy = r#"{"OR": [{"ATT": "A"}, {"AND": [{"ATT": "B"}, {"ATT": "C"}]}]}"#;
+        let sk: KpAbeSecretKey = keygen(&pk, &msk, policy).unwrap();
+        for _set in vec![vec!["A"], vec!["B", "C"], vec!["A", "B", "C"]] {
+            let ct_kp = encrypt(&pk, &attributes(&_set), &plaintext).unwrap();
+            assert_eq!(decrypt(&sk, &ct_kp).unwrap(), plaintext);
+        }
+        let ct_kp = encrypt(&pk, &attributes(&["B", "D"]), &plaintext).unwrap();
+        assert_eq!(decrypt(&sk, &ct_kp).is_err(), true);
+    }
+
+    #[test]
+    fn threshold() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // our policy
+        let policy = r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "B"}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}}"#;
+        let sk: KpAbeSecretKey = keygen(&pk, &msk, policy).unwrap();
+        for _set in vec![vec!["A", "B"], vec!["A", "C", "D"], vec!["B", "C", "D"]] {
+            let ct_kp = encrypt(&pk, &attributes(&_set), &plaintext).unwrap();
+            assert_eq!(decrypt(&sk, &ct_kp).unwrap(), plaintext);
+        }
+        for _set in vec![vec!["A"], vec!["A", "C"], vec!["C", "D"]] {
+            let ct_kp = encrypt(&pk, &attributes(&_set), &plaintext).unwrap();
+            assert_eq!(decrypt(&sk, &ct_kp), Err(RabeError::InsufficientAttributes));
+        }
+    }
+
+    #[test]
+    fn duplicate_leaves() {
+        // setup scheme
+        let (pk, msk) = setup();
+        // our plaintext
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // the attribute A appears in both branches of the policy
+        let policy = r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "A"}, {"ATT": "C"}]}]}"#;
+        let sk: KpAbeSecretKey = keygen(&pk, &msk, policy).unwrap();
+        assert_eq!(sk._d_x.len(), 4);
+        for _set in vec![vec!["A", "B"], vec!["A", "C"]] {
+            let ct_kp = encrypt(&pk, &attributes(&_set), &plaintext).unwrap();
+            assert_eq!(decrypt(&sk, &ct_kp).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn known_hash_exponents() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#;
+        let sk: KpAbeSecretKey = keygen(&pk, &msk, policy).unwrap();
+        let ct_kp = encrypt(&pk, &attributes(&["C"]), &plaintext).unwrap();
+        assert_eq!(decrypt(&sk, &ct_kp), Err(RabeError::InsufficientAttributes));
+        // if H(x) = g1^h(x) with a public h, then g1^s = E_i^(1 / h(i)) for any attribute i of
+        // the ciphertext and e(D_x, E'') / e(g1^s, R_x)^h(x) = e(g1, g2)^(s * q_x(0)) for every
+        // leaf x of the key, whether x is an attribute of the ciphertext or not
+        let _h = |_x: &str| blake2b_hash_fr(&_x.to_string());
+        let _g1_s = ct_kp._e_i[0].1 * _h(ct_kp._e_i[0].0.as_str()).inverse().unwrap();
+        let mut _a = Gt::one();
+        for (_x, _z_x) in calc_coefficients_str(&policy.to_string()).unwrap() {
+            let _d_x = sk._d_x.iter().find(|_d| _d._str == _x).unwrap();
+            let _f = pairing(_d_x._g1, ct_kp._e_pp) *
+                pairing(_g1_s * _h(_x.as_str()), _d_x._g2).inverse();
+            _a = _a * _f.pow(_z_x);
+        }
+        assert_eq!(decrypt_symmetric_gcm(&(ct_kp._e_p * _a.inverse()), &ct_kp._ct), None);
+    }
+
+    #[test]
+    fn invalid_input() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        assert_eq!(keygen(&pk, &msk, "").is_err(), true);
+        assert_eq!(keygen(&pk, &msk, "joking-around?").is_err(), true);
+        assert_eq!(encrypt(&pk, &Vec::new(), &plaintext).is_err(), true);
+        assert_eq!(encrypt(&pk, &attributes(&["A"]), &Vec::new()).is_err(), true);
+        // a tampered payload is rejected
+        let sk = keygen(&pk, &msk, r#"{"ATT": "A"}"#).unwrap();
+        let mut ct_kp = encrypt(&pk, &attributes(&["A"]), &plaintext).unwrap();
+        let _last = ct_kp._ct.len() - 1;
+        ct_kp._ct[_last] ^= 1;
+        assert_eq!(decrypt(&sk, &ct_kp), Err(RabeError::SymmetricDecryptionFailed));
+    }
+}
@@ -23,12 +23,16 @@ use bn::{Group, Fr, G1, G2, Gt, pairing};
 use std::ops::Neg;
 use utils::{
     tools::*,
-    secretsharing::{gen_shares_str, calc_coefficients_str, calc_pruned_str},
+    secretsharing::{gen_shares_str, calc_coefficients_str, calc_pruned_str, validate_policy},
     aes::*,
-    hash::{blake2b_hash_fr, blake2b_hash_g1}
+    error::RabeError,
+    hash::{blake2b_hash_g1, hash_to_fr}
 };
 use rand::Rng;
 
+// the domain of the attribute hashes, see hash_to_fr
+const LSW_ATTRIBUTE_DOMAIN: &'static str = "lsw-attribute";
+
 /// A LSW Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct KpAbePublicKey {
@@ -104,10 +108,11 @@ pub fn keygen(
     _pk: &KpAbePublicKey,
     _msk: &KpAbeMasterKey,
     _policy: &String,
-) -> Option<KpAbeSecretKey> {
+) -> Result<KpAbeSecretKey, RabeError> {
+    validate_policy(_policy)?;
     // random number generator
     let mut _rng = rand::thread_rng();
-    let _shares = gen_shares_str(_msk._alpha1, _policy).unwrap();
+    let _shares = gen_shares_str(_msk._alpha1, _policy)?;
     let mut _d: Vec<(String, G1, G2, G1, G1, G1)> = Vec::new();
     for (_share_str, _share_value) in _shares.into_iter() {
         let _r:Fr = _rng.gen();
@@ -117,7 +122,8 @@ pub fn keygen(
                 G1::zero(),
                 G2::zero(),
                 (_pk._g_g1 * _share_value) + (_pk._g_g1_b2 * _r),
-                _pk._g_g1_b * (blake2b_hash_fr(&_share_str) * _r) + (_msk._h_g1 * _r),
+                _pk._g_g1_b * (hash_to_fr(&_share_str, LSW_ATTRIBUTE_DOMAIN) * _r) +
+                    (_msk._h_g1 * _r),
                 _pk._g_g1 * _r.neg(),
             ));
         } else {
@@ -132,7 +138,7 @@ pub fn keygen(
             ));
         }
     }
-    return Some(KpAbeSecretKey {
+    return Ok(KpAbeSecretKey {
         _policy: _policy.clone(),
         _dj: _d,
     });
@@ -150,9 +156,11 @@ pub fn encrypt(
     _pk: &KpAbePublicKey,
     _attributes: &Vec<String>,
     _plaintext: &[u8],
-) -> Option<KpAbeCiphertext> {
-    if _attributes.is_empty() || _plaintext.is_empty() {
-        return None;
+) -> Result<KpAbeCiphertext, RabeError> {
+    if _attributes.is_empty() {
+        return Err(RabeError::EmptyAttributes);
+    } else if _plaintext.is_empty() {
+        return Err(RabeError::Encryption(String::from("empty plaintext")));
     } else {
         // random number generator
         let mut _rng = rand::thread_rng();
@@ -172,16 +180,20 @@ pub fn encrypt(
                 _attr.to_string(),
                 blake2b_hash_g1(_pk._g_g1, &_attr) * _s,
                 _pk._g_g1_b * _sx[_i],
-                (_pk._g_g1_b2 * (_sx[_i] * blake2b_hash_fr(&_attr))) + (_pk._h_g1_b * _sx[_i]),
+                (_pk._g_g1_b2 * (_sx[_i] * hash_to_fr(&_attr, LSW_ATTRIBUTE_DOMAIN))) +
+                    (_pk._h_g1_b * _sx[_i]),
             ));
         }
         // random message
         let _msg: Gt = _rng.gen();
         let _e1 = _pk._e_gg_alpha.pow(_s) * _msg;
         let _e2 = _pk._g_g2 * _s;
-        let _ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
         //Encrypt plaintext using derived key from secret
-        Some(KpAbeCiphertext {_e1, _e2, _ej, _ct})
+        let _ct = match encrypt_symmetric(&_msg, &_plaintext.to_vec()) {
+            None => return Err(RabeError::Encryption(String::from("symmetric encryption failed"))),
+            Some(_ct) => _ct,
+        };
+        Ok(KpAbeCiphertext {_e1, _e2, _ej, _ct})
     }
 }
 
@@ -192,63 +204,142 @@ pub fn encrypt(
 ///	* `_sk` - A Secret Key (SK), generated by the function keygen()
 ///	* `_ct` - A LSW KP-ABE Ciphertext
 ///
-pub fn decrypt(_sk: &KpAbeSecretKey, _ct: &KpAbeCiphertext) -> Option<Vec<u8>> {
+pub fn decrypt(_sk: &KpAbeSecretKey, _ct: &KpAbeCiphertext) -> Result<Vec<u8>, RabeError> {
     let _attrs_str = _ct
         ._ej
         .iter()
         .map(|values| values.clone().0.to_string())
         .collect::<Vec<_>>();
-    let _pruned = calc_pruned_str(&_attrs_str, &_sk._policy);
-    return match _pruned {
-        None => {
-            None
-        }
-        Some(_p) => {
-            let (_match, _list) = _p;
-            if _match {
-                let mut _prod_t = Gt::one();
-                let mut _z_y = Gt::one();
-                let _coeffs: Vec<(String, Fr)> = calc_coefficients_str(&_sk._policy).unwrap();
-                for _attr_str in _list.iter() {
-                    let _sk_attr = _sk
-                        ._dj
-                        .iter()
-                        .filter(|_attr| _attr.0 == _attr_str.to_string())
-                        .nth(0)
-                        .unwrap();
-                    let _ct_attr = _ct
-                        ._ej
-                        .iter()
-                        .filter(|_attr| _attr.0 == _attr_str.to_string())
-                        .nth(0)
-                        .unwrap();
-                    let _coeff_attr = _coeffs
-                        .iter()
-                        .filter(|_attr| _attr.0 == _attr_str.to_string())
-                        .nth(0)
-                        .unwrap();
-                    if is_negative(&_attr_str) {
-                        // TODO !!
-                        /*let _sum_e4 = G2::zero();
-                        let _sum_e5 = G2::zero();
-                        _prod_t = _prod_t *
-                            (pairing(sk._d_i[_i].3, ct._e2) *
-                                 (pairing(sk._d_i[_i].4, _sum_e4) * pairing(sk._d_i[_i].5, _sum_e5))
-                                     .inverse());
-                        */
-                    } else {
-                        _z_y = pairing(_sk_attr.1, _ct._e2)
-                            * pairing(_ct_attr.1, _sk_attr.2).inverse();
-                    }
-                    _prod_t = _prod_t * _z_y.pow(_coeff_attr.1);
-                }
-                let _msg = _ct._e1 * _prod_t.inverse();
-                // Decrypt plaintext using derived secret from cp-abe scheme
-                decrypt_symmetric(&_msg, &_ct._ct)
-            } else {
-                None
+    let (_match, _list) = calc_pruned_str(&_attrs_str, &_sk._policy)?;
+    if !_match {
+        return Err(RabeError::InsufficientAttributes);
+    }
+    let mut _prod_t = Gt::one();
+    let mut _z_y = Gt::one();
+    let _coeffs: Vec<(String, Fr)> = calc_coefficients_str(&_sk._policy)?;
+    for _attr_str in _list.iter() {
+        let _sk_attr = match _sk._dj.iter().find(|_attr| _attr.0 == *_attr_str) {
+            None => return Err(RabeError::AttributeNotInKey(_attr_str.to_string())),
+            Some(_sk_attr) => _sk_attr,
+        };
+        let _ct_attr = match _ct._ej.iter().find(|_attr| _attr.0 == *_attr_str) {
+            None => {
+                return Err(RabeError::DecryptionFailed(
+                    format!("attribute {} is not part of the ciphertext", _attr_str),
+                ))
+            }
+            Some(_ct_attr) => _ct_attr,
+        };
+        let _coeff_attr = match _coeffs.iter().find(|_attr| _attr.0 == *_attr_str) {
+            None => {
+                return Err(RabeError::DecryptionFailed(
+                    format!("no coefficient for attribute {}", _attr_str),
+                ))
             }
+            Some(_coeff_attr) => _coeff_attr,
+        };
+        if is_negative(&_attr_str) {
+            // TODO !!
+            /*let _sum_e4 = G2::zero();
+            let _sum_e5 = G2::zero();
+            _prod_t = _prod_t *
+                (pairing(sk._d_i[_i].3, ct._e2) *
+                     (pairing(sk._d_i[_i].4, _sum_e4) * pairing(sk._d_i[_i].5, _sum_e5))
+                         .inverse());
+            */
+        } else {
+            _z_y = pairing(_sk_attr.1, _ct._e2)
+                * pairing(_ct_attr.1, _sk_attr.2).inverse();
         }
+        _prod_t = _prod_t * _z_y.pow(_coeff_attr.1);
+    }
+    let _msg = _ct._e1 * _prod_t.inverse();
+    // Decrypt plaintext using derived secret from cp-abe scheme
+    match decrypt_symmetric(&_msg, &_ct._ct) {
+        None => return Err(RabeError::SymmetricDecryptionFailed),
+        Some(_pt) => return Ok(_pt),
+    }
+}
+
+// Read-only accessors for white-box tests of downstream crates. Only available with the
+// `test-internals` feature, they are meant for testing and not covered by any stability guarantee.
+#[cfg(feature = "test-internals")]
+impl KpAbePublicKey {
+    pub fn g_g1(&self) -> &G1 {
+        return &self._g_g1;
+    }
+
+    pub fn g_g2(&self) -> &G2 {
+        return &self._g_g2;
+    }
+
+    pub fn g_g1_b(&self) -> &G1 {
+        return &self._g_g1_b;
+    }
+
+    pub fn g_g1_b2(&self) -> &G1 {
+        return &self._g_g1_b2;
+    }
+
+    pub fn h_g1_b(&self) -> &G1 {
+        return &self._h_g1_b;
+    }
+
+    pub fn e_gg_alpha(&self) -> &Gt {
+        return &self._e_gg_alpha;
+    }
+}
+
+#[cfg(feature = "test-internals")]
+impl KpAbeMasterKey {
+    pub fn alpha1(&self) -> &Fr {
+        return &self._alpha1;
+    }
+
+    pub fn alpha2(&self) -> &Fr {
+        return &self._alpha2;
+    }
+
+    pub fn beta(&self) -> &Fr {
+        return &self._beta;
+    }
+
+    pub fn h_g1(&self) -> &G1 {
+        return &self._h_g1;
+    }
+
+    pub fn h_g2(&self) -> &G2 {
+        return &self._h_g2;
+    }
+}
+
+#[cfg(feature = "test-internals")]
+impl KpAbeSecretKey {
+    pub fn policy(&self) -> &String {
+        return &self._policy;
+    }
+
+    pub fn dj(&self) -> &Vec<(String, G1, G2, G1, G1, G1)> {
+        return &self._dj;
+    }
+}
+
+#[cfg(feature = "test-internals")]
+impl KpAbeCiphertext {
+    pub fn e1(&self) -> &Gt {
+        return &self._e1;
+    }
+
+    pub fn e2(&self) -> &G2 {
+        return &self._e2;
+    }
+
+    pub fn ej(&self) -> &Vec<(String, G1, G1, G1)> {
+        return &self._ej;
+    }
+
+    pub fn ct(&self) -> &Vec<u8> {
+        return &self._ct;
     }
 }
 
@@ -342,6 +433,26 @@ mod tests {
         // a kp-abe SK key
         let sk: KpAbeSecretKey = keygen(&pk, &msk, &policy).unwrap();
         // and now decrypt again with matching sk
-        assert_eq!(decrypt(&sk, &ct_kp_matching), None);
+        assert_eq!(decrypt(&sk, &ct_kp_matching).is_err(), true);
+    }
+
+    #[test]
+    #[cfg(feature = "test-internals")]
+    fn internals() {
+        // setup scheme
+        let (pk, msk) = setup();
+        let plaintext =
+            String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#);
+        let _attributes = vec![String::from("A"), String::from("B"), String::from("C")];
+        let ct_kp: KpAbeCiphertext = encrypt(&pk, &_attributes, &plaintext).unwrap();
+        let sk: KpAbeSecretKey = keygen(&pk, &msk, &policy).unwrap();
+        assert_eq!(*pk.g_g1_b() == pk._g_g1 * msk._beta, true);
+        assert_eq!(*msk.beta() == msk._beta, true);
+        assert_eq!(sk.policy(), &policy);
+        assert_eq!(sk.dj().len(), 2);
+        assert_eq!(ct_kp.ej().len(), 3);
+        assert_eq!(ct_kp.ej()[0].0, String::from("A"));
+        assert_eq!(ct_kp.ct().is_empty(), false);
     }
 }
@@ -0,0 +1,518 @@
+//! This is the documentation for the `MAABE` scheme:
+//!
+//! * Developped by Yannis Rouselakis, Brent Waters, "Efficient Statically-Secure Large-Universe Multi-Authority Attribute-Based Encryption"
+//! * Published in Financial Cryptography and Data Security, 2015. FC'15. Springer
+//! * Available from https://eprint.iacr.org/2015/016.pdf
+//! * Type: encryption (decentralized multi-authority attribute-based)
+//! * Setting: bilinear groups (asymmetric)
+//!
+//! Every authority is set up on its own and issues the attributes carrying its name as prefix,
+//! e.g. `ORG1@DEPT` is issued by the authority `ORG1`. The keys a user receives from several
+//! authorities are bound together by the global identifier (GID) of the user, keys of different
+//! users can not be combined. A ciphertext is encrypted using the public keys of all authorities
+//! whose attributes occur in the policy.
+//!
+//! # Examples
+//!
+//! ```
+//!use rabe::schemes::maabe::*;
+//!let gp = global_setup();
+//!let (pk_1, msk_1) = authority_setup(&gp, "ORG1").unwrap();
+//!let (pk_2, msk_2) = authority_setup(&gp, "ORG2").unwrap();
+//!let plaintext = String::from("dance like no one's watching, encrypt like everyone is!").into_bytes();
+//!let policy = String::from(r#"{"AND": [{"ATT": "ORG1@A"}, {"ATT": "ORG2@B"}]}"#);
+//!let ct: MaAbeCiphertext = encrypt(&gp, &vec![pk_1, pk_2], &policy, &plaintext).unwrap();
+//!let sk_1 = keygen(&gp, &msk_1, "bob", &vec!["ORG1@A".to_string()]).unwrap();
+//!let sk_2 = keygen(&gp, &msk_2, "bob", &vec!["ORG2@B".to_string()]).unwrap();
+//!assert_eq!(decrypt(&gp, &[&sk_1, &sk_2], &ct).unwrap(), plaintext);
+//! ```
+use bn::{Fr, G1, G2, Gt, pairing};
+use rand::Rng;
+use std::collections::HashMap;
+use utils::{
+    secretsharing::{gen_shares_str, calc_coefficients_indexed_str, validate_policy, NEGATION_PREFIX},
+    aes::*,
+    encoding::{check_g1, check_g2, check_gt, RabeEncode},
+    error::RabeError,
+    hash::hash_to_g2,
+};
+
+// the domains of the hashes of global identifiers and attributes, see hash_to_g2
+const MAABE_GID_DOMAIN: &'static str = "maabe-gid";
+const MAABE_ATTRIBUTE_DOMAIN: &'static str = "maabe-attribute";
+
+/// The separator of the authority and the name of an attribute, e.g. `ORG1@DEPT`
+pub const AUTHORITY_SEPARATOR: char = '@';
+
+/// The MAABE Global Parameters (GP)
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct MaAbeGlobalParams {
+    pub _g1: G1,
+    pub _g2: G2,
+    pub _e_gg: Gt,
+}
+
+/// The MAABE Public Key (PK) of an authority
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct MaAbePublicKey {
+    pub _name: String,
+    pub _e_gg_alpha: Gt,
+    pub _g1_y: G1,
+}
+
+/// The MAABE Master Key (MSK) of an authority
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct MaAbeMasterKey {
+    pub _name: String,
+    pub _alpha: Fr,
+    pub _y: Fr,
+}
+
+/// A MAABE Secret User Key (SK), holding the attributes issued to a GID by one authority
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct MaAbeSecretKey {
+    pub _gid: String,
+    pub _attr: Vec<MaAbeAttribute>,
+}
+
+/// The key component of an attribute
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct MaAbeAttribute {
+    pub _str: String,
+    pub _k: G2,
+    pub _k_p: G1,
+}
+
+/// The ciphertext component of a leaf of the policy
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct MaAbeCiphertextRow {
+    pub _str: String,
+    pub _c_1: Gt,
+    pub _c_2: G1,
+    pub _c_3: G1,
+    pub _c_4: G2,
+}
+
+/// A MAABE Ciphertext (CT)
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub struct MaAbeCiphertext {
+    pub _policy: String,
+    pub _c_0: Gt,
+    pub _c: Vec<MaAbeCiphertextRow>,
+    pub _ct: Vec<u8>,
+}
+
+impl RabeEncode for MaAbeGlobalParams {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_g1(&self._g1)?;
+        check_g2(&self._g2)?;
+        return check_gt(&self._e_gg);
+    }
+}
+
+impl RabeEncode for MaAbePublicKey {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_gt(&self._e_gg_alpha)?;
+        return check_g1(&self._g1_y);
+    }
+}
+
+impl RabeEncode for MaAbeMasterKey {}
+
+impl RabeEncode for MaAbeSecretKey {
+    fn validate(&self) -> Result<(), RabeError> {
+        for _attr in self._attr.iter() {
+            check_g2(&_attr._k)?;
+            check_g1(&_attr._k_p)?;
+        }
+        return Ok(());
+    }
+}
+
+impl RabeEncode for MaAbeCiphertext {
+    fn validate(&self) -> Result<(), RabeError> {
+        check_gt(&self._c_0)?;
+        for _row in self._c.iter() {
+            check_gt(&_row._c_1)?;
+            check_g1(&_row._c_2)?;
+            check_g1(&_row._c_3)?;
+            check_g2(&_row._c_4)?;
+        }
+        return Ok(());
+    }
+}
+
+/// Returns the name of the authority of an attribute, i.e. the part before the first `@`, or
+/// None if the attribute has no authority prefix.
+pub fn attribute_authority(_attr: &str) -> Option<&str> {
+    match _attr.find(AUTHORITY_SEPARATOR) {
+        Some(_pos) if _pos > 0 && _pos + 1 < _attr.len() => return Some(&_attr[.._pos]),
+        _ => return None,
+    }
+}
+
+/// The global setup algorithm of MAABE. Generates the MaAbeGlobalParams shared by all
+/// authorities.
+pub fn global_setup() -> MaAbeGlobalParams {
+    // random number generator
+    let mut _rng = rand::thread_rng();
+    // generator of group G1: g1 and generator of group G2: g2
+    let _g1: G1 = _rng.gen();
+    let _g2: G2 = _rng.gen();
+    let _e_gg = pairing(_g1, _g2);
+    return MaAbeGlobalParams {_g1, _g2, _e_gg};
+}
+
+/// The setup algorithm of a MAABE authority. Generates its MaAbePublicKey and MaAbeMasterKey.
+///
+/// # Arguments
+///
+///	* `_gp` - The Global Parameters (GP), generated by the function global_setup()
+///	* `_name` - The name of the authority, the prefix of all attributes it issues
+///
+/// # Errors
+///
+///	* `RabeError::InvalidAttribute` - if `_name` is empty or contains the separator `@`
+///
+pub fn authority_setup(
+    _gp: &MaAbeGlobalParams,
+    _name: &str,
+) -> Result<(MaAbePublicKey, MaAbeMasterKey), RabeError> {
+    if _name.is_empty() || _name.contains(AUTHORITY_SEPARATOR) {
+        return Err(RabeError::InvalidAttribute(
+            format!("{} is not a valid authority name", _name),
+        ));
+    }
+    // random number generator
+    let mut _rng = rand::thread_rng();
+    let _alpha: Fr = _rng.gen();
+    let _y: Fr = _rng.gen();
+    return Ok((
+        MaAbePublicKey {
+            _name: _name.to_string(),
+            _e_gg_alpha: _gp._e_gg.pow(_alpha),
+            _g1_y: _gp._g1 * _y,
+        },
+        MaAbeMasterKey {
+            _name: _name.to_string(),
+            _alpha,
+            _y,
+        },
+    ));
+}
+
+/// The key generation algorithm of a MAABE authority. Generates a MaAbeSecretKey for the
+/// global identifier `_gid` holding the given attributes, each of which has to carry the name
+/// of the authority as prefix.
+///
+/// # Arguments
+///
+///	* `_gp` - The Global Parameters (GP), generated by the function global_setup()
+///	* `_msk` - The Master Key (MSK) of the authority, generated by the function authority_setup()
+///	* `_gid` - The global identifier of the user
+///	* `_attributes` - A Vector of String attributes assigned to this user key
+///
+/// # Errors
+///
+///	* `RabeError::EmptyAttributes` - if `_attributes` is empty
+///	* `RabeError::UnauthorizedAttribute` - if an attribute belongs to another authority
+///
+pub fn keygen(
+    _gp: &MaAbeGlobalParams,
+    _msk: &MaAbeMasterKey,
+    _gid: &str,
+    _attributes: &Vec<String>,
+) -> Result<MaAbeSecretKey, RabeError> {
+    if _attributes.is_empty() {
+        return Err(RabeError::EmptyAttributes);
+    }
+    // random number generator
+    let mut _rng = rand::thread_rng();
+    let _h_gid = hash_to_g2(_gid, MAABE_GID_DOMAIN);
+    let mut _attr: Vec<MaAbeAttribute> = Vec::new();
+    for _u in _attributes {
+        if attribute_authority(_u) != Some(_msk._name.as_str()) {
+            return Err(RabeError::UnauthorizedAttribute(_u.to_string()));
+        }
+        if _attr.iter().any(|_a| _a._str == *_u) {
+            continue;
+        }
+        let _t: Fr = _rng.gen();
+        _attr.push(MaAbeAttribute {
+            _str: _u.to_string(),
+            _k: (_gp._g2 * _msk._alpha) + (_h_gid * _msk._y) +
+                (hash_to_g2(_u, MAABE_ATTRIBUTE_DOMAIN) * _t),
+            _k_p: _gp._g1 * _t,
+        });
+    }
+    return Ok(MaAbeSecretKey {
+        _gid: _gid.to_string(),
+        _attr,
+    });
+}
+
+/// The encrypt algorithm of MAABE. Generates a new MaAbeCiphertext under an access policy over
+/// the attributes of several authorities. The plaintext is encrypted with authenticated AES-GCM.
+///
+/// # Arguments
+///
+///	* `_gp` - The Global Parameters (GP), generated by the function global_setup()
+///	* `_pks` - The Public Keys (PK) of (at least) all authorities of the policy
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as a Vector of u8
+///
+/// # Errors
+///
+///	* `RabeError::UnauthorizedAttribute` - if no public key of the authority of an attribute is given
+///
+pub fn encrypt(
+    _gp: &MaAbeGlobalParams,
+    _pks: &Vec<MaAbePublicKey>,
+    _policy: &String,
+    _plaintext: &Vec<u8>,
+) -> Result<MaAbeCiphertext, RabeError> {
+    validate_policy(_policy)?;
+    // random number generator
+    let mut _rng = rand::thread_rng();
+    let _z: Fr = _rng.gen();
+    let _msg: Gt = _rng.gen();
+    // shares of the secret z and of zero, the latter binds the rows to a single GID
+    let _lambda = gen_shares_str(_z, _policy)?;
+    let _omega = gen_shares_str(Fr::zero(), _policy)?;
+    let _authorities: HashMap<&str, &MaAbePublicKey> = _pks
+        .iter()
+        .map(|_pk| (_pk._name.as_str(), _pk))
+        .collect();
+    let mut _c: Vec<MaAbeCiphertextRow> = Vec::new();
+    for (&(ref _x, _lambda_x), &(_, _omega_x)) in _lambda.iter().zip(_omega.iter()) {
+        // the dummy share of a negated attribute belongs to the authority of the attribute
+        let _u = _x.trim_start_matches(NEGATION_PREFIX);
+        let _pk = match attribute_authority(_u).and_then(|_name| _authorities.get(_name)) {
+            Some(_pk) => _pk,
+            None => return Err(RabeError::UnauthorizedAttribute(_u.to_string())),
+        };
+        let _t: Fr = _rng.gen();
+        _c.push(MaAbeCiphertextRow {
+            _str: _x.to_string(),
+            _c_1: _gp._e_gg.pow(_lambda_x) * _pk._e_gg_alpha.pow(_t),
+            _c_2: _gp._g1 * (Fr::zero() - _t),
+            _c_3: (_pk._g1_y * _t) + (_gp._g1 * _omega_x),
+            _c_4: hash_to_g2(_u, MAABE_ATTRIBUTE_DOMAIN) * _t,
+        });
+    }
+    //Encrypt plaintext using derived key from secret
+    match encrypt_symmetric_gcm(&_msg, &_plaintext) {
+        None => return Err(RabeError::Encryption(String::from("symmetric encryption failed"))),
+        Some(_ct) => {
+            return Ok(MaAbeCiphertext {
+                _policy: _policy.to_string(),
+                _c_0: _msg * _gp._e_gg.pow(_z),
+                _c,
+                _ct,
+            })
+        }
+    }
+}
+
+/// The decrypt algorithm of MAABE. Reconstructs the original plaintext data as Vec<u8>, given
+/// a MaAbeCiphertext and the keys a user received from the authorities of the policy.
+///
+/// # Arguments
+///
+///	* `_gp` - The Global Parameters (GP), generated by the function global_setup()
+///	* `_sks` - The Secret Keys (SK) of one user, generated by the function keygen()
+///	* `_ct` - A MAABE Ciphertext
+///
+/// # Errors
+///
+///	* `RabeError::EmptyAttributes` - if no key is given
+///	* `RabeError::DecryptionFailed` - if the keys belong to different GIDs or a component is missing
+///	* `RabeError::InsufficientAttributes` - if the attributes of `_sks` do not satisfy the policy
+///	* `RabeError::SymmetricDecryptionFailed` - if the payload is missing or corrupt
+///
+pub fn decrypt(
+    _gp: &MaAbeGlobalParams,
+    _sks: &[&MaAbeSecretKey],
+    _ct: &MaAbeCiphertext,
+) -> Result<Vec<u8>, RabeError> {
+    let _gid = match _sks.first() {
+        Some(_sk) => _sk._gid.as_str(),
+        None => return Err(RabeError::EmptyAttributes),
+    };
+    if _sks.iter().any(|_sk| _sk._gid != _gid) {
+        return Err(RabeError::DecryptionFailed(
+            String::from("the keys belong to different global identifiers"),
+        ));
+    }
+    validate_policy(&_ct._policy)?;
+    let _k: HashMap<&str, &MaAbeAttribute> = _sks
+        .iter()
+        .flat_map(|_sk| _sk._attr.iter())
+        .map(|_a| (_a._str.as_str(), _a))
+        .collect();
+    let _attributes: Vec<String> = _k.keys().map(|_a| _a.to_string()).collect();
+    // the coefficients of the leaves used, indexed like the rows of the ciphertext
+    let _w = match calc_coefficients_indexed_str(&_attributes, &_ct._policy)? {
+        None => return Err(RabeError::InsufficientAttributes),
+        Some(_w) => _w,
+    };
+    let _h_gid = hash_to_g2(_gid, MAABE_GID_DOMAIN);
+    let mut _a = Gt::one();
+    for (_index, _x, _w_x) in _w {
+        let _row = match _ct._c.get(_index) {
+            Some(_row) if _row._str == _x => _row,
+            _ => return Err(RabeError::DecryptionFailed(format!("ciphertext component of {}", _x))),
+        };
+        let _k_x = match _k.get(_x.as_str()) {
+            Some(_k_x) => _k_x,
+            None => return Err(RabeError::DecryptionFailed(format!("key component of {}", _x))),
+        };
+        // C_1 * e(C_2, K) * e(C_3, H(GID)) * e(K', C_4) = e(g1, g2)^lambda_x * e(g1, H(GID))^omega_x
+        let _f = _row._c_1 * pairing(_row._c_2, _k_x._k) * pairing(_row._c_3, _h_gid) *
+            pairing(_k_x._k_p, _row._c_4);
+        _a = _a * _f.pow(_w_x);
+    }
+    let _msg = _ct._c_0 * _a.inverse();
+    // Decrypt plaintext using derived secret from the ma-abe scheme
+    match decrypt_symmetric_gcm(&_msg, &_ct._ct) {
+        None => return Err(RabeError::SymmetricDecryptionFailed),
+        Some(_pt) => return Ok(_pt),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use blake2_rfc::blake2b::blake2b;
+
+    fn attributes(_list: &[&str]) -> Vec<String> {
+        return _list.iter().map(|_a| _a.to_string()).collect();
+    }
+
+    #[test]
+    fn two_authorities() {
+        // setup scheme
+        let gp = global_setup();
+        let (pk_1, msk_1) = authority_setup(&gp, "ORG1").unwrap();
+        let (pk_2, msk_2) = authority_setup(&gp, "ORG2").unwrap();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(
+            r#"{"AND": [{"ATT": "ORG1@A"}, {"OR": [{"ATT": "ORG2@B"}, {"ATT": "ORG2@C"}]}]}"#,
+        );
+        let ct = encrypt(&gp, &vec![pk_1.clone(), pk_2.clone()], &policy, &plaintext).unwrap();
+        let bob_1 = keygen(&gp, &msk_1, "bob", &attributes(&["ORG1@A"])).unwrap();
+        let bob_2 = keygen(&gp, &msk_2, "bob", &attributes(&["ORG2@C"])).unwrap();
+        assert_eq!(decrypt(&gp, &[&bob_1, &bob_2], &ct).unwrap(), plaintext);
+        // without the attributes of the second authority
+        assert_eq!(
+            decrypt(&gp, &[&bob_1], &ct).err(),
+            Some(RabeError::InsufficientAttributes)
+        );
+        // keys of different users can not be combined
+        let alice_2 = keygen(&gp, &msk_2, "alice", &attributes(&["ORG2@B"])).unwrap();
+        assert_eq!(decrypt(&gp, &[&bob_1, &alice_2], &ct).is_err(), true);
+        let mut _colluded = bob_1.clone();
+        _colluded._attr.extend(alice_2._attr.iter().cloned());
+        assert_eq!(decrypt(&gp, &[&_colluded], &ct).is_err(), true);
+    }
+
+    #[test]
+    fn different_gids() {
+        // setup scheme
+        let gp = global_setup();
+        let (pk_1, msk_1) = authority_setup(&gp, "ORG1").unwrap();
+        let (pk_2, msk_2) = authority_setup(&gp, "ORG2").unwrap();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"AND": [{"ATT": "ORG1@A"}, {"ATT": "ORG2@B"}]}"#);
+        let ct = encrypt(&gp, &vec![pk_1, pk_2], &policy, &plaintext).unwrap();
+        let alice = keygen(&gp, &msk_1, "alice", &attributes(&["ORG1@A"])).unwrap();
+        let bob = keygen(&gp, &msk_2, "bob", &attributes(&["ORG2@B"])).unwrap();
+        assert_eq!(decrypt(&gp, &[&alice, &bob], &ct).is_err(), true);
+        // the key of alice claims the GID of bob, but is bound to H(alice)
+        let mut _relabeled = alice.clone();
+        _relabeled._gid = String::from("bob");
+        assert_eq!(
+            decrypt(&gp, &[&_relabeled, &bob], &ct).err(),
+            Some(RabeError::SymmetricDecryptionFailed)
+        );
+        let mut _relabeled = bob.clone();
+        _relabeled._gid = String::from("alice");
+        assert_eq!(
+            decrypt(&gp, &[&alice, &_relabeled], &ct).err(),
+            Some(RabeError::SymmetricDecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn known_hash_exponents() {
+        // setup scheme
+        let gp = global_setup();
+        let (pk, msk) = authority_setup(&gp, "ORG1").unwrap();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let policy = String::from(r#"{"ATT": "ORG1@B"}"#);
+        let ct = encrypt(&gp, &vec![pk], &policy, &plaintext).unwrap();
+        let sk = keygen(&gp, &msk, "bob", &attributes(&["ORG1@A"])).unwrap();
+        // if F(u) = g2^f(u) with a public f, then F(A)^t = C_4^(f(A) / f(B)) turns the row of
+        // ORG1@B into a row of ORG1@A. f is the exponent of the former hash_to_g2.
+        let _f = |_u: &str| {
+            let mut _data: Vec<u8> = b"rabe-hash-to-g2-v1".to_vec();
+            _data.extend_from_slice(&(MAABE_ATTRIBUTE_DOMAIN.len() as u64).to_be_bytes());
+            _data.extend_from_slice(MAABE_ATTRIBUTE_DOMAIN.as_bytes());
+            _data.extend_from_slice(_u.as_bytes());
+            return Fr::interpret(array_ref![blake2b(64, &[], &_data).as_ref(), 0, 64]);
+        };
+        let mut _rebased = ct.clone();
+        _rebased._policy = String::from(r#"{"ATT": "ORG1@A"}"#);
+        _rebased._c[0]._str = String::from("ORG1@A");
+        _rebased._c[0]._c_4 = ct._c[0]._c_4 * (_f("ORG1@A") * _f("ORG1@B").inverse().unwrap());
+        assert_eq!(
+            decrypt(&gp, &[&sk], &_rebased).err(),
+            Some(RabeError::SymmetricDecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn authorities() {
+        let gp = global_setup();
+        let (pk_1, msk_1) = authority_setup(&gp, "ORG1").unwrap();
+        let policy = String::from(r#"{"AND": [{"ATT": "ORG1@A"}, {"ATT": "ORG2@B"}]}"#);
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // the public key of ORG2 is missing
+        assert_eq!(
+            encrypt(&gp, &vec![pk_1], &policy, &plaintext).err(),
+            Some(RabeError::UnauthorizedAttribute(String::from("ORG2@B")))
+        );
+        // ORG1 may only issue its own attributes
+        assert_eq!(
+            keygen(&gp, &msk_1, "bob", &attributes(&["ORG2@B"])).err(),
+            Some(RabeError::UnauthorizedAttribute(String::from("ORG2@B")))
+        );
+        assert_eq!(keygen(&gp, &msk_1, "bob", &attributes(&["A"])).is_err(), true);
+        assert_eq!(keygen(&gp, &msk_1, "bob", &Vec::new()).is_err(), true);
+        for _name in ["", "ORG@1"].iter() {
+            assert_eq!(authority_setup(&gp, _name).is_err(), true);
+        }
+        assert_eq!(attribute_authority("ORG1@DEPT"), Some("ORG1"));
+        assert_eq!(attribute_authority("ORG1@"), None);
+        assert_eq!(attribute_authority("@DEPT"), None);
+    }
+
+    #[test]
+    fn encoding() {
+        let gp = global_setup();
+        let (pk, msk) = authority_setup(&gp, "ORG1").unwrap();
+        let policy = String::from(r#"{"OR": [{"ATT": "ORG1@A"}, {"ATT": "ORG1@B"}]}"#);
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let ct = encrypt(&gp, &vec![pk], &policy, &plaintext).unwrap();
+        let sk = keygen(&gp, &msk, "bob", &attributes(&["ORG1@B"])).unwrap();
+        let ct = MaAbeCiphertext::from_bytes(&ct.to_bytes().unwrap()).unwrap();
+        let sk = MaAbeSecretKey::from_base64(&sk.to_base64().unwrap()).unwrap();
+        assert_eq!(decrypt(&gp, &[&sk], &ct).unwrap(), plaintext);
+    }
+}
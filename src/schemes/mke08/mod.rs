@@ -32,11 +32,17 @@ use rand::Rng;
 use std::string::String;
 use utils::{
     aes::*,
-    hash::blake2b_hash_fr,
+    error::RabeError,
+    hash::hash_to_fr,
     policy::dnf::DnfPolicy,
+    secretsharing::validate_policy,
     tools::*
 };
 
+// the domains of the hashes of attributes and authority names, see hash_to_fr
+const MKE08_ATTRIBUTE_DOMAIN: &'static str = "mke08-attribute";
+const MKE08_AUTHORITY_DOMAIN: &'static str = "mke08-authority";
+
 /// A MKE08 Public Key (PK)
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct Mke08PublicKey {
@@ -200,12 +206,13 @@ pub fn request_authority_pk(
     _pk: &Mke08PublicKey,
     _a: &String,
     _sk_a: &Mke08SecretAuthorityKey,
-) -> Option<Mke08PublicAttributeKey> {
+) -> Result<Mke08PublicAttributeKey, RabeError> {
     // if attribute a is from authority sk_a
     return if from_authority(_a, &_sk_a._a) {
-        let exponent = blake2b_hash_fr(_a) * blake2b_hash_fr(&_sk_a._a) * _sk_a._r;
+        let exponent = hash_to_fr(_a, MKE08_ATTRIBUTE_DOMAIN) *
+            hash_to_fr(&_sk_a._a, MKE08_AUTHORITY_DOMAIN) * _sk_a._r;
         // return PK and mke
-        Some(Mke08PublicAttributeKey {
+        Ok(Mke08PublicAttributeKey {
             _str: _a.clone(),
             _g1: _pk._g1 * exponent,
             _g2: _pk._g2 * exponent,
@@ -213,7 +220,7 @@ pub fn request_authority_pk(
             _gt2: _pk._e_gg_y2.pow(exponent),
         })
     } else {
-        None
+        Err(RabeError::UnauthorizedAttribute(_a.clone()))
     }
 }
 
@@ -229,18 +236,19 @@ pub fn request_authority_sk(
     _a: &String,
     _sk_a: &Mke08SecretAuthorityKey,
     _pk_u: &Mke08PublicUserKey,
-) -> Option<Mke08SecretAttributeKey> {
+) -> Result<Mke08SecretAttributeKey, RabeError> {
     // if attribute a is from authority sk_a
     return if from_authority(_a, &_sk_a._a) && is_eligible(_a, &_pk_u._u) {
-        let exponent = blake2b_hash_fr(_a) * blake2b_hash_fr(&_sk_a._a) * _sk_a._r;
+        let exponent = hash_to_fr(_a, MKE08_ATTRIBUTE_DOMAIN) *
+            hash_to_fr(&_sk_a._a, MKE08_AUTHORITY_DOMAIN) * _sk_a._r;
         // return PK and mke
-        Some(Mke08SecretAttributeKey {
+        Ok(Mke08SecretAttributeKey {
             _str: _a.clone(),
             _g1: _pk_u._pk_g1 * exponent,
             _g2: _pk_u._pk_g2 * exponent,
         })
     } else {
-        None
+        Err(RabeError::UnauthorizedAttribute(_a.clone()))
     }
 }
 
@@ -259,13 +267,16 @@ pub fn encrypt(
     _attr_pks: &Vec<Mke08PublicAttributeKey>,
     _policy: &String,
     _plaintext: &[u8],
-) -> Option<Mke08Ciphertext> {
+) -> Result<Mke08Ciphertext, RabeError> {
     // if policy is in DNF
     return if DnfPolicy::is_in_dnf(&_policy) {
         // random number generator
         let mut _rng = rand::thread_rng();
         // an DNF policy from the given String
-        let dnf: DnfPolicy = DnfPolicy::from_string(&_policy, _attr_pks).unwrap();
+        let dnf: DnfPolicy = match DnfPolicy::from_string(&_policy, _attr_pks) {
+            None => return Err(RabeError::PolicyParse(_policy.clone())),
+            Some(_dnf) => _dnf,
+        };
         // random Gt msgs
         let _msg1 = pairing(_rng.gen(), _rng.gen());
         let _msg2 = _msg1.pow(_rng.gen());
@@ -287,10 +298,13 @@ pub fn encrypt(
         }
         //Encrypt plaintext using derived key from secret
         let _policy = _policy.to_string();
-        let _ct = encrypt_symmetric(&_msg, &_plaintext.to_vec()).unwrap();
-        Some(Mke08Ciphertext { _policy, _e, _ct})
+        let _ct = match encrypt_symmetric(&_msg, &_plaintext.to_vec()) {
+            None => return Err(RabeError::Encryption(String::from("symmetric encryption failed"))),
+            Some(_ct) => _ct,
+        };
+        Ok(Mke08Ciphertext { _policy, _e, _ct})
     } else {
-        None
+        Err(RabeError::InvalidPolicy(String::from("the policy is not in DNF")))
     }
 }
 
@@ -303,7 +317,11 @@ pub fn encrypt(
 ///	* `_ct` - A Mke08Ciphertext
 ///	* `_policy` - An access policy given as JSON String
 ///
-pub fn decrypt(_pk: &Mke08PublicKey, _sk: &Mke08UserKey, _ct: &Mke08Ciphertext) -> Option<Vec<u8>> {
+pub fn decrypt(
+    _pk: &Mke08PublicKey,
+    _sk: &Mke08UserKey,
+    _ct: &Mke08Ciphertext,
+) -> Result<Vec<u8>, RabeError> {
     let _attr = _sk._sk_a
         .iter()
         .map(|triple| {
@@ -311,9 +329,9 @@ pub fn decrypt(_pk: &Mke08PublicKey, _sk: &Mke08UserKey, _ct: &Mke08Ciphertext)
             _a._str.to_string()
         })
         .collect::<Vec<_>>();
+    validate_policy(&_ct._policy)?;
     return if traverse_str(&_attr, &_ct._policy) == false {
-        //println!("Error: attributes in sk do not match policy in ct.");
-        None
+        Err(RabeError::InsufficientAttributes)
     } else {
         let mut _msg = Gt::one();
         for (_i, _e_j) in _ct._e.iter().enumerate() {
@@ -327,7 +345,10 @@ pub fn decrypt(_pk: &Mke08PublicKey, _sk: &Mke08UserKey, _ct: &Mke08Ciphertext)
             }
         }
         // Decrypt plaintext using derived secret from mke08 scheme
-        decrypt_symmetric(&_msg, &_ct._ct)
+        match decrypt_symmetric(&_msg, &_ct._ct) {
+            None => Err(RabeError::SymmetricDecryptionFailed),
+            Some(_pt) => Ok(_pt),
+        }
     }
 }
 
@@ -609,6 +630,6 @@ mod tests {
             .unwrap();
         // and now decrypt again with mathcing sk
         let _match = decrypt(&_pk, &_u_key, &_ct);
-        assert_eq!(_match.is_none(), true);
+        assert_eq!(_match.is_err(), true);
     }
 }
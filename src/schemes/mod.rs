@@ -6,12 +6,95 @@
 //! * AW11 CP-ABE
 //! * BDABE CP-ABE
 //! * BSW CP-ABE
+//! * KP (GPSW) KP-ABE
 //! * LSW KP-ABE
+//! * MAABE (RW15) CP-ABE
 //! * MKE08 CP-ABE
 //!
 pub mod ac17;
 pub mod aw11;
 pub mod bdabe;
 pub mod bsw;
+pub mod kp;
 pub mod lsw;
+pub mod maabe;
 pub mod mke08;
+
+use utils::error::RabeError;
+
+/// A common interface of the ABE schemes, so that generic code can be written once and be
+/// instantiated per scheme, e.g. `schemes::bsw::Bsw` or `schemes::kp::Gpsw`.
+///
+/// A key is generated for a `KeyInput` and a ciphertext for an `EncryptInput`: attributes and a
+/// policy for CP-ABE schemes, a policy and attributes for KP-ABE schemes.
+pub trait AbeScheme {
+    type PublicKey;
+    type MasterKey;
+    type SecretKey;
+    type Ciphertext;
+    /// The attributes (CP-ABE) or the policy (KP-ABE) of a secret key
+    type KeyInput: ?Sized;
+    /// The policy (CP-ABE) or the attributes (KP-ABE) of a ciphertext
+    type EncryptInput: ?Sized;
+
+    /// Generates a new public and master key.
+    fn setup() -> (Self::PublicKey, Self::MasterKey);
+
+    /// Generates a secret key for `_input`.
+    fn keygen(
+        _pk: &Self::PublicKey,
+        _msk: &Self::MasterKey,
+        _input: &Self::KeyInput,
+    ) -> Result<Self::SecretKey, RabeError>;
+
+    /// Encrypts `_plaintext` under `_input`.
+    fn encrypt(
+        _pk: &Self::PublicKey,
+        _input: &Self::EncryptInput,
+        _plaintext: &Vec<u8>,
+    ) -> Result<Self::Ciphertext, RabeError>;
+
+    /// Decrypts a ciphertext if the secret key fits.
+    fn decrypt(_sk: &Self::SecretKey, _ct: &Self::Ciphertext) -> Result<Vec<u8>, RabeError>;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // a full round trip, generic over the scheme
+    fn roundtrip<S: AbeScheme>(
+        _matching: &S::KeyInput,
+        _not_matching: &S::KeyInput,
+        _input: &S::EncryptInput,
+    ) {
+        // setup scheme
+        let (pk, msk) = S::setup();
+        let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let ct = S::encrypt(&pk, _input, &plaintext).unwrap();
+        let sk = S::keygen(&pk, &msk, _matching).unwrap();
+        assert_eq!(S::decrypt(&sk, &ct).unwrap(), plaintext);
+        let sk_other = S::keygen(&pk, &msk, _not_matching).unwrap();
+        assert_eq!(S::decrypt(&sk_other, &ct).is_err(), true);
+    }
+
+    #[test]
+    fn generic_bsw() {
+        roundtrip::<bsw::Bsw>(
+            &vec![String::from("A"), String::from("B")],
+            &vec![String::from("A")],
+            &String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#),
+        );
+    }
+
+    #[test]
+    fn generic_kp() {
+        roundtrip::<kp::Gpsw>(
+            &String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#),
+            &String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "C"}]}"#),
+            &vec![String::from("A"), String::from("B")],
+        );
+    }
+}
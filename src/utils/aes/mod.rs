@@ -4,49 +4,164 @@ extern crate crypto;
 extern crate serde;
 extern crate serde_json;
 
-use bincode::serialize;
+use crypto::aead::{AeadDecryptor, AeadEncryptor};
+use crypto::aes_gcm::AesGcm;
 use crypto::buffer::{BufferResult, ReadBuffer, WriteBuffer};
 use crypto::digest::Digest;
 use crypto::sha3::Sha3;
 use crypto::{aes, blockmodes, buffer, symmetriccipher};
 use rand::{RngCore, thread_rng};
+use utils::encoding::encode;
+
+/// The size of the nonce prepended to an AES-GCM payload
+pub const GCM_NONCE_SIZE: usize = 12;
+/// The size of the authentication tag appended to an AES-GCM payload
+pub const GCM_TAG_SIZE: usize = 16;
+// The block size of AES, also the size of the iv in CBC mode
+const AES_BLOCK_SIZE: usize = 16;
 
 /// Key Encapsulation Mechanism (Encryption Function)
 pub fn encrypt_symmetric(_msg: &bn::Gt, _plaintext: &Vec<u8>) -> Option<Vec<u8>> {
-    let mut _key: [u8; 32] = [0; 32];
     let mut _iv: Vec<u8> = vec![0; 16];
-    let mut _ret: Vec<u8> = Vec::new();
-    let mut _sha = Sha3::sha3_256();
     let mut _rng = thread_rng();
-    match serialize(&_msg) {
-        Err(_) => return None,
-        Ok(_serialized_msg) => {
-            _sha.input(&_serialized_msg);
-            _sha.result(&mut _key);
-            _rng.fill_bytes(&mut _iv);
-            _ret.append(&mut _iv.clone());
-            let mut encrypted_data = encrypt_aes(&_plaintext, &_key, &_iv).ok().unwrap();
-            _ret.append(&mut encrypted_data);
-            return Some(_ret);
-        }
-    }
+    _rng.fill_bytes(&mut _iv);
+    return encrypt_symmetric_with_iv(_msg, _plaintext, _iv);
+}
+
+/// Key Encapsulation Mechanism (Encryption Function) using an iv derived from `_nonce_data`
+/// (see `derive_iv`) instead of a random one. Encrypting the same plaintext with the same
+/// `_msg` and `_nonce_data` produces identical bytes.
+///
+/// This is only safe if `_nonce_data` is unique for every `_msg`, e.g. the ABE components of
+/// a ciphertext, which are unique as long as its secret `s` is never reused.
+pub fn encrypt_symmetric_derived_iv(
+    _msg: &bn::Gt,
+    _plaintext: &Vec<u8>,
+    _nonce_data: &[u8],
+) -> Option<Vec<u8>> {
+    return encrypt_symmetric_with_iv(_msg, _plaintext, derive_iv(_nonce_data).to_vec());
+}
+
+/// Derives a 16 byte iv from the given data (the first 16 bytes of its SHA3-256 hash).
+pub fn derive_iv(_nonce_data: &[u8]) -> [u8; 16] {
+    let mut _hash: [u8; 32] = [0; 32];
+    let mut _iv: [u8; 16] = [0; 16];
+    let mut _sha = Sha3::sha3_256();
+    _sha.input(b"rabe-iv");
+    _sha.input(_nonce_data);
+    _sha.result(&mut _hash);
+    _iv.copy_from_slice(&_hash[..16]);
+    return _iv;
+}
+
+/// Derives the 32 byte symmetric key of all schemes from an element of Gt: the SHA3-256 hash
+/// of its serialization in the pinned encoding of `utils::encoding`, so that the key does not
+/// change with the defaults of bincode. The same element always yields the same key.
+///
+/// # Arguments
+///
+///	* `_gt` - The element of Gt encapsulated by an ABE ciphertext
+pub fn kdf_from_gt(_gt: &bn::Gt) -> [u8; 32] {
+    let mut _key: [u8; 32] = [0; 32];
+    let mut _sha = Sha3::sha3_256();
+    let _serialized_gt = encode(_gt).expect("an element of Gt can always be serialized");
+    _sha.input(&_serialized_gt);
+    _sha.result(&mut _key);
+    return _key;
+}
+
+fn encrypt_symmetric_with_iv(
+    _msg: &bn::Gt,
+    _plaintext: &Vec<u8>,
+    _iv: Vec<u8>,
+) -> Option<Vec<u8>> {
+    let _key = kdf_from_gt(_msg);
+    let mut _ret: Vec<u8> = Vec::new();
+    _ret.append(&mut _iv.clone());
+    let mut encrypted_data = encrypt_aes(&_plaintext, &_key, &_iv).ok()?;
+    _ret.append(&mut encrypted_data);
+    return Some(_ret);
 }
 /// Key Encapsulation Mechanism (Decryption Function)
 pub fn decrypt_symmetric(_msg: &bn::Gt, _iv_ct: &Vec<u8>) -> Option<Vec<u8>> {
-    let mut _key: [u8; 32] = [0; 32];
+    // the iv is prepended to the ciphertext, which holds at least one block: PKCS#7 always
+    // pads, a plaintext of a multiple of the block size (even an empty one) gets a full block
+    if _iv_ct.len() < 2 * AES_BLOCK_SIZE || _iv_ct.len() % AES_BLOCK_SIZE != 0 {
+        return None;
+    }
     let mut _iv = _iv_ct.clone();
     let _data = _iv.split_off(16);
-    let mut _sha = Sha3::sha3_256();
-    let mut _rng = thread_rng();
-    match serialize(&_msg) {
-        Err(_) => return None,
-        Ok(_serialized_msg) => {
-            _sha.input(&_serialized_msg);
-            _sha.result(&mut _key);
-            let decrypted_data = decrypt_aes(&_data, &_key, &_iv).ok().unwrap();
-            return Some(decrypted_data);
-        }
+    let _key = kdf_from_gt(_msg);
+    // fails (e.g. with invalid padding) if the key is wrong
+    return decrypt_aes(&_data, &_key, &_iv).ok();
+}
+
+/// Key Encapsulation Mechanism (Encryption Function) using authenticated AES-256/GCM.
+///
+/// The key is derived from `_msg` as in `encrypt_symmetric`. The payload is the random
+/// 12 byte nonce, followed by the ciphertext and the 16 byte authentication tag.
+pub fn encrypt_symmetric_gcm(_msg: &bn::Gt, _plaintext: &Vec<u8>) -> Option<Vec<u8>> {
+    return encrypt_symmetric_gcm_with_rng(_msg, _plaintext, &mut thread_rng());
+}
+
+/// Same as `encrypt_symmetric_gcm`, but draws the nonce from `_rng`.
+pub fn encrypt_symmetric_gcm_with_rng(
+    _msg: &bn::Gt,
+    _plaintext: &Vec<u8>,
+    _rng: &mut impl RngCore,
+) -> Option<Vec<u8>> {
+    let mut _nonce: Vec<u8> = vec![0; GCM_NONCE_SIZE];
+    _rng.fill_bytes(&mut _nonce);
+    return encrypt_symmetric_gcm_with_nonce(_msg, _plaintext, &_nonce);
+}
+
+/// Key Encapsulation Mechanism (Encryption Function) using authenticated AES-256/GCM and a
+/// nonce derived from `_nonce_data` (the first 12 bytes of `derive_iv`) instead of a random one.
+///
+/// The same restrictions as for `encrypt_symmetric_derived_iv` apply: `_nonce_data` has to be
+/// unique for every `_msg`, reusing a nonce under the same key breaks GCM completely.
+pub fn encrypt_symmetric_gcm_derived_nonce(
+    _msg: &bn::Gt,
+    _plaintext: &Vec<u8>,
+    _nonce_data: &[u8],
+) -> Option<Vec<u8>> {
+    let _nonce = derive_iv(_nonce_data);
+    return encrypt_symmetric_gcm_with_nonce(_msg, _plaintext, &_nonce[..GCM_NONCE_SIZE]);
+}
+
+fn encrypt_symmetric_gcm_with_nonce(
+    _msg: &bn::Gt,
+    _plaintext: &Vec<u8>,
+    _nonce: &[u8],
+) -> Option<Vec<u8>> {
+    let _key = kdf_from_gt(_msg);
+    let mut _cipher = AesGcm::new(aes::KeySize::KeySize256, &_key, _nonce, &[]);
+    let mut _ct: Vec<u8> = vec![0; _plaintext.len()];
+    let mut _tag: [u8; GCM_TAG_SIZE] = [0; GCM_TAG_SIZE];
+    _cipher.encrypt(_plaintext, &mut _ct, &mut _tag);
+    let mut _ret: Vec<u8> = _nonce.to_vec();
+    _ret.append(&mut _ct);
+    _ret.extend_from_slice(&_tag);
+    return Some(_ret);
+}
+
+/// Key Encapsulation Mechanism (Decryption Function) for payloads of `encrypt_symmetric_gcm`.
+///
+/// Returns `None` if the key is wrong or the payload has been modified (tag mismatch).
+pub fn decrypt_symmetric_gcm(_msg: &bn::Gt, _nonce_ct_tag: &Vec<u8>) -> Option<Vec<u8>> {
+    if _nonce_ct_tag.len() < GCM_NONCE_SIZE + GCM_TAG_SIZE {
+        return None;
+    }
+    let _nonce = &_nonce_ct_tag[..GCM_NONCE_SIZE];
+    let _ct = &_nonce_ct_tag[GCM_NONCE_SIZE.._nonce_ct_tag.len() - GCM_TAG_SIZE];
+    let _tag = &_nonce_ct_tag[_nonce_ct_tag.len() - GCM_TAG_SIZE..];
+    let _key = kdf_from_gt(_msg);
+    let mut _cipher = AesGcm::new(aes::KeySize::KeySize256, &_key, _nonce, &[]);
+    let mut _pt: Vec<u8> = vec![0; _ct.len()];
+    if !_cipher.decrypt(_ct, &mut _pt, _tag) {
+        return None;
     }
+    return Some(_pt);
 }
 
 /// Decrypts a buffer with the given key and iv using AES-256/CBC/Pkcs encryption.
@@ -157,3 +272,102 @@ fn decrypt_aes(
 
     Ok(final_result)
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_derived_iv_deterministic() {
+        let _plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        // fixed seed
+        let _msg: bn::Gt = StdRng::seed_from_u64(42).gen();
+        let _msg_again: bn::Gt = StdRng::seed_from_u64(42).gen();
+        let _ct1 = encrypt_symmetric_derived_iv(&_msg, &_plaintext, b"components").unwrap();
+        let _ct2 = encrypt_symmetric_derived_iv(&_msg_again, &_plaintext, b"components").unwrap();
+        assert_eq!(_ct1, _ct2);
+        assert_eq!(_ct1[..16].to_vec(), derive_iv(b"components").to_vec());
+        assert_eq!(decrypt_symmetric(&_msg, &_ct1).unwrap(), _plaintext);
+        // other nonce data, other iv
+        let _ct3 = encrypt_symmetric_derived_iv(&_msg, &_plaintext, b"other").unwrap();
+        assert_eq!(_ct1 == _ct3, false);
+        // random ivs differ
+        assert_eq!(
+            encrypt_symmetric(&_msg, &_plaintext).unwrap() ==
+                encrypt_symmetric(&_msg, &_plaintext).unwrap(),
+            false
+        );
+    }
+
+    #[test]
+    fn test_kdf_from_gt() {
+        let _gt: bn::Gt = StdRng::seed_from_u64(42).gen();
+        let _gt_again: bn::Gt = StdRng::seed_from_u64(42).gen();
+        let _other: bn::Gt = StdRng::seed_from_u64(43).gen();
+        assert_eq!(kdf_from_gt(&_gt), kdf_from_gt(&_gt_again));
+        assert_eq!(kdf_from_gt(&_gt) == kdf_from_gt(&_other), false);
+        assert_eq!(kdf_from_gt(&_gt) == kdf_from_gt(&(_gt * _other)), false);
+        // the key of the symmetric layer
+        let _plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let _ct = encrypt_symmetric(&_gt, &_plaintext).unwrap();
+        assert_eq!(
+            decrypt_aes(&_ct[16..], &kdf_from_gt(&_gt_again), &_ct[..16]).unwrap(),
+            _plaintext
+        );
+    }
+
+    #[test]
+    fn test_gcm() {
+        let _plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+            .into_bytes();
+        let _msg: bn::Gt = StdRng::seed_from_u64(42).gen();
+        let _other: bn::Gt = StdRng::seed_from_u64(43).gen();
+        let _ct = encrypt_symmetric_gcm(&_msg, &_plaintext).unwrap();
+        assert_eq!(_ct.len(), GCM_NONCE_SIZE + _plaintext.len() + GCM_TAG_SIZE);
+        assert_eq!(decrypt_symmetric_gcm(&_msg, &_ct).unwrap(), _plaintext);
+        // wrong key
+        assert_eq!(decrypt_symmetric_gcm(&_other, &_ct), None);
+        // every modified byte (nonce, ciphertext or tag) is detected
+        for _i in 0.._ct.len() {
+            let mut _tampered = _ct.clone();
+            _tampered[_i] ^= 0x01;
+            assert_eq!(decrypt_symmetric_gcm(&_msg, &_tampered), None);
+        }
+        assert_eq!(decrypt_symmetric_gcm(&_msg, &_ct[..GCM_TAG_SIZE].to_vec()), None);
+        // derived nonces are deterministic
+        let _ct1 = encrypt_symmetric_gcm_derived_nonce(&_msg, &_plaintext, b"components").unwrap();
+        let _ct2 = encrypt_symmetric_gcm_derived_nonce(&_msg, &_plaintext, b"components").unwrap();
+        assert_eq!(_ct1, _ct2);
+        assert_eq!(
+            _ct1[..GCM_NONCE_SIZE].to_vec(),
+            derive_iv(b"components")[..GCM_NONCE_SIZE].to_vec()
+        );
+        assert_eq!(decrypt_symmetric_gcm(&_msg, &_ct1).unwrap(), _plaintext);
+    }
+
+    #[test]
+    fn test_block_boundaries() {
+        let _msg: bn::Gt = StdRng::seed_from_u64(42).gen();
+        // empty, single byte, around the block size and around the 4096 byte buffer of encrypt_aes
+        for _len in vec![0, 1, 15, 16, 17, 31, 32, 33, 4095, 4096, 4097, 8192] {
+            let _plaintext: Vec<u8> = (0.._len).map(|_i| (_i % 256) as u8).collect();
+            let _ct = encrypt_symmetric(&_msg, &_plaintext).unwrap();
+            // iv and the padded plaintext, a full block of padding for multiples of 16
+            assert_eq!(_ct.len(), AES_BLOCK_SIZE + (_len / AES_BLOCK_SIZE + 1) * AES_BLOCK_SIZE);
+            assert_eq!(decrypt_symmetric(&_msg, &_ct).unwrap(), _plaintext);
+            let _ct = encrypt_symmetric_gcm(&_msg, &_plaintext).unwrap();
+            assert_eq!(_ct.len(), GCM_NONCE_SIZE + _len + GCM_TAG_SIZE);
+            assert_eq!(decrypt_symmetric_gcm(&_msg, &_ct).unwrap(), _plaintext);
+        }
+        // truncated ciphertexts are rejected instead of being decrypted partially
+        let _ct = encrypt_symmetric(&_msg, &vec![7u8; 32]).unwrap();
+        for _len in vec![0, 15, 16, 17, 31, 47, 63] {
+            assert_eq!(decrypt_symmetric(&_msg, &_ct[.._len].to_vec()), None);
+        }
+    }
+}
@@ -0,0 +1,378 @@
+//! This is the documentation for the R-ABE encoding utils
+//!
+//! All keys and ciphertexts implement `RabeEncode` and can be converted to and
+//! from a stable binary format. Single group elements and scalars can be encoded
+//! using `g1_to_bytes`, `g2_to_bytes`, `gt_to_bytes` and `fr_to_bytes`, e.g. for
+//! custom protocols built on the schemes.
+//!
+//! Two formats are offered, both built on the serde derives of the types:
+//!
+//! * bincode (`to_bincode`, `to_bytes`, `to_base64`) - compact, every group element takes its
+//!   fixed size, but not human-readable and only understood by serde/bincode implementations
+//! * JSON (`to_json`) - human-readable and easy to inspect or process in other languages, but
+//!   several times larger, since every byte of a group element becomes a number in an array
+//!
+//! Adding a field changes the binary layout, bincode ignores `#[serde(default)]`. Types whose
+//! layout changed therefore carry a format version in `to_bytes` and keep decoding the earlier
+//! layouts, e.g. the BSW ciphertext since `_compressed` and `_compact_policy` were added.
+//!
+use base64::read::DecoderReader;
+use bincode::{DefaultOptions, Options};
+use bn::{Fr, G1, G2, Gt, Group};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::Read;
+use utils::error::RabeError;
+
+// The bincode configuration used for all binary encodings. It is pinned
+// explicitly (fixed-width integers, little-endian, no trailing bytes) so that
+// the format does not change with the defaults of the bincode crate.
+fn bincode_options() -> impl Options {
+    return DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian();
+}
+
+// encodes a single value (e.g. a group element) using the pinned configuration
+pub(crate) fn encode<T: ?Sized + Serialize>(_value: &T) -> Result<Vec<u8>, RabeError> {
+    match bincode_options().serialize(_value) {
+        Err(_e) => return Err(RabeError::Serialization(_e.to_string())),
+        Ok(_bytes) => return Ok(_bytes),
+    }
+}
+
+// decodes a single value using the pinned configuration, rejecting trailing bytes
+pub(crate) fn decode<T: DeserializeOwned>(_bytes: &[u8]) -> Result<T, RabeError> {
+    match bincode_options().deserialize::<T>(_bytes) {
+        Err(_e) => return Err(RabeError::Serialization(_e.to_string())),
+        Ok(_value) => return Ok(_value),
+    }
+}
+
+// decodes a single value from a reader using the pinned configuration, rejecting trailing data
+pub(crate) fn decode_reader<T: DeserializeOwned, R: Read>(mut _reader: R) -> Result<T, RabeError> {
+    let _value: T = match bincode_options().deserialize_from(&mut _reader) {
+        Err(_e) => return Err(RabeError::Serialization(_e.to_string())),
+        Ok(_value) => _value,
+    };
+    let mut _rest = [0u8; 1];
+    match _reader.read(&mut _rest) {
+        Ok(0) => return Ok(_value),
+        Ok(_) => return Err(RabeError::Serialization(String::from("trailing bytes"))),
+        Err(_e) => return Err(RabeError::Serialization(_e.to_string())),
+    }
+}
+
+// r - 1, where r is the order of G1, G2 and Gt
+fn order_minus_one() -> Fr {
+    return Fr::zero() - Fr::one();
+}
+
+/// Checks that an element of G1 lies in the subgroup of prime order r.
+pub fn check_g1(_g: &G1) -> Result<(), RabeError> {
+    // (r - 1) * g + g = r * g is zero exactly for elements of the subgroup
+    if ((*_g * order_minus_one()) + *_g).is_zero() {
+        return Ok(());
+    }
+    return Err(RabeError::InvalidGroupElement(String::from("G1")));
+}
+
+/// Checks that an element of G2 lies in the subgroup of prime order r.
+pub fn check_g2(_g: &G2) -> Result<(), RabeError> {
+    if ((*_g * order_minus_one()) + *_g).is_zero() {
+        return Ok(());
+    }
+    return Err(RabeError::InvalidGroupElement(String::from("G2")));
+}
+
+/// Checks that an element of Gt lies in the subgroup of prime order r.
+pub fn check_gt(_g: &Gt) -> Result<(), RabeError> {
+    // g^(r - 1) * g = g^r is one exactly for elements of the subgroup
+    if _g.pow(order_minus_one()) * *_g == Gt::one() {
+        return Ok(());
+    }
+    return Err(RabeError::InvalidGroupElement(String::from("Gt")));
+}
+
+// the length of the encoding of every value of type T, taken from the encoding of _sample
+fn encoded_len<T: Serialize>(_sample: &T) -> Result<usize, RabeError> {
+    return Ok(encode(_sample)?.len());
+}
+
+// decodes a single value of the fixed length _len using the pinned configuration
+fn decode_fixed<T: DeserializeOwned>(_bytes: &[u8], _len: usize) -> Result<T, RabeError> {
+    if _bytes.len() != _len {
+        return Err(RabeError::Serialization(
+            format!("expected {} bytes, got {}", _len, _bytes.len()),
+        ));
+    }
+    match bincode_options().deserialize::<T>(_bytes) {
+        Err(_e) => return Err(RabeError::Serialization(_e.to_string())),
+        Ok(_value) => return Ok(_value),
+    }
+}
+
+/// Encodes an element of G1 in the fixed-length format used by `RabeEncode`.
+pub fn g1_to_bytes(_g: &G1) -> Result<Vec<u8>, RabeError> {
+    return encode(_g);
+}
+
+/// Decodes an element of G1 previously encoded by `g1_to_bytes`. Fails if the length does not
+/// match or the element does not lie in the subgroup of prime order (see `check_g1`).
+///
+/// # Arguments
+///
+///	* `_bytes` - the encoded element
+///
+pub fn g1_from_bytes(_bytes: &[u8]) -> Result<G1, RabeError> {
+    let _g: G1 = decode_fixed(_bytes, encoded_len(&G1::one())?)?;
+    check_g1(&_g)?;
+    return Ok(_g);
+}
+
+/// Encodes an element of G2 in the fixed-length format used by `RabeEncode`.
+pub fn g2_to_bytes(_g: &G2) -> Result<Vec<u8>, RabeError> {
+    return encode(_g);
+}
+
+/// Decodes an element of G2 previously encoded by `g2_to_bytes`. Fails if the length does not
+/// match or the element does not lie in the subgroup of prime order (see `check_g2`).
+///
+/// # Arguments
+///
+///	* `_bytes` - the encoded element
+///
+pub fn g2_from_bytes(_bytes: &[u8]) -> Result<G2, RabeError> {
+    let _g: G2 = decode_fixed(_bytes, encoded_len(&G2::one())?)?;
+    check_g2(&_g)?;
+    return Ok(_g);
+}
+
+/// Encodes an element of Gt in the fixed-length format used by `RabeEncode`.
+pub fn gt_to_bytes(_g: &Gt) -> Result<Vec<u8>, RabeError> {
+    return encode(_g);
+}
+
+/// Decodes an element of Gt previously encoded by `gt_to_bytes`. Fails if the length does not
+/// match or the element does not lie in the subgroup of prime order (see `check_gt`).
+///
+/// # Arguments
+///
+///	* `_bytes` - the encoded element
+///
+pub fn gt_from_bytes(_bytes: &[u8]) -> Result<Gt, RabeError> {
+    let _g: Gt = decode_fixed(_bytes, encoded_len(&Gt::one())?)?;
+    check_gt(&_g)?;
+    return Ok(_g);
+}
+
+/// Encodes a scalar of Fr in the fixed-length format used by `RabeEncode`.
+pub fn fr_to_bytes(_x: &Fr) -> Result<Vec<u8>, RabeError> {
+    return encode(_x);
+}
+
+/// Decodes a scalar of Fr previously encoded by `fr_to_bytes`. Fails if the length does not
+/// match or the value is not reduced modulo the group order.
+///
+/// # Arguments
+///
+///	* `_bytes` - the encoded scalar
+///
+pub fn fr_from_bytes(_bytes: &[u8]) -> Result<Fr, RabeError> {
+    return decode_fixed(_bytes, encoded_len(&Fr::one())?);
+}
+
+/// Binary encoding of RABE keys and ciphertexts.
+pub trait RabeEncode: Serialize + DeserializeOwned {
+    /// Encodes a key or ciphertext as a Vector of bytes.
+    fn to_bytes(&self) -> Result<Vec<u8>, RabeError> {
+        return encode(self);
+    }
+
+    /// Checks a decoded key or ciphertext, e.g. that all group elements lie in the subgroup of
+    /// prime order (see `check_g1`, `check_g2` and `check_gt`). Called by all decoding functions.
+    fn validate(&self) -> Result<(), RabeError> {
+        return Ok(());
+    }
+
+    /// Decodes a key or ciphertext previously encoded by `to_bytes`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_bytes` - the encoded key or ciphertext
+    ///
+    fn from_bytes(_bytes: &[u8]) -> Result<Self, RabeError> {
+        let _value: Self = decode(_bytes)?;
+        _value.validate()?;
+        return Ok(_value);
+    }
+
+    /// Encodes a key or ciphertext in the compact binary format, the same as `to_bytes`.
+    fn to_bincode(&self) -> Result<Vec<u8>, RabeError> {
+        return self.to_bytes();
+    }
+
+    /// Decodes a key or ciphertext previously encoded by `to_bincode`, the same as `from_bytes`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_bytes` - the encoded key or ciphertext
+    ///
+    fn from_bincode(_bytes: &[u8]) -> Result<Self, RabeError> {
+        return Self::from_bytes(_bytes);
+    }
+
+    /// Encodes a key or ciphertext as human-readable JSON String.
+    fn to_json(&self) -> Result<String, RabeError> {
+        match serde_json::to_string(self) {
+            Err(_e) => return Err(RabeError::Serialization(_e.to_string())),
+            Ok(_json) => return Ok(_json),
+        }
+    }
+
+    /// Decodes a key or ciphertext previously encoded by `to_json`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_json` - the JSON encoded key or ciphertext
+    ///
+    fn from_json(_json: &str) -> Result<Self, RabeError> {
+        match serde_json::from_str::<Self>(_json) {
+            Err(_e) => return Err(RabeError::Serialization(_e.to_string())),
+            Ok(_value) => {
+                _value.validate()?;
+                return Ok(_value);
+            }
+        }
+    }
+
+    /// Encodes a key or ciphertext as base64 String (of the bytes returned by `to_bytes`).
+    fn to_base64(&self) -> Result<String, RabeError> {
+        return Ok(base64::encode(&self.to_bytes()?));
+    }
+
+    /// Decodes a key or ciphertext previously encoded by `to_base64`.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_base64` - the base64 encoded key or ciphertext
+    ///
+    fn from_base64(_base64: &str) -> Result<Self, RabeError> {
+        match base64::decode(_base64) {
+            Err(_e) => return Err(RabeError::Serialization(_e.to_string())),
+            Ok(_bytes) => return Self::from_bytes(&_bytes),
+        }
+    }
+
+    /// Decodes a key or ciphertext previously encoded by `to_base64` from a reader. The base64
+    /// input is decoded while deserializing, so neither the base64 nor the binary encoding is
+    /// held in memory as a whole. The input must not contain line breaks or other whitespace.
+    ///
+    /// # Arguments
+    ///
+    ///	* `_reader` - a reader of the base64 encoded key or ciphertext
+    ///
+    fn from_base64_reader<R: Read>(mut _reader: R) -> Result<Self, RabeError> {
+        // like from_bytes, trailing data is rejected
+        let _value: Self = decode_reader(DecoderReader::new(&mut _reader, base64::STANDARD))?;
+        _value.validate()?;
+        return Ok(_value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use bn::pairing;
+    use rand::Rng;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Golden {
+        _n: u32,
+        _s: String,
+    }
+
+    impl RabeEncode for Golden {}
+
+    // u32 42, followed by the string "hi" with a u64 length prefix
+    const GOLDEN: [u8; 14] = [42, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 104, 105];
+
+    #[test]
+    fn test_pinned_config() {
+        let _golden = Golden {
+            _n: 42,
+            _s: String::from("hi"),
+        };
+        assert_eq!(Golden::from_bytes(&GOLDEN).unwrap(), _golden);
+        assert_eq!(_golden.to_bytes().unwrap(), GOLDEN.to_vec());
+    }
+
+    #[test]
+    fn test_base64() {
+        let _golden = Golden {
+            _n: 42,
+            _s: String::from("hi"),
+        };
+        let _base64 = _golden.to_base64().unwrap();
+        assert_eq!(_base64, base64::encode(&GOLDEN));
+        assert_eq!(Golden::from_base64(&_base64).unwrap(), _golden);
+        assert_eq!(Golden::from_base64_reader(_base64.as_bytes()).unwrap(), _golden);
+        // trailing data and invalid base64
+        let _trailing = base64::encode(&[&GOLDEN[..], &[0u8][..]].concat());
+        assert_eq!(Golden::from_base64_reader(_trailing.as_bytes()).is_err(), true);
+        assert_eq!(Golden::from_base64("joking-around?").is_err(), true);
+        assert_eq!(Golden::from_base64_reader("joking-around?".as_bytes()).is_err(), true);
+    }
+
+    #[test]
+    fn test_json() {
+        let _golden = Golden {
+            _n: 42,
+            _s: String::from("hi"),
+        };
+        let _json = _golden.to_json().unwrap();
+        assert_eq!(_json, r#"{"_n":42,"_s":"hi"}"#);
+        assert_eq!(Golden::from_json(&_json).unwrap(), _golden);
+        assert_eq!(Golden::from_bincode(&_golden.to_bincode().unwrap()).unwrap(), _golden);
+        assert_eq!(Golden::from_json("joking-around?").is_err(), true);
+        assert_eq!(Golden::from_json(r#"{"_n":42}"#).is_err(), true);
+    }
+
+    #[test]
+    fn test_group_elements() {
+        let mut _rng = rand::thread_rng();
+        let _g1: G1 = _rng.gen();
+        let _g2: G2 = _rng.gen();
+        let _gt: Gt = pairing(_g1, _g2);
+        let _fr: Fr = _rng.gen();
+        assert_eq!(g1_from_bytes(&g1_to_bytes(&_g1).unwrap()).unwrap() == _g1, true);
+        assert_eq!(g2_from_bytes(&g2_to_bytes(&_g2).unwrap()).unwrap() == _g2, true);
+        assert_eq!(gt_from_bytes(&gt_to_bytes(&_gt).unwrap()).unwrap() == _gt, true);
+        assert_eq!(fr_from_bytes(&fr_to_bytes(&_fr).unwrap()).unwrap() == _fr, true);
+        // all elements of a group have the same length
+        assert_eq!(g1_to_bytes(&_g1).unwrap().len(), g1_to_bytes(&G1::one()).unwrap().len());
+        assert_eq!(gt_to_bytes(&_gt).unwrap().len(), gt_to_bytes(&Gt::one()).unwrap().len());
+        assert_eq!(fr_to_bytes(&_fr).unwrap().len(), fr_to_bytes(&Fr::one()).unwrap().len());
+        // wrong lengths
+        let _bytes = g1_to_bytes(&_g1).unwrap();
+        assert_eq!(g1_from_bytes(&_bytes[.._bytes.len() - 1]).is_err(), true);
+        assert_eq!(g1_from_bytes(&[&_bytes[..], &[0u8][..]].concat()).is_err(), true);
+        assert_eq!(g2_from_bytes(&_bytes).is_err(), true);
+        assert_eq!(gt_from_bytes(&[]).is_err(), true);
+        assert_eq!(fr_from_bytes(&_bytes).is_err(), true);
+        // bytes of the right length that are no valid element
+        let _len = fr_to_bytes(&_fr).unwrap().len();
+        assert_eq!(fr_from_bytes(&vec![0xffu8; _len]).is_err(), true);
+        let _len = g1_to_bytes(&_g1).unwrap().len();
+        assert_eq!(g1_from_bytes(&vec![0xffu8; _len]).is_err(), true);
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        let mut _bytes = GOLDEN.to_vec();
+        _bytes.push(0);
+        assert_eq!(Golden::from_bytes(&_bytes).is_err(), true);
+        assert_eq!(Golden::from_bytes(&GOLDEN[..13]).is_err(), true);
+    }
+}
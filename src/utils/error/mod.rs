@@ -0,0 +1,111 @@
+//! This is the documentation for the R-ABE error type
+//!
+//! All fallible library functions report failures using `RabeError`.
+//!
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// The error type of the RABE library
+#[derive(Debug, Clone, PartialEq)]
+pub enum RabeError {
+    /// An underlying I/O operation failed
+    Io(String),
+    /// No attributes were given
+    EmptyAttributes,
+    /// No policy was given
+    EmptyPolicy,
+    /// The policy could not be parsed
+    PolicyParse(String),
+    /// The policy is not a valid policy tree
+    InvalidPolicy(String),
+    /// The policy is valid JSON, but none of AND, OR or ATT was found (holds the keys found instead)
+    NotAPolicy(Vec<String>),
+    /// The policy is nested deeper than allowed, see `MAX_POLICY_DEPTH` and `PolicyLimits`
+    PolicyTooDeep,
+    /// The policy has more nodes than allowed, see `PolicyLimits`
+    PolicyTooLarge,
+    /// A gate has more children than allowed, see `MAX_GATE_WIDTH` (holds the number of children)
+    GateTooWide(usize),
+    /// Lagrange interpolation is not possible for the given points
+    Interpolation(String),
+    /// The attribute is not part of the given secret key
+    AttributeNotInKey(String),
+    /// The attributes do not satisfy the given policy
+    InsufficientAttributes,
+    /// The authority does not handle the attribute or the user is not eligible to receive it
+    UnauthorizedAttribute(String),
+    /// An attribute can not be used, e.g. a numeric attribute with more than one value
+    InvalidAttribute(String),
+    /// A key or ciphertext could not be encoded or decoded
+    Serialization(String),
+    /// A group element is not part of the subgroup of prime order (holds the group)
+    InvalidGroupElement(String),
+    /// The plaintext could not be encrypted
+    Encryption(String),
+    /// An ephemeral secret was used for more than one ciphertext
+    EphemeralReuse,
+    /// Key and ciphertext do not fit together, e.g. a component required by the policy is missing
+    DecryptionFailed(String),
+    /// The key satisfies the policy, but the symmetric payload could not be decrypted
+    SymmetricDecryptionFailed,
+}
+
+impl fmt::Display for RabeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RabeError::Io(ref _details) => write!(f, "I/O failed: {}", _details),
+            RabeError::EmptyAttributes => write!(f, "no attributes given"),
+            RabeError::EmptyPolicy => write!(f, "no policy given"),
+            RabeError::PolicyParse(ref _policy) => write!(f, "could not parse policy: {}", _policy),
+            RabeError::InvalidPolicy(ref _details) => write!(f, "invalid policy: {}", _details),
+            RabeError::NotAPolicy(ref _keys) => {
+                write!(
+                    f,
+                    "not a policy: expected AND, OR or ATT but found [{}]",
+                    _keys.join(", ")
+                )
+            }
+            RabeError::PolicyTooDeep => write!(f, "the policy is nested too deeply"),
+            RabeError::PolicyTooLarge => write!(f, "the policy has too many nodes"),
+            RabeError::GateTooWide(_n) => write!(f, "a gate with {} children is too wide", _n),
+            RabeError::Interpolation(ref _details) => {
+                write!(f, "interpolation failed: {}", _details)
+            }
+            RabeError::AttributeNotInKey(ref _attr) => {
+                write!(f, "attribute {} is not part of the secret key", _attr)
+            }
+            RabeError::InsufficientAttributes => {
+                write!(f, "the attributes do not satisfy the policy")
+            }
+            RabeError::UnauthorizedAttribute(ref _attr) => {
+                write!(f, "the authority may not issue attribute {}", _attr)
+            }
+            RabeError::InvalidAttribute(ref _details) => {
+                write!(f, "invalid attribute: {}", _details)
+            }
+            RabeError::Serialization(ref _details) => {
+                write!(f, "serialization failed: {}", _details)
+            }
+            RabeError::InvalidGroupElement(ref _group) => {
+                write!(f, "invalid group element: not in the prime order subgroup of {}", _group)
+            }
+            RabeError::Encryption(ref _details) => write!(f, "encryption failed: {}", _details),
+            RabeError::EphemeralReuse => write!(f, "the ephemeral secret has already been used"),
+            RabeError::DecryptionFailed(ref _details) => {
+                write!(f, "decryption failed: {}", _details)
+            }
+            RabeError::SymmetricDecryptionFailed => {
+                write!(f, "the symmetric payload could not be decrypted")
+            }
+        }
+    }
+}
+
+impl Error for RabeError {}
+
+impl From<io::Error> for RabeError {
+    fn from(_error: io::Error) -> RabeError {
+        RabeError::Io(_error.to_string())
+    }
+}
@@ -1,25 +1,19 @@
 use std::path::Path;
 use std::fs::File;
 use std::io::{Read, Write};
+use utils::error::RabeError;
+use utils::tools::{contains, normalize_attribute};
 
-pub fn read_file(_path: &Path) -> String {
-    let display = _path.display();
-    let mut file = match File::open(_path) {
-        Err(why) => {
-            panic!(
-                "sorry, couldn't open {}: {}",
-                _path.display(),
-                why.to_string()
-            )
-        }
-        Ok(file) => file,
-    };
-    let mut s = String::new();
-    match file.read_to_string(&mut s) {
-        Err(why) => panic!("sorry, couldn't read {}: {}", display, why.to_string()),
-        Ok(_) => print!("successfully read {}", display),
-    }
-    return s;
+/// Reads the content of a file into a String.
+///
+/// # Arguments
+///
+///	* `_path` - path of the file
+///
+pub fn read_file(_path: &Path) -> Result<String, RabeError> {
+    let mut _content = String::new();
+    File::open(_path)?.read_to_string(&mut _content)?;
+    return Ok(_content);
 }
 
 pub fn read_to_vec(_path: &Path) -> Vec<u8> {
@@ -30,14 +24,8 @@ pub fn read_to_vec(_path: &Path) -> Vec<u8> {
         Ok(file) => file,
     };
     match file.read_to_end(&mut data) {
-        Ok(bytes) => {
-            println!("parsed {:?} bytes", bytes);
-            return data;
-        }
-        Err(e) => {
-            println!("error parsing: {:?}", e);
-            return Vec::new();
-        }
+        Ok(_) => return data,
+        Err(_) => return Vec::new(),
     }
 
 }
@@ -56,7 +44,7 @@ pub fn write_from_vec(_path: &Path, _data: &Vec<u8>) {
                 why.to_string()
             )
         }
-        Ok(_) => println!("successfully wrote to {}", display),
+        Ok(_) => {}
     }
 }
 
@@ -80,8 +68,101 @@ pub fn write_file(_path: &Path, _content: String) -> bool {
         }
         Ok(_) => {
             _ret = true;
-            println!("successfully wrote to {}", display);
         }
     }
     return _ret;
 }
+
+/// Reads a list of attributes from a file containing one attribute per line.
+///
+/// Blank lines and lines starting with `#` are ignored. All other lines are
+/// normalized using `normalize_attribute` and duplicates are dropped, keeping
+/// the order of their first occurrence.
+///
+/// # Arguments
+///
+///	* `_path` - path of the attribute file
+///
+pub fn load_attributes_file(_path: &Path) -> Result<Vec<String>, RabeError> {
+    let mut _content = String::new();
+    File::open(_path)?.read_to_string(&mut _content)?;
+    let mut _attributes: Vec<String> = Vec::new();
+    for _line in _content.lines() {
+        let _attr = normalize_attribute(_line);
+        // skip blank lines and comments
+        if _attr.is_empty() || _attr.starts_with('#') {
+            continue;
+        }
+        if !contains(&_attributes, &_attr) {
+            _attributes.push(_attr);
+        }
+    }
+    if _attributes.is_empty() {
+        return Err(RabeError::EmptyAttributes);
+    }
+    return Ok(_attributes);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+
+    fn temp_file(_name: &str, _content: &str) -> PathBuf {
+        let mut _path = env::temp_dir();
+        _path.push(format!("rabe_{}_{}", _name, ::std::process::id()));
+        File::create(&_path)
+            .unwrap()
+            .write_all(_content.as_bytes())
+            .unwrap();
+        return _path;
+    }
+
+    #[test]
+    fn test_read_file() {
+        let _path = temp_file("read", "A\nB\n");
+        assert_eq!(read_file(&_path).unwrap(), String::from("A\nB\n"));
+        let _ = ::std::fs::remove_file(&_path);
+        assert_eq!(read_file(&_path).is_err(), true);
+    }
+
+    #[test]
+    fn test_load_attributes_comments() {
+        let _path = temp_file("comments", "# issued by HR\nA\n\n# B is revoked\nC\n");
+        let _attributes = load_attributes_file(&_path).unwrap();
+        assert_eq!(_attributes, vec![String::from("A"), String::from("C")]);
+        let _ = ::std::fs::remove_file(&_path);
+    }
+
+    #[test]
+    fn test_load_attributes_whitespace() {
+        let _path = temp_file("whitespace", "  A \n\t B\t\n   \n  # comment\r\nC\r\n");
+        let _attributes = load_attributes_file(&_path).unwrap();
+        assert_eq!(
+            _attributes,
+            vec![String::from("A"), String::from("B"), String::from("C")]
+        );
+        let _ = ::std::fs::remove_file(&_path);
+    }
+
+    #[test]
+    fn test_load_attributes_duplicates() {
+        let _path = temp_file("duplicates", "A\nB\n A\nB \nA\n");
+        let _attributes = load_attributes_file(&_path).unwrap();
+        assert_eq!(_attributes, vec![String::from("A"), String::from("B")]);
+        let _ = ::std::fs::remove_file(&_path);
+    }
+
+    #[test]
+    fn test_load_attributes_errors() {
+        let _path = temp_file("empty", "# nothing here\n\n");
+        assert_eq!(load_attributes_file(&_path), Err(RabeError::EmptyAttributes));
+        let _ = ::std::fs::remove_file(&_path);
+        match load_attributes_file(Path::new("/nonexistent/rabe/attributes")) {
+            Err(RabeError::Io(_)) => {}
+            _ => panic!("expected an I/O error"),
+        }
+    }
+}
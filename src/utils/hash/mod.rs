@@ -1,20 +1,359 @@
 use blake2_rfc::blake2b::blake2b;
 use bn::*;
+use std::ops::Mul;
+use utils::encoding::encode;
 
 /// hash a String to an element of G1 using blake2b and generator g
+///
+/// Note: the discrete logarithm of the hash is known to everyone, see `blake2b_hash_g2`. Use
+/// `hash_to_g1` where it has to be unknown.
 pub fn blake2b_hash_g1(g: bn::G1, data: &String) -> bn::G1 {
     let hash = blake2b(64,&[] , data.as_bytes());
     return g * Fr::interpret(array_ref![hash.as_ref(), 0, 64]);
 }
 
 /// hash a String to an element of G2 using blake2b and generator g
+///
+/// Note: the discrete logarithm of the hash to the base g is the blake2b hash of the String and
+/// thus known to everyone. Schemes relying on hashes with unknown discrete logarithm (e.g. large
+/// universe constructions) have to use `hash_to_g2`.
 pub fn blake2b_hash_g2(g: bn::G2, data: &String) -> bn::G2 {
     let hash = blake2b(64, &[], data.as_bytes());
     return g * Fr::interpret(array_ref![hash.as_ref(), 0, 64]);
 }
 
-/// hash a String to Fr using blake2b
+/// The domain separation tag of `hash_to_g2`. Part of every hash, so changing it changes the
+/// hash of every attribute and invalidates all keys and ciphertexts built on it.
+pub const HASH_TO_G2_DST: &'static str = "rabe-hash-to-g2-v1";
+
+/// The domain separation tag of `hash_to_g1`, see `HASH_TO_G2_DST`.
+pub const HASH_TO_G1_DST: &'static str = "rabe-hash-to-g1-v1";
+
+/// The domain separation tag of `hash_to_fr`, see `HASH_TO_G2_DST`.
+pub const HASH_TO_FR_DST: &'static str = "rabe-hash-to-fr-v1";
+
+// (p + 1) / 4 for the modulus p of Fq, p = 3 mod 4, big endian
+const FQ_SQRT_EXPONENT: [u8; 32] = [
+    0x0c, 0x19, 0x13, 0x9c, 0xb8, 0x4c, 0x68, 0x0a, 0x6e, 0x14, 0x11, 0x6d, 0xa0, 0x60, 0x56, 0x17,
+    0x65, 0xe0, 0x5a, 0xa4, 0x5a, 0x1c, 0x72, 0xa3, 0x4f, 0x08, 0x23, 0x05, 0xb6, 0x1f, 0x3f, 0x52,
+];
+
+// p - 2 for the modulus p of Fq, big endian
+const FQ_INVERSE_EXPONENT: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x45,
+];
+
+// the cofactor 2p - r of G2 in the group of points of the twist, big endian
+const G2_COFACTOR: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5e,
+    0x06, 0xce, 0xec, 0xda, 0x57, 0x2a, 0x24, 0x89, 0x34, 0x5f, 0x22, 0x99, 0xc0, 0xf9, 0xfa, 0x8d,
+];
+
+// the coefficients b = 3 of the curve of G1 and b' = 3 / (9 + i) of the twist of G2
+const G1_CURVE_B: &'static str = "3";
+const G2_CURVE_B: [&'static str; 2] = [
+    "19485874751759354771024239261021720505790618469301721065564631296452457478373",
+    "266929791119991161246907387137283842545076965332900288569378510910307636690",
+];
+
+// the 64 byte blake2b hash of dst, the length of domain as 8 byte big endian integer, domain
+// and label, interpreted as an element of Fr
+fn domain_hash(dst: &str, label: &str, domain: &str) -> Fr {
+    let mut _data: Vec<u8> = dst.as_bytes().to_vec();
+    _data.extend_from_slice(&(domain.len() as u64).to_be_bytes());
+    _data.extend_from_slice(domain.as_bytes());
+    _data.extend_from_slice(label.as_bytes());
+    let hash = blake2b(64, &[], &_data);
+    return Fr::interpret(array_ref![hash.as_ref(), 0, 64]);
+}
+
+// the 64 byte blake2b hash of dst, the length of domain as 8 byte big endian integer, domain,
+// label, the counter as 4 byte big endian integer and the index of the coordinate, interpreted
+// as an element of Fq
+fn domain_hash_fq(dst: &str, label: &str, domain: &str, counter: u32, index: u8) -> Fq {
+    let mut _data: Vec<u8> = dst.as_bytes().to_vec();
+    _data.extend_from_slice(&(domain.len() as u64).to_be_bytes());
+    _data.extend_from_slice(domain.as_bytes());
+    _data.extend_from_slice(label.as_bytes());
+    _data.extend_from_slice(&counter.to_be_bytes());
+    _data.push(index);
+    let hash = blake2b(64, &[], &_data);
+    return Fq::interpret(array_ref![hash.as_ref(), 0, 64]);
+}
+
+// x^e by square and multiply, e given as big endian bytes
+fn pow_bytes<T: Copy + Mul<Output = T>>(x: T, one: T, e: &[u8]) -> T {
+    let mut _result = one;
+    for _byte in e {
+        for _bit in (0..8).rev() {
+            _result = _result * _result;
+            if (_byte >> _bit) & 1 == 1 {
+                _result = _result * x;
+            }
+        }
+    }
+    return _result;
+}
+
+// the square root a^((p + 1) / 4) of a in Fq, if a is a square
+fn fq_sqrt(a: Fq) -> Option<Fq> {
+    let _root = pow_bytes(a, Fq::one(), &FQ_SQRT_EXPONENT);
+    if _root * _root == a {
+        return Some(_root);
+    }
+    return None;
+}
+
+fn fq_inverse(a: Fq) -> Fq {
+    return pow_bytes(a, Fq::one(), &FQ_INVERSE_EXPONENT);
+}
+
+// a square root x0 + x1 * i of a = a0 + a1 * i in Fq2 = Fq[i] / (i^2 + 1), if a is a square:
+// x0^2 = (a0 +- sqrt(a0^2 + a1^2)) / 2 and x1 = a1 / (2 * x0). Returns None in the rare case of
+// x0 = 0 as well, hash_to_g2 then tries the next candidate.
+fn fq2_sqrt(a: Fq2) -> Option<Fq2> {
+    let (_a0, _a1) = (a.real(), a.imaginary());
+    let _norm = fq_sqrt((_a0 * _a0) + (_a1 * _a1))?;
+    let _half = fq_inverse(Fq::one() + Fq::one());
+    let _x0 = match fq_sqrt((_a0 + _norm) * _half) {
+        Some(_x0) => _x0,
+        None => fq_sqrt((_a0 - _norm) * _half)?,
+    };
+    if _x0.is_zero() {
+        return None;
+    }
+    let _root = Fq2::new(_x0, _a1 * fq_inverse(_x0 + _x0));
+    if _root * _root == a {
+        return Some(_root);
+    }
+    return None;
+}
+
+// the multiple c * p of a point p of the twist, c given as big endian bytes. Unlike the
+// multiplication with an element of Fr, c is not reduced modulo the order r.
+fn g2_mul_bytes(p: G2, c: &[u8]) -> G2 {
+    let mut _result = G2::zero();
+    for _byte in c {
+        for _bit in (0..8).rev() {
+            _result = _result + _result;
+            if (_byte >> _bit) & 1 == 1 {
+                _result = _result + p;
+            }
+        }
+    }
+    return _result;
+}
+
+/// hash a label (e.g. an attribute) to an element of G1, separated by a domain (e.g. a scheme).
+/// The discrete logarithm of the hash is unknown, i.e. the hash can be used where a random
+/// oracle into G1 is required, e.g. in large universe constructions.
+///
+/// The hash is pinned across versions and maps to the curve y^2 = x^3 + 3 by try and
+/// increment: for the counters 0, 1, 2, ... the 64 byte blake2b hash of `HASH_TO_G1_DST`, the
+/// length of `domain` as 8 byte big endian integer, `domain`, `label`, the counter as 4 byte big
+/// endian integer and the byte 0 is interpreted as an element x of Fq. The first x, for which
+/// x^3 + 3 is a square, gives the point (x, (x^3 + 3)^((p + 1) / 4)). The cofactor of G1 is 1.
+pub fn hash_to_g1(label: &str, domain: &str) -> bn::G1 {
+    let _b = Fq::from_str(G1_CURVE_B).expect("b is an element of Fq");
+    let mut _counter = 0u32;
+    loop {
+        let _x = domain_hash_fq(HASH_TO_G1_DST, label, domain, _counter, 0);
+        if let Some(_y) = fq_sqrt((_x * _x * _x) + _b) {
+            return G1::new(_x, _y, Fq::one());
+        }
+        _counter += 1;
+    }
+}
+
+/// hash a label (e.g. an attribute) to an element of G2, separated by a domain (e.g. a scheme).
+/// The discrete logarithm of the hash is unknown, i.e. the hash can be used where a random
+/// oracle into G2 is required, e.g. in large universe constructions.
+///
+/// The hash is pinned across versions and maps to the twist y^2 = x^3 + 3 / (9 + i) by try and
+/// increment: for the counters 0, 1, 2, ... the 64 byte blake2b hash of `HASH_TO_G2_DST`, the
+/// length of `domain` as 8 byte big endian integer, `domain`, `label`, the counter as 4 byte big
+/// endian integer and the byte 0 (or 1) is interpreted as an element x0 (or x1) of Fq. The first
+/// x = x0 + x1 * i, for which x^3 + b' has a square root y, gives the point (x, y) of the twist.
+/// The result is this point multiplied by the cofactor 2p - r of G2.
+pub fn hash_to_g2(label: &str, domain: &str) -> bn::G2 {
+    let _b = Fq2::new(
+        Fq::from_str(G2_CURVE_B[0]).expect("b' is an element of Fq2"),
+        Fq::from_str(G2_CURVE_B[1]).expect("b' is an element of Fq2"),
+    );
+    let mut _counter = 0u32;
+    loop {
+        let _x = Fq2::new(
+            domain_hash_fq(HASH_TO_G2_DST, label, domain, _counter, 0),
+            domain_hash_fq(HASH_TO_G2_DST, label, domain, _counter, 1),
+        );
+        if let Some(_y) = fq2_sqrt((_x * _x * _x) + _b) {
+            let _point = g2_mul_bytes(G2::new(_x, _y, Fq2::one()), &G2_COFACTOR);
+            if !_point.is_zero() {
+                return _point;
+            }
+        }
+        _counter += 1;
+    }
+}
+
+/// hash a label (e.g. an attribute) to an element of Fr, separated by a domain (e.g. a scheme
+/// and the use of the hash), so that the hashes of different uses can not alias.
+///
+/// The 64 byte blake2b hash of `HASH_TO_FR_DST`, the length of `domain` as 8 byte big endian
+/// integer, `domain` and `label` is interpreted as an element of Fr.
+pub fn hash_to_fr(label: &str, domain: &str) -> Fr {
+    return domain_hash(HASH_TO_FR_DST, label, domain);
+}
+
+/// hash a String to Fr using blake2b, without domain separation. Prefer `hash_to_fr`.
 pub fn blake2b_hash_fr(data: &String) -> Fr {
     let hash = blake2b(64, &[], data.as_bytes());
     return Fr::interpret(array_ref![hash.as_ref(), 0, 64]);
 }
+
+/// Derives a 32 byte secret from an element of Gt, e.g. to use it as a symmetric key.
+///
+/// The secret is the unkeyed blake2b hash with 32 bytes output of the binary encoding of the
+/// element (see `RabeEncode`). Note that `encrypt_symmetric` derives its AES key differently
+/// (using SHA3-256), so the two are not interchangeable.
+pub fn gt_to_secret(gt: &Gt) -> [u8; 32] {
+    return encoding_to_secret(&encode(gt).expect("elements of Gt can always be encoded"));
+}
+
+// the secret of gt_to_secret of the binary encoding of an element of Gt
+fn encoding_to_secret(_encoded: &[u8]) -> [u8; 32] {
+    let hash = blake2b(32, &[], _encoded);
+    let mut _secret: [u8; 32] = [0; 32];
+    _secret.copy_from_slice(hash.as_bytes());
+    return _secret;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rand::Rng;
+    use utils::encoding::{check_g1, check_g2};
+
+    #[test]
+    fn test_hash_to_g2_vector() {
+        let _label = "A";
+        let _domain = "bsw-attribute";
+        // the input of the first candidate x0 and its digest
+        let mut _data: Vec<u8> = b"rabe-hash-to-g2-v1".to_vec();
+        _data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 13]);
+        _data.extend_from_slice(b"bsw-attributeA");
+        _data.extend_from_slice(&[0, 0, 0, 0, 0]);
+        assert_eq!(
+            domain_hash_fq(HASH_TO_G2_DST, _label, _domain, 0, 0) ==
+                Fq::interpret(array_ref![blake2b(64, &[], &_data).as_ref(), 0, 64]),
+            true
+        );
+        // the affine coordinates of the hash, computed independently of this implementation
+        let _fq = |_s: &str| Fq::from_str(_s).unwrap();
+        let _expected = G2::new(
+            Fq2::new(
+                _fq("4040331999205506825012773525824936895283923684119708209593195775886657541961"),
+                _fq("14447925911844528677225804756978994875880430644668198799358508780296564797949"),
+            ),
+            Fq2::new(
+                _fq("12282522470940380933974894869189665240904358515179014628042699295152496235978"),
+                _fq("2180014217995062362093409200785875034987786028733959350017823738908602464353"),
+            ),
+            Fq2::one(),
+        );
+        let _hash = hash_to_g2(_label, _domain);
+        assert_eq!(_hash == _expected, true);
+        assert_eq!(check_g2(&_hash).is_ok(), true);
+        // not the generator raised to a public exponent, e.g. the hash to Fr of the input
+        let _exponent = Fr::from_str(
+            "9518872158236352704369083720840901720029590759787312206286059960825052514529",
+        ).unwrap();
+        assert_eq!(_hash == G2::one() * _exponent, false);
+        // label and domain are separated
+        assert_eq!(_hash == hash_to_g2("B", _domain), false);
+        assert_eq!(_hash == hash_to_g2(_label, "other"), false);
+        assert_eq!(hash_to_g2("-attributeA", "bsw") == _hash, false);
+    }
+
+    #[test]
+    fn test_hash_to_g1_vector() {
+        let _hash = hash_to_g1("A", "kp-attribute");
+        // the affine coordinates of the hash, computed independently of this implementation
+        let _expected = G1::new(
+            Fq::from_str(
+                "3181728390820667229860192675482485587569424499721532374743978689997852100827",
+            ).unwrap(),
+            Fq::from_str(
+                "8323547580540342857696553818262179206594646345751944063416858071579058501640",
+            ).unwrap(),
+            Fq::one(),
+        );
+        assert_eq!(_hash == _expected, true);
+        assert_eq!(check_g1(&_hash).is_ok(), true);
+        assert_eq!(_hash == G1::one() * hash_to_fr("A", "kp-attribute"), false);
+        assert_eq!(_hash == hash_to_g1("B", "kp-attribute"), false);
+        assert_eq!(_hash == hash_to_g1("A", "other"), false);
+    }
+
+    #[test]
+    fn test_fq_sqrt() {
+        let mut _rng = rand::thread_rng();
+        for _ in 0..16 {
+            let _a: Fq = _rng.gen();
+            assert_eq!(fq_sqrt(_a * _a).map(|_r| _r * _r == _a * _a), Some(true));
+            let _b = Fq2::new(_rng.gen(), _rng.gen());
+            assert_eq!(fq2_sqrt(_b * _b).map(|_r| _r * _r == _b * _b), Some(true));
+        }
+        // -1 is no square in Fq, as p = 3 mod 4
+        assert_eq!(fq_sqrt(-Fq::one()).is_none(), true);
+        let _c = Fq::from_str("82").unwrap();
+        assert_eq!(fq_inverse(_c) * _c == Fq::one(), true);
+    }
+
+    #[test]
+    fn test_hash_to_fr() {
+        let _label = "A";
+        // the documented construction
+        let mut _data: Vec<u8> = b"rabe-hash-to-fr-v1".to_vec();
+        _data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 13]);
+        _data.extend_from_slice(b"lsw-attributeA");
+        let hash = blake2b(64, &[], &_data);
+        let _expected = Fr::interpret(array_ref![hash.as_ref(), 0, 64]);
+        assert_eq!(hash_to_fr(_label, "lsw-attribute") == _expected, true);
+        // the same input under two domains
+        let _attribute = hash_to_fr(_label, "mke08-attribute");
+        assert_eq!(_attribute == hash_to_fr(_label, "mke08-authority"), false);
+        assert_eq!(_attribute == hash_to_fr(_label, "bdabe-attribute"), false);
+        assert_eq!(hash_to_fr("-attributeA", "lsw") == hash_to_fr(_label, "lsw-attribute"), false);
+        // deterministic, distinct from the hash without domain and from hash_to_g2
+        assert_eq!(hash_to_fr(_label, "lsw-attribute") == hash_to_fr(_label, "lsw-attribute"), true);
+        assert_eq!(hash_to_fr(_label, "") == blake2b_hash_fr(&String::from(_label)), false);
+        assert_eq!(
+            G2::one() * hash_to_fr(_label, "bsw-attribute") == hash_to_g2(_label, "bsw-attribute"),
+            false
+        );
+    }
+
+    #[test]
+    fn test_gt_to_secret() {
+        let mut _rng = rand::thread_rng();
+        let _gt: Gt = _rng.gen();
+        let _other: Gt = _rng.gen();
+        // the documented construction, BLAKE2b-256 of the encoding, e.g. of the bytes 0..64
+        let _encoded: Vec<u8> = (0..64).collect();
+        let _expected: [u8; 32] = [
+            0x10, 0xd8, 0xe6, 0xd5, 0x34, 0xb0, 0x09, 0x39,
+            0x84, 0x3f, 0xe9, 0xdc, 0xc4, 0xda, 0xe4, 0x8c,
+            0xdf, 0x00, 0x8f, 0x6b, 0x8b, 0x2b, 0x82, 0xb1,
+            0x56, 0xf5, 0x40, 0x4d, 0x87, 0x48, 0x87, 0xf5,
+        ];
+        assert_eq!(encoding_to_secret(&_encoded), _expected);
+        assert_eq!(gt_to_secret(&_gt), encoding_to_secret(&encode(&_gt).unwrap()));
+        // deterministic and distinct for distinct elements
+        assert_eq!(gt_to_secret(&_gt), gt_to_secret(&_gt));
+        assert_eq!(gt_to_secret(&_gt) == gt_to_secret(&_other), false);
+        assert_eq!(gt_to_secret(&Gt::one()), gt_to_secret(&(_gt * _gt.inverse())));
+    }
+}
@@ -0,0 +1,300 @@
+//! This is the documentation for the linear secret sharing (LSSS) utils
+//!
+//! An LSSS is given by a share-generating matrix `M` and a map `rho` of its rows to attributes.
+//! The share of row `i` is `M_i * (s, r_2, ..., r_n)` for the secret `s` and random `r_j`. A set
+//! of attributes can reconstruct `s` if `(1, 0, ..., 0)` is a linear combination of their rows.
+//!
+extern crate serde_json;
+
+use bn::*;
+use rand::Rng;
+use std::ops::Neg;
+use utils::error::RabeError;
+use utils::secretsharing::{policy_json, policy_node, PolicyNode};
+use utils::tools::contains;
+
+/// Converts an AND/OR policy into a share-generating matrix and the attribute of each row,
+/// using the conversion algorithm of Lewko and Waters. All entries of the matrix are 0, 1 or -1.
+///
+/// # Arguments
+///
+///	* `_policy` - An AND/OR policy in JSON format
+pub fn policy_to_lsss(_policy: &str) -> Result<(Vec<Vec<Fr>>, Vec<String>), RabeError> {
+    let _json = policy_json(&_policy.to_string())?;
+    let mut _rows: Vec<(Vec<Fr>, String)> = Vec::new();
+    let mut _counter = 1usize;
+    lewko_waters(&_json, vec![Fr::one()], &mut _counter, &mut _rows)?;
+    let mut _matrix: Vec<Vec<Fr>> = Vec::new();
+    let mut _rho: Vec<String> = Vec::new();
+    for (mut _row, _attr) in _rows {
+        _row.resize(_counter, Fr::zero());
+        _matrix.push(_row);
+        _rho.push(_attr);
+    }
+    return Ok((_matrix, _rho));
+}
+
+// labels the node with the vector _v: an OR passes _v to all children, an AND of x and y
+// passes (_v, 1) to x and (0, ..., 0, -1) to y, where the new column is _counter
+fn lewko_waters(
+    _json: &serde_json::Value,
+    _v: Vec<Fr>,
+    _counter: &mut usize,
+    _rows: &mut Vec<(Vec<Fr>, String)>,
+) -> Result<(), RabeError> {
+    match policy_node(_json)? {
+        PolicyNode::Leaf(_s) => {
+            _rows.push((_v, _s.to_string()));
+            return Ok(());
+        }
+        PolicyNode::Or(_children) => {
+            for _child in _children {
+                lewko_waters(_child, _v.clone(), _counter, _rows)?;
+            }
+            return Ok(());
+        }
+        PolicyNode::And(_children) => {
+            // an AND of n children is a chain of n - 1 ANDs of two children
+            let mut _v = _v;
+            for _child in _children.iter().take(_children.len() - 1) {
+                let mut _left = _v.clone();
+                _left.resize(*_counter, Fr::zero());
+                _left.push(Fr::one());
+                let mut _right = vec![Fr::zero(); *_counter];
+                _right.push(Fr::one().neg());
+                *_counter += 1;
+                lewko_waters(_child, _left, _counter, _rows)?;
+                _v = _right;
+            }
+            return lewko_waters(&_children[_children.len() - 1], _v, _counter, _rows);
+        }
+        PolicyNode::Threshold(_, _) |
+        PolicyNode::Not(_) => {
+            return Err(RabeError::InvalidPolicy(
+                String::from("only AND, OR and attributes can be converted into an LSSS"),
+            ))
+        }
+    }
+}
+
+/// Generates the shares `M_i * (s, r_2, ..., r_n)` of a secret, one for each row of the matrix.
+///
+/// # Arguments
+///
+///	* `_secret` - The secret to share
+///	* `_matrix` - A share-generating matrix, e.g. generated by policy_to_lsss()
+pub fn lsss_shares(_secret: Fr, _matrix: &Vec<Vec<Fr>>) -> Vec<Fr> {
+    let mut _rng = rand::thread_rng();
+    let _columns = _matrix.iter().map(|_row| _row.len()).max().unwrap_or(0);
+    let mut _vector = vec![_secret];
+    for _ in 1.._columns {
+        _vector.push(_rng.gen());
+    }
+    return _matrix
+        .iter()
+        .map(|_row| {
+            _row.iter().zip(_vector.iter()).fold(Fr::zero(), |_sum, (_m, _r)| _sum + (*_m * *_r))
+        })
+        .collect();
+}
+
+/// Returns the coefficients `w_i` of the rows whose attribute is part of `_attributes`, such that
+/// the sum of `w_i * M_i` is `(1, 0, ..., 0)`, or None if the attributes do not satisfy the LSSS.
+///
+/// # Arguments
+///
+///	* `_matrix` - A share-generating matrix, e.g. generated by policy_to_lsss()
+///	* `_rho` - The attribute of each row of the matrix
+///	* `_attributes` - The attributes used to reconstruct
+pub fn lsss_coefficients(
+    _matrix: &Vec<Vec<Fr>>,
+    _rho: &Vec<String>,
+    _attributes: &Vec<String>,
+) -> Option<Vec<(usize, Fr)>> {
+    let _rows: Vec<usize> = (0.._matrix.len().min(_rho.len()))
+        .filter(|_i| contains(_attributes, &_rho[*_i]))
+        .collect();
+    let _columns = _matrix.iter().map(|_row| _row.len()).max().unwrap_or(0);
+    if _rows.is_empty() || _columns == 0 {
+        return None;
+    }
+    // the system M_I^T * w = (1, 0, ..., 0) as augmented matrix, one equation per column of M
+    let _entry = |_i: usize, _j: usize| *_matrix[_i].get(_j).unwrap_or(&Fr::zero());
+    let mut _system: Vec<Vec<Fr>> = (0.._columns)
+        .map(|_j| {
+            let mut _equation: Vec<Fr> = _rows.iter().map(|_i| _entry(*_i, _j)).collect();
+            _equation.push(if _j == 0 { Fr::one() } else { Fr::zero() });
+            _equation
+        })
+        .collect();
+    // gaussian elimination, free variables are set to zero
+    let _unknowns = _rows.len();
+    let mut _pivots: Vec<usize> = Vec::new();
+    let mut _rank = 0usize;
+    for _col in 0.._unknowns {
+        let _pivot = match (_rank.._columns).find(|_r| _system[*_r][_col] != Fr::zero()) {
+            None => continue,
+            Some(_pivot) => _pivot,
+        };
+        _system.swap(_rank, _pivot);
+        let _inverse = _system[_rank][_col].inverse().unwrap();
+        for _k in _col.._unknowns + 1 {
+            _system[_rank][_k] = _system[_rank][_k] * _inverse;
+        }
+        for _r in 0.._columns {
+            let _factor = _system[_r][_col];
+            if _r != _rank && _factor != Fr::zero() {
+                for _k in _col.._unknowns + 1 {
+                    _system[_r][_k] = _system[_r][_k] - (_factor * _system[_rank][_k]);
+                }
+            }
+        }
+        _pivots.push(_col);
+        _rank += 1;
+    }
+    // the system has no solution if an equation without unknowns is left over
+    if (_rank.._columns).any(|_r| _system[_r][_unknowns] != Fr::zero()) {
+        return None;
+    }
+    let mut _w: Vec<(usize, Fr)> = _rows.iter().map(|_i| (*_i, Fr::zero())).collect();
+    for (_r, _col) in _pivots.iter().enumerate() {
+        _w[*_col].1 = _system[_r][_unknowns];
+    }
+    return Some(_w);
+}
+
+/// Reconstructs the secret from the shares of the rows whose attribute is part of `_attributes`.
+///
+/// # Arguments
+///
+///	* `_matrix` - A share-generating matrix, e.g. generated by policy_to_lsss()
+///	* `_rho` - The attribute of each row of the matrix
+///	* `_attributes` - The attributes used to reconstruct
+///	* `_shares` - The shares of all rows, generated by lsss_shares()
+///
+/// # Errors
+///
+///	* `RabeError::InsufficientAttributes` - if the attributes do not satisfy the LSSS
+///	* `RabeError::Interpolation` - if the number of shares or attributes does not match the rows
+pub fn lsss_reconstruct(
+    _matrix: &Vec<Vec<Fr>>,
+    _rho: &Vec<String>,
+    _attributes: &Vec<String>,
+    _shares: &Vec<Fr>,
+) -> Result<Fr, RabeError> {
+    if _shares.len() != _matrix.len() || _rho.len() != _matrix.len() {
+        return Err(RabeError::Interpolation(format!(
+            "{} shares and {} attributes for {} rows",
+            _shares.len(),
+            _rho.len(),
+            _matrix.len()
+        )));
+    }
+    match lsss_coefficients(_matrix, _rho, _attributes) {
+        None => return Err(RabeError::InsufficientAttributes),
+        Some(_w) => {
+            let mut _secret = Fr::zero();
+            for (_i, _w_i) in _w {
+                _secret = _secret + (_w_i * _shares[_i]);
+            }
+            return Ok(_secret);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn attributes(_list: &[&str]) -> Vec<String> {
+        return _list.iter().map(|_a| _a.to_string()).collect();
+    }
+
+    #[test]
+    fn test_lsss_matrix() {
+        let (_m, _rho) = policy_to_lsss(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#).unwrap();
+        assert_eq!(_rho, attributes(&["A", "B"]));
+        assert_eq!(_m == vec![vec![Fr::one(), Fr::one()], vec![Fr::zero(), Fr::one().neg()]], true);
+        let (_m, _rho) = policy_to_lsss(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#).unwrap();
+        assert_eq!(_rho, attributes(&["A", "B"]));
+        assert_eq!(_m == vec![vec![Fr::one()], vec![Fr::one()]], true);
+    }
+
+    #[test]
+    fn test_lsss_reconstruct() {
+        let _secret: Fr = rand::thread_rng().gen();
+        let _policies = vec![
+            (r#"{"ATT": "A"}"#, vec![vec!["A"]], vec![vec!["B"]]),
+            (
+                r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}"#,
+                vec![vec!["A", "B", "C"]],
+                vec![vec!["A", "B"], vec!["B", "C"]],
+            ),
+            (
+                r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+                vec![vec!["A", "B"], vec!["A", "C"], vec!["A", "B", "C"]],
+                vec![vec!["A"], vec!["B", "C"]],
+            ),
+            (
+                r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}"#,
+                vec![vec!["A", "B"], vec!["C", "D"], vec!["A", "B", "D"]],
+                vec![vec!["A", "C"], vec!["B", "D"]],
+            ),
+            (
+                r#"{"OR": [{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}]}, {"ATT": "E"}]}"#,
+                vec![vec!["A", "B"], vec!["A", "C", "D"], vec!["E"]],
+                vec![vec!["A", "C"], vec!["B", "C", "D"]],
+            ),
+        ];
+        for (_policy, _satisfying, _not_satisfying) in _policies {
+            let (_m, _rho) = policy_to_lsss(_policy).unwrap();
+            let _shares = lsss_shares(_secret, &_m);
+            assert_eq!(_shares.len(), _rho.len());
+            for _set in _satisfying {
+                let _result = lsss_reconstruct(&_m, &_rho, &attributes(&_set), &_shares).unwrap();
+                assert_eq!(_result == _secret, true);
+            }
+            for _set in _not_satisfying {
+                assert_eq!(
+                    lsss_reconstruct(&_m, &_rho, &attributes(&_set), &_shares).err(),
+                    Some(RabeError::InsufficientAttributes)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lsss_reconstruct_share_count() {
+        let _secret: Fr = rand::thread_rng().gen();
+        let (_m, _rho) = policy_to_lsss(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#).unwrap();
+        let _shares = lsss_shares(_secret, &_m);
+        let _attributes = attributes(&["A"]);
+        // too few and too many shares are rejected, even if the rows used have a share
+        for _wrong in vec![_shares[..1].to_vec(), [&_shares[..], &[_secret]].concat()] {
+            assert_eq!(
+                lsss_reconstruct(&_m, &_rho, &_attributes, &_wrong).err(),
+                Some(RabeError::Interpolation(
+                    format!("{} shares and 2 attributes for 2 rows", _wrong.len()),
+                ))
+            );
+        }
+        let _rho_a = _rho[..1].to_vec();
+        assert_eq!(lsss_reconstruct(&_m, &_rho_a, &_attributes, &_shares).is_err(), true);
+        assert_eq!(lsss_reconstruct(&_m, &_rho, &_attributes, &_shares).unwrap() == _secret, true);
+    }
+
+    #[test]
+    fn test_lsss_invalid() {
+        assert_eq!(policy_to_lsss("joking-around?").is_err(), true);
+        assert_eq!(
+            policy_to_lsss(r#"{"THRESHOLD": {"k": 1, "children": [{"ATT": "A"}, {"ATT": "B"}]}}"#)
+                .is_err(),
+            true
+        );
+        assert_eq!(
+            policy_to_lsss(r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#).is_err(),
+            true
+        );
+    }
+}
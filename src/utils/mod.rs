@@ -2,13 +2,22 @@
 //!
 //! Currently those are:
 //! * aes
+//! * encoding
+//! * error
+//! * file
 //! * hash
+//! * lsss
+//! * parameters
 //! * policy
 //! * secretSharing
 //! * tools
 //!
 pub mod aes;
+pub mod encoding;
+pub mod error;
 pub mod hash;
+pub mod lsss;
+pub mod parameters;
 pub mod policy;
 pub mod secretsharing;
 pub mod tools;
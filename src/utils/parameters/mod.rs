@@ -0,0 +1,135 @@
+//! The cryptographic parameters of the pairing backend (`bn`) used by all schemes.
+//!
+use bn::{Fr, G1, G2, Group, Gt, pairing};
+use num_bigint::BigUint;
+use std::str::FromStr;
+use utils::encoding::encode;
+
+/// The name of the pairing friendly curve of the backend
+pub const CURVE: &'static str = "BN254";
+/// The prime order r of G1, G2 and Gt (decimal), checked against `bn::Fr` by the tests
+pub const GROUP_ORDER: &'static str = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+/// The estimated security level of BN254 in bits. Originally 128 bits were assumed, the
+/// exTNFS attacks on the discrete logarithm in Gt (Kim-Barbulescu, 2016) reduce it to about 100.
+pub const SECURITY_BITS: u32 = 100;
+
+/// A report of the cryptographic parameters, see `scheme_parameters`
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct SchemeParameters {
+    pub _curve: String,
+    pub _security_bits: u32,
+    /// the prime order r of the groups (decimal)
+    pub _group_order: String,
+    /// the number of bits of r
+    pub _order_bits: usize,
+    /// the size of a serialized element of Fr, G1, G2 and Gt in bytes
+    pub _fr_bytes: usize,
+    pub _g1_bytes: usize,
+    pub _g2_bytes: usize,
+    pub _gt_bytes: usize,
+    /// the collusion resistance of every scheme
+    pub _collusion: Vec<CollusionResistance>,
+}
+
+/// Whether the keys of a scheme resist collusion, i.e. colluding users can not decrypt more
+/// than each of them alone
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct CollusionResistance {
+    pub _scheme: String,
+    /// true if every ciphertext of the scheme resists collusion
+    pub _resistant: bool,
+    pub _note: String,
+}
+
+/// Returns the curve, security level, group order and the serialized sizes of the group
+/// elements of the compiled backend, e.g. for compliance documentation.
+///
+/// The sizes are measured by encoding elements of the backend, so they always match the
+/// binary format (see `RabeEncode`).
+pub fn scheme_parameters() -> SchemeParameters {
+    let _g1 = G1::one();
+    let _g2 = G2::one();
+    return SchemeParameters {
+        _curve: String::from(CURVE),
+        _security_bits: SECURITY_BITS,
+        _group_order: String::from(GROUP_ORDER),
+        _order_bits: order_bits(),
+        _fr_bytes: encoded_len(&Fr::one()),
+        _g1_bytes: encoded_len(&_g1),
+        _g2_bytes: encoded_len(&_g2),
+        _gt_bytes: encoded_len(&pairing(_g1, _g2)),
+        _collusion: collusion_resistance(),
+    };
+}
+
+// the number of bits of GROUP_ORDER
+fn order_bits() -> usize {
+    return BigUint::from_str(GROUP_ORDER).map(|_r| _r.bits()).unwrap_or(0);
+}
+
+// attributes hashed by blake2b_hash_g1 have a public discrete logarithm, which the security
+// proofs of AC17, AW11 and LSW do not allow
+const KNOWN_DLOG: &'static str = "not guaranteed: attributes are hashed to G1 by blake2b_hash_g1, \
+                                  whose discrete logarithm is public";
+
+fn collusion_resistance() -> Vec<CollusionResistance> {
+    let _entry = |_scheme: &str, _resistant: bool, _note: &str| CollusionResistance {
+        _scheme: String::from(_scheme),
+        _resistant,
+        _note: String::from(_note),
+    };
+    let _randomized = "secret keys are randomized per user";
+    return vec![
+        _entry("AC17CP", false, KNOWN_DLOG),
+        _entry("AC17KP", false, KNOWN_DLOG),
+        _entry("AW11", false, KNOWN_DLOG),
+        _entry("BDABE", true, _randomized),
+        _entry(
+            "BSW",
+            false,
+            "secret keys are randomized per user, but the two layers of update_policy \
+             (decrypt_updated) are independent ciphertexts: two users satisfying one layer each \
+             can decrypt together",
+        ),
+        _entry("KP", true, _randomized),
+        _entry("LSW", false, KNOWN_DLOG),
+        _entry("MAABE", true, "secret keys are bound to the global identifier of the user"),
+        _entry("MKE08", true, _randomized),
+    ];
+}
+
+fn encoded_len<T: ::serde::Serialize>(_value: &T) -> usize {
+    return encode(_value).map(|_bytes| _bytes.len()).unwrap_or(0);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_scheme_parameters() {
+        let _params = scheme_parameters();
+        assert_eq!(_params._curve, "BN254");
+        assert_eq!(_params._group_order, GROUP_ORDER);
+        // r = 0 in Fr, i.e. GROUP_ORDER is the order of the compiled backend
+        let _r = BigUint::from_str(GROUP_ORDER).unwrap();
+        assert_eq!(_r.bits(), _params._order_bits);
+        assert_eq!(_params._order_bits, 254);
+        let _r_minus_one = Fr::from_str(&(_r - BigUint::new(vec![1])).to_string()).unwrap();
+        assert_eq!(_r_minus_one + Fr::one() == Fr::zero(), true);
+        assert_eq!(_r_minus_one == Fr::zero(), false);
+        assert_eq!(_params._g1_bytes > 0, true);
+        assert_eq!(_params._g2_bytes > _params._g1_bytes, true);
+        assert_eq!(_params._gt_bytes > _params._g2_bytes, true);
+        // one entry per scheme
+        let _schemes: Vec<&str> = _params._collusion.iter().map(|_c| &_c._scheme[..]).collect();
+        assert_eq!(
+            _schemes,
+            vec!["AC17CP", "AC17KP", "AW11", "BDABE", "BSW", "KP", "LSW", "MAABE", "MKE08"]
+        );
+        let _bsw = _params._collusion.iter().find(|_c| _c._scheme == "BSW").unwrap();
+        assert_eq!(_bsw._resistant, false);
+        assert_eq!(_bsw._note.contains("update_policy"), true);
+    }
+}
@@ -0,0 +1,113 @@
+use std::iter::FromIterator;
+use std::string::String;
+use utils::policy::json::Policy;
+use utils::policy::numeric::expand_numeric_attributes;
+use utils::secretsharing::TRUE_ATTRIBUTE;
+use utils::tools::{contains, normalize_attribute, traverse_json};
+
+/// A normalized set of attributes, e.g. the attributes of a user key
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct AttributeSet {
+    pub _attributes: Vec<String>,
+}
+
+impl AttributeSet {
+    /// Returns a new AttributeSet. All attributes are normalized and duplicates are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `_attributes` - A Vector of String attributes
+    pub fn new(_attributes: &Vec<String>) -> AttributeSet {
+        let mut _set: Vec<String> = Vec::new();
+        for _attr in _attributes {
+            let _attr = normalize_attribute(_attr);
+            if !contains(&_set, &_attr) {
+                _set.push(_attr);
+            }
+        }
+        return AttributeSet { _attributes: _set };
+    }
+
+    pub fn attributes(&self) -> &Vec<String> {
+        return &self._attributes;
+    }
+
+    pub fn contains(&self, _attr: &str) -> bool {
+        return contains(&self._attributes, &normalize_attribute(_attr));
+    }
+
+    /// Returns true if a key holding this set of attributes satisfies the given policy. Like
+    /// every key, the set satisfies `{"TRUE": true}` and negated attributes it does not hold, and
+    /// numeric attributes are compared through their bits.
+    pub fn satisfies(&self, _policy: &Policy) -> bool {
+        let mut _attributes = match expand_numeric_attributes(&self._attributes) {
+            Err(_) => return false,
+            Ok(_attributes) => _attributes,
+        };
+        _attributes.push(String::from(TRUE_ATTRIBUTE));
+        return traverse_json(&_attributes, &_policy._json);
+    }
+}
+
+impl FromIterator<String> for AttributeSet {
+    fn from_iter<I: IntoIterator<Item = String>>(_iter: I) -> AttributeSet {
+        return AttributeSet::new(&_iter.into_iter().collect());
+    }
+}
+
+impl<'a> FromIterator<&'a str> for AttributeSet {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(_iter: I) -> AttributeSet {
+        return _iter.into_iter().map(String::from).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_attribute_set() {
+        let _set = AttributeSet::new(&vec![
+            String::from(" A"),
+            String::from("B"),
+            String::from("A "),
+        ]);
+        assert_eq!(_set.attributes(), &vec![String::from("A"), String::from("B")]);
+        assert_eq!(_set.contains("A"), true);
+        assert_eq!(_set.contains(" B "), true);
+        assert_eq!(_set.contains("C"), false);
+    }
+
+    #[test]
+    fn test_attribute_set_from_iter() {
+        let _set: AttributeSet = vec!["B", " A", "B"].into_iter().collect();
+        assert_eq!(_set.attributes(), &vec![String::from("B"), String::from("A")]);
+        let _set: AttributeSet = vec![String::from("A"), String::from("C")]
+            .into_iter()
+            .filter(|_a| _a != "C")
+            .collect();
+        assert_eq!(_set.attributes(), &vec![String::from("A")]);
+        let _empty: AttributeSet = Vec::<String>::new().into_iter().collect();
+        assert_eq!(_empty.attributes().is_empty(), true);
+    }
+
+    #[test]
+    fn test_attribute_set_satisfies() {
+        let _not = Policy::from_string(&String::from(
+            r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#,
+        )).unwrap();
+        let _a: AttributeSet = vec!["A"].into_iter().collect();
+        let _ab: AttributeSet = vec!["A", "B"].into_iter().collect();
+        assert_eq!(_a.satisfies(&_not), true);
+        assert_eq!(_ab.satisfies(&_not), false);
+        let _true = Policy::from_string(&String::from(r#"{"TRUE": true}"#)).unwrap();
+        assert_eq!(_a.satisfies(&_true), true);
+        let _or = Policy::from_string(&String::from(
+            r#"{"OR": [{"ATT": "C"}, {"TRUE": true}]}"#,
+        )).unwrap();
+        assert_eq!(_ab.satisfies(&_or), true);
+        let _c = Policy::from_string(&String::from(r#"{"ATT": "C"}"#)).unwrap();
+        assert_eq!(_ab.satisfies(&_c), false);
+    }
+}
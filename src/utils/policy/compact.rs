@@ -0,0 +1,263 @@
+extern crate serde_json;
+
+use std::string::String;
+use utils::error::RabeError;
+use utils::secretsharing::{policy_json, policy_node, PolicyNode, MAX_POLICY_DEPTH, TRUE_ATTRIBUTE};
+
+// Compact policy symbols
+const COMPACT_AND: char = '&';
+const COMPACT_OR: char = '|';
+const COMPACT_OPEN: char = '(';
+const COMPACT_CLOSE: char = ')';
+const COMPACT_SEPARATOR: char = ',';
+const COMPACT_ESCAPE: char = '\\';
+// the policy {"TRUE": true}, no attribute of a policy can have this reserved name
+const COMPACT_TRUE: &'static str = TRUE_ATTRIBUTE;
+
+fn is_special(_c: char) -> bool {
+    return _c == COMPACT_AND || _c == COMPACT_OR || _c == COMPACT_OPEN ||
+        _c == COMPACT_CLOSE || _c == COMPACT_SEPARATOR || _c == COMPACT_ESCAPE;
+}
+
+/// Converts a JSON policy into its compact prefix notation, e.g.
+/// `{"AND": [{"OR": [{"ATT": "A"}, {"ATT": "B"}]}, {"ATT": "C"}]}` becomes `&(|(A,B),C)`.
+/// Special characters in attribute names are escaped with a backslash. `{"TRUE": true}` is
+/// written as `#TRUE`.
+///
+/// # Arguments
+///
+///	* `_policy` - A policy in JSON format as String
+pub fn policy_to_compact(_policy: &String) -> Result<String, RabeError> {
+    let _json = policy_json(_policy)?;
+    let mut _compact = String::new();
+    json_to_compact(&_json, &mut _compact)?;
+    return Ok(_compact);
+}
+
+fn json_to_compact(_json: &serde_json::Value, _compact: &mut String) -> Result<(), RabeError> {
+    let (_gate, _children) = match policy_node(_json)? {
+        PolicyNode::Leaf(_s) if _s == TRUE_ATTRIBUTE => {
+            _compact.push_str(COMPACT_TRUE);
+            return Ok(());
+        }
+        PolicyNode::Leaf(_s) => {
+            if _s.is_empty() {
+                return Err(RabeError::InvalidPolicy(String::from("empty attribute")));
+            }
+            for _c in _s.chars() {
+                if is_special(_c) {
+                    _compact.push(COMPACT_ESCAPE);
+                }
+                _compact.push(_c);
+            }
+            return Ok(());
+        }
+        PolicyNode::And(_children) => (COMPACT_AND, _children),
+        PolicyNode::Or(_children) => (COMPACT_OR, _children),
+        PolicyNode::Threshold(_, _) => {
+            return Err(RabeError::InvalidPolicy(
+                String::from("THRESHOLD has no compact notation"),
+            ))
+        }
+        PolicyNode::Not(_) => {
+            return Err(RabeError::InvalidPolicy(String::from("NOT has no compact notation")))
+        }
+    };
+    _compact.push(_gate);
+    _compact.push(COMPACT_OPEN);
+    for (_i, _child) in _children.iter().enumerate() {
+        if _i > 0 {
+            _compact.push(COMPACT_SEPARATOR);
+        }
+        json_to_compact(_child, _compact)?;
+    }
+    _compact.push(COMPACT_CLOSE);
+    return Ok(());
+}
+
+/// Converts a policy in compact prefix notation (see `policy_to_compact`) back into
+/// its JSON representation. Policies nested deeper than `MAX_POLICY_DEPTH` are rejected
+/// with `RabeError::PolicyTooDeep`.
+///
+/// # Arguments
+///
+///	* `_compact` - A policy in compact notation as String
+pub fn compact_to_policy(_compact: &String) -> Result<String, RabeError> {
+    let _chars: Vec<char> = _compact.chars().collect();
+    let mut _pos = 0usize;
+    let _json = parse_compact(&_chars, &mut _pos, 1)?;
+    if _pos != _chars.len() {
+        return Err(RabeError::PolicyParse(format!(
+            "unexpected '{}' at position {} in {}",
+            _chars[_pos],
+            _pos,
+            _compact
+        )));
+    }
+    return Ok(_json.to_string());
+}
+
+fn parse_compact(
+    _chars: &Vec<char>,
+    _pos: &mut usize,
+    _depth: usize,
+) -> Result<serde_json::Value, RabeError> {
+    if _depth > MAX_POLICY_DEPTH {
+        return Err(RabeError::PolicyTooDeep);
+    }
+    // inner node
+    if *_pos < _chars.len() && (_chars[*_pos] == COMPACT_AND || _chars[*_pos] == COMPACT_OR) {
+        let _gate = if _chars[*_pos] == COMPACT_AND { "AND" } else { "OR" };
+        *_pos += 1;
+        expect(_chars, _pos, COMPACT_OPEN)?;
+        let mut _children: Vec<serde_json::Value> = Vec::new();
+        loop {
+            _children.push(parse_compact(_chars, _pos, _depth + 1)?);
+            if *_pos < _chars.len() && _chars[*_pos] == COMPACT_SEPARATOR {
+                *_pos += 1;
+            } else {
+                break;
+            }
+        }
+        expect(_chars, _pos, COMPACT_CLOSE)?;
+        if _children.len() < 2 {
+            return Err(RabeError::InvalidPolicy(
+                format!("{} with less than two children", _gate),
+            ));
+        }
+        let mut _node = serde_json::Map::new();
+        _node.insert(String::from(_gate), serde_json::Value::Array(_children));
+        return Ok(serde_json::Value::Object(_node));
+    }
+    // leaf node
+    let mut _attr = String::new();
+    while *_pos < _chars.len() {
+        let _c = _chars[*_pos];
+        if _c == COMPACT_ESCAPE {
+            *_pos += 1;
+            if *_pos == _chars.len() {
+                return Err(RabeError::PolicyParse(String::from("dangling escape character")));
+            }
+            _attr.push(_chars[*_pos]);
+        } else if is_special(_c) {
+            break;
+        } else {
+            _attr.push(_c);
+        }
+        *_pos += 1;
+    }
+    if _attr.is_empty() {
+        return Err(RabeError::PolicyParse(
+            format!("expected attribute at position {}", *_pos),
+        ));
+    }
+    let mut _node = serde_json::Map::new();
+    if _attr == COMPACT_TRUE {
+        _node.insert(String::from("TRUE"), serde_json::Value::Bool(true));
+    } else {
+        _node.insert(String::from("ATT"), serde_json::Value::String(_attr));
+    }
+    return Ok(serde_json::Value::Object(_node));
+}
+
+fn expect(_chars: &Vec<char>, _pos: &mut usize, _c: char) -> Result<(), RabeError> {
+    if *_pos < _chars.len() && _chars[*_pos] == _c {
+        *_pos += 1;
+        return Ok(());
+    }
+    return Err(RabeError::PolicyParse(
+        format!("expected '{}' at position {}", _c, *_pos),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::tools::traverse_str;
+
+    #[test]
+    fn test_compact_roundtrip() {
+        let _policy = String::from(
+            r#"{"AND": [{"OR": [{"ATT": "A"}, {"ATT": "B"}]}, {"ATT": "C"}]}"#,
+        );
+        let _compact = policy_to_compact(&_policy).unwrap();
+        assert_eq!(_compact, "&(|(A,B),C)");
+        let _json = compact_to_policy(&_compact).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&_json).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&_policy).unwrap()
+        );
+        assert_eq!(policy_to_compact(&_json).unwrap(), _compact);
+    }
+
+    #[test]
+    fn test_compact_equivalent_nested() {
+        let _policy = String::from(
+            r#"{"OR": [{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}, {"AND": [{"ATT": "D"}, {"ATT": "E"}]}]}]}, {"AND": [{"ATT": "F"}, {"ATT": "G"}]}]}"#,
+        );
+        let _json = compact_to_policy(&policy_to_compact(&_policy).unwrap()).unwrap();
+        let _sets = vec![
+            vec!["A", "B"],
+            vec!["A", "D"],
+            vec!["A", "D", "E"],
+            vec!["F"],
+            vec!["F", "G"],
+            vec!["B", "C", "G"],
+        ];
+        for _set in _sets {
+            let _attr: Vec<String> = _set.iter().map(|a| a.to_string()).collect();
+            assert_eq!(traverse_str(&_attr, &_json), traverse_str(&_attr, &_policy));
+        }
+    }
+
+    #[test]
+    fn test_compact_true() {
+        let _policy = String::from(r#"{"OR": [{"ATT": "A"}, {"TRUE": true}]}"#);
+        let _compact = policy_to_compact(&_policy).unwrap();
+        assert_eq!(_compact, "|(A,#TRUE)");
+        let _json = compact_to_policy(&_compact).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&_json).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&_policy).unwrap()
+        );
+        let _attr = vec![String::from("B"), String::from(TRUE_ATTRIBUTE)];
+        assert_eq!(traverse_str(&_attr, &_json), true);
+        let _true = String::from(r#"{"TRUE": true}"#);
+        let _json = compact_to_policy(&policy_to_compact(&_true).unwrap()).unwrap();
+        assert_eq!(_json, r#"{"TRUE":true}"#);
+    }
+
+    #[test]
+    fn test_compact_escaping() {
+        let _policy = String::from(r#"{"AND": [{"ATT": "dept(a&b)"}, {"ATT": "x,y\\z"}]}"#);
+        let _compact = policy_to_compact(&_policy).unwrap();
+        assert_eq!(_compact, r#"&(dept\(a\&b\),x\,y\\z)"#);
+        let _json: serde_json::Value =
+            serde_json::from_str(&compact_to_policy(&_compact).unwrap()).unwrap();
+        assert_eq!(_json["AND"][0]["ATT"], "dept(a&b)");
+        assert_eq!(_json["AND"][1]["ATT"], "x,y\\z");
+    }
+
+    #[test]
+    fn test_compact_errors() {
+        for _compact in ["&(A)", "&(A,B", "|(A,,B)", "&A,B", "A)", "", "&(A,B\\"].iter() {
+            assert_eq!(compact_to_policy(&_compact.to_string()).is_err(), true);
+        }
+        assert_eq!(policy_to_compact(&String::from("joking-around?")).is_err(), true);
+    }
+
+    #[test]
+    fn test_compact_deep() {
+        let _nested = |_depth: usize| {
+            format!("{}B{}", "&(A,".repeat(_depth - 1), ")".repeat(_depth - 1))
+        };
+        assert_eq!(compact_to_policy(&_nested(MAX_POLICY_DEPTH)).is_ok(), true);
+        assert_eq!(
+            compact_to_policy(&_nested(MAX_POLICY_DEPTH + 1)).err(),
+            Some(RabeError::PolicyTooDeep)
+        );
+        // several thousand levels return an error instead of overflowing the stack
+        assert_eq!(compact_to_policy(&_nested(100000)).err(), Some(RabeError::PolicyTooDeep));
+    }
+}
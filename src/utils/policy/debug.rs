@@ -0,0 +1,156 @@
+//! Diagnostics for the secret sharing of a policy, useful when debugging failed decryptions.
+//!
+//! Only available with the `test-utils` feature.
+//!
+use bn::Fr;
+use rand::Rng;
+use std::fmt;
+use std::string::String;
+use utils::encoding::encode;
+use utils::error::RabeError;
+use utils::policy::sample::sample_satisfying_set;
+use utils::secretsharing::{calc_coefficients_indexed_str, gen_shares_json, policy_json};
+
+/// A single leaf of a policy with its share and its lagrange coefficient
+#[derive(PartialEq, Clone)]
+pub struct ReconstructionRow {
+    pub _attribute: String,
+    pub _share: Fr,
+    /// the coefficient of a used leaf, `None` if the leaf is not used
+    pub _coefficient: Option<Fr>,
+    /// whether the leaf is used to reconstruct the secret
+    pub _used: bool,
+}
+
+/// The share / coefficient alignment of a policy
+#[derive(PartialEq, Clone)]
+pub struct Reconstruction {
+    pub _secret: Fr,
+    pub _rows: Vec<ReconstructionRow>,
+    /// the (smallest satisfying) set of attributes used for the reconstruction
+    pub _attributes: Vec<String>,
+    /// whether every coefficient belongs to a share of the same attribute
+    pub _aligned: bool,
+    /// whether the sum of coefficient * share over the used leaves is the secret
+    pub _reconstructs: bool,
+}
+
+/// Shares a random secret according to the given policy, computes the lagrange coefficients
+/// and reconstructs the secret from a smallest satisfying set of attributes, the same way
+/// decryption does, i.e. per leaf. The `Display` of the result is a table of all leaves.
+///
+/// # Arguments
+///
+/// * `_policy` - A policy in JSON format
+pub fn debug_reconstruction(_policy: &str) -> Result<Reconstruction, RabeError> {
+    let _json = policy_json(&_policy.to_string())?;
+    let _secret: Fr = rand::thread_rng().gen();
+    let _shares = gen_shares_json(_secret, &_json)?;
+    let _attributes = sample_satisfying_set(_policy)?;
+    let _coefficients = calc_coefficients_indexed_str(&_attributes, &_policy.to_string())?
+        .unwrap_or(Vec::new());
+    let mut _rows: Vec<ReconstructionRow> = _shares
+        .iter()
+        .map(|_share| ReconstructionRow {
+            _attribute: _share.0.clone(),
+            _share: _share.1,
+            _coefficient: None,
+            _used: false,
+        })
+        .collect();
+    // every coefficient belongs to the leaf with its index
+    let mut _aligned = !_coefficients.is_empty();
+    for (_index, _attr, _c) in _coefficients {
+        match _rows.get_mut(_index) {
+            Some(_row) if _row._attribute == _attr && !_row._used => {
+                _row._coefficient = Some(_c);
+                _row._used = true;
+            }
+            _ => _aligned = false,
+        }
+    }
+    let mut _sum = Fr::zero();
+    for _row in _rows.iter() {
+        if let Some(_c) = _row._coefficient {
+            _sum = _sum + (_c * _row._share);
+        }
+    }
+    let _reconstructs = _aligned && _sum == _secret;
+    return Ok(Reconstruction {
+        _secret,
+        _rows,
+        _attributes,
+        _aligned,
+        _reconstructs,
+    });
+}
+
+impl fmt::Display for Reconstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<20} {:<66} {:<66} {}", "attribute", "share", "coefficient", "used")?;
+        for _row in self._rows.iter() {
+            writeln!(
+                f,
+                "{:<20} {:<66} {:<66} {}",
+                _row._attribute,
+                to_hex(&_row._share),
+                match _row._coefficient {
+                    None => String::from("-"),
+                    Some(_c) => to_hex(&_c),
+                },
+                _row._used
+            )?;
+        }
+        write!(f, "aligned: {}, reconstructs: {}", self._aligned, self._reconstructs)
+    }
+}
+
+fn to_hex(_fr: &Fr) -> String {
+    match encode(_fr) {
+        Err(_) => return String::from("?"),
+        Ok(_bytes) => return _bytes.iter().map(|_b| format!("{:02x}", _b)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_debug_reconstruction() {
+        let _policies = vec![
+            r#"{"ATT": "A"}"#,
+            r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}"#,
+            r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"ATT": "C"}]}"#,
+            r#"{"AND": [{"OR": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}"#,
+            r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}}"#,
+            r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#,
+        ];
+        for _p in _policies {
+            let _result = debug_reconstruction(_p).unwrap();
+            assert_eq!(_result._aligned, true);
+            assert_eq!(_result._reconstructs, true);
+            assert_eq!(_result._rows.iter().any(|_r| _r._used), true);
+        }
+        let _result = debug_reconstruction(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#).unwrap();
+        assert_eq!(_result._rows.len(), 2);
+        assert_eq!(_result._rows.iter().filter(|_r| _r._used).count(), 1);
+        assert_eq!(debug_reconstruction("joking-around?").is_err(), true);
+    }
+
+    #[test]
+    fn test_debug_reconstruction_repeated_attribute() {
+        // {A} satisfies the second leaf A, not the first one below the AND
+        let _result =
+            debug_reconstruction(r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"ATT": "A"}]}"#)
+                .unwrap();
+        assert_eq!(_result._attributes, vec![String::from("A")]);
+        let _used: Vec<bool> = _result._rows.iter().map(|_r| _r._used).collect();
+        assert_eq!(_used, vec![false, false, true]);
+        assert_eq!(_result._reconstructs, true);
+        let _table = _result.to_string();
+        assert_eq!(_table.lines().count(), 5);
+        assert_eq!(_table.ends_with("aligned: true, reconstructs: true"), true);
+    }
+}
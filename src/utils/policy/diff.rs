@@ -0,0 +1,243 @@
+//! Differences between two JSON policies, e.g. to review the change of a policy.
+//!
+extern crate serde_json;
+
+use std::collections::BTreeSet;
+use std::string::String;
+use utils::error::RabeError;
+use utils::secretsharing::{policy_json, Canonical};
+
+/// The differences between two policies, see `policy_diff`
+#[derive(PartialEq, Clone, Debug)]
+pub struct PolicyDiff {
+    /// attributes that only occur in the new policy
+    pub _added: Vec<String>,
+    /// attributes that only occur in the old policy
+    pub _removed: Vec<String>,
+    /// readable descriptions of the structural changes
+    pub _changes: Vec<String>,
+}
+
+impl PolicyDiff {
+    /// Returns true if both policies are equivalent (after canonicalization).
+    pub fn is_empty(&self) -> bool {
+        return self._added.is_empty() && self._removed.is_empty() && self._changes.is_empty();
+    }
+}
+
+impl Canonical {
+    fn leaves(&self, _leaves: &mut BTreeSet<String>) {
+        match *self {
+            Canonical::Leaf(ref _s) |
+            Canonical::Not(ref _s) => {
+                _leaves.insert(_s.clone());
+            }
+            Canonical::And(ref _children) |
+            Canonical::Or(ref _children) |
+            Canonical::Threshold(_, ref _children) => {
+                for _child in _children {
+                    _child.leaves(_leaves);
+                }
+            }
+        }
+    }
+
+    fn gate(&self) -> &'static str {
+        match *self {
+            Canonical::Leaf(_) => return "ATT",
+            Canonical::And(_) => return "AND",
+            Canonical::Or(_) => return "OR",
+            Canonical::Threshold(_, _) => return "THRESHOLD",
+            Canonical::Not(_) => return "NOT",
+        }
+    }
+
+    fn render(&self) -> String {
+        match *self {
+            Canonical::Leaf(ref _s) => return _s.clone(),
+            Canonical::Not(ref _s) => return format!("{}({})", self.gate(), _s),
+            Canonical::And(ref _children) |
+            Canonical::Or(ref _children) => {
+                let _list: Vec<String> = _children.iter().map(|_c| _c.render()).collect();
+                return format!("{}({})", self.gate(), _list.join(", "));
+            }
+            Canonical::Threshold(_k, ref _children) => {
+                let _list: Vec<String> = _children.iter().map(|_c| _c.render()).collect();
+                return format!("{}({} of {})", self.gate(), _k, _list.join(", "));
+            }
+        }
+    }
+}
+
+/// Compares two policies after bringing both into a canonical form (nested gates of the same
+/// type merged, children sorted and deduplicated) and reports the attributes added and removed
+/// as well as the structural changes. Both trees are walked recursively, their depth is
+/// bounded by the parser (see `MAX_POLICY_DEPTH`).
+///
+/// # Arguments
+///
+/// * `_old` - The old policy in JSON format
+/// * `_new` - The new policy in JSON format
+pub fn policy_diff(_old: &str, _new: &str) -> Result<PolicyDiff, RabeError> {
+    let _old_tree = Canonical::from_json(&policy_json(&_old.to_string())?)?;
+    let _new_tree = Canonical::from_json(&policy_json(&_new.to_string())?)?;
+    let mut _old_leaves: BTreeSet<String> = BTreeSet::new();
+    let mut _new_leaves: BTreeSet<String> = BTreeSet::new();
+    _old_tree.leaves(&mut _old_leaves);
+    _new_tree.leaves(&mut _new_leaves);
+    let mut _changes: Vec<String> = Vec::new();
+    compare(&_old_tree, &_new_tree, &String::from("/"), &mut _changes);
+    return Ok(PolicyDiff {
+        _added: _new_leaves.difference(&_old_leaves).cloned().collect(),
+        _removed: _old_leaves.difference(&_new_leaves).cloned().collect(),
+        _changes,
+    });
+}
+
+fn compare(_old: &Canonical, _new: &Canonical, _path: &String, _changes: &mut Vec<String>) {
+    if _old == _new {
+        return;
+    }
+    let (_old_children, _new_children) = match (_old, _new) {
+        (&Canonical::And(ref _o), &Canonical::And(ref _n)) |
+        (&Canonical::Or(ref _o), &Canonical::Or(ref _n)) => (_o, _n),
+        (&Canonical::Threshold(_old_k, ref _o), &Canonical::Threshold(_new_k, ref _n)) => {
+            if _old_k != _new_k {
+                _changes.push(format!("{}: k changed from {} to {}", _path, _old_k, _new_k));
+            }
+            (_o, _n)
+        }
+        (&Canonical::And(ref _o), &Canonical::Or(ref _n)) |
+        (&Canonical::Or(ref _o), &Canonical::And(ref _n)) => {
+            _changes.push(format!("{}: {} changed to {}", _path, _old.gate(), _new.gate()));
+            (_o, _n)
+        }
+        _ => {
+            _changes.push(format!(
+                "{}: {} replaced by {}",
+                _path,
+                _old.render(),
+                _new.render()
+            ));
+            return;
+        }
+    };
+    let _removed: Vec<&Canonical> = _old_children
+        .iter()
+        .filter(|_c| !_new_children.contains(*_c))
+        .collect();
+    let _added: Vec<&Canonical> = _new_children
+        .iter()
+        .filter(|_c| !_old_children.contains(*_c))
+        .collect();
+    // a single changed child is compared in detail
+    if _removed.len() == 1 && _added.len() == 1 {
+        let _index = _new_children.iter().position(|_c| _c == _added[0]).unwrap_or(0);
+        compare(_removed[0], _added[0], &format!("{}{}/", _path, _index), _changes);
+        return;
+    }
+    for _child in _removed {
+        _changes.push(format!("{}: removed {}", _path, _child.render()));
+    }
+    for _child in _added {
+        _changes.push(format!("{}: added {}", _path, _child.render()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_diff_equivalent() {
+        // order, duplicates and nesting of the same gate do not matter
+        let _diff = policy_diff(
+            r#"{"AND": [{"ATT": "A"}, {"AND": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+            r#"{"AND": [{"ATT": "C"}, {"ATT": "B"}, {"ATT": "A"}, {"ATT": "A"}]}"#,
+        ).unwrap();
+        assert_eq!(_diff.is_empty(), true);
+    }
+
+    #[test]
+    fn test_diff_added_leaf() {
+        let _diff = policy_diff(
+            r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#,
+            r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}"#,
+        ).unwrap();
+        assert_eq!(_diff._added, vec![String::from("C")]);
+        assert_eq!(_diff._removed.is_empty(), true);
+        assert_eq!(_diff._changes, vec![String::from("/: added C")]);
+    }
+
+    #[test]
+    fn test_diff_removed_leaf() {
+        let _diff = policy_diff(
+            r#"{"OR": [{"ATT": "A"}, {"AND": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+            r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#,
+        ).unwrap();
+        assert_eq!(_diff._added.is_empty(), true);
+        assert_eq!(_diff._removed, vec![String::from("C")]);
+        assert_eq!(_diff._changes, vec![String::from("/1/: AND(B, C) replaced by B")]);
+    }
+
+    #[test]
+    fn test_diff_and_to_or() {
+        let _diff = policy_diff(
+            r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#,
+            r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#,
+        ).unwrap();
+        assert_eq!(_diff._added.is_empty(), true);
+        assert_eq!(_diff._removed.is_empty(), true);
+        assert_eq!(_diff._changes, vec![String::from("/: AND changed to OR")]);
+        // nested changes are reported with their path
+        let _diff = policy_diff(
+            r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+            r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "D"}]}]}"#,
+        ).unwrap();
+        assert_eq!(_diff._changes, vec![String::from("/1/1/: C replaced by D")]);
+        // an OR turned into an AND is merged into its parent AND
+        let _diff = policy_diff(
+            r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+            r#"{"AND": [{"ATT": "A"}, {"AND": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+        ).unwrap();
+        assert_eq!(
+            _diff._changes,
+            vec![
+                String::from("/: removed OR(B, C)"),
+                String::from("/: added B"),
+                String::from("/: added C"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_threshold() {
+        let _diff = policy_diff(
+            r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}}"#,
+            r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "C"}, {"ATT": "B"}, {"ATT": "A"}]}}"#,
+        ).unwrap();
+        assert_eq!(_diff.is_empty(), true);
+        let _diff = policy_diff(
+            r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}}"#,
+            r#"{"THRESHOLD": {"k": 3, "children": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}}"#,
+        ).unwrap();
+        assert_eq!(_diff._changes, vec![String::from("/: k changed from 2 to 3")]);
+    }
+
+    #[test]
+    fn test_diff_not() {
+        let _diff = policy_diff(
+            r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#,
+            r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#,
+        ).unwrap();
+        assert_eq!(_diff._added.is_empty(), true);
+        assert_eq!(_diff._changes, vec![String::from("/1/: NOT(B) replaced by B")]);
+    }
+
+    #[test]
+    fn test_diff_invalid() {
+        assert_eq!(policy_diff("joking-around?", r#"{"ATT": "A"}"#).is_err(), true);
+        assert_eq!(policy_diff(r#"{"ATT": "A"}"#, r#"{"foo": "bar"}"#).is_err(), true);
+    }
+}
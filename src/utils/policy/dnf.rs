@@ -29,7 +29,6 @@ impl DnfPolicy {
     ) -> Option<DnfPolicy> {
         match string_to_json(_policy) {
             None => {
-                println!("Error parsing policy");
                 return None;
             }
             Some(_j) => {
@@ -110,7 +109,6 @@ impl PublicAttributeKey for BdabePublicAttributeKey {
 
 pub fn policy_in_dnf(p: &serde_json::Value, conjunction: bool) -> bool {
     if *p == serde_json::Value::Null {
-        println!("Error passed null!");
         return false;
     }
     let mut ret = true;
@@ -134,7 +132,6 @@ pub fn policy_in_dnf(p: &serde_json::Value, conjunction: bool) -> bool {
     else if p["ATT"] != serde_json::Value::Null {
         return true;
     } else {
-        println!("Policy invalid. No AND or OR found");
         return false;
     }
 }
@@ -147,7 +144,6 @@ pub fn dnf<K: PublicAttributeKey>(
     _i: usize,
 ) -> bool {
     if *_p == serde_json::Value::Null {
-        println!("Error passed null!");
         return false;
     }
     let mut ret = true;
@@ -193,13 +189,11 @@ pub fn dnf<K: PublicAttributeKey>(
                 }
             }
             None => {
-                println!("ERROR attribute value");
                 return false;
             }
         }
         return true;
     } else {
-        println!("Policy invalid. No AND or OR found");
         return false;
     }
 }
@@ -0,0 +1,299 @@
+//! Parsing of policies written as boolean expressions, e.g. `A and (B or C)`.
+//!
+extern crate serde_json;
+
+use std::string::String;
+use utils::error::RabeError;
+use utils::secretsharing::MAX_POLICY_DEPTH;
+
+// Infix policy symbols
+const INFIX_OPEN: char = '(';
+const INFIX_CLOSE: char = ')';
+const INFIX_QUOTE: char = '"';
+const INFIX_ESCAPE: char = '\\';
+const INFIX_AND: &str = "and";
+const INFIX_OR: &str = "or";
+
+#[derive(PartialEq, Clone, Debug)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Attribute(String),
+}
+
+/// Converts a policy given as boolean expression into its JSON representation, e.g.
+/// `A and (B or C)` becomes `{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}`.
+///
+/// The operators `and` and `or` are case insensitive and `and` binds tighter than `or`.
+/// Chains of the same operator become a single gate, parentheses are kept as written.
+/// Attribute names containing spaces, parentheses or quotes, or names equal to an operator,
+/// have to be quoted with `"`; inside quotes a backslash escapes the next character.
+/// Parentheses nested deeper than `MAX_POLICY_DEPTH` are rejected with
+/// `RabeError::PolicyTooDeep`.
+///
+/// # Arguments
+///
+/// * `_input` - A policy as boolean expression
+pub fn parse_policy(_input: &str) -> Result<String, RabeError> {
+    let _tokens = tokenize(_input)?;
+    if _tokens.is_empty() {
+        return Err(RabeError::EmptyPolicy);
+    }
+    let mut _parser = Parser {
+        _tokens,
+        _pos: 0,
+        _end: _input.chars().count(),
+    };
+    let _json = _parser.parse_or(1)?;
+    if let Some(&(_at, ref _token)) = _parser.peek() {
+        return Err(RabeError::PolicyParse(
+            format!("unexpected {} at position {}", describe(_token), _at),
+        ));
+    }
+    return Ok(_json.to_string());
+}
+
+fn tokenize(_input: &str) -> Result<Vec<(usize, Token)>, RabeError> {
+    let _chars: Vec<char> = _input.chars().collect();
+    let mut _tokens: Vec<(usize, Token)> = Vec::new();
+    let mut _pos = 0usize;
+    while _pos < _chars.len() {
+        let _c = _chars[_pos];
+        if _c.is_whitespace() {
+            _pos += 1;
+        } else if _c == INFIX_OPEN {
+            _tokens.push((_pos, Token::Open));
+            _pos += 1;
+        } else if _c == INFIX_CLOSE {
+            _tokens.push((_pos, Token::Close));
+            _pos += 1;
+        } else if _c == INFIX_QUOTE {
+            let _start = _pos;
+            let mut _attr = String::new();
+            _pos += 1;
+            loop {
+                if _pos == _chars.len() {
+                    return Err(RabeError::PolicyParse(
+                        format!("unterminated quote at position {}", _start),
+                    ));
+                }
+                if _chars[_pos] == INFIX_QUOTE {
+                    _pos += 1;
+                    break;
+                }
+                if _chars[_pos] == INFIX_ESCAPE && _pos + 1 < _chars.len() {
+                    _pos += 1;
+                }
+                _attr.push(_chars[_pos]);
+                _pos += 1;
+            }
+            if _attr.is_empty() {
+                return Err(RabeError::PolicyParse(
+                    format!("empty attribute at position {}", _start),
+                ));
+            }
+            _tokens.push((_start, Token::Attribute(_attr)));
+        } else {
+            let _start = _pos;
+            let mut _word = String::new();
+            while _pos < _chars.len() && !_chars[_pos].is_whitespace() &&
+                _chars[_pos] != INFIX_OPEN && _chars[_pos] != INFIX_CLOSE &&
+                _chars[_pos] != INFIX_QUOTE
+            {
+                _word.push(_chars[_pos]);
+                _pos += 1;
+            }
+            let _token = if _word.to_lowercase() == INFIX_AND {
+                Token::And
+            } else if _word.to_lowercase() == INFIX_OR {
+                Token::Or
+            } else {
+                Token::Attribute(_word)
+            };
+            _tokens.push((_start, _token));
+        }
+    }
+    return Ok(_tokens);
+}
+
+fn describe(_token: &Token) -> String {
+    match *_token {
+        Token::Open => return format!("'{}'", INFIX_OPEN),
+        Token::Close => return format!("'{}'", INFIX_CLOSE),
+        Token::And => return String::from(INFIX_AND),
+        Token::Or => return String::from(INFIX_OR),
+        Token::Attribute(ref _s) => return format!("attribute {}", _s),
+    }
+}
+
+struct Parser {
+    _tokens: Vec<(usize, Token)>,
+    _pos: usize,
+    // the length of the input, the position reported if the input ends too early
+    _end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        return self._tokens.get(self._pos);
+    }
+
+    // the position of the current token, or the end of the input
+    fn position(&self) -> usize {
+        match self.peek() {
+            Some(&(_at, _)) => return _at,
+            None => return self._end,
+        }
+    }
+
+    // _depth is the nesting of parentheses, which bounds the recursion
+    fn parse_or(&mut self, _depth: usize) -> Result<serde_json::Value, RabeError> {
+        if _depth > MAX_POLICY_DEPTH {
+            return Err(RabeError::PolicyTooDeep);
+        }
+        let mut _children = vec![self.parse_and(_depth)?];
+        while let Some(&(_, Token::Or)) = self.peek() {
+            self._pos += 1;
+            _children.push(self.parse_and(_depth)?);
+        }
+        return Ok(gate("OR", _children));
+    }
+
+    fn parse_and(&mut self, _depth: usize) -> Result<serde_json::Value, RabeError> {
+        let mut _children = vec![self.parse_factor(_depth)?];
+        while let Some(&(_, Token::And)) = self.peek() {
+            self._pos += 1;
+            _children.push(self.parse_factor(_depth)?);
+        }
+        return Ok(gate("AND", _children));
+    }
+
+    fn parse_factor(&mut self, _depth: usize) -> Result<serde_json::Value, RabeError> {
+        let _at = self.position();
+        let _token = self.peek().map(|_t| _t.1.clone());
+        match _token {
+            Some(Token::Open) => {
+                self._pos += 1;
+                let _json = self.parse_or(_depth + 1)?;
+                match self.peek() {
+                    Some(&(_, Token::Close)) => {
+                        self._pos += 1;
+                        return Ok(_json);
+                    }
+                    _ => {
+                        return Err(RabeError::PolicyParse(
+                            format!("expected '{}' at position {}", INFIX_CLOSE, self.position()),
+                        ))
+                    }
+                }
+            }
+            Some(Token::Attribute(_attr)) => {
+                self._pos += 1;
+                let mut _node = serde_json::Map::new();
+                _node.insert(String::from("ATT"), serde_json::Value::String(_attr));
+                return Ok(serde_json::Value::Object(_node));
+            }
+            _ => {
+                return Err(RabeError::PolicyParse(
+                    format!("expected attribute or '{}' at position {}", INFIX_OPEN, _at),
+                ))
+            }
+        }
+    }
+}
+
+// a gate with the given children, or the child itself if there is only one
+fn gate(_gate: &str, mut _children: Vec<serde_json::Value>) -> serde_json::Value {
+    if _children.len() == 1 {
+        return _children.remove(0);
+    }
+    let mut _node = serde_json::Map::new();
+    _node.insert(String::from(_gate), serde_json::Value::Array(_children));
+    return serde_json::Value::Object(_node);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn json(_policy: &str) -> serde_json::Value {
+        return serde_json::from_str(_policy).unwrap();
+    }
+
+    #[test]
+    fn test_parse_policy_equivalents() {
+        let _policies = vec![
+            ("A", r#"{"ATT": "A"}"#),
+            ("A and B", r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#),
+            ("A or B", r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#),
+            ("A AND B AND C", r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}"#),
+            (
+                "A and B or C",
+                r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"ATT": "C"}]}"#,
+            ),
+            (
+                "A and (B or C)",
+                r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+            ),
+            (
+                "(A or B) and (C and D)",
+                r#"{"AND": [{"OR": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}"#,
+            ),
+            (
+                "((A))or(B and C)",
+                r#"{"OR": [{"ATT": "A"}, {"AND": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+            ),
+        ];
+        for (_infix, _policy) in _policies {
+            assert_eq!(json(&parse_policy(_infix).unwrap()), json(_policy));
+        }
+    }
+
+    #[test]
+    fn test_parse_policy_quoted() {
+        let _json = json(&parse_policy(r#""first name" and "and" or "say \"hi\"""#).unwrap());
+        assert_eq!(_json["OR"][0]["AND"][0]["ATT"], "first name");
+        assert_eq!(_json["OR"][0]["AND"][1]["ATT"], "and");
+        assert_eq!(_json["OR"][1]["ATT"], "say \"hi\"");
+    }
+
+    #[test]
+    fn test_parse_policy_errors() {
+        assert_eq!(parse_policy("  "), Err(RabeError::EmptyPolicy));
+        assert_eq!(
+            parse_policy("A and"),
+            Err(RabeError::PolicyParse(
+                String::from("expected attribute or '(' at position 5"),
+            ))
+        );
+        assert_eq!(
+            parse_policy("(A or B"),
+            Err(RabeError::PolicyParse(String::from("expected ')' at position 7")))
+        );
+        assert_eq!(
+            parse_policy("A B"),
+            Err(RabeError::PolicyParse(String::from("unexpected attribute B at position 2")))
+        );
+        assert_eq!(
+            parse_policy(r#"A or "B"#),
+            Err(RabeError::PolicyParse(String::from("unterminated quote at position 5")))
+        );
+        for _infix in ["or A", "A and ()", "A)", r#""" and A"#].iter() {
+            assert_eq!(parse_policy(_infix).is_err(), true);
+        }
+    }
+
+    #[test]
+    fn test_parse_policy_deep() {
+        let _nested = |_depth: usize| {
+            format!("{}B{}", "A and (".repeat(_depth - 1), ")".repeat(_depth - 1))
+        };
+        assert_eq!(parse_policy(&_nested(MAX_POLICY_DEPTH)).is_ok(), true);
+        assert_eq!(parse_policy(&_nested(MAX_POLICY_DEPTH + 1)), Err(RabeError::PolicyTooDeep));
+        // several thousand levels return an error instead of overflowing the stack
+        assert_eq!(parse_policy(&"(".repeat(100000)), Err(RabeError::PolicyTooDeep));
+    }
+}
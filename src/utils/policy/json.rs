@@ -0,0 +1,102 @@
+extern crate serde_json;
+
+use std::string::String;
+use utils::error::RabeError;
+use utils::policy::attributes::AttributeSet;
+use utils::policy::numeric::expand_numeric_attributes;
+use utils::secretsharing::{missing_attributes, policy_json};
+
+/// An access policy in JSON format
+#[derive(PartialEq, Clone, Debug)]
+pub struct Policy {
+    pub _policy: String,
+    /// the policy tree, with comparisons of numeric attributes expanded as in `encrypt`
+    pub _json: serde_json::Value,
+}
+
+impl Policy {
+    /// Returns a new Policy parsed from its JSON representation, the same way `encrypt` parses
+    /// policies (including comparisons of numeric attributes, e.g. `GE`).
+    ///
+    /// # Arguments
+    ///
+    /// * `_policy` - A policy in JSON format as String
+    pub fn from_string(_policy: &String) -> Result<Policy, RabeError> {
+        let _json = policy_json(_policy)?;
+        // make sure the policy tree itself is well formed
+        missing_attributes(&Vec::new(), &_json)?;
+        return Ok(Policy {
+            _policy: _policy.to_string(),
+            _json,
+        });
+    }
+
+    /// Returns a smallest list of attributes that has to be added to `_attributes` so that
+    /// they satisfy this policy. The list is empty if `_attributes` already satisfy the policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `_attributes` - An AttributeSet
+    pub fn required_for(&self, _attributes: &AttributeSet) -> Vec<String> {
+        // numeric attributes are compared through their bits, as in a key
+        let _attributes = match expand_numeric_attributes(_attributes.attributes()) {
+            Err(_) => return Vec::new(),
+            Ok(_attributes) => _attributes,
+        };
+        // the policy tree has been checked by from_string
+        match missing_attributes(&_attributes, &self._json) {
+            Err(_) => return Vec::new(),
+            Ok(_missing) => return _missing,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_policy_required_for() {
+        let _policy = Policy::from_string(&String::from(
+            r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+        )).unwrap();
+        let _a = AttributeSet::new(&vec![String::from("A")]);
+        let _ac = AttributeSet::new(&vec![String::from("A"), String::from("C")]);
+        let _bc = AttributeSet::new(&vec![String::from("B"), String::from("C")]);
+        assert_eq!(_policy.required_for(&_a), vec![String::from("B")]);
+        assert_eq!(_policy.required_for(&_ac).is_empty(), true);
+        assert_eq!(_policy.required_for(&_bc), vec![String::from("A")]);
+        assert_eq!(_a.satisfies(&_policy), false);
+        assert_eq!(_ac.satisfies(&_policy), true);
+        assert_eq!(_bc.satisfies(&_policy), false);
+    }
+
+    #[test]
+    fn test_policy_numeric() {
+        let _policy = Policy::from_string(&String::from(
+            r#"{"AND": [{"ATT": "A"}, {"GE": {"att": "age", "value": 18}}]}"#,
+        )).unwrap();
+        let _adult = AttributeSet::new(&vec![String::from("A"), String::from("age=42")]);
+        let _minor = AttributeSet::new(&vec![String::from("A"), String::from("age=17")]);
+        assert_eq!(_policy.required_for(&_adult).is_empty(), true);
+        assert_eq!(_policy.required_for(&_minor).is_empty(), false);
+        assert_eq!(_adult.satisfies(&_policy), true);
+        assert_eq!(_minor.satisfies(&_policy), false);
+        for _p in vec![
+            r#"{"LE": {"att": "age", "value": 18}}"#,
+            r#"{"EQ": {"att": "age", "value": 18}}"#,
+        ] {
+            assert_eq!(Policy::from_string(&String::from(_p)).is_ok(), true);
+        }
+    }
+
+    #[test]
+    fn test_policy_parse_error() {
+        assert_eq!(Policy::from_string(&String::from("joking-around?")).is_err(), true);
+        assert_eq!(
+            Policy::from_string(&String::from(r#"{"NAND": [{"ATT": "A"}]}"#)).is_err(),
+            true
+        );
+    }
+}
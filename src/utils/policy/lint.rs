@@ -0,0 +1,219 @@
+//! Checks of JSON policies for redundant or needlessly complex parts.
+//!
+extern crate serde_json;
+
+use std::collections::HashSet;
+use std::string::String;
+use utils::secretsharing::{policy_json, policy_node, PolicyNode};
+
+/// The kind of issue found by `lint_policy`
+#[derive(PartialEq, Clone, Debug)]
+pub enum PolicyLintKind {
+    /// The policy can not be parsed or is not a valid policy tree
+    InvalidPolicy,
+    /// An OR branch requires a superset of the attributes of a sibling branch
+    RedundantOrBranch,
+    /// An AND contains the same leaf more than once
+    DuplicateLeaf,
+    /// A gate has a child gate of the same type, which could be merged into it
+    FlattenableGate,
+}
+
+/// An issue found by `lint_policy`
+#[derive(PartialEq, Clone, Debug)]
+pub struct PolicyLint {
+    pub _kind: PolicyLintKind,
+    /// the child indices leading from the root of the policy to the node
+    pub _path: Vec<usize>,
+    pub _message: String,
+}
+
+/// Checks a policy for redundant OR branches, duplicate leaves in ANDs and gates that could
+/// be flattened. Returns an empty list if no issues were found. The policy is walked
+/// recursively, its depth is bounded by the parser (see `MAX_POLICY_DEPTH`).
+///
+/// # Arguments
+///
+/// * `_policy` - A policy in JSON format
+pub fn lint_policy(_policy: &str) -> Vec<PolicyLint> {
+    let mut _lints: Vec<PolicyLint> = Vec::new();
+    match policy_json(&_policy.to_string()) {
+        Err(_e) => _lints.push(lint(PolicyLintKind::InvalidPolicy, &Vec::new(), _e.to_string())),
+        Ok(_json) => lint_node(&_json, &mut Vec::new(), &mut _lints),
+    }
+    return _lints;
+}
+
+fn lint(_kind: PolicyLintKind, _path: &Vec<usize>, _message: String) -> PolicyLint {
+    return PolicyLint {
+        _kind,
+        _path: _path.clone(),
+        _message,
+    };
+}
+
+fn lint_node(_json: &serde_json::Value, _path: &mut Vec<usize>, _lints: &mut Vec<PolicyLint>) {
+    let (_gate, _children) = match policy_node(_json) {
+        Err(_e) => {
+            _lints.push(lint(PolicyLintKind::InvalidPolicy, _path, _e.to_string()));
+            return;
+        }
+        Ok(PolicyNode::Leaf(_)) |
+        Ok(PolicyNode::Not(_)) => return,
+        Ok(PolicyNode::And(_children)) => ("AND", _children),
+        Ok(PolicyNode::Or(_children)) => ("OR", _children),
+        // thresholds are not simplified, only their children are checked
+        Ok(PolicyNode::Threshold(_, _children)) => {
+            lint_children(_children, _path, _lints);
+            return;
+        }
+    };
+    // child gates of the same type
+    for (_i, _child) in _children.iter().enumerate() {
+        if _child.get(_gate).is_some() {
+            _path.push(_i);
+            _lints.push(lint(
+                PolicyLintKind::FlattenableGate,
+                _path,
+                format!("{} directly inside {} can be merged into its parent", _gate, _gate),
+            ));
+            _path.pop();
+        }
+    }
+    if _gate == "AND" {
+        // the same leaf twice
+        let mut _seen: HashSet<&str> = HashSet::new();
+        for (_i, _child) in _children.iter().enumerate() {
+            if let Ok(PolicyNode::Leaf(_s)) = policy_node(_child) {
+                if !_seen.insert(_s) {
+                    _path.push(_i);
+                    _lints.push(lint(
+                        PolicyLintKind::DuplicateLeaf,
+                        _path,
+                        format!("attribute {} appears more than once in this AND", _s),
+                    ));
+                    _path.pop();
+                }
+            }
+        }
+    } else {
+        // a branch requiring all attributes of a sibling (and possibly more) is never needed
+        let _sets: Vec<Option<HashSet<String>>> = _children.iter().map(conjunction).collect();
+        for (_i, _set) in _sets.iter().enumerate() {
+            if let Some(ref _set) = *_set {
+                let _redundant = _sets.iter().enumerate().any(|(_j, _other)| match *_other {
+                    Some(ref _other) if _i != _j => {
+                        _other.is_subset(_set) && (_other.len() < _set.len() || _j < _i)
+                    }
+                    _ => false,
+                });
+                if _redundant {
+                    _path.push(_i);
+                    _lints.push(lint(
+                        PolicyLintKind::RedundantOrBranch,
+                        _path,
+                        String::from("this OR branch requires all attributes of a sibling branch"),
+                    ));
+                    _path.pop();
+                }
+            }
+        }
+    }
+    lint_children(_children, _path, _lints);
+}
+
+fn lint_children(
+    _children: &Vec<serde_json::Value>,
+    _path: &mut Vec<usize>,
+    _lints: &mut Vec<PolicyLint>,
+) {
+    for (_i, _child) in _children.iter().enumerate() {
+        _path.push(_i);
+        lint_node(_child, _path, _lints);
+        _path.pop();
+    }
+}
+
+// the attributes of a node that is a leaf or an AND of leaves (recursively), None otherwise
+fn conjunction(_json: &serde_json::Value) -> Option<HashSet<String>> {
+    match policy_node(_json) {
+        Ok(PolicyNode::Leaf(_s)) => {
+            let mut _set = HashSet::new();
+            _set.insert(_s.to_string());
+            return Some(_set);
+        }
+        Ok(PolicyNode::And(_children)) => {
+            let mut _set = HashSet::new();
+            for _child in _children {
+                _set.extend(conjunction(_child)?);
+            }
+            return Some(_set);
+        }
+        _ => return None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn kinds(_policy: &str) -> Vec<PolicyLintKind> {
+        return lint_policy(_policy).into_iter().map(|_l| _l._kind).collect();
+    }
+
+    #[test]
+    fn test_lint_clean() {
+        assert_eq!(lint_policy(r#"{"ATT": "A"}"#).is_empty(), true);
+        assert_eq!(
+            lint_policy(r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#)
+                .is_empty(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_lint_redundant_or() {
+        let _lints = lint_policy(
+            r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"ATT": "A"}]}"#,
+        );
+        assert_eq!(_lints.len(), 1);
+        assert_eq!(_lints[0]._kind, PolicyLintKind::RedundantOrBranch);
+        assert_eq!(_lints[0]._path, vec![0]);
+        // of two equal branches only the second one is redundant
+        let _lints = lint_policy(r#"{"OR": [{"ATT": "A"}, {"ATT": "A"}]}"#);
+        assert_eq!(_lints.len(), 1);
+        assert_eq!(_lints[0]._path, vec![1]);
+    }
+
+    #[test]
+    fn test_lint_duplicate_leaf() {
+        let _lints = lint_policy(
+            r#"{"OR": [{"ATT": "C"}, {"AND": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "A"}]}]}"#,
+        );
+        assert_eq!(_lints.len(), 1);
+        assert_eq!(_lints[0]._kind, PolicyLintKind::DuplicateLeaf);
+        assert_eq!(_lints[0]._path, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_lint_flattenable() {
+        assert_eq!(
+            kinds(r#"{"AND": [{"ATT": "A"}, {"AND": [{"ATT": "B"}, {"ATT": "C"}]}]}"#),
+            vec![PolicyLintKind::FlattenableGate]
+        );
+        assert_eq!(
+            kinds(r#"{"OR": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#),
+            vec![PolicyLintKind::FlattenableGate]
+        );
+    }
+
+    #[test]
+    fn test_lint_invalid() {
+        assert_eq!(kinds("joking-around?"), vec![PolicyLintKind::InvalidPolicy]);
+        let _lints = lint_policy(r#"{"AND": [{"ATT": "A"}, {"foo": "bar"}]}"#);
+        assert_eq!(_lints.len(), 1);
+        assert_eq!(_lints[0]._kind, PolicyLintKind::InvalidPolicy);
+        assert_eq!(_lints[0]._path, vec![1]);
+    }
+}
@@ -1,2 +1,13 @@
+pub mod attributes;
+pub mod compact;
+#[cfg(feature = "test-utils")]
+pub mod debug;
+pub mod diff;
 pub mod dnf;
+pub mod infix;
+pub mod json;
+pub mod lint;
 pub mod msp;
+pub mod numeric;
+#[cfg(feature = "test-utils")]
+pub mod sample;
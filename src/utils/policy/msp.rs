@@ -30,7 +30,6 @@ impl AbePolicy {
     pub fn from_string(_policy: &String) -> Option<AbePolicy> {
         match string_to_json(_policy) {
             None => {
-                println!("Error parsing policy");
                 return None;
             }
             Some(json) => {
@@ -48,13 +47,11 @@ fn lw(msp: &mut AbePolicy, p: &serde_json::Value, v: Vec<i32>) -> bool {
     let mut v_tmp_right = v.clone();
 
     if *p == serde_json::Value::Null {
-        println!("Error passed null!");
         return false;
     }
     // inner node
     if p["OR"].is_array() {
         if p["OR"].as_array().unwrap().len() < 2 {
-            println!("Invalid policy. Number of arguments under OR < 2");
             return false;
         }
         let mut _ret = true;
@@ -64,12 +61,10 @@ fn lw(msp: &mut AbePolicy, p: &serde_json::Value, v: Vec<i32>) -> bool {
         return _ret;
     } else if p["AND"].is_array() {
         if p["AND"].as_array().unwrap().len() != 2 {
-            println!("Invalid policy. Number of arguments under AND != 2");
             return false;
         }
         let left = &p["AND"][0];
         if left["OR"] != serde_json::Value::Null {
-            println!("Invalid policy. Not in DNF");
             return false;
         }
         v_tmp_right.resize(msp._deg, ZERO);
@@ -81,14 +76,15 @@ fn lw(msp: &mut AbePolicy, p: &serde_json::Value, v: Vec<i32>) -> bool {
     }
     //Leaf
     else if p["ATT"] != serde_json::Value::Null {
-        msp._m.insert(0, v_tmp_right);
         match p["ATT"].as_str() {
-            Some(s) => msp._pi.insert(0, String::from(s)),
-            None => println!("ERROR attribute value"),
+            Some(s) => {
+                msp._m.insert(0, v_tmp_right);
+                msp._pi.insert(0, String::from(s));
+            }
+            None => return false,
         }
         return true;
     } else {
-        println!("Policy invalid. No AND or OR found");
         return false;
     }
 }
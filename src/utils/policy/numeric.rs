@@ -0,0 +1,271 @@
+//! Numeric attributes and comparisons, e.g. `age=18` and `{"GE": {"att": "age", "value": 18}}`.
+//!
+//! Numeric attributes are opt-in: only attributes written `name=value` are numeric, other
+//! attributes such as `time:41` are ordinary attributes, whatever they look like. A numeric
+//! attribute is decomposed into one bit attribute `#name:bit<i>:<0|1>` per bit of its value
+//! (see `NUMERIC_BITS`). A comparison is rewritten into a tree of ANDs and ORs of these bit
+//! attributes, so that the secret sharing only sees ordinary leaves:
+//!
+//! * `{"GE": {"att": "age", "value": 18}}` - the value of `age` is at least 18
+//! * `{"LE": {"att": "age", "value": 18}}` - the value of `age` is at most 18
+//! * `{"EQ": {"att": "age", "value": 18}}` - the value of `age` is 18, i.e. `{"ATT": "age=18"}`
+//!
+extern crate serde_json;
+
+use std::collections::HashMap;
+use std::string::String;
+use utils::error::RabeError;
+use utils::secretsharing::RESERVED_PREFIX;
+
+/// The number of bits of the values of numeric attributes, values are in 0..2^32
+pub const NUMERIC_BITS: usize = 32;
+
+/// The prefix of bit attributes, which a key only holds along with their numeric attribute
+pub const BIT_ATTRIBUTE_PREFIX: &'static str = RESERVED_PREFIX;
+
+// Comparison symbols
+const NUMERIC_SEPARATOR: char = '=';
+const BIT_SEPARATOR: char = ':';
+const COMPARISON_GE: &'static str = "GE";
+const COMPARISON_LE: &'static str = "LE";
+const COMPARISON_EQ: &'static str = "EQ";
+const COMPARISON_ATT: &'static str = "att";
+const COMPARISON_VALUE: &'static str = "value";
+
+/// Splits a numeric attribute `name=value` into its name and value. Returns `None` if the
+/// attribute is not numeric, i.e. has no `=` or the value is not in 0..2^NUMERIC_BITS.
+pub fn numeric_attribute(_attr: &str) -> Option<(&str, u64)> {
+    let _pos = _attr.rfind(NUMERIC_SEPARATOR)?;
+    let (_name, _value) = (&_attr[.._pos], &_attr[_pos + 1..]);
+    if _name.is_empty() || !_value.chars().all(|_c| _c.is_ascii_digit()) {
+        return None;
+    }
+    match _value.parse::<u64>() {
+        Ok(_v) if _v >> NUMERIC_BITS == 0 => return Some((_name, _v)),
+        _ => return None,
+    }
+}
+
+/// The bit attribute `#name:bit<i>:<bit>` of bit `_i` (0 is the least significant bit).
+pub fn bit_attribute(_name: &str, _i: usize, _bit: u64) -> String {
+    return format!(
+        "{}{}{}bit{}{}{}",
+        BIT_ATTRIBUTE_PREFIX,
+        _name,
+        BIT_SEPARATOR,
+        _i,
+        BIT_SEPARATOR,
+        _bit
+    );
+}
+
+/// Adds the bit attributes of every numeric attribute (see `numeric_attribute`) to a set of
+/// attributes, e.g. before issuing a key. The original attributes are kept.
+///
+/// # Arguments
+///
+///	* `_attributes` - A Vector of String attributes
+///
+/// # Errors
+///
+///	* `RabeError::InvalidAttribute` - if the set holds two values of the same numeric attribute
+pub fn expand_numeric_attributes(_attributes: &Vec<String>) -> Result<Vec<String>, RabeError> {
+    let mut _expanded = _attributes.clone();
+    let mut _values: HashMap<&str, u64> = HashMap::new();
+    for _attr in _attributes {
+        if let Some((_name, _value)) = numeric_attribute(_attr) {
+            match _values.insert(_name, _value) {
+                Some(_other) if _other != _value => {
+                    return Err(RabeError::InvalidAttribute(
+                        format!("{} has more than one value", _name),
+                    ))
+                }
+                Some(_) => continue,
+                None => {}
+            }
+            for _i in 0..NUMERIC_BITS {
+                let _bit = bit_attribute(_name, _i, (_value >> _i) & 1);
+                if !_expanded.contains(&_bit) {
+                    _expanded.push(_bit);
+                }
+            }
+        }
+    }
+    return Ok(_expanded);
+}
+
+/// Rewrites all comparisons of a JSON policy into trees of bit attributes. Other nodes are
+/// kept as they are and checked later on.
+pub fn expand_comparisons(_json: serde_json::Value) -> Result<serde_json::Value, RabeError> {
+    match _json {
+        serde_json::Value::Object(_map) => {
+            for _op in [COMPARISON_GE, COMPARISON_LE, COMPARISON_EQ].iter() {
+                if let Some(_comparison) = _map.get(*_op) {
+                    return comparison(_op, _comparison);
+                }
+            }
+            let mut _result = serde_json::Map::new();
+            for (_key, _value) in _map {
+                _result.insert(_key, expand_comparisons(_value)?);
+            }
+            return Ok(serde_json::Value::Object(_result));
+        }
+        serde_json::Value::Array(_children) => {
+            let mut _result: Vec<serde_json::Value> = Vec::new();
+            for _child in _children {
+                _result.push(expand_comparisons(_child)?);
+            }
+            return Ok(serde_json::Value::Array(_result));
+        }
+        _ => return Ok(_json),
+    }
+}
+
+fn comparison(_op: &str, _comparison: &serde_json::Value) -> Result<serde_json::Value, RabeError> {
+    let _name = match _comparison.get(COMPARISON_ATT).and_then(|_a| _a.as_str()) {
+        Some(_name) if !_name.is_empty() => _name,
+        _ => return Err(RabeError::InvalidPolicy(format!("{} without an attribute", _op))),
+    };
+    let _value = match _comparison.get(COMPARISON_VALUE).and_then(|_v| _v.as_u64()) {
+        Some(_value) if _value >> NUMERIC_BITS == 0 => _value,
+        _ => {
+            return Err(RabeError::InvalidPolicy(format!(
+                "{} without a value of at most {} bits",
+                _op,
+                NUMERIC_BITS
+            )))
+        }
+    };
+    if _op == COMPARISON_EQ {
+        return Ok(leaf(&format!("{}{}{}", _name, NUMERIC_SEPARATOR, _value)));
+    }
+    // x >= v: from the lowest bit set in v upwards, a set bit of v requires the bit of x and
+    // a cleared bit of v is satisfied by the bit of x OR the lower bits. x <= v is the same
+    // with the cleared bits of x and the bits of v inverted.
+    let (_required, _v) = if _op == COMPARISON_GE {
+        (1, _value)
+    } else {
+        (0, !_value & ((1u64 << NUMERIC_BITS) - 1))
+    };
+    if _v == 0 {
+        // every value compares true, any value of the attribute is required
+        let mut _node = serde_json::Map::new();
+        _node.insert(
+            String::from("OR"),
+            serde_json::Value::Array(vec![
+                leaf(&bit_attribute(_name, 0, 0)),
+                leaf(&bit_attribute(_name, 0, 1)),
+            ]),
+        );
+        return Ok(serde_json::Value::Object(_node));
+    }
+    let _lowest = _v.trailing_zeros() as usize;
+    let mut _tree = leaf(&bit_attribute(_name, _lowest, _required));
+    for _i in (_lowest + 1)..NUMERIC_BITS {
+        let _bit = leaf(&bit_attribute(_name, _i, _required));
+        let _gate = if (_v >> _i) & 1 == 1 { "AND" } else { "OR" };
+        let mut _node = serde_json::Map::new();
+        _node.insert(String::from(_gate), serde_json::Value::Array(vec![_bit, _tree]));
+        _tree = serde_json::Value::Object(_node);
+    }
+    return Ok(_tree);
+}
+
+fn leaf(_attr: &str) -> serde_json::Value {
+    let mut _node = serde_json::Map::new();
+    _node.insert(String::from("ATT"), serde_json::Value::String(_attr.to_string()));
+    return serde_json::Value::Object(_node);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::tools::traverse_json;
+
+    fn satisfies(_value: u64, _policy: &str) -> bool {
+        let _attr = expand_numeric_attributes(&vec![format!("age={}", _value)]).unwrap();
+        let _json = expand_comparisons(serde_json::from_str(_policy).unwrap()).unwrap();
+        return traverse_json(&_attr, &_json);
+    }
+
+    #[test]
+    fn test_numeric_attribute() {
+        assert_eq!(numeric_attribute("age=18"), Some(("age", 18)));
+        assert_eq!(numeric_attribute("a:b=7"), Some(("a:b", 7)));
+        assert_eq!(numeric_attribute("age"), None);
+        assert_eq!(numeric_attribute("age=-1"), None);
+        assert_eq!(numeric_attribute("age="), None);
+        assert_eq!(numeric_attribute("=18"), None);
+        assert_eq!(numeric_attribute("age=4294967296"), None);
+        // attributes that merely look numeric are ordinary attributes
+        assert_eq!(numeric_attribute("time:41"), None);
+        let _plain = vec![String::from("time:41"), String::from("time:42"), String::from("user:7")];
+        assert_eq!(expand_numeric_attributes(&_plain).unwrap(), _plain);
+        let _attr = expand_numeric_attributes(&vec![String::from("A"), String::from("age=5")])
+            .unwrap();
+        assert_eq!(_attr.len(), 2 + NUMERIC_BITS);
+        assert_eq!(_attr.contains(&bit_attribute("age", 0, 1)), true);
+        assert_eq!(_attr.contains(&bit_attribute("age", 1, 0)), true);
+        assert_eq!(_attr.contains(&bit_attribute("age", 2, 1)), true);
+        assert_eq!(bit_attribute("age", 2, 1), "#age:bit2:1");
+        assert_eq!(
+            expand_numeric_attributes(&vec![String::from("age=5"), String::from("age=6")]).is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_ge() {
+        let _policy = r#"{"GE": {"att": "age", "value": 18}}"#;
+        for _value in vec![18, 19, 30, 64, 4294967295] {
+            assert_eq!(satisfies(_value, _policy), true);
+        }
+        for _value in vec![0, 1, 16, 17] {
+            assert_eq!(satisfies(_value, _policy), false);
+        }
+        // every value is at least 0, but the attribute itself is required
+        assert_eq!(satisfies(0, r#"{"GE": {"att": "age", "value": 0}}"#), true);
+        assert_eq!(satisfies(4294967295, r#"{"LE": {"att": "age", "value": 4294967295}}"#), true);
+        let _json = expand_comparisons(
+            serde_json::from_str(r#"{"GE": {"att": "age", "value": 0}}"#).unwrap(),
+        ).unwrap();
+        assert_eq!(traverse_json(&vec![String::from("A")], &_json), false);
+    }
+
+    #[test]
+    fn test_le() {
+        let _policy = r#"{"LE": {"att": "age", "value": 18}}"#;
+        for _value in vec![0, 1, 17, 18] {
+            assert_eq!(satisfies(_value, _policy), true);
+        }
+        for _value in vec![19, 32, 4294967295] {
+            assert_eq!(satisfies(_value, _policy), false);
+        }
+    }
+
+    #[test]
+    fn test_eq_and_ranges() {
+        let _policy = r#"{"EQ": {"att": "age", "value": 18}}"#;
+        assert_eq!(satisfies(18, _policy), true);
+        assert_eq!(satisfies(19, _policy), false);
+        // comparisons can be combined with other gates
+        let _range = r#"{"AND": [{"GE": {"att": "age", "value": 18}}, {"LE": {"att": "age", "value": 65}}]}"#;
+        for _value in 0..100 {
+            assert_eq!(satisfies(_value, _range), _value >= 18 && _value <= 65);
+        }
+    }
+
+    #[test]
+    fn test_invalid_comparisons() {
+        for _policy in [
+            r#"{"GE": {"value": 18}}"#,
+            r#"{"GE": {"att": "age"}}"#,
+            r#"{"LE": {"att": "age", "value": -1}}"#,
+            r#"{"EQ": {"att": "age", "value": 4294967296}}"#,
+        ].iter()
+        {
+            assert_eq!(expand_comparisons(serde_json::from_str(_policy).unwrap()).is_err(), true);
+        }
+    }
+}
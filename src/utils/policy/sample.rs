@@ -0,0 +1,105 @@
+//! Sampling of attribute sets for a given policy, useful when writing tests.
+//!
+//! Only available with the `test-utils` feature.
+//!
+extern crate serde_json;
+
+use std::string::String;
+use utils::error::RabeError;
+use utils::secretsharing::{missing_attributes, required_attributes};
+use utils::tools::{contains, string_to_json};
+
+/// Returns a smallest set of attributes satisfying the given policy.
+///
+/// # Arguments
+///
+/// * `_policy` - A policy in JSON format
+pub fn sample_satisfying_set(_policy: &str) -> Result<Vec<String>, RabeError> {
+    let _json = parse(_policy)?;
+    // a minimal set of attributes missing from the empty set is a minimal satisfying set
+    return missing_attributes(&Vec::new(), &_json);
+}
+
+/// Returns a large set of attributes (taken from the leaves of the policy) that does
+/// not satisfy the given policy, i.e. a "near miss".
+///
+/// # Arguments
+///
+/// * `_policy` - A policy in JSON format
+pub fn sample_unsatisfying_set(_policy: &str) -> Result<Vec<String>, RabeError> {
+    let _json = parse(_policy)?;
+    let mut _set: Vec<String> = Vec::new();
+    leaves(&_json, &mut _set);
+    // remove one attribute of a satisfying subset until the policy is no longer satisfied
+    loop {
+        match required_attributes(&_set, &_json)? {
+            (false, _) => return Ok(_set),
+            (true, _pruned) => {
+                let _remove = _pruned[0].clone();
+                _set.retain(|_attr| *_attr != _remove);
+            }
+        }
+    }
+}
+
+fn parse(_policy: &str) -> Result<serde_json::Value, RabeError> {
+    match string_to_json(&_policy.to_string()) {
+        None => return Err(RabeError::PolicyParse(_policy.to_string())),
+        Some(_json) => return Ok(_json),
+    }
+}
+
+// collects all attributes of the policy (in order of appearence, without duplicates)
+fn leaves(_json: &serde_json::Value, _leaves: &mut Vec<String>) {
+    match _json["ATT"].as_str() {
+        Some(_s) => {
+            if !contains(_leaves, &_s.to_string()) {
+                _leaves.push(_s.to_string());
+            }
+        }
+        None => {
+            for _gate in ["AND", "OR"].iter() {
+                if let Some(_children) = _json[*_gate].as_array() {
+                    for _child in _children {
+                        leaves(_child, _leaves);
+                    }
+                }
+            }
+            if let Some(_children) = _json["THRESHOLD"]["children"].as_array() {
+                for _child in _children {
+                    leaves(_child, _leaves);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use utils::tools::traverse_str;
+
+    #[test]
+    fn test_sample_sets() {
+        let _policies = vec![
+            r#"{"ATT": "A"}"#,
+            r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#,
+            r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"ATT": "C"}]}"#,
+            r#"{"AND": [{"OR": [{"ATT": "A"}, {"ATT": "B"}]}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#,
+        ];
+        for _p in _policies {
+            let _policy = String::from(_p);
+            let _sat = sample_satisfying_set(_p).unwrap();
+            assert_eq!(traverse_str(&_sat, &_policy), true);
+            let _unsat = sample_unsatisfying_set(_p).unwrap();
+            assert_eq!(traverse_str(&_unsat, &_policy), false);
+        }
+        assert_eq!(
+            sample_satisfying_set(r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"ATT": "C"}]}"#)
+                .unwrap(),
+            vec![String::from("C")]
+        );
+        assert_eq!(sample_satisfying_set("joking-around?").is_err(), true);
+    }
+}
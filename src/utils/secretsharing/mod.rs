@@ -4,235 +4,1245 @@ extern crate serde;
 extern crate serde_json;
 
 use bn::*;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use utils::error::RabeError;
+use utils::policy::infix::parse_policy;
+use utils::policy::numeric::expand_comparisons;
 use utils::tools::{contains, string_to_json, usize_to_fr};
-use rand::Rng;
+use rand::{Rng, RngCore};
 // Policy variables
 const POLICY_OR: &'static str = "OR";
 const POLICY_AND: &'static str = "AND";
 const POLICY_ATT: &'static str = "ATT";
+const POLICY_TRUE: &'static str = "TRUE";
+const POLICY_THRESHOLD: &'static str = "THRESHOLD";
+const POLICY_NOT: &'static str = "NOT";
+const POLICY_THRESHOLD_K: &'static str = "k";
+const POLICY_THRESHOLD_CHILDREN: &'static str = "children";
 
-pub fn calc_pruned_str(_attr: &Vec<String>, _policy: &String) -> Option<(bool, Vec<String>)> {
-    let _json = string_to_json(_policy);
-    match _json {
-        None => {
-            println!("Error in policy (could not parse json): {:?}", _policy);
-            return None;
+/// The reserved attribute behind the policy `{"TRUE": true}`, which is satisfied by every key
+/// of an authority. Schemes supporting this policy issue the attribute with every key.
+pub const TRUE_ATTRIBUTE: &'static str = "#TRUE";
+
+/// The prefix of the reserved attributes, i.e. `TRUE_ATTRIBUTE` and the bit attributes of
+/// numeric attributes (see `utils::policy::numeric`). The schemes add them to keys and
+/// ciphertexts themselves and reject them as attributes given by the caller. Policies may
+/// refer to bit attributes, the expanded form of comparisons, but not to `TRUE_ATTRIBUTE`,
+/// which is written `{"TRUE": true}`.
+pub const RESERVED_PREFIX: &'static str = "#";
+
+/// Checks that none of the given attributes is reserved (see `RESERVED_PREFIX`).
+///
+/// # Arguments
+///
+/// * `_attributes` - A Vector of String attributes, e.g. of a new key
+///
+/// # Errors
+///
+/// * `RabeError::InvalidAttribute` - if an attribute starts with `RESERVED_PREFIX`
+pub fn check_reserved(_attributes: &Vec<String>) -> Result<(), RabeError> {
+    match _attributes.iter().find(|_a| _a.starts_with(RESERVED_PREFIX)) {
+        Some(_a) => return Err(RabeError::InvalidAttribute(format!("{} is reserved", _a))),
+        None => return Ok(()),
+    }
+}
+
+/// The prefix of the dummy share of a negated attribute `{"NOT": {"ATT": "B"}}`, i.e. `!B`.
+///
+/// Negations are a documented limitation: `NOT` may only wrap an attribute and only be a
+/// child of an AND with at least one child that is not negated. The negated attribute does
+/// not take part in the secret sharing (it receives a random dummy share and a coefficient
+/// of zero) and is only evaluated when matching the policy. A key holding the negated
+/// attribute is rejected by the policy check, but the negation is not enforced
+/// cryptographically.
+pub const NEGATION_PREFIX: &'static str = "!";
+
+/// The maximum nesting depth of a policy, a single attribute has depth 1. Deeper policies are
+/// rejected with `RabeError::PolicyTooDeep` by the secret sharing, by `compact_to_policy` and
+/// by `validate_policy` (unless lower `PolicyLimits` are given).
+/// Note that JSON policies nested deeper than the recursion limit of serde_json already fail
+/// to parse.
+///
+/// The traversals that do not track the depth themselves (e.g. `required_attributes`,
+/// `missing_attributes`, `lint_policy` and `policy_diff`) recurse once per level and depend on
+/// this bound: a policy parsed from a String has at most the 128 levels serde_json accepts plus
+/// the levels of its expanded comparisons. A `serde_json::Value` built otherwise must not be
+/// nested deeper than `MAX_POLICY_DEPTH`.
+pub const MAX_POLICY_DEPTH: usize = 128;
+
+/// The maximum number of children of a single AND, OR or THRESHOLD gate. Wider gates are
+/// rejected with `RabeError::GateTooWide` by the secret sharing and by `validate_policy`.
+/// The shares of a gate with n children are the evaluations at 1..n of a polynomial of degree
+/// up to n - 1, so the points are far from the field modulus and always distinct; the bound
+/// keeps the (quadratic) cost of sharing and interpolating a single gate in check.
+pub const MAX_GATE_WIDTH: usize = 1024;
+
+// The nodes of a JSON policy tree
+pub(crate) enum PolicyNode<'a> {
+    Leaf(&'a str),
+    And(&'a Vec<serde_json::Value>),
+    Or(&'a Vec<serde_json::Value>),
+    // k of the children are required
+    Threshold(usize, &'a Vec<serde_json::Value>),
+    // a negated attribute, only allowed as child of an AND
+    Not(&'a str),
+}
+
+// checks a single node of a JSON policy tree
+pub(crate) fn policy_node(_json: &serde_json::Value) -> Result<PolicyNode, RabeError> {
+    if *_json == serde_json::Value::Null {
+        return Err(RabeError::InvalidPolicy(String::from("passed null as json")));
+    }
+    // the "anyone" policy is a leaf of the reserved attribute
+    if let Some(_true) = _json.get(POLICY_TRUE) {
+        match _true.as_bool() {
+            Some(true) => return Ok(PolicyNode::Leaf(TRUE_ATTRIBUTE)),
+            _ => return Err(RabeError::InvalidPolicy(format!("{} is not true", _true))),
+        }
+    }
+    if let Some(_not) = _json.get(POLICY_NOT) {
+        match policy_node(_not)? {
+            PolicyNode::Leaf(_s) => return Ok(PolicyNode::Not(_s)),
+            _ => {
+                return Err(RabeError::InvalidPolicy(
+                    format!("{} may only wrap an attribute", POLICY_NOT),
+                ))
+            }
+        }
+    }
+    if let Some(_att) = _json.get(POLICY_ATT) {
+        match _att.as_str() {
+            Some(_s) if _s == TRUE_ATTRIBUTE => {
+                return Err(RabeError::InvalidPolicy(
+                    format!("{} is reserved, use {{\"{}\": true}}", _s, POLICY_TRUE),
+                ))
+            }
+            Some(_s) => return Ok(PolicyNode::Leaf(_s)),
+            None => return Err(RabeError::InvalidPolicy(format!("{} is not a string", _att))),
+        }
+    }
+    for _gate in [POLICY_AND, POLICY_OR].iter() {
+        if let Some(_children) = _json.get(*_gate) {
+            match _children.as_array() {
+                None => {
+                    return Err(RabeError::InvalidPolicy(
+                        format!("{} is not an array of policies", _gate),
+                    ))
+                }
+                Some(_array) => {
+                    if _array.len() < 2 {
+                        return Err(RabeError::InvalidPolicy(
+                            format!("{} with less than two children", _gate),
+                        ));
+                    }
+                    if *_gate == POLICY_AND {
+                        return Ok(PolicyNode::And(_array));
+                    }
+                    return Ok(PolicyNode::Or(_array));
+                }
+            }
+        }
+    }
+    if let Some(_threshold) = _json.get(POLICY_THRESHOLD) {
+        let _children = match _threshold.get(POLICY_THRESHOLD_CHILDREN).and_then(|_c| _c.as_array()) {
+            None => {
+                return Err(RabeError::InvalidPolicy(
+                    format!("{} without an array of children", POLICY_THRESHOLD),
+                ))
+            }
+            Some(_array) => _array,
+        };
+        match _threshold.get(POLICY_THRESHOLD_K).and_then(|_k| _k.as_u64()) {
+            Some(_k) if _k >= 1 && _k <= _children.len() as u64 => {
+                return Ok(PolicyNode::Threshold(_k as usize, _children));
+            }
+            Some(_k) => {
+                return Err(RabeError::InvalidPolicy(format!(
+                    "{} with k = {} but {} children",
+                    POLICY_THRESHOLD,
+                    _k,
+                    _children.len()
+                )))
+            }
+            None => {
+                return Err(RabeError::InvalidPolicy(
+                    format!("{} without a positive integer k", POLICY_THRESHOLD),
+                ))
+            }
+        }
+    }
+    // valid JSON, but not a policy node
+    let _keys = match _json.as_object() {
+        Some(_object) => _object.keys().cloned().collect(),
+        None => Vec::new(),
+    };
+    return Err(RabeError::NotAPolicy(_keys));
+}
+
+fn not_outside_and() -> RabeError {
+    return RabeError::InvalidPolicy(format!("{} is only allowed inside {}", POLICY_NOT, POLICY_AND));
+}
+
+// splits the children of an AND into the children taking part in the secret sharing and
+// the negated attributes, which are only checked against the attributes of a key
+pub(crate) fn split_negated(
+    _children: &Vec<serde_json::Value>,
+) -> Result<(Vec<&serde_json::Value>, Vec<&str>), RabeError> {
+    let mut _positive: Vec<&serde_json::Value> = Vec::new();
+    let mut _negated: Vec<&str> = Vec::new();
+    for _child in _children {
+        match policy_node(_child)? {
+            PolicyNode::Not(_s) => _negated.push(_s),
+            _ => _positive.push(_child),
+        }
+    }
+    if _positive.is_empty() {
+        return Err(RabeError::InvalidPolicy(format!(
+            "{} with only negated children",
+            POLICY_AND
+        )));
+    }
+    return Ok((_positive, _negated));
+}
+
+// true if one of the negated attributes is part of _attr
+fn violates(_attr: &Vec<String>, _negated: &Vec<&str>) -> bool {
+    return _negated.iter().any(|_s| contains(_attr, &_s.to_string()));
+}
+
+pub(crate) fn policy_json(_policy: &String) -> Result<serde_json::Value, RabeError> {
+    match string_to_json(_policy) {
+        None => return Err(RabeError::PolicyParse(_policy.to_string())),
+        // comparisons of numeric attributes become trees of ordinary leaves
+        Some(_json) => return expand_comparisons(_json),
+    }
+}
+
+/// The maximum number of nodes (gates and attributes) of a policy accepted by `validate_policy`.
+/// Comparisons of numeric attributes count with the nodes they are expanded into.
+pub const MAX_POLICY_NODES: usize = 8192;
+
+/// The limits on the size of a policy enforced by `validate_policy_with_limits`, e.g. to reject
+/// gigantic policies of hostile ciphertexts before decrypting. The default limits are
+/// `MAX_POLICY_NODES` and `MAX_POLICY_DEPTH`.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct PolicyLimits {
+    pub _max_nodes: usize,
+    pub _max_depth: usize,
+}
+
+impl Default for PolicyLimits {
+    fn default() -> PolicyLimits {
+        return PolicyLimits {
+            _max_nodes: MAX_POLICY_NODES,
+            _max_depth: MAX_POLICY_DEPTH,
+        };
+    }
+}
+
+/// Checks that a policy is well-formed by walking its JSON tree once. Reports the first defect
+/// found: `PolicyParse` for malformed JSON, `NotAPolicy` for an unknown node type,
+/// `InvalidPolicy` for structural problems like an AND or OR with less than two children, an
+/// empty attribute or a misplaced NOT, and `PolicyTooLarge` or `PolicyTooDeep` for policies
+/// exceeding the default `PolicyLimits`.
+pub fn validate_policy(_policy: &str) -> Result<(), RabeError> {
+    return validate_policy_with_limits(_policy, &PolicyLimits::default());
+}
+
+/// Same as `validate_policy`, but with the given limits on the number of nodes and the depth.
+/// Note that the secret sharing never accepts policies deeper than `MAX_POLICY_DEPTH`.
+///
+/// # Arguments
+///
+/// * `_policy` - An access policy given as JSON String
+/// * `_limits` - The maximum number of nodes and depth
+pub fn validate_policy_with_limits(_policy: &str, _limits: &PolicyLimits) -> Result<(), RabeError> {
+    if _policy.trim().is_empty() {
+        return Err(RabeError::EmptyPolicy);
+    }
+    let _json = policy_json(&_policy.to_string())?;
+    let mut _nodes = 0usize;
+    return validate_node(&_json, 1, _limits, &mut _nodes);
+}
+
+fn validate_node(
+    _json: &serde_json::Value,
+    _depth: usize,
+    _limits: &PolicyLimits,
+    _nodes: &mut usize,
+) -> Result<(), RabeError> {
+    if _depth > _limits._max_depth {
+        return Err(RabeError::PolicyTooDeep);
+    }
+    *_nodes += 1;
+    if *_nodes > _limits._max_nodes {
+        return Err(RabeError::PolicyTooLarge);
+    }
+    let _children: Vec<&serde_json::Value> = match policy_node(_json)? {
+        PolicyNode::Leaf(_s) => {
+            if _s.is_empty() {
+                return Err(RabeError::InvalidPolicy(String::from("empty attribute")));
+            }
+            return Ok(());
+        }
+        PolicyNode::And(_children) |
+        PolicyNode::Or(_children) |
+        PolicyNode::Threshold(_, _children) if _children.len() > MAX_GATE_WIDTH => {
+            return Err(RabeError::GateTooWide(_children.len()))
+        }
+        PolicyNode::And(_children) => {
+            let (_positive, _negated) = split_negated(_children)?;
+            if _negated.iter().any(|_s| _s.is_empty()) {
+                return Err(RabeError::InvalidPolicy(String::from("empty attribute")));
+            }
+            // a NOT and its attribute count as a single node
+            *_nodes += _negated.len();
+            if *_nodes > _limits._max_nodes {
+                return Err(RabeError::PolicyTooLarge);
+            }
+            _positive
+        }
+        PolicyNode::Or(_children) |
+        PolicyNode::Threshold(_, _children) => _children.iter().collect(),
+        PolicyNode::Not(_) => return Err(not_outside_and()),
+    };
+    for _child in _children {
+        validate_node(_child, _depth + 1, _limits, _nodes)?;
+    }
+    return Ok(());
+}
+
+// a policy tree in canonical form: nested gates of the same type are merged,
+// children are sorted and duplicate children are removed (except in thresholds,
+// where duplicates count)
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub(crate) enum Canonical {
+    Leaf(String),
+    And(Vec<Canonical>),
+    Or(Vec<Canonical>),
+    Threshold(usize, Vec<Canonical>),
+    Not(String),
+}
+
+impl Canonical {
+    pub(crate) fn from_json(_json: &serde_json::Value) -> Result<Canonical, RabeError> {
+        let (_is_and, _children) = match policy_node(_json)? {
+            PolicyNode::Leaf(_s) => return Ok(Canonical::Leaf(_s.to_string())),
+            PolicyNode::Not(_s) => return Ok(Canonical::Not(_s.to_string())),
+            PolicyNode::And(_children) => (true, _children),
+            PolicyNode::Or(_children) => (false, _children),
+            PolicyNode::Threshold(_k, _children) => {
+                let mut _nodes: Vec<Canonical> = Vec::new();
+                for _child in _children {
+                    _nodes.push(Canonical::from_json(_child)?);
+                }
+                _nodes.sort();
+                return Ok(Canonical::Threshold(_k, _nodes));
+            }
+        };
+        let mut _nodes: BTreeSet<Canonical> = BTreeSet::new();
+        for _child in _children {
+            match (Canonical::from_json(_child)?, _is_and) {
+                (Canonical::And(_grandchildren), true) => _nodes.extend(_grandchildren),
+                (Canonical::Or(_grandchildren), false) => _nodes.extend(_grandchildren),
+                (_node, _) => {
+                    _nodes.insert(_node);
+                }
+            }
         }
-        Some(_json) => {
-            return required_attributes(_attr, &_json);
+        let mut _nodes: Vec<Canonical> = _nodes.into_iter().collect();
+        // a gate with a single (duplicated) child is the child itself
+        if _nodes.len() == 1 {
+            return Ok(_nodes.remove(0));
         }
+        if _is_and {
+            return Ok(Canonical::And(_nodes));
+        }
+        return Ok(Canonical::Or(_nodes));
+    }
+
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut _node = serde_json::Map::new();
+        match *self {
+            Canonical::Leaf(ref _s) if _s == TRUE_ATTRIBUTE => {
+                _node.insert(String::from(POLICY_TRUE), serde_json::Value::Bool(true));
+            }
+            Canonical::Leaf(ref _s) => {
+                _node.insert(String::from(POLICY_ATT), serde_json::Value::String(_s.clone()));
+            }
+            Canonical::Not(ref _s) => {
+                _node.insert(String::from(POLICY_NOT), Canonical::Leaf(_s.clone()).to_json());
+            }
+            Canonical::And(ref _children) |
+            Canonical::Or(ref _children) => {
+                let _gate = if let Canonical::And(_) = *self { POLICY_AND } else { POLICY_OR };
+                let _list = _children.iter().map(|_c| _c.to_json()).collect();
+                _node.insert(String::from(_gate), serde_json::Value::Array(_list));
+            }
+            Canonical::Threshold(_k, ref _children) => {
+                let mut _threshold = serde_json::Map::new();
+                _threshold.insert(
+                    String::from(POLICY_THRESHOLD_K),
+                    serde_json::Value::from(_k as u64),
+                );
+                _threshold.insert(
+                    String::from(POLICY_THRESHOLD_CHILDREN),
+                    serde_json::Value::Array(_children.iter().map(|_c| _c.to_json()).collect()),
+                );
+                _node.insert(String::from(POLICY_THRESHOLD), serde_json::Value::Object(_threshold));
+            }
+        }
+        return serde_json::Value::Object(_node);
     }
 }
 
+/// Brings a policy into a canonical form, so that equivalent policies written with different
+/// whitespace, key order or order of the children of AND and OR result in the same String:
+/// nested gates of the same type are merged, the children of AND and OR are sorted and
+/// deduplicated (a gate left with a single child becomes that child), the children of a
+/// THRESHOLD are sorted and comparisons are expanded. The result is compact JSON.
+///
+/// The encrypt algorithms store the policy as given, canonicalize it first to make the
+/// policies of ciphertexts comparable.
+///
+/// # Arguments
+///
+/// * `_policy` - An access policy given as JSON String
+pub fn canonicalize_policy(_policy: &str) -> Result<String, RabeError> {
+    validate_policy(_policy)?;
+    let _json = policy_json(&_policy.to_string())?;
+    return Ok(Canonical::from_json(&_json)?.to_json().to_string());
+}
+
+/// Reads and validates a policy from a file, given either as JSON or as boolean expression
+/// (see `parse_policy`), and returns it as JSON String. Files with the extension `.json` are
+/// read as JSON, otherwise the content is read as JSON if it starts with `{`.
+///
+/// # Arguments
+///
+/// * `_path` - The path of the policy file
+///
+/// # Errors
+///
+/// * `RabeError::Io` - if the file can not be read
+/// * `RabeError::PolicyParse` and others - if the content is not a valid policy
+pub fn policy_from_file(_path: &Path) -> Result<String, RabeError> {
+    let _content = fs::read_to_string(_path)?;
+    let _content = _content.trim();
+    let _is_json = match _path.extension().and_then(|_e| _e.to_str()) {
+        Some(_extension) if _extension.eq_ignore_ascii_case("json") => true,
+        _ => _content.starts_with('{'),
+    };
+    let _policy = if _is_json {
+        _content.to_string()
+    } else {
+        parse_policy(_content)?
+    };
+    validate_policy(&_policy)?;
+    return Ok(_policy);
+}
+
+/// Builds the policy "`_k` out of the attributes `_attrs`", a single THRESHOLD gate, e.g.
+/// `{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}}`.
+///
+/// # Arguments
+///
+/// * `_attrs` - The attributes, each may be used once
+/// * `_k` - The number of attributes required, between 1 and the number of attributes
+///
+/// # Errors
+///
+/// * `RabeError::EmptyAttributes` - if `_attrs` is empty
+/// * `RabeError::InvalidPolicy` - if `_k` is out of range or an attribute is empty or repeated
+pub fn threshold_policy(_attrs: &[String], _k: usize) -> Result<String, RabeError> {
+    if _attrs.is_empty() {
+        return Err(RabeError::EmptyAttributes);
+    }
+    if _k < 1 || _k > _attrs.len() {
+        return Err(RabeError::InvalidPolicy(format!(
+            "{} requires k between 1 and {}, got {}",
+            POLICY_THRESHOLD,
+            _attrs.len(),
+            _k
+        )));
+    }
+    let mut _children: Vec<serde_json::Value> = Vec::new();
+    for (_i, _attr) in _attrs.iter().enumerate() {
+        if _attr.is_empty() || _attrs[.._i].contains(_attr) {
+            return Err(RabeError::InvalidPolicy(
+                format!("{} with an empty or repeated attribute", POLICY_THRESHOLD),
+            ));
+        }
+        let mut _leaf = serde_json::Map::new();
+        _leaf.insert(String::from(POLICY_ATT), serde_json::Value::String(_attr.clone()));
+        _children.push(serde_json::Value::Object(_leaf));
+    }
+    let mut _threshold = serde_json::Map::new();
+    _threshold.insert(String::from(POLICY_THRESHOLD_K), serde_json::Value::from(_k as u64));
+    _threshold.insert(
+        String::from(POLICY_THRESHOLD_CHILDREN),
+        serde_json::Value::Array(_children),
+    );
+    let mut _node = serde_json::Map::new();
+    _node.insert(String::from(POLICY_THRESHOLD), serde_json::Value::Object(_threshold));
+    return Ok(serde_json::Value::Object(_node).to_string());
+}
+
+/// Returns the sorted and deduplicated names of all attributes a policy refers to, including
+/// negated attributes. The reserved `TRUE_ATTRIBUTE` of the policy `{"TRUE": true}` is omitted.
+pub fn policy_attributes(_policy: &str) -> Result<Vec<String>, RabeError> {
+    let _json = policy_json(&_policy.to_string())?;
+    let mut _attributes: BTreeSet<String> = BTreeSet::new();
+    collect_attributes(&_json, &mut _attributes)?;
+    return Ok(_attributes.into_iter().collect());
+}
+
+fn collect_attributes(
+    _json: &serde_json::Value,
+    _attributes: &mut BTreeSet<String>,
+) -> Result<(), RabeError> {
+    match policy_node(_json)? {
+        PolicyNode::Leaf(_s) |
+        PolicyNode::Not(_s) => {
+            if _s != TRUE_ATTRIBUTE {
+                _attributes.insert(_s.to_string());
+            }
+        }
+        PolicyNode::And(_children) |
+        PolicyNode::Or(_children) |
+        PolicyNode::Threshold(_, _children) => {
+            for _child in _children {
+                collect_attributes(_child, _attributes)?;
+            }
+        }
+    }
+    return Ok(());
+}
+
+// Tags of the binary policy encoding, see policy_to_bytes
+const BINARY_VERSION: u8 = 1;
+const BINARY_ATT: u8 = 0;
+const BINARY_AND: u8 = 1;
+const BINARY_OR: u8 = 2;
+const BINARY_THRESHOLD: u8 = 3;
+const BINARY_NOT: u8 = 4;
+const BINARY_TRUE: u8 = 5;
+
+/// Encodes a JSON policy in a compact binary format: a version byte followed by the nodes in
+/// prefix order. A node is a type byte, followed by the length (2 bytes, big endian) and the
+/// UTF-8 bytes of an attribute (`ATT`, `NOT`), by the number of children (2 bytes) and the
+/// children (`AND`, `OR`) or by `k`, the number of children (2 bytes each) and the children
+/// (`THRESHOLD`). `{"TRUE": true}` is the type byte alone. Comparisons are expanded first.
+///
+/// # Arguments
+///
+/// * `_policy` - An access policy given as JSON String
+pub fn policy_to_bytes(_policy: &str) -> Result<Vec<u8>, RabeError> {
+    let _json = policy_json(&_policy.to_string())?;
+    let mut _bytes = vec![BINARY_VERSION];
+    encode_node(&_json, &mut _bytes)?;
+    return Ok(_bytes);
+}
+
+/// Decodes a policy encoded by `policy_to_bytes` into its JSON String. Policies nested deeper
+/// than `MAX_POLICY_DEPTH` are rejected with `RabeError::PolicyTooDeep`.
+///
+/// # Arguments
+///
+/// * `_bytes` - The binary encoding of a policy
+pub fn policy_from_bytes(_bytes: &[u8]) -> Result<String, RabeError> {
+    let mut _pos = 0usize;
+    if take(_bytes, &mut _pos, 1)? != [BINARY_VERSION] {
+        return Err(RabeError::Serialization(String::from("unknown policy encoding")));
+    }
+    let _json = decode_node(_bytes, &mut _pos, 1)?;
+    if _pos != _bytes.len() {
+        return Err(RabeError::Serialization(String::from("trailing bytes after the policy")));
+    }
+    let _policy = _json.to_string();
+    validate_policy(&_policy)?;
+    return Ok(_policy);
+}
+
+fn encode_u16(_value: usize, _bytes: &mut Vec<u8>) -> Result<(), RabeError> {
+    if _value > u16::max_value() as usize {
+        return Err(RabeError::Serialization(format!("{} does not fit into 2 bytes", _value)));
+    }
+    _bytes.extend_from_slice(&(_value as u16).to_be_bytes());
+    return Ok(());
+}
+
+fn encode_attribute(_tag: u8, _s: &str, _bytes: &mut Vec<u8>) -> Result<(), RabeError> {
+    _bytes.push(_tag);
+    encode_u16(_s.len(), _bytes)?;
+    _bytes.extend_from_slice(_s.as_bytes());
+    return Ok(());
+}
+
+fn encode_node(_json: &serde_json::Value, _bytes: &mut Vec<u8>) -> Result<(), RabeError> {
+    let (_tag, _children) = match policy_node(_json)? {
+        PolicyNode::Leaf(_s) if _s == TRUE_ATTRIBUTE && _json.get(POLICY_TRUE).is_some() => {
+            _bytes.push(BINARY_TRUE);
+            return Ok(());
+        }
+        PolicyNode::Leaf(_s) => return encode_attribute(BINARY_ATT, _s, _bytes),
+        PolicyNode::Not(_s) => return encode_attribute(BINARY_NOT, _s, _bytes),
+        PolicyNode::And(_children) => (BINARY_AND, _children),
+        PolicyNode::Or(_children) => (BINARY_OR, _children),
+        PolicyNode::Threshold(_k, _children) => {
+            _bytes.push(BINARY_THRESHOLD);
+            encode_u16(_k, _bytes)?;
+            encode_u16(_children.len(), _bytes)?;
+            for _child in _children {
+                encode_node(_child, _bytes)?;
+            }
+            return Ok(());
+        }
+    };
+    _bytes.push(_tag);
+    encode_u16(_children.len(), _bytes)?;
+    for _child in _children {
+        encode_node(_child, _bytes)?;
+    }
+    return Ok(());
+}
+
+// the next _len bytes of the input
+fn take<'a>(_bytes: &'a [u8], _pos: &mut usize, _len: usize) -> Result<&'a [u8], RabeError> {
+    if _bytes.len() - *_pos < _len {
+        return Err(RabeError::Serialization(String::from("truncated policy")));
+    }
+    *_pos += _len;
+    return Ok(&_bytes[*_pos - _len..*_pos]);
+}
+
+fn decode_u16(_bytes: &[u8], _pos: &mut usize) -> Result<usize, RabeError> {
+    let _value = take(_bytes, _pos, 2)?;
+    return Ok(u16::from_be_bytes([_value[0], _value[1]]) as usize);
+}
+
+fn decode_attribute(_bytes: &[u8], _pos: &mut usize) -> Result<serde_json::Value, RabeError> {
+    let _len = decode_u16(_bytes, _pos)?;
+    match String::from_utf8(take(_bytes, _pos, _len)?.to_vec()) {
+        Err(_e) => return Err(RabeError::Serialization(_e.to_string())),
+        Ok(_s) => return Ok(node(POLICY_ATT, serde_json::Value::String(_s))),
+    }
+}
+
+// the children of a node at depth _depth
+fn decode_children(
+    _bytes: &[u8],
+    _pos: &mut usize,
+    _depth: usize,
+) -> Result<serde_json::Value, RabeError> {
+    let _count = decode_u16(_bytes, _pos)?;
+    let mut _children: Vec<serde_json::Value> = Vec::new();
+    for _ in 0.._count {
+        _children.push(decode_node(_bytes, _pos, _depth + 1)?);
+    }
+    return Ok(serde_json::Value::Array(_children));
+}
+
+// the JSON object {_key: _value}
+fn node(_key: &str, _value: serde_json::Value) -> serde_json::Value {
+    let mut _node = serde_json::Map::new();
+    _node.insert(_key.to_string(), _value);
+    return serde_json::Value::Object(_node);
+}
+
+fn decode_node(
+    _bytes: &[u8],
+    _pos: &mut usize,
+    _depth: usize,
+) -> Result<serde_json::Value, RabeError> {
+    if _depth > MAX_POLICY_DEPTH {
+        return Err(RabeError::PolicyTooDeep);
+    }
+    match take(_bytes, _pos, 1)?[0] {
+        BINARY_ATT => return decode_attribute(_bytes, _pos),
+        BINARY_NOT => return Ok(node(POLICY_NOT, decode_attribute(_bytes, _pos)?)),
+        BINARY_TRUE => return Ok(node(POLICY_TRUE, serde_json::Value::Bool(true))),
+        BINARY_AND => return Ok(node(POLICY_AND, decode_children(_bytes, _pos, _depth)?)),
+        BINARY_OR => return Ok(node(POLICY_OR, decode_children(_bytes, _pos, _depth)?)),
+        BINARY_THRESHOLD => {
+            let _k = decode_u16(_bytes, _pos)?;
+            let _children = decode_children(_bytes, _pos, _depth)?;
+            let mut _threshold = serde_json::Map::new();
+            _threshold.insert(String::from(POLICY_THRESHOLD_K), serde_json::Value::from(_k));
+            _threshold.insert(String::from(POLICY_THRESHOLD_CHILDREN), _children);
+            return Ok(node(POLICY_THRESHOLD, serde_json::Value::Object(_threshold)));
+        }
+        _tag => return Err(RabeError::Serialization(format!("unknown policy node {}", _tag))),
+    }
+}
+
+/// Returns whether the attributes satisfy the policy and the attributes of the leaves used to
+/// satisfy it. The recursion is bounded by the parser, see `MAX_POLICY_DEPTH`.
+///
+/// # Arguments
+///
+/// * `_attr` - The attributes at hand, e.g. of a key
+/// * `_policy` - An access policy given as JSON String
+pub fn calc_pruned_str(
+    _attr: &Vec<String>,
+    _policy: &String,
+) -> Result<(bool, Vec<String>), RabeError> {
+    let _json = policy_json(_policy)?;
+    return required_attributes(_attr, &_json);
+}
+
+/// Same as `calc_pruned_str` for a parsed policy. Recurses once per level of the policy, which
+/// must not be nested deeper than `MAX_POLICY_DEPTH`.
+///
+/// # Arguments
+///
+/// * `_attr` - The attributes at hand, e.g. of a key
+/// * `_json` - An access policy as parsed JSON value
 pub fn required_attributes(
     _attr: &Vec<String>,
     _json: &serde_json::Value,
-) -> Option<(bool, Vec<String>)> {
-    if *_json == serde_json::Value::Null {
-        println!("Error: passed null as json!");
-        return None;
-    } else {
-        let mut _match: bool = false;
-        let mut _emtpy_list: Vec<String> = Vec::new();
-        if _json[POLICY_OR].is_array() {
-            let _num_terms = _json[POLICY_OR].as_array().unwrap().len();
-            if _num_terms >= 2 {
-                for _i in 0usize.._num_terms {
-                    let (_found, mut _list) = required_attributes(_attr, &_json[POLICY_OR][_i])
-                        .unwrap();
-                    _match = _match || _found;
-                    if _match {
-                        _emtpy_list.append(&mut _list);
-                        break;
-                    }
+) -> Result<(bool, Vec<String>), RabeError> {
+    match policy_node(_json)? {
+        // leaf node
+        PolicyNode::Leaf(_s) => {
+            if contains(_attr, &_s.to_string()) {
+                return Ok((true, vec![_s.to_string()]));
+            }
+            return Ok((false, Vec::new()));
+        }
+        // inner node: the first satisfied child is used
+        PolicyNode::Or(_children) => {
+            for _child in _children {
+                let (_found, _list) = required_attributes(_attr, _child)?;
+                if _found {
+                    return Ok((true, _list));
                 }
-                return Some((_match, _emtpy_list));
-            } else {
-                println!("Error: Invalid policy (OR with just a single child).");
-                return None;
-            }
-        }
-        // inner node
-        else if _json[POLICY_AND].is_array() {
-            let _num_terms = _json[POLICY_AND].as_array().unwrap().len();
-            _match = true;
-            if _num_terms >= 2 {
-                for _i in 0usize.._num_terms {
-                    let (_found, mut _list) = required_attributes(_attr, &_json[POLICY_AND][_i])
-                        .unwrap();
-                    _match = _match && _found;
-                    if _match {
-                        _emtpy_list.append(&mut _list);
+            }
+            return Ok((false, Vec::new()));
+        }
+        // inner node: all children are required and no negated attribute may be present
+        PolicyNode::And(_children) => {
+            let (_positive, _negated) = split_negated(_children)?;
+            if violates(_attr, &_negated) {
+                return Ok((false, Vec::new()));
+            }
+            let mut _list: Vec<String> = Vec::new();
+            for _child in _positive {
+                let (_found, mut _child_list) = required_attributes(_attr, _child)?;
+                if !_found {
+                    return Ok((false, Vec::new()));
+                }
+                _list.append(&mut _child_list);
+            }
+            return Ok((true, _list));
+        }
+        // inner node: the first k satisfied children are used
+        PolicyNode::Threshold(_k, _children) => {
+            let mut _list: Vec<String> = Vec::new();
+            let mut _satisfied = 0;
+            for _child in _children {
+                let (_found, mut _child_list) = required_attributes(_attr, _child)?;
+                if _found {
+                    _list.append(&mut _child_list);
+                    _satisfied += 1;
+                    if _satisfied == _k {
+                        return Ok((true, _list));
                     }
                 }
-            } else {
-                println!("Error: Invalid policy (AND with just a single child).");
-                return None;
             }
-            if !_match {
-                _emtpy_list = Vec::new();
+            return Ok((false, Vec::new()));
+        }
+        PolicyNode::Not(_) => return Err(not_outside_and()),
+    }
+}
+
+/// Same as `calc_pruned_str`, but evaluates every node of the policy (see
+/// `required_attributes_ct`). Returns whether the attributes satisfy the policy and the
+/// attributes of the leaves used to satisfy it.
+///
+/// # Arguments
+///
+/// * `_attr` - The attributes at hand, e.g. of a key
+/// * `_policy` - An access policy given as JSON String
+pub fn calc_pruned_str_ct(
+    _attr: &Vec<String>,
+    _policy: &String,
+) -> Result<(bool, Vec<String>), RabeError> {
+    let _json = policy_json(_policy)?;
+    return required_attributes_ct(_attr, &_json);
+}
+
+/// Same result as `required_attributes`, but every node of the policy is evaluated and no loop
+/// ends early, so that the time taken does not depend on which branch is satisfied. This hides
+/// the structure of the satisfied sub-policy, not the time of the string comparisons itself.
+/// Like `required_attributes` it recurses once per level, see `MAX_POLICY_DEPTH`.
+///
+/// # Arguments
+///
+/// * `_attr` - The attributes at hand, e.g. of a key
+/// * `_json` - An access policy as parsed JSON value
+pub fn required_attributes_ct(
+    _attr: &Vec<String>,
+    _json: &serde_json::Value,
+) -> Result<(bool, Vec<String>), RabeError> {
+    match policy_node(_json)? {
+        // leaf node, contains() compares with all attributes
+        PolicyNode::Leaf(_s) => {
+            let _found = contains(_attr, &_s.to_string());
+            return Ok((_found, select(_found, vec![_s.to_string()])));
+        }
+        // inner node: the first satisfied child is used
+        PolicyNode::Or(_children) => {
+            let mut _found = false;
+            let mut _list: Vec<String> = Vec::new();
+            for _child in _children {
+                let (_child_found, _child_list) = required_attributes_ct(_attr, _child)?;
+                let _take = _child_found & !_found;
+                _list = if _take { _child_list } else { _list };
+                _found |= _child_found;
+            }
+            return Ok((_found, _list));
+        }
+        // inner node: all children are required and no negated attribute may be present
+        PolicyNode::And(_children) => {
+            let (_positive, _negated) = split_negated(_children)?;
+            let mut _found = _negated
+                .iter()
+                .fold(true, |_ok, _s| _ok & !contains(_attr, &_s.to_string()));
+            let mut _list: Vec<String> = Vec::new();
+            for _child in _positive {
+                let (_child_found, mut _child_list) = required_attributes_ct(_attr, _child)?;
+                _found &= _child_found;
+                _list.append(&mut _child_list);
             }
-            return Some((_match, _emtpy_list));
+            return Ok((_found, select(_found, _list)));
         }
+        // inner node: the first k satisfied children are used
+        PolicyNode::Threshold(_k, _children) => {
+            let mut _list: Vec<String> = Vec::new();
+            let mut _satisfied = 0;
+            for _child in _children {
+                let (_child_found, mut _child_list) = required_attributes_ct(_attr, _child)?;
+                let _take = _child_found & (_satisfied < _k);
+                _list.append(&mut select(_take, _child_list));
+                _satisfied += _take as usize;
+            }
+            let _found = _satisfied == _k;
+            return Ok((_found, select(_found, _list)));
+        }
+        PolicyNode::Not(_) => return Err(not_outside_and()),
+    }
+}
+
+// the list if _keep is set, an empty list otherwise
+fn select(_keep: bool, mut _list: Vec<String>) -> Vec<String> {
+    _list.truncate(_list.len() * (_keep as usize));
+    return _list;
+}
+
+pub fn missing_attributes_str(
+    _attr: &Vec<String>,
+    _policy: &String,
+) -> Result<Vec<String>, RabeError> {
+    let _json = policy_json(_policy)?;
+    return missing_attributes(_attr, &_json);
+}
+
+// returns a smallest set of attributes that has to be added to _attr in order to satisfy the policy,
+// recursing once per level (see MAX_POLICY_DEPTH)
+pub fn missing_attributes(
+    _attr: &Vec<String>,
+    _json: &serde_json::Value,
+) -> Result<Vec<String>, RabeError> {
+    let mut _missing: Vec<String> = Vec::new();
+    match policy_node(_json)? {
         // leaf node
-        else if _json[POLICY_ATT] != serde_json::Value::Null {
-            match _json[POLICY_ATT].as_str() {
-                Some(_s) => {
-                    if contains(_attr, &_s.to_string()) {
-                        return Some((true, vec![_s.to_string()]));
-                    } else {
-                        return Some((false, _emtpy_list));
+        PolicyNode::Leaf(_s) => {
+            if !contains(_attr, &_s.to_string()) {
+                _missing.push(_s.to_string());
+            }
+        }
+        // inner node: every child has to be satisfied, negated attributes can not be fixed by
+        // adding attributes and are ignored
+        PolicyNode::And(_children) => {
+            for _child in split_negated(_children)?.0 {
+                for _s in missing_attributes(_attr, _child)? {
+                    if !contains(&_missing, &_s) {
+                        _missing.push(_s);
                     }
                 }
-                None => {
-                    println!("ERROR attribute value");
-                    return None;
+            }
+        }
+        // inner node: the cheapest child has to be satisfied
+        PolicyNode::Or(_children) => {
+            let mut _best: Option<Vec<String>> = None;
+            for _child in _children {
+                let _list = missing_attributes(_attr, _child)?;
+                let _better = match _best {
+                    None => true,
+                    Some(ref _b) => _list.len() < _b.len(),
+                };
+                if _better {
+                    _best = Some(_list);
                 }
             }
-        } else {
-            return None;
+            _missing = _best.unwrap_or_default();
         }
+        // inner node: the k cheapest children have to be satisfied
+        PolicyNode::Threshold(_k, _children) => {
+            let mut _lists: Vec<Vec<String>> = Vec::new();
+            for _child in _children {
+                _lists.push(missing_attributes(_attr, _child)?);
+            }
+            _lists.sort_by_key(|_list| _list.len());
+            for _list in _lists.into_iter().take(_k) {
+                for _s in _list {
+                    if !contains(&_missing, &_s) {
+                        _missing.push(_s);
+                    }
+                }
+            }
+        }
+        PolicyNode::Not(_) => return Err(not_outside_and()),
     }
+    return Ok(_missing);
 }
 
-pub fn calc_coefficients_str(_policy: &String) -> Option<Vec<(String, Fr)>> {
-    match string_to_json(_policy) {
-        None => {
-            println!("Error in policy: {:?}", _policy);
-            return None;
-        }
-        Some(_json) => {
-            return calc_coefficients(&_json, Fr::one());
-        }
-    }
+pub fn calc_coefficients_str(_policy: &String) -> Result<Vec<(String, Fr)>, RabeError> {
+    let _json = policy_json(_policy)?;
+    return calc_coefficients(&_json, Fr::one());
 }
 
-pub fn calc_coefficients(_json: &serde_json::Value, _coeff: Fr) -> Option<Vec<(String, Fr)>> {
+pub fn calc_coefficients(
+    _json: &serde_json::Value,
+    _coeff: Fr,
+) -> Result<Vec<(String, Fr)>, RabeError> {
     let mut _result: Vec<(String, Fr)> = Vec::new();
-    // leaf node
-    if _json[POLICY_ATT] != serde_json::Value::Null {
-        match _json[POLICY_ATT].as_str() {
-            Some(_s) => {
-                _result.push((_s.to_string(), _coeff));
-                return Some(_result);
+    match policy_node(_json)? {
+        // leaf node
+        PolicyNode::Leaf(_s) => {
+            _result.push((_s.to_string(), _coeff));
+        }
+        // inner node: the children hold the shares 1..n, negated attributes a dummy share
+        PolicyNode::And(_children) => {
+            let (_positive, _negated) = split_negated(_children)?;
+            let _points: Vec<Fr> = (1.._positive.len() + 1).map(usize_to_fr).collect();
+            let _this_coeff = recover_coefficients(_points)?;
+            for (_i, _child) in _positive.iter().enumerate() {
+                _result.extend(calc_coefficients(_child, _coeff * _this_coeff[_i])?);
             }
-            None => {
-                println!("ERROR attribute value");
-                return None;
+            for _s in _negated {
+                _result.push((format!("{}{}", NEGATION_PREFIX, _s), Fr::zero()));
             }
         }
+        // inner node: every child holds the secret itself
+        PolicyNode::Or(_children) => {
+            let _this_coeff = recover_coefficients(vec![Fr::one()])?;
+            for _child in _children {
+                _result.extend(calc_coefficients(_child, _coeff * _this_coeff[0])?);
+            }
+        }
+        // the coefficients depend on the children used, see calc_coefficients_pruned
+        PolicyNode::Threshold(_, _) => {
+            return Err(RabeError::Interpolation(format!(
+                "the coefficients of {} depend on the attributes used",
+                POLICY_THRESHOLD
+            )))
+        }
+        PolicyNode::Not(_) => return Err(not_outside_and()),
+    }
+    return Ok(_result);
+}
+
+pub fn calc_coefficients_pruned_str(
+    _attr: &Vec<String>,
+    _policy: &String,
+) -> Result<Option<Vec<(String, Fr)>>, RabeError> {
+    let _json = policy_json(_policy)?;
+    return calc_coefficients_pruned(_attr, &_json, Fr::one());
+}
+
+// the coefficients of the leaves used to satisfy the policy with the attributes _attr (the same
+// leaves as in required_attributes), or None if the attributes do not satisfy the policy
+pub fn calc_coefficients_pruned(
+    _attr: &Vec<String>,
+    _json: &serde_json::Value,
+    _coeff: Fr,
+) -> Result<Option<Vec<(String, Fr)>>, RabeError> {
+    match pruned_leaves(_attr, _json, _coeff, 0)? {
+        None => return Ok(None),
+        Some(_leaves) => {
+            return Ok(Some(
+                _leaves.into_iter().map(|(_, _s, _c)| (_s, _c)).collect(),
+            ))
+        }
     }
-    // inner node
-    else if _json[POLICY_AND].is_array() {
-        let _len = _json[POLICY_AND].as_array().unwrap().len();
-        let mut _vec = vec![Fr::one()];
-        for _i in 1.._len {
-            let _prev = _vec[_i - 1].clone();
-            _vec.push(_prev + Fr::one());
+}
+
+/// Returns the position of every share of `gen_shares_str` in the policy tree together with
+/// its attribute, in the order of the shares. A position is the path of child indices from the
+/// root `0`, e.g. `0.1.0` for the first child of the second child of the root, and is unique
+/// even if an attribute occurs more than once in the policy. Negated attributes (prefixed by
+/// `NEGATION_PREFIX`) follow the other children of their AND, like their dummy shares.
+///
+/// # Arguments
+///
+/// * `_policy` - An access policy given as JSON String
+pub fn share_paths(_policy: &str) -> Result<Vec<(String, String)>, RabeError> {
+    validate_policy(_policy)?;
+    let _json = policy_json(&_policy.to_string())?;
+    let mut _paths: Vec<(String, String)> = Vec::new();
+    node_paths(&_json, String::from("0"), &mut _paths)?;
+    return Ok(_paths);
+}
+
+// the share_paths of the node _json at _path, its depth is bounded by validate_policy
+fn node_paths(
+    _json: &serde_json::Value,
+    _path: String,
+    _paths: &mut Vec<(String, String)>,
+) -> Result<(), RabeError> {
+    let _children = match policy_node(_json)? {
+        PolicyNode::Leaf(_s) => {
+            _paths.push((_path, _s.to_string()));
+            return Ok(());
         }
-        let _this_coeff = recover_coefficients(_vec);
-        for _i in 0.._len {
-            match calc_coefficients(&_json[POLICY_AND][_i], _coeff * _this_coeff[_i]) {
-                None => return None,
-                Some(_res) => {
-                    _result.extend(_res.iter().cloned());
-                }
+        PolicyNode::Not(_) => return Err(not_outside_and()),
+        PolicyNode::Or(_children) |
+        PolicyNode::And(_children) |
+        PolicyNode::Threshold(_, _children) => _children,
+    };
+    let mut _negated: Vec<(String, String)> = Vec::new();
+    for (_i, _child) in _children.iter().enumerate() {
+        let _child_path = format!("{}.{}", _path, _i);
+        match policy_node(_child)? {
+            PolicyNode::Not(_s) => {
+                _negated.push((_child_path, format!("{}{}", NEGATION_PREFIX, _s)))
             }
+            _ => node_paths(_child, _child_path, _paths)?,
         }
-        return Some(_result);
     }
-    // inner node
-    else if _json[POLICY_OR].is_array() {
-        let _len = _json[POLICY_OR].as_array().unwrap().len();
-        let _this_coeff = recover_coefficients(vec![Fr::one()]);
-        for _i in 0.._len {
-            match calc_coefficients(&_json[POLICY_OR][_i], _coeff * _this_coeff[0]) {
-                None => return None,
-                Some(_res) => {
-                    _result.extend(_res.iter().cloned());
-                }
+    _paths.extend(_negated);
+    return Ok(());
+}
+
+// like calc_coefficients_pruned_str, but every coefficient also holds the index of its leaf in
+// the shares of gen_shares_str (and share_paths), which tells apart leaves with the same attribute
+pub fn calc_coefficients_indexed_str(
+    _attr: &Vec<String>,
+    _policy: &String,
+) -> Result<Option<Vec<(usize, String, Fr)>>, RabeError> {
+    let _json = policy_json(_policy)?;
+    return pruned_leaves(_attr, &_json, Fr::one(), 0);
+}
+
+// the used leaves of calc_coefficients_pruned with their index in the shares of gen_shares_json,
+// _offset is the index of the first share of _json
+fn pruned_leaves(
+    _attr: &Vec<String>,
+    _json: &serde_json::Value,
+    _coeff: Fr,
+    _offset: usize,
+) -> Result<Option<Vec<(usize, String, Fr)>>, RabeError> {
+    let (_k, _children): (usize, Vec<&serde_json::Value>) = match policy_node(_json)? {
+        // leaf node
+        PolicyNode::Leaf(_s) => {
+            if contains(_attr, &_s.to_string()) {
+                return Ok(Some(vec![(_offset, _s.to_string(), _coeff)]));
             }
+            return Ok(None);
+        }
+        // inner nodes
+        PolicyNode::Or(_children) => (1, _children.iter().collect()),
+        PolicyNode::And(_children) => {
+            let (_positive, _negated) = split_negated(_children)?;
+            if violates(_attr, &_negated) {
+                return Ok(None);
+            }
+            (_positive.len(), _positive)
+        }
+        PolicyNode::Threshold(_k, _children) => (_k, _children.iter().collect()),
+        PolicyNode::Not(_) => return Err(not_outside_and()),
+    };
+    // the first k satisfied children (with their share number and first share index) are used
+    let mut _used: Vec<(usize, usize, &serde_json::Value)> = Vec::new();
+    let mut _index = _offset;
+    for (_i, _child) in _children.iter().enumerate() {
+        if _used.len() < _k && required_attributes(_attr, _child)?.0 {
+            _used.push((_i + 1, _index, *_child));
+        }
+        _index += share_count(_child)?;
+    }
+    if _used.len() < _k {
+        return Ok(None);
+    }
+    let _points: Vec<Fr> = _used.iter().map(|&(_x, _, _)| usize_to_fr(_x)).collect();
+    let _this_coeff = recover_coefficients(_points)?;
+    let mut _result: Vec<(usize, String, Fr)> = Vec::new();
+    for (_j, &(_, _index, _child)) in _used.iter().enumerate() {
+        match pruned_leaves(_attr, _child, _coeff * _this_coeff[_j], _index)? {
+            None => return Ok(None),
+            Some(_list) => _result.extend(_list),
         }
-        return Some(_result);
-    } else {
-        return None;
     }
+    return Ok(Some(_result));
+}
+
+// the number of shares gen_shares_json generates for a policy (including dummy shares of NOT)
+fn share_count(_json: &serde_json::Value) -> Result<usize, RabeError> {
+    let _children = match policy_node(_json)? {
+        PolicyNode::Leaf(_) => return Ok(1),
+        PolicyNode::Not(_) => return Err(not_outside_and()),
+        PolicyNode::Or(_children) |
+        PolicyNode::And(_children) |
+        PolicyNode::Threshold(_, _children) => _children,
+    };
+    let mut _count = 0;
+    for _child in _children {
+        _count += match policy_node(_child)? {
+            // the dummy share of a negated attribute inside an AND
+            PolicyNode::Not(_) => 1,
+            _ => share_count(_child)?,
+        };
+    }
+    return Ok(_count);
 }
 
 // lagrange interpolation
-pub fn recover_coefficients(_list: Vec<Fr>) -> Vec<Fr> {
+pub fn recover_coefficients(_list: Vec<Fr>) -> Result<Vec<Fr>, RabeError> {
     let mut _coeff: Vec<Fr> = Vec::new();
-    for _i in _list.clone() {
+    for (_i_index, _i) in _list.iter().enumerate() {
         let mut _result = Fr::one();
-        for _j in _list.clone() {
-            if _i != _j {
-                _result = _result * ((Fr::zero() - _j) * (_i - _j).inverse().unwrap());
+        for (_j_index, _j) in _list.iter().enumerate() {
+            if _i_index != _j_index {
+                match (*_i - *_j).inverse() {
+                    None => {
+                        return Err(RabeError::Interpolation(
+                            String::from("duplicate interpolation points"),
+                        ))
+                    }
+                    Some(_inverse) => {
+                        _result = _result * ((Fr::zero() - *_j) * _inverse);
+                    }
+                }
             }
         }
         _coeff.push(_result);
     }
-    return _coeff;
+    return Ok(_coeff);
 }
 
-pub fn gen_shares_str(_secret: Fr, _policy: &String) -> Option<Vec<(String, Fr)>> {
-    match string_to_json(_policy) {
-        None => {
-            println!("Could not convert policy {:?} to JSON", _policy);
-            return None;
-        }
-        Some(_json_policy) => {
-            return gen_shares_json(_secret, &_json_policy);
-        }
-    }
+pub fn gen_shares_str(_secret: Fr, _policy: &String) -> Result<Vec<(String, Fr)>, RabeError> {
+    return gen_shares_str_with_rng(_secret, _policy, &mut rand::thread_rng());
 }
 
-pub fn gen_shares_json(_secret: Fr, _json: &serde_json::Value) -> Option<Vec<(String, Fr)>> {
+/// Same as `gen_shares_str`, but draws all randomness from `_rng`.
+pub fn gen_shares_str_with_rng(
+    _secret: Fr,
+    _policy: &String,
+    _rng: &mut impl RngCore,
+) -> Result<Vec<(String, Fr)>, RabeError> {
+    let _json = policy_json(_policy)?;
+    return gen_shares_json_with_rng(_secret, &_json, _rng);
+}
+
+pub fn gen_shares_json(
+    _secret: Fr,
+    _json: &serde_json::Value,
+) -> Result<Vec<(String, Fr)>, RabeError> {
+    return gen_shares_json_with_rng(_secret, _json, &mut rand::thread_rng());
+}
+
+/// Same as `gen_shares_json`, but draws all randomness from `_rng`.
+pub fn gen_shares_json_with_rng(
+    _secret: Fr,
+    _json: &serde_json::Value,
+    _rng: &mut impl RngCore,
+) -> Result<Vec<(String, Fr)>, RabeError> {
     let mut _result: Vec<(String, Fr)> = Vec::new();
-    let mut _k = 0;
-    let mut _length = 0;
-    let mut _type = "";
-    // leaf node
-    if _json[POLICY_ATT] != serde_json::Value::Null {
-        match _json[POLICY_ATT].as_str() {
-            Some(_s) => {
-                _result.push((_s.to_string(), _secret));
-                return Some(_result);
-            }
-            None => {
-                println!("Error (gen_shares_json): unkown attribute value");
-                return None;
+    // the nodes still to share, the top of the stack is shared next (prefix order)
+    let mut _stack: Vec<ShareItem> = vec![ShareItem::Node(_json, _secret, 1)];
+    while let Some(_item) = _stack.pop() {
+        let (_json, _secret, _depth) = match _item {
+            ShareItem::Node(_json, _secret, _depth) => (_json, _secret, _depth),
+            ShareItem::Negated(_s) => {
+                _result.push((format!("{}{}", NEGATION_PREFIX, _s), _rng.gen()));
+                continue;
             }
+        };
+        if _depth > MAX_POLICY_DEPTH {
+            return Err(RabeError::PolicyTooDeep);
+        }
+        let (_k, _children, _negated): (usize, Vec<&serde_json::Value>, Vec<&str>) =
+            match policy_node(_json)? {
+                // leaf node
+                PolicyNode::Leaf(_s) => {
+                    _result.push((_s.to_string(), _secret));
+                    continue;
+                }
+                // inner nodes
+                PolicyNode::Or(_children) => (1, _children.iter().collect(), Vec::new()),
+                PolicyNode::And(_children) => {
+                    let (_positive, _negated) = split_negated(_children)?;
+                    (_positive.len(), _positive, _negated)
+                }
+                PolicyNode::Threshold(_k, _children) => {
+                    (_k, _children.iter().collect(), Vec::new())
+                }
+                PolicyNode::Not(_) => return Err(not_outside_and()),
+            };
+        let _shares = child_shares(_secret, _k, _children.len(), _rng)?;
+        // negated attributes get a dummy share after the shares of the other children
+        for _s in _negated.into_iter().rev() {
+            _stack.push(ShareItem::Negated(_s));
+        }
+        for (_i, _child) in _children.into_iter().enumerate().rev() {
+            _stack.push(ShareItem::Node(_child, _shares[_i + 1], _depth + 1));
         }
     }
-    // inner node
-    else if _json[POLICY_OR].is_array() {
-        _type = POLICY_OR;
-        _length = _json[POLICY_OR].as_array().unwrap().len();
-        _k = 1;
-    }
-    // inner node
-    else if _json[POLICY_AND].is_array() {
-        _type = POLICY_AND;
-        _length = _json[POLICY_AND].as_array().unwrap().len();
-        _k = _length;
+    return Ok(_result);
+}
+
+// the work items of gen_shares_json: a node with its secret and depth, or a negated attribute
+enum ShareItem<'a> {
+    Node(&'a serde_json::Value, Fr, usize),
+    Negated(&'a str),
+}
+
+// the shares 0..n of the children of a node, an error instead of too few shares (if k > n
+// or n > MAX_GATE_WIDTH)
+fn child_shares(
+    _secret: Fr,
+    _k: usize,
+    _n: usize,
+    _rng: &mut impl RngCore,
+) -> Result<Vec<Fr>, RabeError> {
+    if _n > MAX_GATE_WIDTH {
+        return Err(RabeError::GateTooWide(_n));
     }
-    let shares = gen_shares(_secret, _k, _length);
-    for _count in 0.._length {
-        match gen_shares_json(shares[_count + 1], &_json[_type][_count]) {
-            None => return None,
-            Some(_items) => {
-                _result.extend(_items.iter().cloned());
-            }
-        }
+    let _shares = gen_shares_with_rng(_secret, _k, _n, _rng);
+    if _shares.len() < _n + 1 {
+        return Err(RabeError::InvalidPolicy(
+            format!("{} of {} children required", _k, _n),
+        ));
     }
-    return Some(_result);
+    return Ok(_shares);
 }
 
+/// Shares a secret among `_n` children, `_k` of which are required: returns the secret followed
+/// by the shares of the children 1..n. Returns no shares if `_k > _n` or if `_n` exceeds
+/// `MAX_GATE_WIDTH`.
 pub fn gen_shares(_secret: Fr, _k: usize, _n: usize) -> Vec<Fr> {
+    return gen_shares_with_rng(_secret, _k, _n, &mut rand::thread_rng());
+}
+
+/// Same as `gen_shares`, but draws the coefficients of the polynomial from `_rng`, e.g. a
+/// seeded random number generator for reproducible shares.
+pub fn gen_shares_with_rng(_secret: Fr, _k: usize, _n: usize, _rng: &mut impl RngCore) -> Vec<Fr> {
     let mut _shares: Vec<Fr> = Vec::new();
-    if _k <= _n {
-        // random number generator
-        let mut _rng = rand::thread_rng();
+    if _k <= _n && _n <= MAX_GATE_WIDTH {
         // polynomial coefficients
         let mut _a: Vec<Fr> = Vec::new();
         for _i in 0.._k {
@@ -251,13 +1261,47 @@ pub fn gen_shares(_secret: Fr, _k: usize, _n: usize) -> Vec<Fr> {
 }
 
 #[allow(dead_code)]
-pub fn recover_secret(_shares: Vec<Fr>, _policy: &String) -> Fr {
-    let _coeff = calc_coefficients_str(_policy).unwrap();
+pub fn recover_secret(_shares: Vec<Fr>, _policy: &String) -> Result<Fr, RabeError> {
+    let _coeff = calc_coefficients_str(_policy)?;
     let mut _secret = Fr::zero();
-    for _i in 0usize.._shares.len() {
-        _secret = _secret + (_coeff[_i].1 * _shares[_i]);
+    for (_c, _share) in _coeff.iter().zip(_shares.iter()) {
+        _secret = _secret + (_c.1 * *_share);
     }
-    return _secret;
+    return Ok(_secret);
+}
+
+/// Recovers the secret from the shares of a set of attributes, e.g. the shares of
+/// `gen_shares_str` of the attributes at hand. In contrast to `recover_secret` the shares are
+/// matched to the leaves of the policy by attribute, and only the leaves needed to satisfy the
+/// policy with the given attributes are used (see `calc_coefficients_pruned_str`). Returns None
+/// if the attributes do not satisfy the policy, the policy is invalid, or an attribute with a
+/// single share would have to be used for more than one leaf.
+///
+/// The shares are not verified. Of the children of an OR (or a THRESHOLD) the first satisfied
+/// ones in policy order are used and the others are ignored, e.g. for `A OR B` only the share
+/// of `A` is used if both are given, so that a wrong share of `A` yields a wrong secret even if
+/// the share of `B` is correct.
+///
+/// # Arguments
+///
+/// * `_shares` - The shares of the attributes at hand
+/// * `_policy` - An access policy given as JSON String
+pub fn recover_secret_map(_shares: &HashMap<String, Fr>, _policy: &str) -> Option<Fr> {
+    let _attr: Vec<String> = _shares.keys().cloned().collect();
+    let _coeff = match calc_coefficients_pruned_str(&_attr, &_policy.to_string()) {
+        Ok(Some(_coeff)) => _coeff,
+        _ => return None,
+    };
+    let mut _used: BTreeSet<&String> = BTreeSet::new();
+    let mut _secret = Fr::zero();
+    for (_s, _c) in _coeff.iter() {
+        // the leaves of an attribute occurring twice in the policy hold different shares
+        if !_used.insert(_s) {
+            return None;
+        }
+        _secret = _secret + (*_c * *_shares.get(_s)?);
+    }
+    return Some(_secret);
 }
 
 pub fn polynomial(_coeff: Vec<Fr>, _x: Fr) -> Fr {
@@ -287,7 +1331,7 @@ mod tests {
         let _reconstruct = recover_secret(
             _input,
             &String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#),
-        );
+        ).unwrap();
         assert!(_k == _reconstruct);
     }
 
@@ -329,7 +1373,7 @@ mod tests {
         let _reconstruct = recover_secret(
             _input,
             &String::from(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#),
-        );
+        ).unwrap();
         //println!("_reconstructed: {:?}", into_dec(_reconstruct).unwrap());
         assert!(_k == _reconstruct);
     }
@@ -368,4 +1412,658 @@ mod tests {
         assert_eq!(_match3, false);
         assert_eq!(_list3.is_empty(), true);
     }
+
+    #[test]
+    fn test_pruning_ct() {
+        let _policies = vec![
+            r#"{"OR": [{"AND": [{"ATT": "1"}, {"ATT": "2"}]}, {"AND": [{"ATT": "3"}, {"ATT": "4"}]}]}"#,
+            r#"{"OR": [{"ATT": "3"}, {"AND": [{"ATT": "4"}, {"ATT": "5"}]}]}"#,
+            r#"{"AND": [{"AND": [{"ATT": "1"}, {"ATT": "2"}]}, {"AND": [{"ATT": "3"}, {"ATT": "4"}]}]}"#,
+            r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "B"}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}}"#,
+            r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}, {"OR": [{"ATT": "C"}, {"ATT": "D"}]}]}"#,
+            r#"{"ATT": "A"}"#,
+            r#"{"TRUE": true}"#,
+        ];
+        let _sets = vec![
+            vec!["3", "4"],
+            vec!["1", "2", "3", "4"],
+            vec!["A", "B"],
+            vec!["A", "C"],
+            vec!["A", "B", "C", "D"],
+            vec!["B", "C", "D"],
+            vec![TRUE_ATTRIBUTE],
+            vec!["5"],
+        ];
+        for _policy in _policies {
+            let _policy = String::from(_policy);
+            for _set in _sets.iter() {
+                let _attr: Vec<String> = _set.iter().map(|_a| _a.to_string()).collect();
+                assert_eq!(
+                    calc_pruned_str_ct(&_attr, &_policy).unwrap(),
+                    calc_pruned_str(&_attr, &_policy).unwrap()
+                );
+            }
+        }
+        let _not = String::from(r#"{"OR": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#);
+        assert_eq!(calc_pruned_str_ct(&vec![String::from("A")], &_not).is_err(), true);
+    }
+
+    #[test]
+    fn test_binary_policy() {
+        let _policies = vec![
+            r#"{"ATT": "A"}"#,
+            r#"{"TRUE": true}"#,
+            r#"{"OR": [{"AND": [{"ATT": "1"}, {"ATT": "2"}]}, {"AND": [{"ATT": "3"}, {"ATT": "4"}]}]}"#,
+            r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "B"}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}}"#,
+            r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}, {"OR": [{"ATT": "C"}, {"ATT": "ünïcödé"}]}]}"#,
+            r#"{"OR": [{"AND": [{"ATT": "C"}, {"ATT": "A"}, {"NOT": {"ATT": "D"}}]}, {"THRESHOLD": {"k": 2, "children": [{"ATT": "B"}, {"ATT": "A"}, {"OR": [{"ATT": "C"}, {"TRUE": true}]}]}}]}"#,
+        ];
+        for _policy in _policies {
+            let _bytes = policy_to_bytes(_policy).unwrap();
+            assert_eq!(_bytes.len() < _policy.len(), true);
+            let _decoded = policy_from_bytes(&_bytes).unwrap();
+            assert_eq!(string_to_json(&_decoded), string_to_json(&_policy.to_string()));
+            assert_eq!(policy_to_bytes(&_decoded).unwrap(), _bytes);
+        }
+        // the encoding of an AND of two attributes
+        assert_eq!(
+            policy_to_bytes(r#"{"AND": [{"ATT": "A"}, {"ATT": "BC"}]}"#).unwrap(),
+            vec![1, 1, 0, 2, 0, 0, 1, b'A', 0, 0, 2, b'B', b'C']
+        );
+        // malformed input
+        let _bytes = policy_to_bytes(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#).unwrap();
+        assert_eq!(policy_from_bytes(&[]).is_err(), true);
+        assert_eq!(policy_from_bytes(&_bytes[.._bytes.len() - 1]).is_err(), true);
+        assert_eq!(policy_from_bytes(&[&_bytes[..], &[0]].concat()).is_err(), true);
+        assert_eq!(policy_from_bytes(&[1, 9]).is_err(), true);
+        assert_eq!(policy_from_bytes(&[2, 0, 0, 1, b'A']).is_err(), true);
+        // an AND with a single child is not a valid policy
+        assert_eq!(policy_from_bytes(&[1, 1, 0, 1, 0, 0, 1, b'A']).is_err(), true);
+        assert_eq!(policy_to_bytes("joking-around?").is_err(), true);
+    }
+
+    #[test]
+    fn test_binary_policy_deep() {
+        // AND(A, AND(A, ... AND(A, B))) with _depth levels
+        let _nested = |_depth: usize| {
+            let mut _bytes = vec![BINARY_VERSION];
+            for _ in 1.._depth {
+                _bytes.extend_from_slice(&[BINARY_AND, 0, 2, BINARY_ATT, 0, 1, b'A']);
+            }
+            _bytes.extend_from_slice(&[BINARY_ATT, 0, 1, b'B']);
+            return _bytes;
+        };
+        assert_eq!(policy_from_bytes(&_nested(3)).is_ok(), true);
+        assert_eq!(
+            policy_from_bytes(&_nested(MAX_POLICY_DEPTH + 1)).err(),
+            Some(RabeError::PolicyTooDeep)
+        );
+        // several thousand levels return an error instead of overflowing the stack
+        assert_eq!(policy_from_bytes(&_nested(100000)).err(), Some(RabeError::PolicyTooDeep));
+    }
+
+    #[test]
+    fn test_missing_attributes() {
+        let _policy = String::from(
+            r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}, {"ATT": "C"}]}, {"AND": [{"ATT": "D"}, {"ATT": "E"}]}]}"#,
+        );
+        let _none: Vec<String> = Vec::new();
+        assert_eq!(
+            missing_attributes_str(&_none, &_policy).unwrap(),
+            vec!["D".to_string(), "E".to_string()]
+        );
+        let _ab = vec!["A".to_string(), "B".to_string()];
+        assert_eq!(
+            missing_attributes_str(&_ab, &_policy).unwrap(),
+            vec!["C".to_string()]
+        );
+        let _de = vec!["D".to_string(), "E".to_string()];
+        assert_eq!(missing_attributes_str(&_de, &_policy).unwrap().is_empty(), true);
+        assert_eq!(
+            missing_attributes_str(&_de, &String::from(r#"{"ATT": 1}"#)).is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_malformed_policies() {
+        let _attributes = vec![String::from("A"), String::from("B")];
+        let _secret = Fr::one();
+        // AND without an array of children
+        let _not_an_array = String::from(r#"{"AND": {"ATT": "A"}}"#);
+        assert_eq!(calc_pruned_str(&_attributes, &_not_an_array).is_err(), true);
+        assert_eq!(calc_coefficients_str(&_not_an_array).is_err(), true);
+        assert_eq!(gen_shares_str(_secret, &_not_an_array).is_err(), true);
+        // ATT that is not a string
+        let _not_a_string = String::from(r#"{"OR": [{"ATT": "A"}, {"ATT": 42}]}"#);
+        assert_eq!(calc_coefficients_str(&_not_a_string).is_err(), true);
+        assert_eq!(gen_shares_str(_secret, &_not_a_string).is_err(), true);
+        match required_attributes(&_attributes, &string_to_json(&_not_a_string).unwrap()["OR"][1]) {
+            Err(RabeError::InvalidPolicy(_)) => {}
+            _ => panic!("expected RabeError::InvalidPolicy"),
+        }
+        // invalid JSON
+        match gen_shares_str(_secret, &String::from("joking-around?")) {
+            Err(RabeError::PolicyParse(_)) => {}
+            _ => panic!("expected RabeError::PolicyParse"),
+        }
+    }
+
+    #[test]
+    fn test_validate_policy() {
+        assert_eq!(
+            validate_policy(r#"{"OR": [{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}, {"THRESHOLD": {"k": 1, "children": [{"ATT": "C"}]}}]}"#),
+            Ok(())
+        );
+        assert_eq!(validate_policy(r#"{"TRUE": true}"#), Ok(()));
+        assert_eq!(validate_policy(""), Err(RabeError::EmptyPolicy));
+        // malformed JSON
+        match validate_policy(r#"{"AND": [{"ATT": "A"}"#) {
+            Err(RabeError::PolicyParse(_)) => {}
+            _ => panic!("expected RabeError::PolicyParse"),
+        }
+        // unknown node type, also deep inside the tree
+        match validate_policy(r#"{"OR": [{"ATT": "A"}, {"AND": [{"ATT": "B"}, {"XOR": []}]}]}"#) {
+            Err(RabeError::NotAPolicy(_keys)) => assert_eq!(_keys, vec![String::from("XOR")]),
+            _ => panic!("expected RabeError::NotAPolicy"),
+        }
+        // gates with less than two children
+        assert_eq!(
+            validate_policy(r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}]}]}"#),
+            Err(RabeError::InvalidPolicy(String::from("OR with less than two children")))
+        );
+        // empty attributes, also when negated
+        assert_eq!(
+            validate_policy(r#"{"OR": [{"ATT": "A"}, {"ATT": ""}]}"#),
+            Err(RabeError::InvalidPolicy(String::from("empty attribute")))
+        );
+        assert_eq!(
+            validate_policy(r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": ""}}]}"#),
+            Err(RabeError::InvalidPolicy(String::from("empty attribute")))
+        );
+        // NOT outside of an AND
+        assert_eq!(validate_policy(r#"{"OR": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#).is_err(), true);
+    }
+
+    #[test]
+    fn test_policy_attributes() {
+        let _policy = r#"{"OR": [{"AND": [{"ATT": "C"}, {"ATT": "A"}, {"NOT": {"ATT": "D"}}]}, {"THRESHOLD": {"k": 2, "children": [{"ATT": "B"}, {"ATT": "A"}, {"OR": [{"ATT": "C"}, {"TRUE": true}]}]}}]}"#;
+        assert_eq!(
+            policy_attributes(_policy).unwrap(),
+            vec![String::from("A"), String::from("B"), String::from("C"), String::from("D")]
+        );
+        assert_eq!(policy_attributes(r#"{"ATT": "A"}"#).unwrap(), vec![String::from("A")]);
+        assert_eq!(policy_attributes(r#"{"TRUE": true}"#).unwrap().is_empty(), true);
+        assert_eq!(policy_attributes("joking-around?").is_err(), true);
+        assert_eq!(policy_attributes(r#"{"AND": [{"ATT": "A"}, {"foo": "bar"}]}"#).is_err(), true);
+    }
+
+    #[test]
+    fn test_not_a_policy() {
+        let _attributes = vec![String::from("A"), String::from("B")];
+        let _foo = String::from(r#"{"foo": "bar"}"#);
+        match calc_coefficients_str(&_foo) {
+            Err(RabeError::NotAPolicy(_keys)) => assert_eq!(_keys, vec![String::from("foo")]),
+            _ => panic!("expected RabeError::NotAPolicy"),
+        }
+        match calc_pruned_str(&_attributes, &_foo) {
+            Err(RabeError::NotAPolicy(_keys)) => assert_eq!(_keys, vec![String::from("foo")]),
+            _ => panic!("expected RabeError::NotAPolicy"),
+        }
+        // a nested non-policy node reports its own keys
+        let _nested = String::from(r#"{"AND": [{"ATT": "A"}, {"and": [], "att": "B"}]}"#);
+        match calc_pruned_str(&_attributes, &_nested) {
+            Err(RabeError::NotAPolicy(_keys)) => {
+                assert_eq!(_keys.contains(&String::from("and")), true);
+                assert_eq!(_keys.contains(&String::from("att")), true);
+            }
+            _ => panic!("expected RabeError::NotAPolicy"),
+        }
+        // JSON that is not an object
+        match calc_coefficients_str(&String::from("42")) {
+            Err(RabeError::NotAPolicy(_keys)) => assert_eq!(_keys.is_empty(), true),
+            _ => panic!("expected RabeError::NotAPolicy"),
+        }
+        // wrong attributes are not an error
+        assert_eq!(
+            calc_pruned_str(&vec![String::from("C")], &String::from(r#"{"ATT": "A"}"#)).unwrap().0,
+            false
+        );
+    }
+
+    #[test]
+    fn test_true_policy() {
+        let _true = String::from(r#"{"TRUE": true}"#);
+        let _attributes = vec![String::from("A"), String::from(TRUE_ATTRIBUTE)];
+        assert_eq!(
+            calc_pruned_str(&_attributes, &_true).unwrap(),
+            (true, vec![String::from(TRUE_ATTRIBUTE)])
+        );
+        assert_eq!(
+            calc_pruned_str(&vec![String::from("A")], &_true).unwrap().0,
+            false
+        );
+        let _shares = gen_shares_str(Fr::one(), &_true).unwrap();
+        assert_eq!(_shares.len(), 1);
+        assert_eq!(_shares[0].0, String::from(TRUE_ATTRIBUTE));
+        assert_eq!(_shares[0].1 == Fr::one(), true);
+        assert_eq!(
+            calc_pruned_str(&_attributes, &String::from(r#"{"TRUE": false}"#)).is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_duplicate_interpolation_points() {
+        // the interpolation would divide by (1 - 1)
+        match recover_coefficients(vec![Fr::one(), Fr::one()]) {
+            Err(RabeError::Interpolation(_)) => {}
+            _ => panic!("expected RabeError::Interpolation"),
+        }
+        assert_eq!(recover_coefficients(vec![Fr::one(), usize_to_fr(2)]).is_ok(), true);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let _policy = String::from(
+            r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "B"}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}}"#,
+        );
+        let _secret: Fr = rand::thread_rng().gen();
+        let _shares = gen_shares_str(_secret, &_policy).unwrap();
+        assert_eq!(_shares.len(), 4);
+        // any two satisfied children reconstruct the secret
+        let _sets = vec![vec!["A", "B"], vec!["A", "C", "D"], vec!["B", "C", "D"], vec!["A", "B", "C", "D"]];
+        for _set in _sets {
+            let _attr: Vec<String> = _set.iter().map(|_a| _a.to_string()).collect();
+            assert_eq!(calc_pruned_str(&_attr, &_policy).unwrap().0, true);
+            let _coeff = calc_coefficients_pruned_str(&_attr, &_policy).unwrap().unwrap();
+            let mut _reconstruct = Fr::zero();
+            for (_name, _c) in _coeff {
+                let _share = _shares.iter().find(|_s| _s.0 == _name).unwrap().1;
+                _reconstruct = _reconstruct + (_c * _share);
+            }
+            assert_eq!(_reconstruct == _secret, true);
+        }
+        // one child is not enough
+        let _a_c = vec![String::from("A"), String::from("C")];
+        assert_eq!(calc_pruned_str(&_a_c, &_policy).unwrap().0, false);
+        assert_eq!(calc_coefficients_pruned_str(&_a_c, &_policy).unwrap().is_none(), true);
+        assert_eq!(missing_attributes_str(&_a_c, &_policy).unwrap(), vec![String::from("B")]);
+        // the coefficients of a threshold depend on the attributes
+        assert_eq!(calc_coefficients_str(&_policy).is_err(), true);
+    }
+
+    #[test]
+    fn test_indexed_coefficients() {
+        // the same attribute in two leaves, with a negation shifting the indices
+        let _policy = String::from(
+            r#"{"OR": [{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "C"}}, {"ATT": "B"}]}, {"AND": [{"ATT": "A"}, {"ATT": "D"}]}]}"#,
+        );
+        let _secret: Fr = rand::thread_rng().gen();
+        let _shares = gen_shares_str(_secret, &_policy).unwrap();
+        assert_eq!(_shares.len(), 5);
+        for _set in vec![vec!["A", "B"], vec!["A", "D"], vec!["A", "C", "D"]] {
+            let _attr: Vec<String> = _set.iter().map(|_a| _a.to_string()).collect();
+            let _coeff = calc_coefficients_indexed_str(&_attr, &_policy).unwrap().unwrap();
+            let mut _reconstruct = Fr::zero();
+            for (_index, _name, _c) in _coeff {
+                assert_eq!(_shares[_index].0, _name);
+                _reconstruct = _reconstruct + (_c * _shares[_index].1);
+            }
+            assert_eq!(_reconstruct == _secret, true);
+        }
+        let _a_c = vec![String::from("A"), String::from("C")];
+        assert_eq!(calc_coefficients_indexed_str(&_a_c, &_policy).unwrap().is_none(), true);
+    }
+
+    #[test]
+    fn test_short_shares() {
+        let _secret: Fr = rand::thread_rng().gen();
+        // gen_shares returns no shares if more shares are required than generated
+        assert_eq!(gen_shares(_secret, 3, 2).is_empty(), true);
+        assert_eq!(
+            child_shares(_secret, 3, 2, &mut rand::thread_rng()).err(),
+            Some(RabeError::InvalidPolicy(String::from("3 of 2 children required")))
+        );
+        assert_eq!(child_shares(_secret, 2, 2, &mut rand::thread_rng()).unwrap().len(), 3);
+        // a node requiring more children than it has is an error, not a panic
+        let _policy = String::from(
+            r#"{"AND": [{"ATT": "A"}, {"THRESHOLD": {"k": 4, "children": [{"ATT": "B"}, {"ATT": "C"}]}}]}"#,
+        );
+        assert_eq!(gen_shares_str(_secret, &_policy).is_err(), true);
+    }
+
+    #[test]
+    fn test_threshold_invalid() {
+        let _policies = vec![
+            r#"{"THRESHOLD": {"k": 3, "children": [{"ATT": "A"}, {"ATT": "B"}]}}"#,
+            r#"{"THRESHOLD": {"k": 0, "children": [{"ATT": "A"}, {"ATT": "B"}]}}"#,
+            r#"{"THRESHOLD": {"k": -1, "children": [{"ATT": "A"}, {"ATT": "B"}]}}"#,
+            r#"{"THRESHOLD": {"children": [{"ATT": "A"}, {"ATT": "B"}]}}"#,
+            r#"{"THRESHOLD": {"k": 1, "children": {"ATT": "A"}}}"#,
+        ];
+        for _p in _policies {
+            match gen_shares_str(Fr::one(), &String::from(_p)) {
+                Err(RabeError::InvalidPolicy(_)) => {}
+                _ => panic!("expected RabeError::InvalidPolicy for {}", _p),
+            }
+        }
+    }
+
+    #[test]
+    fn test_not() {
+        let _policy = String::from(
+            r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}, {"OR": [{"ATT": "C"}, {"ATT": "D"}]}]}"#,
+        );
+        let _secret: Fr = rand::thread_rng().gen();
+        let _shares = gen_shares_str(_secret, &_policy).unwrap();
+        let _names: Vec<&str> = _shares.iter().map(|_s| _s.0.as_str()).collect();
+        assert_eq!(_names, vec!["A", "C", "D", "!B"]);
+        // the negated attribute has a coefficient of zero
+        let _coeff = calc_coefficients_str(&_policy).unwrap();
+        assert_eq!(_coeff[3].0, String::from("!B"));
+        assert_eq!(_coeff[3].1 == Fr::zero(), true);
+        let _a_c = vec![String::from("A"), String::from("C")];
+        assert_eq!(calc_pruned_str(&_a_c, &_policy).unwrap(), (true, _a_c.clone()));
+        let _z = calc_coefficients_pruned_str(&_a_c, &_policy).unwrap().unwrap();
+        let mut _reconstruct = Fr::zero();
+        for (_name, _c) in _z {
+            let _share = _shares.iter().find(|_s| _s.0 == _name).unwrap().1;
+            _reconstruct = _reconstruct + (_c * _share);
+        }
+        assert_eq!(_reconstruct == _secret, true);
+        // a key with the negated attribute does not satisfy the policy
+        let _a_b_c = vec![String::from("A"), String::from("B"), String::from("C")];
+        assert_eq!(calc_pruned_str(&_a_b_c, &_policy).unwrap().0, false);
+        assert_eq!(calc_coefficients_pruned_str(&_a_b_c, &_policy).unwrap().is_none(), true);
+        // NOT only wraps attributes and only inside AND
+        let _invalid = vec![
+            r#"{"NOT": {"ATT": "B"}}"#,
+            r#"{"OR": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#,
+            r#"{"AND": [{"NOT": {"ATT": "A"}}, {"NOT": {"ATT": "B"}}]}"#,
+            r#"{"AND": [{"ATT": "A"}, {"NOT": {"OR": [{"ATT": "B"}, {"ATT": "C"}]}}]}"#,
+        ];
+        for _p in _invalid {
+            match gen_shares_str(Fr::one(), &String::from(_p)) {
+                Err(RabeError::InvalidPolicy(_)) => {}
+                _ => panic!("expected RabeError::InvalidPolicy for {}", _p),
+            }
+        }
+    }
+
+    #[test]
+    fn test_recover_secret_map() {
+        let _secret: Fr = rand::thread_rng().gen();
+        let _policies = vec![
+            (r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#, vec!["B"], vec!["C"]),
+            (r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#, vec!["A", "B"], vec!["B"]),
+            (
+                r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}"#,
+                vec!["C", "D"],
+                vec!["A", "C"],
+            ),
+            (
+                r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"AND": [{"ATT": "C"}, {"ATT": "D"}]}]}]}"#,
+                vec!["A", "C", "D"],
+                vec!["C", "D"],
+            ),
+        ];
+        for (_policy, _satisfying, _not_satisfying) in _policies {
+            let _all: HashMap<String, Fr> = gen_shares_str(_secret, &_policy.to_string())
+                .unwrap()
+                .into_iter()
+                .collect();
+            // only the shares of the attributes at hand, in no particular order
+            let _subset = |_set: &Vec<&str>| -> HashMap<String, Fr> {
+                return _set.iter().map(|_a| (_a.to_string(), _all[*_a])).collect();
+            };
+            let mut _shares = _subset(&_satisfying);
+            assert_eq!(recover_secret_map(&_shares, _policy) == Some(_secret), true);
+            // shares of attributes that are not needed are ignored
+            _shares.entry(String::from("C")).or_insert(Fr::one());
+            assert_eq!(recover_secret_map(&_shares, _policy) == Some(_secret), true);
+            let _shares: HashMap<String, Fr> = _not_satisfying
+                .iter()
+                .map(|_a| (_a.to_string(), *_all.get(*_a).unwrap_or(&Fr::one())))
+                .collect();
+            assert_eq!(recover_secret_map(&_shares, _policy).is_none(), true);
+        }
+        assert_eq!(recover_secret_map(&HashMap::new(), "joking-around?").is_none(), true);
+    }
+
+    #[test]
+    fn test_recover_secret_map_first_branch() {
+        let _secret: Fr = rand::thread_rng().gen();
+        let _policy = r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#;
+        let _all: HashMap<String, Fr> = gen_shares_str(_secret, &_policy.to_string())
+            .unwrap()
+            .into_iter()
+            .collect();
+        let mut _shares: HashMap<String, Fr> = HashMap::new();
+        _shares.insert(String::from("B"), _all["B"]);
+        assert_eq!(recover_secret_map(&_shares, _policy) == Some(_secret), true);
+        // a bogus share of A wins over the correct share of B, the first branch is used
+        _shares.insert(String::from("A"), _all["A"] + Fr::one());
+        assert_eq!(recover_secret_map(&_shares, _policy) == Some(_secret + Fr::one()), true);
+        // a bogus share of B is not used
+        _shares.insert(String::from("A"), _all["A"]);
+        _shares.insert(String::from("B"), Fr::one());
+        assert_eq!(recover_secret_map(&_shares, _policy) == Some(_secret), true);
+    }
+
+    #[test]
+    fn test_share_paths() {
+        let _policy = String::from(
+            r#"{"OR": [{"ATT": "A"}, {"AND": [{"NOT": {"ATT": "C"}}, {"ATT": "A"}, {"ATT": "B"}]}]}"#,
+        );
+        let _paths = share_paths(&_policy).unwrap();
+        assert_eq!(
+            _paths,
+            vec![
+                (String::from("0.0"), String::from("A")),
+                (String::from("0.1.1"), String::from("A")),
+                (String::from("0.1.2"), String::from("B")),
+                (String::from("0.1.0"), format!("{}C", NEGATION_PREFIX)),
+            ]
+        );
+        // in the order of the shares, the indexed coefficients refer to them
+        let _shares = gen_shares_str(Fr::one(), &_policy).unwrap();
+        let _names: Vec<String> = _shares.into_iter().map(|_s| _s.0).collect();
+        assert_eq!(_names, _paths.iter().map(|_p| _p.1.clone()).collect::<Vec<_>>());
+        let _attr = vec![String::from("A"), String::from("B")];
+        let _coeff = calc_coefficients_indexed_str(&_attr, &_policy).unwrap().unwrap();
+        assert_eq!(_coeff.iter().map(|_c| _c.0).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(share_paths(r#"{"ATT": "A"}"#).unwrap(), vec![(String::from("0"), String::from("A"))]);
+        assert_eq!(share_paths("joking-around?").is_err(), true);
+    }
+
+    #[test]
+    fn test_policy_from_file() {
+        let _dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("data");
+        let _inline = r#"{"OR": [{"AND": [{"ATT": "A"}, {"ATT": "B"}]}, {"AND": [{"ATT": "C"}, {"OR": [{"ATT": "D"}, {"ATT": "E"}]}]}]}"#;
+        let _expected: serde_json::Value = serde_json::from_str(_inline).unwrap();
+        for _file in ["policy.json", "policy.txt"].iter() {
+            let _policy = policy_from_file(&_dir.join(_file)).unwrap();
+            assert_eq!(string_to_json(&_policy).unwrap(), _expected);
+            assert_eq!(canonicalize_policy(&_policy), canonicalize_policy(_inline));
+        }
+        assert_eq!(policy_from_file(&_dir.join("no-such-policy.json")).is_err(), true);
+        assert_eq!(policy_from_file(&_dir.join("README.md")).is_err(), true);
+    }
+
+    #[test]
+    fn test_threshold_policy() {
+        let _attrs = vec![String::from("A"), String::from("B"), String::from("C")];
+        let _policy = threshold_policy(&_attrs, 2).unwrap();
+        assert_eq!(
+            _policy,
+            r#"{"THRESHOLD":{"children":[{"ATT":"A"},{"ATT":"B"},{"ATT":"C"}],"k":2}}"#
+        );
+        assert_eq!(validate_policy(&_policy), Ok(()));
+        assert_eq!(validate_policy(&threshold_policy(&_attrs, 1).unwrap()), Ok(()));
+        assert_eq!(validate_policy(&threshold_policy(&_attrs, 3).unwrap()), Ok(()));
+        // k has to be in 1..=n
+        assert_eq!(threshold_policy(&_attrs, 0).is_err(), true);
+        assert_eq!(threshold_policy(&_attrs, 4).is_err(), true);
+        assert_eq!(threshold_policy(&[], 1), Err(RabeError::EmptyAttributes));
+        let _repeated = vec![String::from("A"), String::from("A"), String::from("B")];
+        assert_eq!(threshold_policy(&_repeated, 2).is_err(), true);
+        assert_eq!(threshold_policy(&[String::new()], 1).is_err(), true);
+    }
+
+    #[test]
+    fn test_canonicalize_policy() {
+        let _policy = r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "C"}, {"ATT": "B"}]}]}"#;
+        let _equivalent = r#"{ "AND" : [ {"OR":[{"ATT":"B"},{"ATT":"C"},{"ATT":"B"}]},
+            {"AND": [{"ATT": "A"}, {"ATT": "A"}]} ] }"#;
+        let _canonical = canonicalize_policy(_policy).unwrap();
+        assert_eq!(
+            _canonical,
+            r#"{"AND":[{"ATT":"A"},{"OR":[{"ATT":"B"},{"ATT":"C"}]}]}"#
+        );
+        assert_eq!(canonicalize_policy(_equivalent).unwrap(), _canonical);
+        // the canonical form is a fixed point and keeps TRUE, NOT and THRESHOLD
+        assert_eq!(canonicalize_policy(&_canonical).unwrap(), _canonical);
+        let _policy = r#"{"OR": [{"TRUE": true}, {"AND": [{"NOT": {"ATT": "B"}}, {"THRESHOLD": {"k": 1, "children": [{"ATT": "D"}, {"ATT": "C"}]}}]}]}"#;
+        let _canonical = canonicalize_policy(_policy).unwrap();
+        assert_eq!(canonicalize_policy(&_canonical).unwrap(), _canonical);
+        assert_eq!(validate_policy(&_canonical), Ok(()));
+        assert_eq!(_canonical.contains(r#"{"TRUE":true}"#), true);
+        // different policies stay different
+        assert_eq!(
+            canonicalize_policy(r#"{"OR": [{"ATT": "A"}, {"ATT": "B"}]}"#).unwrap() ==
+                canonicalize_policy(r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#).unwrap(),
+            false
+        );
+        assert_eq!(canonicalize_policy("joking-around?").is_err(), true);
+    }
+
+    #[test]
+    fn test_policy_limits() {
+        let _policy = r#"{"AND": [{"ATT": "A"}, {"OR": [{"ATT": "B"}, {"ATT": "C"}]}]}"#;
+        assert_eq!(validate_policy(_policy), Ok(()));
+        let _limits = PolicyLimits {
+            _max_nodes: 5,
+            _max_depth: 3,
+        };
+        assert_eq!(validate_policy_with_limits(_policy, &_limits), Ok(()));
+        assert_eq!(
+            validate_policy_with_limits(_policy, &PolicyLimits { _max_nodes: 4, .._limits }),
+            Err(RabeError::PolicyTooLarge)
+        );
+        assert_eq!(
+            validate_policy_with_limits(_policy, &PolicyLimits { _max_depth: 2, .._limits }),
+            Err(RabeError::PolicyTooDeep)
+        );
+        // negated attributes count as well
+        assert_eq!(
+            validate_policy_with_limits(
+                r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "B"}}]}"#,
+                &PolicyLimits { _max_nodes: 2, .._limits }
+            ),
+            Err(RabeError::PolicyTooLarge)
+        );
+        // an OR of MAX_POLICY_NODES attributes exceeds the default limit by one node
+        let _leaves: Vec<String> = (0..MAX_POLICY_NODES)
+            .map(|_i| format!(r#"{{"ATT": "A{}"}}"#, _i))
+            .collect();
+        let _oversized = format!(r#"{{"OR": [{}]}}"#, _leaves.join(", "));
+        assert_eq!(validate_policy(&_oversized), Err(RabeError::PolicyTooLarge));
+        let _largest = format!(r#"{{"OR": [{}]}}"#, _leaves[1..].join(", "));
+        assert_eq!(validate_policy(&_largest), Ok(()));
+    }
+
+    // an OR of an attribute and the next level, _depth levels deep
+    fn nested_policy(_depth: usize) -> serde_json::Value {
+        let mut _json = serde_json::from_str(r#"{"ATT": "A0"}"#).unwrap();
+        for _i in 1.._depth {
+            let mut _leaf = serde_json::Map::new();
+            _leaf.insert(String::from("ATT"), serde_json::Value::String(format!("A{}", _i)));
+            let mut _node = serde_json::Map::new();
+            _node.insert(
+                String::from("OR"),
+                serde_json::Value::Array(vec![serde_json::Value::Object(_leaf), _json]),
+            );
+            _json = serde_json::Value::Object(_node);
+        }
+        return _json;
+    }
+
+    // takes a nested policy apart level by level, dropping it at once would recurse as well
+    fn drop_nested(mut _json: serde_json::Value) {
+        loop {
+            let _next = match _json.get_mut("OR").and_then(|_c| _c.as_array_mut()) {
+                Some(_children) => _children.pop().unwrap(),
+                None => break,
+            };
+            _json = _next;
+        }
+    }
+
+    #[test]
+    fn test_deep_policy() {
+        let _secret: Fr = rand::thread_rng().gen();
+        let _json = nested_policy(MAX_POLICY_DEPTH);
+        let _shares = gen_shares_json(_secret, &_json).unwrap();
+        assert_eq!(_shares.len(), MAX_POLICY_DEPTH);
+        // the shares are in prefix order, the innermost attribute comes last
+        assert_eq!(_shares[0].0, format!("A{}", MAX_POLICY_DEPTH - 1));
+        assert_eq!(_shares[MAX_POLICY_DEPTH - 1].0, String::from("A0"));
+        assert_eq!(_shares.iter().all(|_share| _share.1 == _secret), true);
+        drop_nested(_json);
+        // several thousand levels return an error instead of overflowing the stack
+        let _json = nested_policy(5000);
+        assert_eq!(gen_shares_json(_secret, &_json).err(), Some(RabeError::PolicyTooDeep));
+        drop_nested(_json);
+        // as String, deep JSON is rejected by the parser
+        let _policy = format!(
+            "{}{}{}",
+            r#"{"AND": [{"ATT": "A"}, "#.repeat(5000),
+            r#"{"ATT": "B"}"#,
+            "]}".repeat(5000)
+        );
+        assert_eq!(gen_shares_str(_secret, &_policy).is_err(), true);
+        assert_eq!(validate_policy(&_policy).is_err(), true);
+    }
+
+    #[test]
+    fn test_wide_gates() {
+        let _secret: Fr = rand::thread_rng().gen();
+        // 128 of 256 and 256 of 256 children, reconstructed from the last k shares
+        for _k in vec![128, 256] {
+            let _shares = gen_shares(_secret, _k, 256);
+            assert_eq!(_shares.len(), 257);
+            let _points: Vec<usize> = ((257 - _k)..257).collect();
+            let _coeff = recover_coefficients(_points.iter().map(|_x| usize_to_fr(*_x)).collect())
+                .unwrap();
+            let _result = _points
+                .iter()
+                .zip(_coeff.iter())
+                .fold(Fr::zero(), |_sum, (_x, _c)| _sum + (*_c * _shares[*_x]));
+            assert_eq!(_result == _secret, true);
+        }
+        // as policy, using the pruned coefficients of the satisfied children
+        let _attrs: Vec<String> = (0..256).map(|_i| format!("A{}", _i)).collect();
+        let _policy = threshold_policy(&_attrs, 200).unwrap();
+        let _shares = gen_shares_str(_secret, &_policy).unwrap();
+        let _coeff = calc_coefficients_pruned_str(&_attrs[56..].to_vec(), &_policy)
+            .unwrap()
+            .unwrap();
+        let mut _result = Fr::zero();
+        for (_attr, _c) in _coeff {
+            let _share = _shares.iter().find(|_s| _s.0 == _attr).unwrap().1;
+            _result = _result + (_c * _share);
+        }
+        assert_eq!(_result == _secret, true);
+        // gates wider than MAX_GATE_WIDTH are rejected
+        assert_eq!(gen_shares(_secret, 1, MAX_GATE_WIDTH + 1).is_empty(), true);
+        assert_eq!(gen_shares(_secret, 1, MAX_GATE_WIDTH).len(), MAX_GATE_WIDTH + 1);
+        let _attrs: Vec<String> = (0..MAX_GATE_WIDTH + 1).map(|_i| format!("A{}", _i)).collect();
+        let _policy = threshold_policy(&_attrs, 1).unwrap();
+        assert_eq!(
+            gen_shares_str(_secret, &_policy).err(),
+            Some(RabeError::GateTooWide(MAX_GATE_WIDTH + 1))
+        );
+        assert_eq!(validate_policy(&_policy), Err(RabeError::GateTooWide(MAX_GATE_WIDTH + 1)));
+    }
 }
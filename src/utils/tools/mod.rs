@@ -9,6 +9,8 @@ extern crate serde_json;
 use bn::*;
 use num_bigint::ToBigInt;
 use std::collections::HashSet;
+use utils::policy::numeric::expand_comparisons;
+use utils::secretsharing::TRUE_ATTRIBUTE;
 
 pub fn is_negative(_attr: &String) -> bool {
     let first_char = &_attr[..1];
@@ -22,8 +24,7 @@ pub fn usize_to_fr(_i: usize) -> Fr {
 
 pub fn string_to_json(policy: &String) -> Option<serde_json::Value> {
     match serde_json::from_str(policy) {
-        Err(e) => {
-            println!("string_to_json ERROR: {:?}", e);
+        Err(_) => {
             return None;
         }
         Ok(pol) => {
@@ -32,6 +33,13 @@ pub fn string_to_json(policy: &String) -> Option<serde_json::Value> {
     }
 }
 
+// used to bring attribute names into their canonical form (surrounding whitespace is not significant)
+pub fn normalize_attribute(_attr: &str) -> String {
+    return _attr.trim().to_string();
+}
+
+/// Returns true if `value` is one of the attributes of `data`. Attributes are compared exactly,
+/// i.e. case sensitive and without normalization (see `normalize_attribute`).
 pub fn contains(data: &Vec<String>, value: &String) -> bool {
     let len = data.into_iter()
         .filter(|&i| i == value)
@@ -40,13 +48,88 @@ pub fn contains(data: &Vec<String>, value: &String) -> bool {
     return len >= 1;
 }
 
-// used to check if a set of attributes is a subset of another
+/// The suffix of a wildcard attribute, e.g. `dept.*` matches `dept.sales`, see `matches_attribute`
+pub const WILDCARD_SUFFIX: char = '*';
+
+/// Returns true if the attribute is a wildcard, i.e. a non-empty prefix followed by `*`.
+pub fn is_wildcard(_attr: &str) -> bool {
+    return _attr.len() > 1 && _attr.ends_with(WILDCARD_SUFFIX);
+}
+
+/// Returns true if the key attribute `_key_attr` matches the attribute `_attr`, i.e. both are
+/// equal or `_key_attr` is a wildcard and `_attr` is longer than its prefix and starts with it.
+/// `dept.*` matches `dept.sales` and `dept.sales.emea`, but neither `dept.` nor `dept`.
+pub fn matches_attribute(_key_attr: &str, _attr: &str) -> bool {
+    if _key_attr == _attr {
+        return true;
+    }
+    if !is_wildcard(_key_attr) || is_wildcard(_attr) {
+        return false;
+    }
+    let _prefix = &_key_attr[.._key_attr.len() - 1];
+    return _attr.len() > _prefix.len() && _attr.starts_with(_prefix);
+}
+
+/// Same as `contains`, but the wildcard attributes of `_data` match (see `matches_attribute`).
+pub fn contains_matching(_data: &Vec<String>, _value: &String) -> bool {
+    return _data.iter().any(|_attr| matches_attribute(_attr, _value));
+}
+
+/// Replaces the wildcard attributes of a set by the attributes of `_universe` they match, other
+/// attributes are kept. Wildcards have no cryptographic meaning: a key component is bound to a
+/// concrete attribute name, so a key can only be issued for the concrete attributes known when
+/// it is generated. Expanding against `policy_attributes` of a policy checks whether a wildcard
+/// set would satisfy that policy.
+///
+/// # Arguments
+///
+///	* `_attributes` - A Vector of String attributes, possibly holding wildcards
+///	* `_universe` - The concrete attributes the wildcards are matched against
+pub fn expand_wildcards(_attributes: &Vec<String>, _universe: &Vec<String>) -> Vec<String> {
+    let mut _expanded: Vec<String> = Vec::new();
+    for _attr in _attributes {
+        if !is_wildcard(_attr) {
+            if !_expanded.contains(_attr) {
+                _expanded.push(_attr.clone());
+            }
+            continue;
+        }
+        for _u in _universe {
+            if matches_attribute(_attr, _u) && !_expanded.contains(_u) {
+                _expanded.push(_u.clone());
+            }
+        }
+    }
+    return _expanded;
+}
+
+/// Returns true if every attribute of `_subset` is one of the attributes of `_attr`, e.g. the
+/// attributes of a key. Both are treated as sets: duplicates and order do not matter and the
+/// empty set is a subset of every set. Attributes are compared exactly, as in `contains`.
 pub fn is_subset(_subset: &Vec<String>, _attr: &Vec<String>) -> bool {
     let super_set: HashSet<_> = _attr.iter().cloned().collect();
     let sub_set: HashSet<_> = _subset.iter().cloned().collect();
     return sub_set.is_subset(&super_set);
 }
 
+/// Returns the attributes of `_a` that are also part of `_b`, e.g. the attributes of a key
+/// that are relevant to a policy (given by `policy_attributes`). The result keeps the order
+/// of `_a` and holds each attribute once. Attributes are compared exactly, as in `contains`.
+///
+/// # Arguments
+///
+///	* `_a` - A set of attributes, e.g. of a key
+///	* `_b` - Another set of attributes, e.g. of a policy
+pub fn attribute_intersection(_a: &[String], _b: &[String]) -> Vec<String> {
+    let _other: HashSet<&String> = _b.iter().collect();
+    let mut _seen: HashSet<&String> = HashSet::new();
+    return _a
+        .iter()
+        .filter(|_attr| _other.contains(_attr) && _seen.insert(_attr))
+        .cloned()
+        .collect();
+}
+
 pub fn traverse_str(_attr: &Vec<String>, _policy: &String) -> bool {
     match string_to_json(_policy) {
         None => return false,
@@ -57,11 +140,9 @@ pub fn traverse_str(_attr: &Vec<String>, _policy: &String) -> bool {
 // used to traverse / check policy tree
 pub fn traverse_json(_attr: &Vec<String>, _json: &serde_json::Value) -> bool {
     if *_json == serde_json::Value::Null {
-        println!("Error: passed null as json!");
         return false;
     }
     if _attr.len() == 0 {
-        println!("Error: No attributes in List!");
         return false;
     }
     // inner node or
@@ -74,7 +155,6 @@ pub fn traverse_json(_attr: &Vec<String>, _json: &serde_json::Value) -> bool {
             }
             return ret;
         } else {
-            println!("Error: Invalid policy (OR with just a single child).");
             return false;
         }
     }
@@ -88,10 +168,45 @@ pub fn traverse_json(_attr: &Vec<String>, _json: &serde_json::Value) -> bool {
             }
             return ret;
         } else {
-            println!("Error: Invalid policy (AND with just a single child).");
             return false;
         }
     }
+    // inner node threshold, k of the children are required
+    else if _json["THRESHOLD"].is_object() {
+        match (_json["THRESHOLD"]["k"].as_u64(), _json["THRESHOLD"]["children"].as_array()) {
+            (Some(_k), Some(_children)) if _k >= 1 && _k <= _children.len() as u64 => {
+                let _satisfied = _children
+                    .iter()
+                    .filter(|_child| traverse_json(_attr, _child))
+                    .count();
+                return _satisfied as u64 >= _k;
+            }
+            _ => {
+                return false;
+            }
+        }
+    }
+    // negated leaf node
+    else if _json["NOT"] != serde_json::Value::Null {
+        if _json["NOT"]["ATT"].is_string() {
+            return !traverse_json(_attr, &_json["NOT"]);
+        }
+        return false;
+    }
+    // the "anyone" policy
+    else if _json["TRUE"] != serde_json::Value::Null {
+        return _json["TRUE"] == serde_json::Value::Bool(true) &&
+            (&_attr).into_iter().any(|x| x == TRUE_ATTRIBUTE);
+    }
+    // numeric comparison, see utils::policy::numeric
+    else if _json["GE"] != serde_json::Value::Null || _json["LE"] != serde_json::Value::Null ||
+             _json["EQ"] != serde_json::Value::Null
+    {
+        match expand_comparisons(_json.clone()) {
+            Ok(_expanded) => return traverse_json(_attr, &_expanded),
+            Err(_) => return false,
+        }
+    }
     // leaf node
     else if _json["ATT"] != serde_json::Value::Null {
         match _json["ATT"].as_str() {
@@ -100,14 +215,12 @@ pub fn traverse_json(_attr: &Vec<String>, _json: &serde_json::Value) -> bool {
                 return (&_attr).into_iter().any(|x| x == s);
             }
             None => {
-                println!("Error: in attribute String");
                 return false;
             }
         }
     }
     // error
     else {
-        println!("Error: Policy invalid. No AND or OR found");
         return false;
     }
 }
@@ -157,5 +270,82 @@ mod tests {
         assert_eq!(traverse_str(&_set1, &policy3), false);
         assert_eq!(traverse_str(&_set2, &policy3), false);
         assert_eq!(traverse_str(&_set3, &policy3), true);
+
+        let policy_true = String::from(r#"{"TRUE": true}"#);
+        let mut _set4 = _set0.clone();
+        _set4.push(String::from("#TRUE"));
+        assert_eq!(traverse_str(&_set0, &policy_true), false);
+        assert_eq!(traverse_str(&_set4, &policy_true), true);
+        assert_eq!(traverse_str(&_set4, &String::from(r#"{"TRUE": false}"#)), false);
+
+        let policy_threshold = String::from(
+            r#"{"THRESHOLD": {"k": 2, "children": [{"ATT": "A"}, {"ATT": "C"}, {"ATT": "X"}]}}"#,
+        );
+        assert_eq!(traverse_str(&_set0, &policy_threshold), false);
+        let policy_not = String::from(r#"{"AND": [{"ATT": "A"}, {"NOT": {"ATT": "C"}}]}"#);
+        assert_eq!(traverse_str(&_set1, &policy_not), true);
+        assert_eq!(traverse_str(&_set3, &policy_not), false);
+        assert_eq!(traverse_str(&_set1, &policy_threshold), false);
+        assert_eq!(traverse_str(&_set3, &policy_threshold), true);
+        assert_eq!(
+            traverse_str(
+                &_set3,
+                &String::from(r#"{"THRESHOLD": {"k": 3, "children": [{"ATT": "A"}, {"ATT": "B"}]}}"#),
+            ),
+            false
+        );
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert_eq!(is_wildcard("dept.*"), true);
+        assert_eq!(is_wildcard("*"), false);
+        assert_eq!(is_wildcard("dept"), false);
+        assert_eq!(matches_attribute("dept.*", "dept.sales"), true);
+        assert_eq!(matches_attribute("dept.*", "dept.sales.emea"), true);
+        assert_eq!(matches_attribute("dept.sales", "dept.sales"), true);
+        for _attr in ["dept.", "dept", "department.sales", "sales.dept", "dept.x*"].iter() {
+            assert_eq!(matches_attribute("dept.*", _attr), false);
+        }
+        assert_eq!(matches_attribute("dept.sales", "dept.*"), false);
+        let _key = vec![String::from("dept.*"), String::from("A")];
+        assert_eq!(contains_matching(&_key, &String::from("dept.hr")), true);
+        assert_eq!(contains_matching(&_key, &String::from("A")), true);
+        assert_eq!(contains_matching(&_key, &String::from("lab.hr")), false);
+        let _universe = vec![
+            String::from("dept.sales"),
+            String::from("lab.x"),
+            String::from("dept.hr"),
+        ];
+        assert_eq!(
+            expand_wildcards(&_key, &_universe),
+            vec![String::from("dept.sales"), String::from("dept.hr"), String::from("A")]
+        );
+    }
+
+    #[test]
+    fn test_attribute_sets() {
+        let _key = vec![
+            String::from("A"),
+            String::from("B"),
+            String::from("A"),
+            String::from("C"),
+        ];
+        let _policy = vec![String::from("C"), String::from("A"), String::from("D")];
+        let _empty: Vec<String> = Vec::new();
+        assert_eq!(attribute_intersection(&_key, &_policy), vec![String::from("A"), String::from("C")]);
+        assert_eq!(attribute_intersection(&_policy, &_key), vec![String::from("C"), String::from("A")]);
+        assert_eq!(attribute_intersection(&_key, &_empty).is_empty(), true);
+        assert_eq!(attribute_intersection(&_empty, &_key).is_empty(), true);
+        // disjoint sets and case sensitivity
+        assert_eq!(attribute_intersection(&_key, &vec![String::from("X"), String::from("a")]).is_empty(), true);
+        assert_eq!(contains(&_key, &String::from("A")), true);
+        assert_eq!(contains(&_key, &String::from("a")), false);
+        assert_eq!(contains(&_empty, &String::from("A")), false);
+        assert_eq!(is_subset(&vec![String::from("C"), String::from("A"), String::from("A")], &_key), true);
+        assert_eq!(is_subset(&_empty, &_key), true);
+        assert_eq!(is_subset(&_empty, &_empty), true);
+        assert_eq!(is_subset(&_policy, &_key), false);
+        assert_eq!(is_subset(&_key, &_empty), false);
     }
 }
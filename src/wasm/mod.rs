@@ -0,0 +1,102 @@
+//! This is the documentation for the WebAssembly bindings of R-ABE schemes
+//!
+//! Currently those are:
+//! * BSW CP-ABE for JavaScript
+//!
+//! The bindings are part of the feature `wasm` and exchange keys and ciphertexts as base64
+//! Strings (see `RabeEncode::to_base64`). The randomness of `rand::thread_rng()` comes from
+//! `getrandom`, which uses `crypto.getRandomValues` of the browser (or node) with this feature.
+//! Build for `wasm32-unknown-unknown` without the feature `ffi`, e.g.
+//!
+//! ```bash
+//! wasm-pack build -- --features wasm
+//! ```
+//!
+use schemes::bsw;
+use utils::encoding::RabeEncode;
+use utils::error::RabeError;
+use wasm_bindgen::prelude::*;
+
+/// The base64 encoded keys of `bsw_setup`
+#[wasm_bindgen]
+pub struct BswSetup {
+    _pk: String,
+    _msk: String,
+}
+
+#[wasm_bindgen]
+impl BswSetup {
+    /// The base64 encoded Public Key (PK)
+    #[wasm_bindgen(getter)]
+    pub fn pk(&self) -> String {
+        return self._pk.clone();
+    }
+
+    /// The base64 encoded Master Key (MSK)
+    #[wasm_bindgen(getter)]
+    pub fn msk(&self) -> String {
+        return self._msk.clone();
+    }
+}
+
+fn to_js(_error: RabeError) -> JsValue {
+    return JsValue::from_str(&_error.to_string());
+}
+
+/// The setup algorithm of BSW CP-ABE, see `schemes::bsw::setup`.
+#[wasm_bindgen]
+pub fn bsw_setup() -> Result<BswSetup, JsValue> {
+    let (_pk, _msk) = bsw::setup();
+    return Ok(BswSetup {
+        _pk: _pk.to_base64().map_err(to_js)?,
+        _msk: _msk.to_base64().map_err(to_js)?,
+    });
+}
+
+/// The key generation algorithm of BSW CP-ABE, see `schemes::bsw::keygen`. Returns the base64
+/// encoded Secret Key (SK).
+///
+/// # Arguments
+///
+///	* `_pk` - The base64 encoded Public Key (PK), generated by bsw_setup()
+///	* `_msk` - The base64 encoded Master Key (MSK), generated by bsw_setup()
+///	* `_attributes` - A comma separated list of attributes assigned to this user key
+///
+#[wasm_bindgen]
+pub fn bsw_keygen(_pk: &str, _msk: &str, _attributes: &str) -> Result<String, JsValue> {
+    let _pk = bsw::CpAbePublicKey::from_base64(_pk).map_err(to_js)?;
+    let _msk = bsw::CpAbeMasterKey::from_base64(_msk).map_err(to_js)?;
+    let _attr: Vec<String> = _attributes.split(',').map(|_a| _a.trim().to_string()).collect();
+    let _sk = bsw::keygen(&_pk, &_msk, &_attr).map_err(to_js)?;
+    return _sk.to_base64().map_err(to_js);
+}
+
+/// The encrypt algorithm of BSW CP-ABE, see `schemes::bsw::encrypt`. Returns the base64
+/// encoded Ciphertext (CT).
+///
+/// # Arguments
+///
+///	* `_pk` - The base64 encoded Public Key (PK), generated by bsw_setup()
+///	* `_policy` - An access policy given as JSON String
+///	* `_plaintext` - plaintext data given as bytes
+///
+#[wasm_bindgen]
+pub fn bsw_encrypt(_pk: &str, _policy: &str, _plaintext: &[u8]) -> Result<String, JsValue> {
+    let _pk = bsw::CpAbePublicKey::from_base64(_pk).map_err(to_js)?;
+    let _ct = bsw::encrypt(&_pk, &_policy.to_string(), &_plaintext.to_vec()).map_err(to_js)?;
+    return _ct.to_base64().map_err(to_js);
+}
+
+/// The decrypt algorithm of BSW CP-ABE, see `schemes::bsw::decrypt`. Returns the plaintext.
+///
+/// # Arguments
+///
+///	* `_sk` - The base64 encoded Secret Key (SK), generated by bsw_keygen()
+///	* `_ct` - The base64 encoded Ciphertext (CT), generated by bsw_encrypt()
+///
+#[wasm_bindgen]
+pub fn bsw_decrypt(_sk: &str, _ct: &str) -> Result<Vec<u8>, JsValue> {
+    let _sk = bsw::CpAbeSecretKey::from_base64(_sk).map_err(to_js)?;
+    let _ct = bsw::CpAbeCiphertext::from_base64(_ct).map_err(to_js)?;
+    return bsw::decrypt(&_sk, &_ct).map_err(to_js);
+}
@@ -0,0 +1,96 @@
+//! Runs the key lifecycle of the `rabe-bsw` binary in a temporary directory.
+//!
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+// a fresh directory below the temporary directory of the system
+fn temp_dir(_name: &str) -> PathBuf {
+    let _dir = std::env::temp_dir().join(format!("rabe-cli-{}-{}", _name, std::process::id()));
+    let _ = fs::remove_dir_all(&_dir);
+    fs::create_dir_all(&_dir).unwrap();
+    return _dir;
+}
+
+fn rabe_bsw(_dir: &PathBuf, _args: &[&str]) -> bool {
+    return Command::new(env!("CARGO_BIN_EXE_rabe-bsw"))
+        .current_dir(_dir)
+        .args(_args)
+        .status()
+        .unwrap()
+        .success();
+}
+
+#[test]
+fn bsw_lifecycle() {
+    let _dir = temp_dir("lifecycle");
+    let plaintext = "dance like no one's watching, encrypt like everyone is!";
+    fs::write(_dir.join("plain.txt"), plaintext).unwrap();
+    assert_eq!(rabe_bsw(&_dir, &["setup"]), true);
+    assert_eq!(_dir.join("pk.rkey").exists() && _dir.join("msk.rkey").exists(), true);
+    assert_eq!(rabe_bsw(&_dir, &["keygen", "--attrs", "A,B"]), true);
+    assert_eq!(
+        rabe_bsw(&_dir, &["keygen", "--attrs", "C", "--out", "sk_c.rkey"]),
+        true
+    );
+    // a policy as JSON and as boolean expression
+    assert_eq!(
+        rabe_bsw(
+            &_dir,
+            &[
+                "encrypt",
+                "--policy",
+                r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#,
+                "--in",
+                "plain.txt",
+                "--out",
+                "json.rct",
+            ]
+        ),
+        true
+    );
+    assert_eq!(
+        rabe_bsw(
+            &_dir,
+            &["encrypt", "--policy", "A and B", "--in", "plain.txt", "--out", "infix.rct"]
+        ),
+        true
+    );
+    // or read from a file
+    fs::write(_dir.join("policy.txt"), "A and B").unwrap();
+    assert_eq!(
+        rabe_bsw(
+            &_dir,
+            &["encrypt", "--policy-file", "policy.txt", "--in", "plain.txt", "--out", "file.rct"]
+        ),
+        true
+    );
+    for _ct in ["json.rct", "infix.rct", "file.rct"].iter() {
+        assert_eq!(
+            rabe_bsw(&_dir, &["decrypt", "--in", _ct, "--out", "decrypted.txt"]),
+            true
+        );
+        assert_eq!(fs::read_to_string(_dir.join("decrypted.txt")).unwrap(), plaintext);
+        assert_eq!(
+            rabe_bsw(
+                &_dir,
+                &["decrypt", "--key", "sk_c.rkey", "--in", _ct, "--out", "denied.txt"]
+            ),
+            false
+        );
+    }
+    assert_eq!(_dir.join("denied.txt").exists(), false);
+    // invalid input fails with an exit code instead of a panic
+    assert_eq!(
+        rabe_bsw(
+            &_dir,
+            &["encrypt", "--policy", "A and", "--in", "plain.txt", "--out", "x.rct"]
+        ),
+        false
+    );
+    assert_eq!(
+        rabe_bsw(&_dir, &["decrypt", "--in", "plain.txt", "--out", "x.txt"]),
+        false
+    );
+    fs::remove_dir_all(&_dir).unwrap();
+}
@@ -0,0 +1,86 @@
+/*
+ * C test harness of the BSW FFI, see the README for the build step:
+ *
+ *   cargo build --features ffi
+ *   cc tests/ffi/bsw_test.c -L./target/debug -lrabe -o bsw_test
+ *   LD_LIBRARY_PATH=./target/debug ./bsw_test
+ */
+#include <stdint.h>
+#include <stdlib.h>
+#include <stdio.h>
+#include <string.h>
+#include <assert.h>
+
+#define RABE_OK 0
+#define RABE_ERR_INVALID_ARGUMENT -1
+#define RABE_ERR_INVALID_POLICY -5
+#define RABE_ERR_INSUFFICIENT_ATTRIBUTES -8
+
+struct CpAbeContext;
+struct CpAbeSecretKey;
+
+struct CpAbeContext* rabe_bsw_setup();
+void rabe_bsw_context_destroy(struct CpAbeContext* ctx);
+struct CpAbeSecretKey* rabe_bsw_keygen(const struct CpAbeContext* ctx, const char* attributes);
+void rabe_bsw_keygen_destroy(struct CpAbeSecretKey* sk);
+int32_t rabe_bsw_delegate(const struct CpAbeContext* ctx, const struct CpAbeSecretKey* sk,
+                          const char* const* attributes, uint32_t attributes_len,
+                          struct CpAbeSecretKey** dsk);
+int32_t rabe_bsw_encrypt(const struct CpAbeContext* ctx, const char* policy,
+                         const uint8_t* pt, uint32_t pt_len,
+                         uint8_t** ct_buf, uint32_t* ct_len);
+int32_t rabe_bsw_decrypt(const struct CpAbeSecretKey* sk, const uint8_t* ct, uint32_t ct_len,
+                         uint8_t** pt_buf, uint32_t* pt_len);
+void rabe_free(void* buf);
+
+int main () {
+  const char *pt = "dance like no one's watching, encrypt like everyone is!";
+  const char *policy = "{\"AND\": [{\"ATT\": \"A\"}, {\"ATT\": \"B\"}]}";
+  uint8_t *ct_buf = NULL;
+  uint32_t ct_len = 0;
+  uint8_t *pt_buf = NULL;
+  uint32_t pt_len = 0;
+
+  /* Setup */
+  struct CpAbeContext *ctx = rabe_bsw_setup ();
+  assert (NULL != ctx);
+
+  /* Keygen */
+  struct CpAbeSecretKey *sk = rabe_bsw_keygen (ctx, "A,B");
+  struct CpAbeSecretKey *sk_a = rabe_bsw_keygen (ctx, "A");
+  assert (NULL != sk && NULL != sk_a);
+
+  /* Encrypt */
+  assert (RABE_OK == rabe_bsw_encrypt (ctx, policy, (const uint8_t*) pt, strlen (pt),
+                                       &ct_buf, &ct_len));
+  assert (RABE_ERR_INVALID_POLICY == rabe_bsw_encrypt (ctx, "joking-around?",
+                                                       (const uint8_t*) pt, strlen (pt),
+                                                       &pt_buf, &pt_len));
+
+  /* Decrypt */
+  assert (RABE_OK == rabe_bsw_decrypt (sk, ct_buf, ct_len, &pt_buf, &pt_len));
+  assert (strlen (pt) == pt_len);
+  assert (0 == memcmp (pt_buf, pt, pt_len));
+  rabe_free (pt_buf);
+  pt_buf = NULL;
+  assert (RABE_ERR_INSUFFICIENT_ATTRIBUTES == rabe_bsw_decrypt (sk_a, ct_buf, ct_len,
+                                                                &pt_buf, &pt_len));
+  assert (RABE_ERR_INVALID_ARGUMENT == rabe_bsw_decrypt (NULL, ct_buf, ct_len,
+                                                         &pt_buf, &pt_len));
+
+  /* Delegate */
+  const char *attributes[] = {"A", "B"};
+  struct CpAbeSecretKey *dsk = NULL;
+  assert (RABE_OK == rabe_bsw_delegate (ctx, sk, attributes, 2, &dsk));
+  assert (RABE_OK == rabe_bsw_decrypt (dsk, ct_buf, ct_len, &pt_buf, &pt_len));
+  assert (0 == memcmp (pt_buf, pt, pt_len));
+  rabe_free (pt_buf);
+  rabe_bsw_keygen_destroy (dsk);
+
+  rabe_free (ct_buf);
+  rabe_bsw_keygen_destroy (sk_a);
+  rabe_bsw_keygen_destroy (sk);
+  rabe_bsw_context_destroy (ctx);
+  printf ("ok\n");
+  return 0;
+}
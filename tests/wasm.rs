@@ -0,0 +1,26 @@
+//! Round trip of the WebAssembly bindings, run with
+//!
+//! ```bash
+//! wasm-pack test --node -- --features wasm
+//! ```
+//!
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+extern crate rabe;
+extern crate wasm_bindgen_test;
+
+use rabe::wasm::*;
+use wasm_bindgen_test::*;
+
+#[wasm_bindgen_test]
+fn bsw_roundtrip() {
+    // setup scheme
+    let keys = bsw_setup().unwrap();
+    let plaintext = String::from("dance like no one's watching, encrypt like everyone is!")
+        .into_bytes();
+    let policy = r#"{"AND": [{"ATT": "A"}, {"ATT": "B"}]}"#;
+    let sk = bsw_keygen(&keys.pk(), &keys.msk(), "A,B").unwrap();
+    let ct = bsw_encrypt(&keys.pk(), policy, &plaintext).unwrap();
+    assert_eq!(bsw_decrypt(&sk, &ct).unwrap(), plaintext);
+    let sk_a = bsw_keygen(&keys.pk(), &keys.msk(), "A").unwrap();
+    assert_eq!(bsw_decrypt(&sk_a, &ct).is_err(), true);
+}